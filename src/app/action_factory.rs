@@ -1,9 +1,14 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{collections::HashMap, fs::OpenOptions, io::Write, num::NonZeroU32, path::PathBuf, process::Command, rc::Rc, sync::Mutex, thread, time::{Duration, Instant}};
 
-use clipboard_win::{Clipboard, Setter, Unicode};
+use clipboard_win::{count_formats, empty, get_clipboard, seq_num, Clipboard, Setter, Unicode};
+use windows::Win32::{
+    Media::Audio::{eMultimedia, eRender, Endpoints::IAudioEndpointVolume, IMMDeviceEnumerator, MMDeviceEnumerator},
+    System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_APARTMENTTHREADED},
+    UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_MEDIA_STOP, VK_VOLUME_DOWN, VK_VOLUME_MUTE, VK_VOLUME_UP},
+};
 
-use crate::core::{ActionType, ActionParams, SettingsRepository, SettingsRepositoryMut};
-use crate::input::{script, script::InputScript};
+use crate::core::{build_query_url, expand_path, split_snippet, ActionType, ActionParams, AudioCommand, MediaKey, Param, SettingsRepository, SettingsRepositoryMut, TextTransform};
+use crate::input::{script, script::InputScript, ModifierState};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ActionResult {
@@ -74,6 +79,11 @@ impl<'a, R: SettingsRepository + SettingsRepositoryMut> ActionFactoryImpl<'a, R>
                 let script = script::for_shortcut(text.clone());
                 Box::new(InputScriptAction { script })
             },
+            ActionType::ShortcutKeys { text, keys } => {
+                log::debug!(target:"input_api", "Shortcut keys: {}", text);
+                let script = script::for_keys(&script::KeySequence::from_combinations(keys.iter().map(|chord| chord.combination())));
+                Box::new(InputScriptAction { script })
+            },
             ActionType::Text(text) => {
                 let script = script::for_text(text.clone());
                 Box::new(InputScriptAction { script })
@@ -82,11 +92,34 @@ impl<'a, R: SettingsRepository + SettingsRepositoryMut> ActionFactoryImpl<'a, R>
                 let script = script::for_line(text.clone());
                 Box::new(InputScriptAction { script })
             },
+            ActionType::Snippet(text) => {
+                let (before, after) = split_snippet(text);
+                let script = script::for_snippet(&before, &after);
+                Box::new(InputScriptAction { script })
+            },
             ActionType::Paste(text) => {
-                Box::new(PasteAction { text: text.clone(), enter: false })
+                Box::new(PasteAction { text: text.clone(), enter: false, restore: true })
             },
             ActionType::PasteEnter(text) => {
-                Box::new(PasteAction { text: text.clone(), enter: true })
+                Box::new(PasteAction { text: text.clone(), enter: true, restore: true })
+            },
+            ActionType::PasteKeep(text) => {
+                Box::new(PasteAction { text: text.clone(), enter: false, restore: false })
+            },
+            ActionType::PasteEnterKeep(text) => {
+                Box::new(PasteAction { text: text.clone(), enter: true, restore: false })
+            },
+            ActionType::TypeClipboard(delay) => {
+                Box::new(TypeClipboardAction { delay: *delay })
+            },
+            ActionType::TypeTransformed { text, transform } => {
+                Box::new(TypeTransformedAction { text: text.clone(), transform: *transform })
+            },
+            ActionType::WaitForClipboard { timeout_ms } => {
+                Box::new(WaitForClipboardAction { timeout: Duration::from_millis(*timeout_ms) })
+            },
+            ActionType::WrapSelection { prefix, suffix } => {
+                Box::new(WrapSelectionAction { prefix: prefix.clone(), suffix: suffix.clone() })
             },
             ActionType::Pause(duration) => {
                 let script = script::for_pause(*duration);
@@ -95,6 +128,38 @@ impl<'a, R: SettingsRepository + SettingsRepositoryMut> ActionFactoryImpl<'a, R>
             ActionType::OpenUrl(url) => {
                 Box::new(OpenUrlAction { url: url.clone() })
             },
+            ActionType::OpenQuery { base, params } => {
+                Box::new(OpenQueryAction { base: base.clone(), params: params.clone() })
+            },
+            ActionType::LaunchApp { path, args, working_dir } => {
+                Box::new(LaunchAppAction { path: expand_path(path), args: args.clone(), working_dir: working_dir.as_deref().map(expand_path) })
+            },
+            ActionType::AppendFile { path, text, add_newline } => {
+                Box::new(AppendFileAction { path: expand_path(path), text: text.clone(), add_newline: *add_newline })
+            },
+            ActionType::SwitchTextStyle(_) => {
+                // Applied to the displayed board by the application, the board stays open
+                Box::new(NoOpAction)
+            },
+            ActionType::FocusPrevious => {
+                // Needs the window the board was opened over, activated by the application
+                Box::new(NoOpAction)
+            },
+            ActionType::Audio { command } => {
+                Box::new(AudioAction { command: *command })
+            },
+            ActionType::MediaKey(key) => {
+                let script = script::for_keys(&script::KeySequence::from_combinations([(ModifierState::default(), media_vk_code(*key).0)]));
+                Box::new(InputScriptAction { script })
+            },
+            ActionType::WindowCommand(_) => {
+                // Applied to the window the board was opened over by the application
+                Box::new(NoOpAction)
+            },
+            ActionType::IfTime { .. } => {
+                // Replaced by its branch before the pad's actions run
+                Box::new(NoOpAction)
+            },
             ActionType::Custom(custom_action) => {
                 self.registry
                     .get_factory(&custom_action.action_type)
@@ -141,28 +206,354 @@ impl Action for OpenUrlAction {
     }
 }
 
+struct OpenQueryAction {
+    base: String,
+    params: Vec<Param>,
+}
+
+impl Action for OpenQueryAction {
+    fn run(&self) -> ActionResult {
+        // Clipboard is read only when a param refers to it
+        let clipboard = if self.params.iter().any(|p| p.value.contains("{clipboard}")) {
+            get_clipboard::<String, Unicode>(Unicode).unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        let url = build_query_url(&self.base, &self.params, &[("clipboard", clipboard.as_str())]);
+        OpenUrlAction { url }.run()
+    }
+}
+
+struct LaunchAppAction {
+    path: PathBuf,
+    args: Vec<String>,
+    working_dir: Option<PathBuf>,
+}
+
+impl Action for LaunchAppAction {
+    fn run(&self) -> ActionResult {
+        // A bare file name is looked up on the PATH when started, a path has to point to the executable
+        if self.path.components().count() > 1 && !self.path.is_file() {
+            log::error!("Application not found: {}", self.path.display());
+            return ActionResult::Error(format!("Application not found: {}", self.path.display()));
+        }
+
+        let mut command = Command::new(&self.path);
+        command.args(&self.args);
+        match &self.working_dir {
+            Some(dir) if dir.is_dir() => { command.current_dir(dir); },
+            Some(dir) => log::warn!("Working directory {} not found, starting {} in the current one", dir.display(), self.path.display()),
+            None => {},
+        }
+
+        match command.spawn() {
+            Ok(child) => {
+                log::info!("Launched {} (pid {})", self.path.display(), child.id());
+                ActionResult::Success
+            },
+            Err(e) => {
+                log::error!("Failed to launch {}: {}", self.path.display(), e);
+                ActionResult::Error(format!("Failed to launch application: {}", e))
+            }
+        }
+    }
+}
+
+struct AppendFileAction {
+    path: PathBuf,
+    text: String,
+    add_newline: bool,
+}
+
+impl Action for AppendFileAction {
+    fn run(&self) -> ActionResult {
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| {
+                file.write_all(self.text.as_bytes())?;
+                if self.add_newline {
+                    file.write_all(b"\n")?;
+                }
+                Ok(())
+            });
+
+        match result {
+            Ok(()) => ActionResult::Success,
+            Err(e) => {
+                log::error!("Failed to append to file {}: {}", self.path.display(), e);
+                ActionResult::Error(format!("Failed to append to file: {}", e))
+            }
+        }
+    }
+}
+
+struct TypeClipboardAction {
+    delay: u64,
+}
+
+impl Action for TypeClipboardAction {
+    fn run(&self) -> ActionResult {
+        match get_clipboard::<String, Unicode>(Unicode) {
+            Ok(text) if text.is_empty() => {
+                log::info!("Clipboard is empty, nothing to type");
+                ActionResult::Success
+            },
+            Ok(text) => {
+                script::for_unicode_text(text, self.delay).play();
+                ActionResult::Success
+            },
+            Err(e) => {
+                // Images, files and other formats can't be typed
+                log::warn!("Clipboard holds no text: {}", e);
+                ActionResult::Error(format!("Clipboard holds no text: {}", e))
+            }
+        }
+    }
+}
+
+struct TypeTransformedAction {
+    text: String,
+    transform: TextTransform,
+}
+
+impl Action for TypeTransformedAction {
+    fn run(&self) -> ActionResult {
+        if !self.text.is_empty() {
+            script::for_unicode_text(self.transform.apply(&self.text), 0).play();
+            return ActionResult::Success;
+        }
+        match get_clipboard::<String, Unicode>(Unicode) {
+            Ok(text) => {
+                script::for_unicode_text(self.transform.apply(&text), 0).play();
+                ActionResult::Success
+            },
+            Err(e) => {
+                log::warn!("Clipboard holds no text: {}", e);
+                ActionResult::Error(format!("Clipboard holds no text: {}", e))
+            }
+        }
+    }
+}
+
+const CLIPBOARD_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Polls the clipboard sequence number, true when it changed before the timeout elapsed
+pub fn wait_for_clipboard_change(timeout: Duration) -> bool {
+    wait_for_clipboard_change_from(seq_num(), timeout)
+}
+
+/// Same as [`wait_for_clipboard_change`], for a change made since the sequence number was `initial`
+fn wait_for_clipboard_change_from(initial: Option<NonZeroU32>, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        thread::sleep(CLIPBOARD_POLL_INTERVAL.min(timeout));
+        if seq_num() != initial {
+            log::info!("Clipboard changed after {} ms", start.elapsed().as_millis());
+            return true;
+        }
+    }
+    log::info!("Clipboard unchanged after {} ms, continuing", timeout.as_millis());
+    false
+}
+
+/// Blocks the calling thread, the application runs the wait on a worker instead
+struct WaitForClipboardAction {
+    timeout: Duration,
+}
+
+impl Action for WaitForClipboardAction {
+    fn run(&self) -> ActionResult {
+        wait_for_clipboard_change(self.timeout);
+        ActionResult::Success
+    }
+}
+
+/// How long the focused window gets to answer the Ctrl+C of a WrapSelection
+const SELECTION_COPY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Copies the selected text and types `prefix`, the selection and `suffix` in its place, then puts back
+/// the text the clipboard held before, or empties it again. Nothing selected (the copy left the clipboard
+/// unchanged) types `prefix` and `suffix` with the caret between them.
+pub fn wrap_selection(prefix: &str, suffix: &str) {
+    let saved = SavedClipboard::read();
+    let initial = seq_num();
+    script::for_shortcut("Ctrl C".to_string()).play();
+
+    if !wait_for_clipboard_change_from(initial, SELECTION_COPY_TIMEOUT) {
+        script::for_snippet(prefix, suffix).play();
+        return;
+    }
+
+    let selection = get_clipboard::<String, Unicode>(Unicode).unwrap_or_default();
+    script::for_unicode_text(format!("{}{}{}", prefix, selection, suffix), 0).play();
+
+    saved.restore();
+}
+
+/// Blocks the calling thread, the application runs it on a worker instead
+struct WrapSelectionAction {
+    prefix: String,
+    suffix: String,
+}
+
+impl Action for WrapSelectionAction {
+    fn run(&self) -> ActionResult {
+        wrap_selection(&self.prefix, &self.suffix);
+        ActionResult::Success
+    }
+}
+
+fn media_vk_code(key: MediaKey) -> VIRTUAL_KEY {
+    match key {
+        MediaKey::VolumeUp => VK_VOLUME_UP,
+        MediaKey::VolumeDown => VK_VOLUME_DOWN,
+        MediaKey::Mute => VK_VOLUME_MUTE,
+        MediaKey::PlayPause => VK_MEDIA_PLAY_PAUSE,
+        MediaKey::NextTrack => VK_MEDIA_NEXT_TRACK,
+        MediaKey::PrevTrack => VK_MEDIA_PREV_TRACK,
+        MediaKey::Stop => VK_MEDIA_STOP,
+    }
+}
+
+struct AudioAction {
+    command: AudioCommand,
+}
+
+impl AudioAction {
+    /// Applies the command to the default playback device
+    fn apply(&self) -> windows::core::Result<()> {
+        unsafe {
+            let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let device = enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia)?;
+            let volume: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None)?;
+            match self.command {
+                AudioCommand::VolumeUp => volume.VolumeStepUp(std::ptr::null()),
+                AudioCommand::VolumeDown => volume.VolumeStepDown(std::ptr::null()),
+                AudioCommand::Mute => volume.SetMute(!volume.GetMute()?.as_bool(), std::ptr::null()),
+                AudioCommand::SetVolume(percent) => volume.SetMasterVolumeLevelScalar(percent.min(100) as f32 / 100.0, std::ptr::null()),
+            }
+        }
+    }
+}
+
+impl Action for AudioAction {
+    fn run(&self) -> ActionResult {
+        // Also succeeds (S_FALSE) when COM was already initialized on the thread, each success needs its uninitialize
+        let initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+        let result = self.apply();
+        if initialized {
+            unsafe { CoUninitialize() };
+        }
+
+        match result {
+            Ok(()) => ActionResult::Success,
+            Err(e) => {
+                log::error!("Failed to apply audio command {}: {}", self.command, e);
+                ActionResult::Error(format!("Failed to apply audio command: {}", e))
+            }
+        }
+    }
+}
+
+/// How long the focused window gets to read the pasted text before the clipboard's previous content is put back.
+/// Raise it when slow applications paste the previous content instead.
+const CLIPBOARD_RESTORE_DELAY: Duration = Duration::from_millis(300);
+
+/// What the clipboard held before a paste or a copy of the selection
+enum SavedClipboard {
+    Text(String),
+    Empty,
+    /// Images, files and other formats, these are not restored
+    Other,
+}
+
+impl SavedClipboard {
+    fn read() -> Self {
+        match get_clipboard::<String, Unicode>(Unicode) {
+            Ok(text) => SavedClipboard::Text(text),
+            Err(_) if count_formats() == Some(0) => SavedClipboard::Empty,
+            Err(_) => SavedClipboard::Other,
+        }
+    }
+
+    fn restore(self) {
+        if let SavedClipboard::Other = self {
+            log::info!("Clipboard held no text before, the text put on it stays");
+            return;
+        }
+        let _clip = match Clipboard::new_attempts(10) {
+            Ok(clip) => clip,
+            Err(e) => {
+                log::warn!("Failed to open clipboard to restore it: {}", e);
+                return;
+            }
+        };
+        let restored = match self {
+            SavedClipboard::Text(text) => Unicode.write_clipboard(&text),
+            _ => empty(),
+        };
+        if let Err(e) = restored {
+            log::warn!("Failed to restore clipboard: {}", e);
+        }
+    }
+}
+
+/// The clipboard content from before a paste, with the clipboard sequence number the paste left.
+/// Pastes in quick succession share it, only the restore of the latest one puts it back.
+static PENDING_RESTORE: Mutex<Option<(SavedClipboard, Option<NonZeroU32>)>> = Mutex::new(None);
+
+/// Puts the saved clipboard content back after the delay, unless another paste or a copy changed the clipboard meanwhile
+fn restore_clipboard_later(saved: SavedClipboard) {
+    let paste_seq = seq_num();
+    *PENDING_RESTORE.lock().unwrap() = Some((saved, paste_seq));
+
+    thread::spawn(move || {
+        thread::sleep(CLIPBOARD_RESTORE_DELAY);
+        let mut pending = PENDING_RESTORE.lock().unwrap();
+        if pending.as_ref().is_some_and(|(_, seq)| *seq == paste_seq) {
+            let (saved, _) = pending.take().unwrap();
+            match seq_num() == paste_seq {
+                true => saved.restore(),
+                false => log::info!("Clipboard changed after the paste, not restored"),
+            }
+        }
+    });
+}
+
 struct PasteAction {
     text: String,
     enter: bool,
+    /// Puts the clipboard's previous content back once the text is pasted
+    restore: bool,
 }
 
 impl Action for PasteAction {
     fn run(&self) -> ActionResult {
-        let text = self.text.as_str();
-        if let Ok(_clip) = Clipboard::new_attempts(10) {
-            match Unicode.write_clipboard(&text) {
-                Err(e) => {
-                    log::error!("Failed to set clipboard text: {}", e);
-                    return ActionResult::Error(format!("Failed to set clipboard text: {}", e))
-                }
-                Ok(_) => { /* Clipboard set successfully */ }
+        // A restore still pending from the paste before holds what the clipboard had before both
+        let saved = self.restore.then(|| match PENDING_RESTORE.lock().unwrap().take() {
+            Some((saved, _)) => saved,
+            None => SavedClipboard::read(),
+        });
+
+        let written = match Clipboard::new_attempts(10) {
+            Ok(_clip) => Unicode.write_clipboard(&self.text).map_err(|e| format!("Failed to set clipboard text: {}", e)),
+            Err(e) => Err(format!("Failed to open clipboard: {}", e)),
+        };
+        if let Err(e) = written {
+            log::error!("{}", e);
+            if let Some(saved) = saved {
+                saved.restore();
             }
-        } else {
-            log::error!("Failed to open clipboard");
-            return ActionResult::Error("Failed to open clipboard".to_string());
+            return ActionResult::Error(e);
         }
 
         script::for_shortcut(format!("Ctrl V{}", if self.enter { " + Enter" } else { "" })).play();
+        if let Some(saved) = saved {
+            restore_clipboard_later(saved);
+        }
         ActionResult::Success
     }
 }
@@ -1,32 +1,70 @@
-use std::{ process::Command, rc::Rc, sync::mpsc::{channel, Receiver}, thread };
+use std::{ process::Command, rc::Rc, sync::mpsc::{channel, Receiver}, thread, time::Duration };
 use std::ffi::c_void;
 use windows::{
     core::{Result, HSTRING},
     Win32::{
         Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+        System::SystemInformation::GetLocalTime,
         UI::WindowsAndMessaging::{
-            DefWindowProcW, DispatchMessageW, GetMessageW, MessageBoxW, PostQuitMessage, TranslateMessage, IDOK, MB_ICONERROR, MB_OK, MB_OKCANCEL, MSG, WM_CLOSE, WM_USER
+            DefWindowProcW, DispatchMessageW, GetDoubleClickTime, GetMessageW, KillTimer, MessageBoxW, PostMessageW, PostQuitMessage, SetForegroundWindow, SetTimer, TranslateMessage,
+            IDOK, MB_ICONERROR, MB_OK, MB_OKCANCEL, MSG, WM_CLOSE, WM_LBUTTONDBLCLK, WM_LBUTTONUP, WM_MBUTTONUP, WM_RBUTTONUP, WM_TIMER, WM_USER
         },
     },
 };
 
 use super::{
-    BoardManager, ActionFactoryRegistry, BoardFactoryRegistry, ActionFactoryImpl, BoardFactoryImpl,
+    BoardManager, ActionFactoryRegistry, BoardFactoryRegistry, ActionFactoryImpl, BoardFactoryImpl, wait_for_clipboard_change, wrap_selection, set_log_level,
     hook, hook::win_icon, message, message::Message,
-    windows::{ MainWindow, tray_item, WM_BOARD_COMMAND, WM_BOARD_FINISHED, WM_UPDATE_LAYOUT, WM_OPEN_SETTINGS, WM_RELOAD_SETTINGS, WM_SAVE_SETTINGS }
+    windows::{ MainWindow, TrayIcon, chosen_log_level, tray_item, tray_menu, WM_TRAY_NOTIFY, WM_BOARD_COMMAND, WM_BOARD_FINISHED, WM_GO_HOME, WM_SAVE_AND_CLOSE, WM_UPDATE_LAYOUT, WM_ZOOM_CHANGED, WM_OPEN_SETTINGS, WM_RELOAD_ICONS, WM_RELOAD_SETTINGS, WM_SAVE_SETTINGS, WM_SET_LOG_LEVEL }
 };
 
 use crate::{
-    app::windows::WM_SHOW_APPLICATION, core::{data::Detection, resources::DetectedIcon, Param, Resources, SettingsRepository, SettingsRepositoryMut}, model::{PadId, PadSet}, settings::*, ui::shared::utils
+    app::windows::WM_SHOW_APPLICATION, components::{error_board, unseen_entries, ConfirmPadBoard, SafeModeBoard, StateMachineBoard, WhatsNewBoard}, core::{data::{is_diagnostic_board, Detection}, resources::DetectedIcon, resolve_conditions, ActionType, BoardType, PadBehavior, Param, Resources, SettingsRepository, SettingsRepositoryMut, WindowCommand}, input::{script, script::{parse_hotkey, KeySequence}}, model::{BoardHandle, Pad, PadId, PadSet, PadSource}, settings::*, ui::{components::initialize_icon_caches, shared::utils}
 };
 
 pub const WM_HOOK_TRIGGER:u32 = WM_USER + 1;
+pub const WM_HOTKEY_TRIGGER:u32 = WM_USER + 2;
+pub const WM_FOREGROUND_TRIGGER:u32 = WM_USER + 3;
+pub const WM_KEY_RELEASE_TRIGGER:u32 = WM_USER + 4;
+pub const WM_WAIT_TRIGGER:u32 = WM_USER + 5;
+pub const WM_PAD_REPEAT_TRIGGER:u32 = WM_USER + 6;
+
+/// What a hotkey registered with the hook does, listed in the order of registration
+#[derive(Clone, Debug)]
+enum HotkeyTarget {
+    BoardToggle,
+    Board(String),
+}
+
+impl std::fmt::Display for HotkeyTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HotkeyTarget::BoardToggle => write!(f, "the board toggle"),
+            HotkeyTarget::Board(name) => write!(f, "board '{}'", name),
+        }
+    }
+}
+
+// Restarted on every foreground change, the board is switched once it elapses
+const ID_TIMER_AUTO_SWITCH: usize = 0xA5;
+
+// Periodic check closing boards left without input, runs while idle_timeout is configured
+const ID_TIMER_IDLE: usize = 0xA6;
+const IDLE_CHECK_INTERVAL: u32 = 1000;
+
+// Delays a tray click until it can't become a double click any more
+const ID_TIMER_TRAY_CLICK: usize = 0xA7;
+
+// Periodic check opening scheduled boards, runs while a schedule is configured
+const ID_TIMER_SCHEDULE: usize = 0xA8;
+const SCHEDULE_CHECK_INTERVAL: u32 = 10_000;
 
 #[repr(C)]
 struct ProcessInfo {
     pub pid: u32,
     pub name: [u8; 260], // MAX_PATH
     pub title: [u8; 260], // MAX_TITLE
+    pub hwnd: isize,
 }
 
 impl ProcessInfo {
@@ -35,6 +73,7 @@ impl ProcessInfo {
             pid: pinfo.pid,
             name: [0; 260],
             title: [0; 260],
+            hwnd: pinfo.hwnd,
         };
         utils::copy_string_to_array(&mut info.name, &pinfo.name);
         utils::copy_string_to_array(&mut info.title, &pinfo.title);
@@ -49,6 +88,27 @@ impl ProcessInfo {
     }
 }
 
+/// A pad's actions in progress, parked while a Pause, WaitForClipboard or WrapSelection runs on a worker thread
+/// and between the runs of a repeating pad.
+/// `actions` are the pad's actions with their conditions resolved when the pad was selected,
+/// `target_window` the window its board was opened over, boards opened meanwhile don't change it.
+struct PadRun {
+    pad: Pad,
+    actions: Vec<ActionType>,
+    target_window: Option<isize>,
+    held_key: u16,
+    next_action: usize,
+    runs_left: u32,
+    needs_reload: bool,
+    needs_restart: bool,
+}
+
+/// Minutes since midnight, local time
+fn local_minute_of_day() -> u32 {
+    let now = unsafe { GetLocalTime() };
+    now.wHour as u32 * 60 + now.wMinute as u32
+}
+
 
 
 
@@ -58,6 +118,19 @@ pub struct Application {
     board_factory_registry: BoardFactoryRegistry<Settings>,
     board_manager: BoardManager,
     restart_info: Option<Option<String>>,
+    toggled_board: Option<usize>,
+    pending_switch: Option<ProcessInfo>,
+    momentary_keys: Option<(u16, Vec<KeySequence>)>,
+    latched_keys: Vec<KeySequence>,
+    main_hwnd: Option<HWND>,
+    last_board: Option<String>,
+    recent_boards_changed: bool,
+    pending_run: Option<(usize, PadRun)>,
+    parked_runs: usize,
+    tray: Option<TrayIcon>,
+    tray_double_clicked: bool,
+    last_schedule_check: Option<u32>,
+    hotkey_targets: Vec<HotkeyTarget>,
 }
 
 impl Application {
@@ -69,7 +142,7 @@ impl Application {
     ) -> Self {
         let board_manager = BoardManager::new(settings.clone());
 
-        Self { settings, action_factory_registry, board_factory_registry, board_manager, restart_info: None }
+        Self { settings, action_factory_registry, board_factory_registry, board_manager, restart_info: None, toggled_board: None, pending_switch: None, momentary_keys: None, latched_keys: vec![], main_hwnd: None, last_board: None, recent_boards_changed: false, pending_run: None, parked_runs: 0, tray: None, tray_double_clicked: false, last_schedule_check: None, hotkey_targets: vec![] }
     }
 
     fn show_board(&mut self, board_name: String, params: Vec<Param>, timeout: u32) ->  core::result::Result<(), Box<dyn std::error::Error>> {
@@ -80,6 +153,14 @@ impl Application {
         match board_trait {
             Ok(board_trait) => {
                 self.board_manager.show_board(board_trait, timeout, self.settings.feedback());
+                // Only configured boards can be restored, detected ones depend on their params
+                if let Ok(board) = self.settings.get_board(&board_name) {
+                    // The recent boards board would only list itself first
+                    if !matches!(board.board_type, BoardType::Recent) {
+                        self.recent_boards_changed |= self.settings.add_recent_board(&board_name);
+                    }
+                    self.last_board = Some(board_name);
+                }
                 Ok(())
             },
             Err(err) => {
@@ -90,7 +171,8 @@ impl Application {
     }
 
 
-    pub fn run(&mut self, board_name: Option<String>, params: Vec<Param>) -> Result<()> {
+    /// `quiet` starts in the tray only, unless a board is given or the settings failed to load
+    pub fn run(&mut self, board_name: Option<String>, params: Vec<Param>, quiet: bool) -> Result<()> {
         let (tx, rx) = channel::<Message>();
         let join_handle = Self::event_proc(rx,
             self.settings.get_resources().clone(),
@@ -98,20 +180,32 @@ impl Application {
         );
 
         hook::install(tx.clone());
+        self.register_hotkeys();
+        self.update_foreground_watcher();
         {
             let main_window = MainWindow::new("HotKeys", 20, 20)?; // , self as _)?;
 
             tx.send(Message::WinCreated(main_window.hwnd())).unwrap_or_default();
-            let _tray = tray_item(main_window.hwnd());
+            self.tray = Some(tray_item(main_window.hwnd()));
+            self.main_hwnd = Some(HWND(main_window.hwnd() as *mut c_void));
+            self.update_idle_timer();
+            self.update_schedule_timer();
+            self.update_log_level();
 
-            let board_name = board_name
-            .and_then(|name| self.settings
-                .get_board(&name)
-                .ok()
-                .map(|b| b.name.clone())
-            ).unwrap_or_else(|| self.settings.home_board_name());
+            // Release notes take the place of the home board, an explicit board (e.g. after restart) wins
+            if let Some(error) = self.settings.load_error() {
+                self.show_safe_mode(error);
+            } else if quiet && board_name.is_none() {
+                log::debug!("Quiet start, no board displayed until requested");
+            } else if board_name.is_some() || !self.show_whats_new() {
+                let board_name = board_name
+                .and_then(|name| match is_diagnostic_board(&name) {
+                    true => Some(name),
+                    false => self.settings.get_board(&name).ok().map(|b| b.name.clone()),
+                }).unwrap_or_else(|| self.settings.startup_board_name());
 
-            self.show_board(board_name, params, 0).unwrap_or_default();
+                self.show_board(board_name, params, 0).unwrap_or_default();
+            }
 
             let mut message = MSG::default();
             unsafe {
@@ -120,7 +214,12 @@ impl Application {
                     DispatchMessageW(&message);
                 }
             }
+            self.tray = None;
         }
+        self.main_hwnd = None;
+        self.release_held_keys();
+        self.save_board_history();
+        hook::uninstall_foreground_watcher();
         hook::uninstall();
 
         tx.send(Message::Quit).unwrap_or_default();
@@ -152,10 +251,23 @@ impl Application {
                         }
                         Ok(_) => {
                             log::info!("Settings reloaded");
+                            self.register_hotkeys();
+                            self.update_foreground_watcher();
+                            self.update_idle_timer();
+                            self.update_schedule_timer();
+                            self.update_log_level();
                             self.board_manager.redraw_board();
                         }
                     }
                 },
+                WM_RELOAD_ICONS => {
+                    if initialize_icon_caches(self.settings.get_resources()) {
+                        log::info!("Icon caches reloaded");
+                        self.board_manager.redraw_board();
+                    } else {
+                        log::warn!("Icon caches are in use, not reloaded");
+                    }
+                },
                 WM_SAVE_SETTINGS => {
                     match self.settings.flush() {
                         Err(e) => {
@@ -163,13 +275,34 @@ impl Application {
                         }
                         Ok(_) => {
                             log::info!("Settings saved");
-                            // self.post_setting_changed(hwnd);
+                            self.register_hotkeys();
                         }
                     }
                 },
                 WM_SHOW_APPLICATION => {
                     self.show_board(self.settings.home_board_name(), vec![], 0).unwrap_or_default();
                 },
+                WM_SET_LOG_LEVEL => {
+                    let level = chosen_log_level(wparam);
+                    match set_log_level(level) {
+                        Err(e) => {
+                            MessageBoxW(Some(hwnd), &HSTRING::from(format!("Failed to change the log level: {}", e)), &HSTRING::from("Error"), MB_OK | MB_ICONERROR);
+                        }
+                        Ok(_) => {
+                            self.settings.set_log_level(level);
+                            if let Err(e) = self.settings.flush() {
+                                log::error!("Failed to save log level: {}", e);
+                            }
+                        }
+                    }
+                },
+                WM_TRAY_NOTIFY => {
+                    self.on_tray_notify(hwnd, (lparam.0 & 0xFFFF) as u32);
+                },
+                WM_TIMER if wparam.0 == ID_TIMER_TRAY_CLICK => {
+                    let _ = KillTimer(Some(hwnd), ID_TIMER_TRAY_CLICK);
+                    self.run_tray_action(hwnd, self.settings.tray().click);
+                },
                 WM_HOOK_TRIGGER => {
                     let process_info = utils::receive_window_message::<ProcessInfo>(wparam);
                     let board_name = self.settings.detect(process_info.get_name());
@@ -181,15 +314,79 @@ impl Application {
                     self.show_board(board_name, params, self.settings.timeout() as u32).unwrap_or_default();
 
                 },
+                WM_FOREGROUND_TRIGGER => {
+                    let process_info = utils::receive_window_message::<ProcessInfo>(wparam);
+                    if let Some(auto_switch) = self.settings.auto_switch() {
+                        // Rapid focus changes keep restarting the timer, only the last one wins
+                        self.pending_switch = Some(process_info);
+                        SetTimer(Some(hwnd), ID_TIMER_AUTO_SWITCH, auto_switch.debounce as u32, None);
+                    }
+                },
+                WM_TIMER if wparam.0 == ID_TIMER_AUTO_SWITCH => {
+                    let _ = KillTimer(Some(hwnd), ID_TIMER_AUTO_SWITCH);
+                    if let Some(process_info) = self.pending_switch.take() {
+                        self.auto_switch(process_info);
+                    }
+                },
+                WM_HOTKEY_TRIGGER => {
+                    let index = utils::receive_window_message::<usize>(wparam);
+                    match self.hotkey_targets.get(index).cloned() {
+                        Some(HotkeyTarget::BoardToggle) => self.toggle_board(),
+                        Some(HotkeyTarget::Board(board_name)) => match self.hotkey_board(index) {
+                            Some(board_name) => {
+                                self.show_board(board_name, vec![], self.settings.timeout() as u32).unwrap_or_default();
+                            },
+                            None => log::warn!("The hotkey of board '{}' no longer opens a board", board_name),
+                        },
+                        None => log::warn!("Unknown hotkey index: {}", index),
+                    }
+                },
                 WM_BOARD_COMMAND => {
-                    self.handle_board_command(wparam.0);
+                    self.handle_board_command(wparam.0, lparam.0 as u16);
                 }
+                WM_KEY_RELEASE_TRIGGER => {
+                    let vk_code = utils::receive_window_message::<u16>(wparam);
+                    self.release_momentary_keys(vk_code);
+                },
+                WM_WAIT_TRIGGER | WM_PAD_REPEAT_TRIGGER => {
+                    // Runs superseded by a newer pad command have nothing left to resume
+                    if self.pending_run.as_ref().is_some_and(|(wait_id, _)| *wait_id == wparam.0) {
+                        let (_, run) = self.pending_run.take().unwrap();
+                        self.run_pad_actions(run);
+                    }
+                },
                 WM_BOARD_FINISHED => {
+                    self.board_manager.close_board();
+                },
+                WM_GO_HOME => {
                     self.board_manager.hide_board();
+                    self.show_board(self.settings.home_key_board(), vec![], 0).unwrap_or_default();
                 },
                 WM_UPDATE_LAYOUT => {
                     self.board_manager.save_layout();
                 }
+                WM_ZOOM_CHANGED => {
+                    self.board_manager.save_zoom();
+                }
+                WM_TIMER if wparam.0 == ID_TIMER_IDLE => {
+                    self.close_idle_board();
+                },
+                WM_TIMER if wparam.0 == ID_TIMER_SCHEDULE => {
+                    self.open_scheduled_board();
+                },
+                WM_SAVE_AND_CLOSE => {
+                    if self.settings.is_dirty() {
+                        if let Err(e) = self.settings.flush() {
+                            // Keep the board open, closing would hide that the changes are not saved
+                            MessageBoxW(Some(hwnd), &HSTRING::from(format!("Failed to save settings: {}", e)), &HSTRING::from("Error"), MB_OK | MB_ICONERROR);
+                            return LRESULT(0);
+                        }
+                        log::info!("Settings saved");
+                        self.register_hotkeys();
+                    }
+                    self.board_manager.close_board();
+                }
+                _ if self.tray.as_ref().is_some_and(|tray| tray.on_taskbar_created(msg)) => {},
                 _ => return DefWindowProcW(hwnd, msg, wparam, lparam)
             }
             LRESULT(0)
@@ -227,6 +424,21 @@ impl Application {
                             utils::send_window_message(HWND(hwnd as *mut c_void), WM_HOOK_TRIGGER, ProcessInfo::new(pinfo));
                         }
                     },
+                    Message::ForegroundEvt(pinfo) => {
+                        if let Some(hwnd) = main_hwnd {
+                            utils::send_window_message(HWND(hwnd as *mut c_void), WM_FOREGROUND_TRIGGER, ProcessInfo::new(pinfo));
+                        }
+                    },
+                    Message::HotKeyEvt(index) => {
+                        if let Some(hwnd) = main_hwnd {
+                            utils::send_window_message(HWND(hwnd as *mut c_void), WM_HOTKEY_TRIGGER, index);
+                        }
+                    },
+                    Message::KeyReleasedEvt(vk_code) => {
+                        if let Some(hwnd) = main_hwnd {
+                            utils::send_window_message(HWND(hwnd as *mut c_void), WM_KEY_RELEASE_TRIGGER, vk_code);
+                        }
+                    },
                     Message::Quit => { break; }
                 }
             }
@@ -236,35 +448,205 @@ impl Application {
 
     }
 
-    fn handle_board_command(&mut self, pad_id: usize) {
+    /// `held_key` is the key that selected a momentary pad and is still down, 0 otherwise
+    fn handle_board_command(&mut self, pad_id: usize, held_key: u16) {
 
         // Get selected pad and close window
-        let pad = self.board_manager.board.as_ref()
-            .map(|bw| bw.board().data()
-                .padset(Some(bw.modifier_state().clone()))
-                .flatten()
-                .pad(PadId::from_keypad_int(pad_id as i32)))
-            .unwrap_or_else(|| PadId::One.into());
+        let pad_id = PadId::from_keypad_int(pad_id as i32);
+        let Some(bw) = self.board_manager.board.as_ref() else { return };
+        let board = bw.board().data();
+        let (source, pad) = match board.pad_source(pad_id) {
+            // The palette's pads stand for pads of other boards, those run as their boards have them
+            Some(source) => match BoardHandle::new(self.settings.clone(), source.board.clone()).pads(Some(source.modifiers)) {
+                Ok(pads) => {
+                    let pad = pads.pad(source.pad_id);
+                    (source, pad)
+                },
+                Err(e) => {
+                    log::error!("Failed to read pad {} of board {}: {}", source.pad_id.to_string(), source.board, e);
+                    return;
+                }
+            },
+            None => {
+                let modifiers = bw.modifier_state().clone();
+                let pad = board.padset(Some(modifiers)).flatten().pad(pad_id);
+                (PadSource { board: board.name(), modifiers, pad_id }, pad)
+            },
+        };
+        if self.board_manager.in_cooldown(&source) {
+            log::debug!("Pad {} of board {} is in its cooldown, not run", source.pad_id.to_string(), source.board);
+            return;
+        }
 
-        self.board_manager.hide_board();
+        if let Some(prompt) = pad.confirm() {
+            let board = self.board_manager.board.as_ref().map(|bw| bw.board().data());
+            let color_scheme = board.map(|b| b.color_scheme()).unwrap_or_default();
+            let text_style = board.map(|b| b.text_style()).unwrap_or_default();
 
-        // Execute actions first
-        let mut needs_reload = false;
-        let mut needs_restart = false;
+            // Nothing runs until confirmed, the question replaces the current board
+            self.board_manager.hide_board();
+            let confirm_board = ConfirmPadBoard::new(prompt, pad.data.clone(), color_scheme, text_style);
+            self.board_manager.show_board(Box::new(StateMachineBoard::new(Box::new(confirm_board))), 0, self.settings.feedback());
+            return;
+        }
 
+        // A pad repeated 0 times does nothing but open its board
+        let actions = match pad.repeat() {
+            0 => vec![],
+            _ => resolve_conditions(pad.actions(), local_minute_of_day()),
+        };
+        let text_style = actions.iter().find_map(|action| match action {
+            ActionType::SwitchTextStyle(style_name) => Some(style_name.clone()),
+            _ => None,
+        });
+        if let Some(style_name) = text_style {
+            self.switch_text_style(&source.board, &style_name);
+            return;
+        }
+
+        if let Some(cooldown) = pad.cooldown() {
+            self.board_manager.start_cooldown(source, cooldown);
+        }
+        // The run keeps the target, a board opened while it is parked starts a session of its own
+        let target_window = self.board_manager.target_window;
+        self.board_manager.close_board();
+
+        if !actions.is_empty() && !self.activate_target_window(target_window) {
+            return;
+        }
+
+        if self.pending_run.take().is_some() {
+            log::info!("Remaining actions of the previous pad dropped, it was still running");
+        }
+        let runs_left = pad.repeat().saturating_sub(1);
+        hook::watch_escape(runs_left > 0 || actions.iter().any(|action| matches!(action, ActionType::Pause(_))));
+        self.run_pad_actions(PadRun { pad, actions, target_window, held_key, next_action: 0, runs_left, needs_reload: false, needs_restart: false });
+    }
+
+    /// Changes the text style of the pad's board and keeps the displayed board open, an unknown style shows an error board instead
+    fn switch_text_style(&mut self, board_name: &str, style_name: &str) {
+        let Some(bw) = self.board_manager.board.as_mut() else { return };
+        bw.clear_selection();
+
+        let result = match self.settings.get_text_style(style_name) {
+            Some(_) => BoardHandle::new(self.settings.clone(), board_name.to_string())
+                .set_text_style(Some(style_name.to_string())),
+            None => Err(format!("Text style \"{}\" not found", style_name).into()),
+        };
+
+        match result {
+            Ok(()) => self.board_manager.redraw_board(),
+            Err(e) => {
+                log::error!("Failed to switch text style: {}", e);
+                let error = error_board(format!("{}", e), bw.board());
+                self.board_manager.hide_board();
+                self.board_manager.show_board(Box::new(StateMachineBoard::new(Box::new(error))), 0, self.settings.feedback());
+            }
+        }
+    }
+
+    /// Reactivates the window the board was opened over, so the pad's input doesn't land elsewhere.
+    /// Returns false when that window was closed meanwhile and `closed_target` says to abort.
+    fn activate_target_window(&self, target_window: Option<isize>) -> bool {
+        let Some(hwnd) = target_window else { return true };
+        if hook::activate_window(hwnd) {
+            return true;
+        }
+
+        match self.settings.closed_target() {
+            ClosedTarget::Abort => {
+                log::warn!("Target window closed while the board was open, pad actions not run");
+                false
+            },
+            ClosedTarget::Foreground => {
+                log::info!("Target window closed while the board was open, running pad actions in the current foreground window");
+                true
+            },
+        }
+    }
+
+    /// Gives the focus back to the window the board was opened over, e.g. after an action opened another window
+    fn focus_previous_window(&self, target_window: Option<isize>) {
+        match target_window {
+            Some(hwnd) if hook::activate_window(hwnd) => {},
+            Some(_) => log::warn!("Previous window closed while the pad was running, focus not restored"),
+            None => log::info!("No previous window to focus"),
+        }
+    }
+
+    /// Minimizes, maximizes, closes or snaps the window the pad's board was opened over
+    fn run_window_command(&self, target_window: Option<isize>, command: WindowCommand) {
+        match target_window {
+            Some(hwnd) if hook::apply_window_command(hwnd, command) => log::debug!("Window command {} applied", command),
+            Some(_) => log::warn!("Previous window closed while the pad was running, {} not applied", command),
+            None => log::info!("No previous window for {}", command),
+        }
+    }
+
+    /// Runs the pad's actions from `next_action` on, then reloads, restarts or navigates as requested.
+    /// Stops at pauses and the actions waiting for the clipboard, their worker thread resumes the run when it finishes.
+    fn run_pad_actions(&mut self, mut run: PadRun) {
+        while let Some(action_type) = run.actions.get(run.next_action).cloned() {
+            if hook::escape_pressed() {
+                log::info!("Pad stopped with Escape, {} actions and {} runs left", run.actions.len() - run.next_action, run.runs_left);
+                run.runs_left = 0;
+                break;
+            }
+            run.next_action += 1;
+
+            let wait_id = match &action_type {
+                ActionType::Pause(pause) if *pause > 0 => {
+                    let pause = Duration::from_millis(*pause);
+                    self.start_wait(move || thread::sleep(pause))
+                },
+                ActionType::WaitForClipboard { timeout_ms } => {
+                    let timeout = Duration::from_millis(*timeout_ms);
+                    self.start_wait(move || { wait_for_clipboard_change(timeout); })
+                },
+                ActionType::WrapSelection { prefix, suffix } => {
+                    let (prefix, suffix) = (prefix.clone(), suffix.clone());
+                    self.start_wait(move || wrap_selection(&prefix, &suffix))
+                },
+                _ => None,
+            };
+            if let Some(wait_id) = wait_id {
+                self.pending_run = Some((wait_id, run));
+                return;
+            }
+
+            if matches!(action_type, ActionType::FocusPrevious) {
+                self.focus_previous_window(run.target_window);
+                continue;
+            }
+            if let ActionType::WindowCommand(command) = action_type {
+                self.run_window_command(run.target_window, command);
+                continue;
+            }
+
+            // Only pads with a behavior hold their keys, the others run shortcuts as regular actions
+            let shortcut = match &action_type {
+                _ if run.pad.behavior().is_none() => None,
+                ActionType::Shortcut(text) => Some(KeySequence::parse(text)),
+                ActionType::ShortcutKeys { keys, .. } => Some(KeySequence::from_combinations(keys.iter().map(|chord| chord.combination()))),
+                _ => None,
+            };
+            if let Some(keys) = shortcut {
+                if self.hold_shortcut(run.pad.behavior(), keys, run.held_key) {
+                    continue;
+                }
+            }
 
-        for action_type in pad.actions() {
             let action_factory_registry = &self.action_factory_registry;
             let action_factory = ActionFactoryImpl::new(self.settings.clone(), action_factory_registry);
-            let action = action_factory.create_action(action_type);
+            let action = action_factory.create_action(&action_type);
 
             match action.run() {
                 crate::app::action_factory::ActionResult::Success => {
                     if action.requires_reload() {
-                        needs_reload = true;
+                        run.needs_reload = true;
                     }
                     if action.requires_restart() {
-                        needs_restart = true;
+                        run.needs_restart = true;
                     }
                 },
                 crate::app::action_factory::ActionResult::Error(err) => {
@@ -273,6 +655,27 @@ impl Application {
             }
         }
 
+        // Each further run starts from the message loop, so the keys pressed meanwhile reach the hook and Escape stops it
+        if run.runs_left > 0 {
+            if hook::escape_pressed() {
+                log::info!("Repeating pad stopped with Escape, {} runs left", run.runs_left);
+            } else if let Some(run_id) = self.post_next_run() {
+                run.runs_left -= 1;
+                run.next_action = 0;
+                self.pending_run = Some((run_id, run));
+                return;
+            }
+        }
+        hook::watch_escape(false);
+
+        let PadRun { pad, target_window, held_key, needs_reload, needs_restart, .. } = run;
+
+        // Released right away if the key went up while the actions ran
+        let holding = self.momentary_keys.as_ref().is_some_and(|(vk_code, _)| held_key != 0 && *vk_code == held_key);
+        if holding && !hook::watch_key_release(held_key) {
+            self.release_momentary_keys(held_key);
+        }
+
         // Handle reload if any action requested it
         if needs_reload {
             self.settings.reload().unwrap_or_default();
@@ -285,12 +688,331 @@ impl Application {
             return; // Exit early since we're restarting
         }
 
-        // Handle board navigation (only if not restarting)
+        // Handle board navigation (only if not restarting), the board continues the session the pad was selected in
         if let Some(ref board_name) = pad.board() {
             if let Ok(board) = self.settings.get_board(board_name) {
+                self.board_manager.continue_session(target_window);
                 self.show_board(board.name, pad.board_params().to_vec(), 0).unwrap_or_default();
             }
         }
+
+        // The pad's board failed to open
+        if self.board_manager.board.is_none() {
+            self.board_manager.end_session();
+        }
+    }
+
+    /// Runs `wait` off the main thread, blocking it would stall the keyboard hook (and the copy being waited for)
+    /// and keep Escape from stopping the pad.
+    /// Returns the id the finished wait reports back with, None when there is no window to report to.
+    fn start_wait(&mut self, wait: impl FnOnce() + Send + 'static) -> Option<usize> {
+        let hwnd_val = self.main_hwnd?.0 as usize;
+        self.parked_runs += 1;
+        let wait_id = self.parked_runs;
+
+        thread::spawn(move || {
+            wait();
+            unsafe {
+                PostMessageW(
+                    Some(HWND(hwnd_val as *mut c_void)),
+                    WM_WAIT_TRIGGER,
+                    WPARAM(wait_id),
+                    LPARAM(0)
+                ).unwrap_or_default();
+            }
+        });
+        Some(wait_id)
+    }
+
+    /// Asks the message loop for the next run of a repeating pad, returns the id it reports back with
+    fn post_next_run(&mut self) -> Option<usize> {
+        let hwnd = self.main_hwnd?;
+        self.parked_runs += 1;
+        unsafe { PostMessageW(Some(hwnd), WM_PAD_REPEAT_TRIGGER, WPARAM(self.parked_runs), LPARAM(0)) }.ok()?;
+        Some(self.parked_runs)
+    }
+
+    /// Presses the keys of a momentary or toggle pad's shortcut without releasing them.
+    /// Returns false for fire-once shortcuts, which run as regular actions.
+    fn hold_shortcut(&mut self, behavior: Option<PadBehavior>, keys: KeySequence, held_key: u16) -> bool {
+        match behavior {
+            Some(PadBehavior::Momentary) if held_key != 0 => {
+                // A hold whose release was missed must not leave keys stuck
+                let stale_key = self.momentary_keys.as_ref().map(|(vk_code, _)| *vk_code).filter(|vk_code| *vk_code != held_key);
+                if let Some(vk_code) = stale_key {
+                    self.release_momentary_keys(vk_code);
+                }
+                script::for_keys_press(&keys).play();
+                self.momentary_keys.get_or_insert_with(|| (held_key, vec![])).1.push(keys);
+                true
+            },
+            Some(PadBehavior::Toggle) => {
+                match self.latched_keys.iter().position(|latched| *latched == keys) {
+                    Some(index) => script::for_keys_release(&self.latched_keys.remove(index)).play(),
+                    None => {
+                        script::for_keys_press(&keys).play();
+                        self.latched_keys.push(keys);
+                    }
+                }
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn release_momentary_keys(&mut self, vk_code: u16) {
+        if self.momentary_keys.as_ref().is_some_and(|(held_key, _)| *held_key == vk_code) {
+            let (_, shortcuts) = self.momentary_keys.take().unwrap();
+            shortcuts.iter().rev().for_each(|keys| script::for_keys_release(keys).play());
+        }
+    }
+
+    /// Nothing stays pressed once the application exits
+    fn release_held_keys(&mut self) {
+        if let Some((vk_code, _)) = self.momentary_keys {
+            self.release_momentary_keys(vk_code);
+        }
+        self.latched_keys.drain(..).rev().for_each(|keys| script::for_keys_release(&keys).play());
+    }
+
+    fn show_safe_mode(&mut self, error: String) {
+        let board = SafeModeBoard::new(error, self.settings.get_resources().clone(), self.settings.clone());
+        self.board_manager.show_board(Box::new(StateMachineBoard::new(Box::new(board))), 0, self.settings.feedback());
+    }
+
+    /// Shows the release notes once the running version is newer than the last seen one
+    fn show_whats_new(&mut self) -> bool {
+        let entries = unseen_entries(self.settings.last_seen_version().as_deref());
+        if entries.is_empty() {
+            return false;
+        }
+
+        let board = WhatsNewBoard::new(self.settings.clone(), entries);
+        self.board_manager.show_board(Box::new(StateMachineBoard::new(Box::new(board))), 0, self.settings.feedback());
+        true
+    }
+
+    /// Registers the board toggle hotkey and the hotkeys opening boards.
+    /// A hotkey already registered is logged as a conflict and skipped, the first registration wins.
+    fn register_hotkeys(&mut self) {
+        let toggle = self.settings.board_toggle().map(|toggle| (toggle.hotkey, HotkeyTarget::BoardToggle));
+        let boards = self.settings.boards().into_iter()
+            .filter_map(|board_name| self.settings.get_board(&board_name).ok())
+            .filter_map(|board| board.hotkey.map(|hotkey| (hotkey, HotkeyTarget::Board(board.name))));
+
+        let mut hotkeys = vec![];
+        let mut targets: Vec<HotkeyTarget> = vec![];
+        for (text, target) in toggle.into_iter().chain(boards) {
+            let Some(hotkey) = parse_hotkey(&text) else {
+                log::warn!("Invalid hotkey '{}' of {}", text, target);
+                continue;
+            };
+            match hotkeys.iter().position(|registered| *registered == hotkey) {
+                Some(index) => log::warn!("Hotkey '{}' of {} conflicts with {}, not registered", text, target, targets[index]),
+                None => {
+                    hotkeys.push(hotkey);
+                    targets.push(target);
+                },
+            }
+        }
+        self.hotkey_targets = targets;
+        hook::set_hotkeys(hotkeys);
+    }
+
+    /// The board with the hotkey reported, looked up when it fires since the board may have been renamed or deleted
+    /// after the hotkeys were registered. Hotkeys added since take effect when the settings are saved.
+    fn hotkey_board(&self, index: usize) -> Option<String> {
+        let hotkey = hook::hotkey(index)?;
+        self.settings.boards().into_iter().find(|board_name| {
+            self.settings.get_board(board_name).ok()
+                .and_then(|board| board.hotkey)
+                .and_then(|text| parse_hotkey(&text)) == Some(hotkey)
+        })
+    }
+
+    /// Watches foreground changes only while auto switching is configured
+    fn update_foreground_watcher(&mut self) {
+        if self.settings.auto_switch().is_some() {
+            hook::install_foreground_watcher();
+        } else {
+            hook::uninstall_foreground_watcher();
+            self.pending_switch = None;
+        }
+    }
+
+    /// Remembers the last active board and the recently used ones for the next start. Skipped when the user chose
+    /// to close without saving, flushing would write their discarded changes as well.
+    fn save_board_history(&mut self) {
+        let last_board = self.last_board.take()
+            .filter(|_| self.settings.restore_last_board())
+            .filter(|board_name| self.settings.last_board().as_deref() != Some(board_name.as_str()));
+        let recent_boards_changed = std::mem::take(&mut self.recent_boards_changed);
+        if last_board.is_none() && !recent_boards_changed {
+            return;
+        }
+        if self.settings.is_dirty() {
+            log::info!("Last and recent boards not saved, settings have unsaved changes");
+            return;
+        }
+
+        match last_board {
+            Some(board_name) => self.settings.set_last_board(&board_name),
+            None => self.settings.mark_dirty(),
+        }
+        if let Err(e) = self.settings.flush() {
+            log::error!("Failed to save last and recent boards: {}", e);
+        }
+    }
+
+    /// Applies the level chosen in the settings, re-reading the log configuration file
+    fn update_log_level(&self) {
+        if let Err(e) = set_log_level(self.settings.log_level()) {
+            log::error!("Failed to apply log level: {}", e);
+        }
+    }
+
+    /// The idle check runs on the main window, board windows handle their own timers
+    fn update_idle_timer(&self) {
+        let Some(hwnd) = self.main_hwnd else { return };
+        unsafe {
+            if self.settings.idle_timeout().is_some() {
+                SetTimer(Some(hwnd), ID_TIMER_IDLE, IDLE_CHECK_INTERVAL, None);
+            } else {
+                let _ = KillTimer(Some(hwnd), ID_TIMER_IDLE);
+            }
+        }
+    }
+
+    /// Closes the board once it went without input for `idle_timeout` seconds.
+    /// Boards with an open dialog or unsaved changes are being edited and stay open.
+    fn close_idle_board(&mut self) {
+        let Some(idle_timeout) = self.settings.idle_timeout() else { return };
+        if self.settings.is_dirty() {
+            return;
+        }
+
+        let idle_time = self.board_manager.board.as_ref().and_then(|board_window| board_window.idle_time());
+        if idle_time.is_some_and(|idle_time| idle_time.as_secs() >= idle_timeout) {
+            log::info!("Closing board after {}s without input", idle_timeout);
+            self.board_manager.close_board();
+        }
+    }
+
+    fn update_schedule_timer(&self) {
+        let Some(hwnd) = self.main_hwnd else { return };
+        unsafe {
+            if !self.settings.schedule().is_empty() {
+                SetTimer(Some(hwnd), ID_TIMER_SCHEDULE, SCHEDULE_CHECK_INTERVAL, None);
+            } else {
+                let _ = KillTimer(Some(hwnd), ID_TIMER_SCHEDULE);
+            }
+        }
+    }
+
+    /// Opens the board scheduled for the current minute, each minute is handled once.
+    /// Times passed while the app wasn't running or the system was asleep are skipped, not caught up on.
+    fn open_scheduled_board(&mut self) {
+        let minute = local_minute_of_day();
+        if self.last_schedule_check.replace(minute) == Some(minute) {
+            return;
+        }
+
+        let Some(entry) = self.settings.schedule().into_iter().find(|entry| entry.minute_of_day() == Some(minute)) else { return };
+        if self.settings.is_dirty() {
+            log::info!("Scheduled board '{}' not opened, settings have unsaved changes", entry.board);
+            return;
+        }
+
+        log::info!("Opening board '{}' scheduled for {}", entry.board, entry.time);
+        self.board_manager.close_board();
+        if let Err(err) = self.show_board(entry.board.clone(), vec![], 0) {
+            log::error!("Failed to open board '{}' scheduled for {}: {}", entry.board, entry.time, err);
+        }
+    }
+
+    /// Replaces an idle board with the one detected for the foreground application.
+    /// Editors, dialogs and boards with a held modifier are left alone.
+    fn auto_switch(&mut self, process_info: ProcessInfo) {
+        let Some(auto_switch) = self.settings.auto_switch() else { return };
+        let Some(board_name) = self.settings.detect(process_info.get_name()) else { return };
+
+        match self.board_manager.board.as_ref() {
+            Some(board_window) => {
+                let current = board_window.board().data().name();
+                let idle = self.settings.get_board(&current).is_ok() && board_window.modifier_state().is_none();
+                if !idle || current == board_name {
+                    return;
+                }
+                // Opened for another application, whose window the new board's pads go to
+                log::info!("Switching to board '{}' for process '{}'", board_name, process_info.get_name());
+                self.board_manager.close_board();
+                self.show_board(board_name, vec![], 0).unwrap_or_default();
+            },
+            None if auto_switch.open => {
+                log::info!("Opening board '{}' for process '{}'", board_name, process_info.get_name());
+                self.show_board(board_name, vec![], self.settings.timeout() as u32).unwrap_or_default();
+            },
+            None => return,
+        }
+
+        // Showing the board took the focus, give it back to the application the user switched to
+        unsafe {
+            let _ = SetForegroundWindow(HWND(process_info.hwnd as *mut c_void));
+        }
+    }
+
+    /// Maps mouse input on the tray icon to the configured actions, a right click always opens the menu.
+    /// While a double click is mapped, a single click waits out the double-click time so only one of them runs.
+    fn on_tray_notify(&mut self, hwnd: HWND, mouse_msg: u32) {
+        let tray = self.settings.tray();
+        match mouse_msg {
+            WM_LBUTTONUP if self.tray_double_clicked => {
+                // Release of the second click of a double click
+                self.tray_double_clicked = false;
+            },
+            WM_LBUTTONUP if tray.double_click == TrayAction::None => {
+                self.run_tray_action(hwnd, tray.click);
+            },
+            WM_LBUTTONUP => unsafe {
+                SetTimer(Some(hwnd), ID_TIMER_TRAY_CLICK, GetDoubleClickTime(), None);
+            },
+            // Without a double click action the second of two quick clicks is a click too, run on its release
+            WM_LBUTTONDBLCLK if tray.double_click == TrayAction::None => {},
+            WM_LBUTTONDBLCLK => {
+                unsafe {
+                    let _ = KillTimer(Some(hwnd), ID_TIMER_TRAY_CLICK);
+                }
+                self.tray_double_clicked = true;
+                self.run_tray_action(hwnd, tray.double_click);
+            },
+            WM_MBUTTONUP => self.run_tray_action(hwnd, tray.middle_click),
+            WM_RBUTTONUP => self.run_tray_action(hwnd, TrayAction::Menu),
+            _ => {},
+        }
+    }
+
+    fn run_tray_action(&self, hwnd: HWND, action: TrayAction) {
+        let msg = match action {
+            TrayAction::None => return,
+            TrayAction::Menu => return tray_menu(hwnd, self.settings.log_level()),
+            TrayAction::Home => WM_SHOW_APPLICATION,
+            TrayAction::Settings => WM_OPEN_SETTINGS,
+        };
+        unsafe {
+            PostMessageW(Some(hwnd), msg, WPARAM(0), LPARAM(0)).unwrap_or_default();
+        }
+    }
+
+    /// Shows the other board of the A/B toggle pair, starting with the first one
+    fn toggle_board(&mut self) {
+        if let Some(toggle) = self.settings.board_toggle() {
+            let next = match self.toggled_board {
+                Some(0) => 1,
+                _ => 0,
+            };
+            self.toggled_board = Some(next);
+            self.show_board(toggle.boards[next].clone(), vec![], self.settings.timeout() as u32).unwrap_or_default();
+        }
     }
 
     fn open_settings_editor(&self) {
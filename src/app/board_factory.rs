@@ -2,7 +2,8 @@ use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::core::{BoardType, Param, Resources, SettingsRepository, SettingsRepositoryMut, Params};
-use crate::components::{ BoardComponent, HomeBoard, MainBoard, SettingsBoard, StateMachineBoard };
+use crate::components::{ BoardComponent, HomeBoard, KeyTesterBoard, MainBoard, RecentBoardsBoard, SelfTestBoard, SettingsBoard, StateMachineBoard, TemplateBoard };
+use crate::model::chain_members;
 
 pub struct BoardRuntimeContext<R: SettingsRepository + SettingsRepositoryMut> {
     pub repository: Rc<R>,
@@ -53,12 +54,21 @@ impl<'a, R: SettingsRepository + SettingsRepositoryMut + 'static> BoardFactoryIm
     }
 
     pub fn create_board(&self, name: &str, dynamic_params: Vec<Param>) -> Result<Box<dyn BoardComponent>, Box<dyn std::error::Error>> {
+        if name == crate::core::data::SELFTEST_BOARD_NAME {
+            return Ok(Box::new(StateMachineBoard::new(Box::new(SelfTestBoard::new(self.repository.clone())))));
+        }
+        if name == crate::core::data::KEYTEST_BOARD_NAME {
+            return Ok(Box::new(StateMachineBoard::new(Box::new(KeyTesterBoard::new(self.repository.clone())))));
+        }
+
         let board = self.repository.get_board(name)?;
         let context = BoardRuntimeContext { repository: self.repository.clone(), resources: self.resources.clone() };
         match &board.board_type {
             BoardType::Static => create_main_board(&context, &board, dynamic_params),
             BoardType::Home => create_home_board(&context, &board, dynamic_params),
-            BoardType::Chain(params) => create_board_chain(&context, params.merge_params(dynamic_params).into()),
+            BoardType::Recent => create_recent_board(&context, &board),
+            BoardType::Chain(params) => create_board_chain(&context, &board, params.merge_params(dynamic_params).into()),
+            BoardType::Template(params) => create_template_board(&context, &board, params, dynamic_params),
             BoardType::Custom(params) => {
                 match self.registry.get_factory(&params.board_type) {
                     Some(factory) => factory.create_board(&context, &board, params.merge_params(dynamic_params)),
@@ -137,16 +147,29 @@ fn create_main_board<R: SettingsRepository + SettingsRepositoryMut + 'static>(
 
 fn create_board_chain<R: SettingsRepository + SettingsRepositoryMut + 'static>(
     context: &BoardRuntimeContext<R>,
+    board: &crate::core::Board,
     dynamic_params: crate::core::integration::ChainParams
 ) -> Result<Box<dyn BoardComponent>, Box<dyn std::error::Error>> {
 
+    // Nested collections are flattened, an initial collection starts at its first board
+    let repository = context.repository.as_ref();
+    let boards = chain_members(repository, &board.name, &dynamic_params.boards())?;
+    if boards.is_empty() {
+        return Err(format!("Collection '{}' has no boards", board.name).into());
+    }
+    let initial_board = dynamic_params.initial_board
+        .map(|initial| chain_members(repository, &board.name, &[initial.clone()])
+            .ok()
+            .and_then(|members| members.into_iter().next())
+            .unwrap_or(initial));
+
     Ok(
         Box::new(
             StateMachineBoard::new(
                 Box::new(
                     crate::components::BoardChain::new(
-                        dynamic_params.boards(),
-                        dynamic_params.initial_board,
+                        boards,
+                        initial_board,
                         dynamic_params.params,
                         context.resources.clone(),
                         context.repository.clone()
@@ -155,4 +178,54 @@ fn create_board_chain<R: SettingsRepository + SettingsRepositoryMut + 'static>(
             )
         )
     )
-}
\ No newline at end of file
+}
+
+
+
+fn create_recent_board<R: SettingsRepository + SettingsRepositoryMut + 'static>(
+    context: &BoardRuntimeContext<R>,
+    board: &crate::core::Board,
+) -> Result<Box<dyn BoardComponent>, Box<dyn std::error::Error>> {
+
+    Ok(
+        Box::new(
+            StateMachineBoard::new(
+                Box::new(
+                    RecentBoardsBoard::new(
+                        board.name.clone(),
+                        context.repository.clone()
+                    )
+                )
+            )
+        )
+    )
+}
+
+
+
+fn create_template_board<R: SettingsRepository + SettingsRepositoryMut + 'static>(
+    context: &BoardRuntimeContext<R>,
+    board: &crate::core::Board,
+    template: &crate::core::integration::TemplateParams,
+    dynamic_params: Vec<Param>
+) -> Result<Box<dyn BoardComponent>, Box<dyn std::error::Error>> {
+
+    if template.items(&dynamic_params).is_empty() {
+        return Err(format!("Template board '{}' has no items", board.name).into());
+    }
+
+    Ok(
+        Box::new(
+            StateMachineBoard::new(
+                Box::new(
+                    TemplateBoard::new(
+                        board.name.clone(),
+                        template.clone(),
+                        dynamic_params,
+                        context.repository.clone()
+                    )
+                )
+            )
+        )
+    )
+}
@@ -1,16 +1,29 @@
-use std::rc::Rc;
+use std::{ffi::c_void, rc::Rc, time::{Duration, Instant}};
+
+use windows::Win32::{Foundation::{HWND, LPARAM, WPARAM}, UI::WindowsAndMessaging::PostMessageW};
 
 use crate::{
     components::{BoardComponent, PadMapping},
+    core::{self, data::DEFAULT_ZOOM, SettingsRepository, WindowRect, WindowSizing},
+    model::{BoardHandle, PadSource},
     settings::{LayoutSettings, Settings},
-    ui::shared::layout::{Rect, WindowLayout, WindowStyle}
+    ui::{components::{painter::{content_size, icon_requests}, warmup::warm_up_icons}, shared::layout::{dpi_scale, Rect, WindowLayout, WindowStyle}}
 };
 
-use super::windows::BoardWindow;
+use super::{hook, windows::{BoardWindow, BoardWindowOptions, PadCooldown, WM_ICONS_WARMED}};
 
 pub struct BoardManager {
     pub board: Option<Box<BoardWindow<Settings>>>,
     pub settings: Rc<Settings>,
+    /// Window the displayed board was opened over, pad actions are sent to it.
+    /// Recorded when a session's first board opens, the boards replacing it keep it.
+    pub target_window: Option<isize>,
+    /// Boards shown until `close_board` or `end_session` belong to the same session
+    session_open: bool,
+    /// How the displayed board's window was sized when it opened
+    sizing: WindowSizing,
+    /// Pads that ran on any board and are still in their cooldown, they outlive the board windows
+    cooldowns: Vec<PadCooldown>,
 }
 
 impl BoardManager {
@@ -19,6 +32,10 @@ impl BoardManager {
         Self {
             board: None,
             settings,
+            target_window: None,
+            session_open: false,
+            sizing: WindowSizing::Fixed,
+            cooldowns: vec![],
         }
     }
 
@@ -26,22 +43,101 @@ impl BoardManager {
         self.settings.get_layout_settings().map(|ls| ls.into()).unwrap_or_default()
     }
 
+    /// The shared layout, at the board's own rect when it has one
+    fn board_layout(&self, board: Option<&core::Board>) -> WindowLayout {
+        match board.and_then(|b| b.rect) {
+            Some(rect) => WindowLayout { rect: rect.into(), ..self.layout() },
+            None => self.layout(),
+        }
+    }
+
     pub fn show_board(&mut self, board: Box<dyn BoardComponent>, timeout: u32, feedback: u64) {
         if let Some(ref mut _board) = self.board {
             log::warn!("Board already displayed, cannot create a new one");
             return;
         }
 
+        // Boards replacing one another keep the window the first one was opened over
+        if !self.session_open {
+            self.target_window = hook::foreground_target();
+            self.session_open = true;
+        }
+
+        let board_settings = self.settings.get_board(&board.data().name()).ok();
+        let zoom = board_settings.as_ref()
+            .map(|b| b.zoom())
+            .unwrap_or(DEFAULT_ZOOM);
+
+        // Boards pinned to a monitor open there, wherever the last board was moved to
+        let layout = self.board_layout(board_settings.as_ref());
+        let layout = match board_settings.and_then(|b| b.monitor) {
+            Some(monitor) => layout.on_monitor(&monitor),
+            None => layout,
+        };
+        // The window draws the board at its zoom on the DPI scale of the monitor
+        let scale = zoom * dpi_scale(layout.dpi());
+
+        let icon_requests = icon_requests(board.data(), scale);
+
+        self.sizing = board.data().sizing();
+        // Saved on a monitor that has since been disconnected or shrunk, the board still opens on screen
+        let layout = layout.sized(self.sizing, scale, || content_size(board.data(), scale)).on_screen(scale);
+
         self.board = Some(BoardWindow::new(
             "HotKeys",
-            self.layout(),
+            layout,
             board,
             timeout,
-            feedback,
-            PadMapping::new(self.settings.clone())
+            PadMapping::new(self.settings.clone()),
+            zoom,
+            self.window_options(feedback),
         ).unwrap());
+
+        // The first paint leaves out the icons still being rasterized, the window paints again once they are ready.
+        // Posted rather than invalidated from the warmup thread, so the repaint is queued behind the first paint.
+        if let Some(ref window) = self.board {
+            let hwnd = window.hwnd();
+            warm_up_icons(icon_requests, self.settings.get_resources(), move || unsafe {
+                let _ = PostMessageW(Some(HWND(hwnd as *mut c_void)), WM_ICONS_WARMED, WPARAM(0), LPARAM(0));
+            });
+        }
+
+        self.cooldowns.retain(|cooldown| !cooldown.is_over());
+        if let Some(ref mut window) = self.board {
+            let board_name = window.board().data().name();
+            window.set_cooldowns(self.cooldowns.iter().filter(|cooldown| cooldown.board == board_name).cloned().collect());
+        }
+    }
+
+    /// Starts the cooldown of a pad, it outlives the board it was selected on
+    pub fn start_cooldown(&mut self, source: PadSource, length: Duration) {
+        self.cooldowns.retain(|cooldown| !cooldown.is_of(&source));
+        self.cooldowns.push(PadCooldown { board: source.board, modifiers: source.modifiers, pad_id: source.pad_id, started: Instant::now(), length });
+    }
+
+    /// Whether the pad ran recently and its cooldown isn't over yet
+    pub fn in_cooldown(&self, source: &PadSource) -> bool {
+        self.cooldowns.iter().any(|cooldown| cooldown.is_of(source) && !cooldown.is_over())
+    }
+
+    fn window_options(&self, feedback: u64) -> BoardWindowOptions {
+        BoardWindowOptions {
+            feedback,
+            flash_feedback: self.settings.flash_feedback(),
+            show_cooldown: self.settings.show_cooldown(),
+            save_and_close_key: self.settings.save_and_close_key(),
+            fast_render: self.settings.fast_render(),
+            renderer: self.settings.renderer(),
+            modifier_grace: self.settings.modifier_grace(),
+            help_key: self.settings.help_key(),
+            stack_key: self.settings.stack_key(),
+            focus_key: self.settings.focus_key(),
+            home_key: self.settings.home_key(),
+            home_board: self.settings.home_key_board(),
+        }
     }
 
+    /// Hides the board to replace it with another one of the same session
     pub fn hide_board(&mut self) {
         if let Some(ref mut board) = self.board {
             board.hide();
@@ -49,9 +145,52 @@ impl BoardManager {
         }
     }
 
+    /// Hides the board for good, the next board starts a new session over the window in the foreground then
+    pub fn close_board(&mut self) {
+        self.hide_board();
+        self.end_session();
+    }
+
+    /// Reopens a closed session, e.g. for the board a pad navigates to once its actions ran
+    pub fn continue_session(&mut self, target_window: Option<isize>) {
+        if self.board.is_none() {
+            self.target_window = target_window;
+            self.session_open = true;
+        }
+    }
+
+    pub fn end_session(&mut self) {
+        self.session_open = false;
+        self.target_window = None;
+    }
+
+    /// Persists the layout of the displayed board, as its own rect and as the layout of the boards without one.
+    /// A fitted window's size is its board's own, only where it was moved to is kept for the other boards.
     pub fn save_layout(&mut self) {
-        if let Some(ref mut board) = self.board {
-            self.settings.set_layout_settings(board.layout().clone().into());
+        if let Some(ref board) = self.board {
+            let (own, shared) = match self.sizing {
+                WindowSizing::Fixed => (board.base_layout(), board.base_layout()),
+                WindowSizing::Fit => (
+                    board.base_layout().centered_on(&board.layout().rect),
+                    self.layout().centered_on(&board.layout().rect),
+                ),
+            };
+            self.settings.set_layout_settings(shared.into());
+
+            let board_name = board.board().data().name();
+            if !self.settings.set_board_rect(&board_name, own.rect.into()) {
+                log::debug!("Window rect of board '{}' not saved, it is not in the settings", board_name);
+            }
+        }
+    }
+
+    /// Persists the zoom of the displayed board, boards that are not in the settings keep it for the session only
+    pub fn save_zoom(&mut self) {
+        if let Some(ref board) = self.board {
+            let board_name = board.board().data().name();
+            if let Err(e) = BoardHandle::new(self.settings.clone(), board_name.clone()).set_zoom(board.zoom()) {
+                log::debug!("Zoom of board '{}' not saved: {}", board_name, e);
+            }
         }
     }
 
@@ -78,6 +217,18 @@ impl Into<LayoutSettings> for WindowLayout {
     }
 }
 
+impl From<WindowRect> for Rect {
+    fn from(rect: WindowRect) -> Self {
+        Rect { left: rect.x, top: rect.y, right: rect.x + rect.width, bottom: rect.y + rect.height }
+    }
+}
+
+impl From<Rect> for WindowRect {
+    fn from(rect: Rect) -> Self {
+        WindowRect { x: rect.left, y: rect.top, width: rect.width(), height: rect.height() }
+    }
+}
+
 impl From<LayoutSettings> for WindowLayout {
     fn from(layout: LayoutSettings) -> Self {
         WindowLayout {
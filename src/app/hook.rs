@@ -1,4 +1,4 @@
-use std::{ffi::OsString, sync::{mpsc::Sender, Mutex, OnceLock}};
+use std::{ffi::OsString, mem, sync::{mpsc::Sender, Mutex, OnceLock}};
 use std::fmt::Display;
 use std::os::windows::ffi::OsStringExt;
 use std::path::Path;
@@ -6,19 +6,29 @@ use std::process;
 
 
 use windows::Win32::{
-    Foundation::{CloseHandle, HANDLE, HINSTANCE, LPARAM, LRESULT, RECT, WPARAM}, System::{
-        ProcessStatus::K32GetProcessImageFileNameW, Threading::{OpenProcess, PROCESS_ACCESS_RIGHTS, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ}
+    Foundation::{CloseHandle, HANDLE, HINSTANCE, HWND, LPARAM, LRESULT, RECT, WPARAM}, System::{
+        ProcessStatus::{K32EnumProcesses, K32GetProcessImageFileNameW},
+        Threading::{OpenProcess, PROCESS_ACCESS_RIGHTS, PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ}
     }, UI::{
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
         Input::KeyboardAndMouse::GetAsyncKeyState, WindowsAndMessaging::{
-            CallNextHookEx, GetForegroundWindow, GetWindowRect, GetWindowThreadProcessId, SetWindowsHookExW, UnhookWindowsHookEx, HHOOK, WH_KEYBOARD_LL
+            CallNextHookEx, GetForegroundWindow, GetWindowRect, GetWindowThreadProcessId, IsIconic, IsWindow, IsZoomed, PostMessageW, SetForegroundWindow, SetWindowPos, SetWindowsHookExW, ShowWindow, UnhookWindowsHookEx, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED, WH_KEYBOARD_LL,
+            EVENT_SYSTEM_FOREGROUND, SWP_NOACTIVATE, SWP_NOZORDER, SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS, WM_CLOSE
         }
     }
 };
 
 use crate::app::message::{Message, ProcessInfo};
+use crate::core::{slugify_process_name, WindowCommand};
+use crate::input::ModifierState;
+use crate::ui::shared::layout::window_work_area;
 
 static SENDER: OnceLock<Mutex<Option<Sender<Message>>>> = OnceLock::new();
 static HOOK: OnceLock<Mutex<Option<Hook>>> = OnceLock::new();
+static HOTKEYS: OnceLock<Mutex<Vec<(ModifierState, u16)>>> = OnceLock::new();
+static FOREGROUND_HOOK: OnceLock<Mutex<Option<ForegroundHook>>> = OnceLock::new();
+static RELEASE_WATCH: OnceLock<Mutex<Option<u16>>> = OnceLock::new();
+static ESCAPE_WATCH: OnceLock<Mutex<Option<bool>>> = OnceLock::new();
 
 pub struct ProcessHandle {
     handle: HANDLE,
@@ -78,6 +88,44 @@ impl Drop for Hook {
     }
 }
 
+/// Out-of-context WinEvent hook reporting foreground window changes of other processes.
+/// The callback runs on the installing thread, which has to pump messages.
+pub struct ForegroundHook {
+    hook: HWINEVENTHOOK
+}
+
+// SAFETY: HWINEVENTHOOK is a handle, it is only installed and removed from the UI thread.
+unsafe impl Send for ForegroundHook {}
+unsafe impl Sync for ForegroundHook {}
+
+impl ForegroundHook {
+    pub fn new() -> Option<ForegroundHook> {
+        let hook = unsafe {
+            SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                None,
+                Some(foreground_callback),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS)
+        };
+        if hook.is_invalid() {
+            None
+        } else {
+            Some(ForegroundHook { hook })
+        }
+    }
+}
+
+impl Drop for ForegroundHook {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = UnhookWinEvent(self.hook);
+        }
+    }
+}
+
 pub fn install(sender: Sender<Message>) {
     {
         let mut s = SENDER.get_or_init(|| Mutex::new(None)).lock().unwrap();
@@ -92,6 +140,18 @@ pub fn install(sender: Sender<Message>) {
     }
 }
 
+/// Replaces the additional global hotkeys watched by the hook.
+/// A match is reported as `Message::HotKeyEvt` with the hotkey's index in this list.
+pub fn set_hotkeys(hotkeys: Vec<(ModifierState, u16)>) {
+    let mut h = HOTKEYS.get_or_init(|| Mutex::new(vec![])).lock().unwrap();
+    *h = hotkeys;
+}
+
+/// The hotkey reported with the index, None when the hotkeys were replaced since
+pub fn hotkey(index: usize) -> Option<(ModifierState, u16)> {
+    HOTKEYS.get_or_init(|| Mutex::new(vec![])).lock().unwrap().get(index).copied()
+}
+
 pub fn uninstall() {
     {
         let mut hook = HOOK.get_or_init(|| Mutex::new(None)).lock().unwrap();
@@ -106,6 +166,56 @@ pub fn uninstall() {
     }
 }
 
+/// Starts reporting foreground changes as `Message::ForegroundEvt`, does nothing when already watching.
+/// Must be called from the thread running the message loop.
+pub fn install_foreground_watcher() {
+    let mut hook = FOREGROUND_HOOK.get_or_init(|| Mutex::new(None)).lock().unwrap();
+    if hook.is_none() {
+        *hook = ForegroundHook::new();
+        if hook.is_none() {
+            log::error!("Failed to install the foreground window watcher");
+        }
+    }
+}
+
+pub fn uninstall_foreground_watcher() {
+    let mut hook = FOREGROUND_HOOK.get_or_init(|| Mutex::new(None)).lock().unwrap();
+    hook.take();
+}
+
+/// Reports the next physical release of `vk_code` as `Message::KeyReleasedEvt`.
+/// Returns false when the key is already up, nothing is reported then.
+pub fn watch_key_release(vk_code: u16) -> bool {
+    let mut watch = RELEASE_WATCH.get_or_init(|| Mutex::new(None)).lock().unwrap();
+    *watch = Some(vk_code);
+
+    // Armed before checking, so a release in between is either seen here or by the hook
+    if unsafe { GetAsyncKeyState(vk_code as i32) }.is_negative() {
+        true
+    } else {
+        watch.take();
+        false
+    }
+}
+
+/// Watches for Escape while a repeating pad runs, a watched Escape is not passed on. `false` ends the watch.
+pub fn watch_escape(active: bool) {
+    *ESCAPE_WATCH.get_or_init(|| Mutex::new(None)).lock().unwrap() = active.then_some(false);
+}
+
+/// Whether Escape was pressed since the watch started
+pub fn escape_pressed() -> bool {
+    ESCAPE_WATCH.get_or_init(|| Mutex::new(None)).lock().unwrap().unwrap_or(false)
+}
+
+unsafe extern "system" fn foreground_callback(_hook: HWINEVENTHOOK, _event: u32, hwnd: HWND, _id_object: i32, _id_child: i32, _thread: u32, _time: u32) {
+    // Elevated and system processes can't be inspected, switching just doesn't happen for them
+    match window_process(hwnd) {
+        Ok(proc_info) => send_message(Message::ForegroundEvt(proc_info)),
+        Err(e) => log::trace!("Foreground window not inspected: {}", e),
+    }
+}
+
 unsafe extern "system" fn hook_callback(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
     unsafe {
         if is_ctrl_alt_numpad0(code, wparam, lparam) {
@@ -114,6 +224,15 @@ unsafe extern "system" fn hook_callback(code: i32, wparam: WPARAM, lparam: LPARA
                 trigger_hook_event(fgproc);
                 return LRESULT(1)
             }
+        } else if let Some(index) = find_hotkey(code, wparam, lparam) {
+            send_message(Message::HotKeyEvt(index));
+            return LRESULT(1)
+        } else if let Some(vk_code) = find_watched_release(code, wparam, lparam) {
+            // The target never saw the matching key down, so the release isn't passed on either
+            send_message(Message::KeyReleasedEvt(vk_code));
+            return LRESULT(1)
+        } else if is_watched_escape(code, wparam, lparam) {
+            return LRESULT(1)
         }
         return CallNextHookEx(Some(HHOOK::default()), code, wparam, lparam);
     }
@@ -134,10 +253,132 @@ fn is_ctrl_alt_numpad0(code: i32, wparam: WPARAM, lparam: LPARAM) -> bool {
     }
 }
 
+fn find_hotkey(code: i32, wparam: WPARAM, lparam: LPARAM) -> Option<usize> {
+    const WM_KEYDOWN : WPARAM = WPARAM(0x0100);
+    const WM_SYSKEYDOWN : WPARAM = WPARAM(0x0104);
+
+    if code < 0 || (wparam != WM_KEYDOWN && wparam != WM_SYSKEYDOWN) {
+        return None;
+    }
+
+    let hotkeys = HOTKEYS.get_or_init(|| Mutex::new(vec![])).lock().unwrap();
+    if hotkeys.is_empty() {
+        return None;
+    }
+
+    unsafe {
+        let vk_code = *(lparam.0 as *const u32) as u16;
+        let modifiers = ModifierState {
+            ctrl: GetAsyncKeyState(0x11).is_negative(),
+            shift: GetAsyncKeyState(0x10).is_negative(),
+            alt: GetAsyncKeyState(0x12).is_negative(),
+            super_key: GetAsyncKeyState(0x5B).is_negative() || GetAsyncKeyState(0x5C).is_negative(),
+        };
+        hotkeys.iter().position(|(m, vk)| *vk == vk_code && *m == modifiers)
+    }
+}
+
+fn find_watched_release(code: i32, wparam: WPARAM, lparam: LPARAM) -> Option<u16> {
+    const WM_KEYUP : WPARAM = WPARAM(0x0101);
+    const WM_SYSKEYUP : WPARAM = WPARAM(0x0105);
+
+    if code < 0 || (wparam != WM_KEYUP && wparam != WM_SYSKEYUP) {
+        return None;
+    }
+
+    let mut watch = RELEASE_WATCH.get_or_init(|| Mutex::new(None)).lock().unwrap();
+    let event = unsafe { &*(lparam.0 as *const KBDLLHOOKSTRUCT) };
+
+    // Our own SendInput releases must not end the hold
+    if (event.flags.0 & LLKHF_INJECTED.0) != 0 || *watch != Some(event.vkCode as u16) {
+        return None;
+    }
+    watch.take()
+}
+
+fn is_watched_escape(code: i32, wparam: WPARAM, lparam: LPARAM) -> bool {
+    const WM_KEYDOWN : WPARAM = WPARAM(0x0100);
+    const VK_ESCAPE : u32 = 0x1B;
+
+    if code < 0 || wparam != WM_KEYDOWN {
+        return false;
+    }
+
+    let mut watch = ESCAPE_WATCH.get_or_init(|| Mutex::new(None)).lock().unwrap();
+    let event = unsafe { &*(lparam.0 as *const KBDLLHOOKSTRUCT) };
+
+    // Escape typed by the pad's own actions doesn't stop it
+    if watch.is_none() || event.vkCode != VK_ESCAPE || (event.flags.0 & LLKHF_INJECTED.0) != 0 {
+        return false;
+    }
+    *watch = Some(true);
+    true
+}
+
+/// Foreground window the board is about to be shown over, None when it is one of our own windows
+pub fn foreground_target() -> Option<isize> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+        (!hwnd.is_invalid() && pid != process::id()).then_some(hwnd.0 as isize)
+    }
+}
+
+/// Brings the window back to the foreground, false when it no longer exists
+pub fn activate_window(hwnd: isize) -> bool {
+    unsafe {
+        let hwnd = HWND(hwnd as *mut _);
+        if !IsWindow(Some(hwnd)).as_bool() {
+            return false;
+        }
+        if GetForegroundWindow() != hwnd && !SetForegroundWindow(hwnd).as_bool() {
+            log::debug!("Target window {:?} could not be brought to the foreground", hwnd);
+        }
+        true
+    }
+}
+
+/// Applies the command to the window, false when it no longer exists
+pub fn apply_window_command(hwnd: isize, command: WindowCommand) -> bool {
+    unsafe {
+        let hwnd = HWND(hwnd as *mut _);
+        if !IsWindow(Some(hwnd)).as_bool() {
+            return false;
+        }
+        match command {
+            WindowCommand::Minimize => { let _ = ShowWindow(hwnd, SW_MINIMIZE); },
+            WindowCommand::Maximize => { let _ = ShowWindow(hwnd, SW_MAXIMIZE); },
+            WindowCommand::Restore => { let _ = ShowWindow(hwnd, SW_RESTORE); },
+            WindowCommand::Close => if let Err(e) = PostMessageW(Some(hwnd), WM_CLOSE, WPARAM(0), LPARAM(0)) {
+                log::warn!("Close not sent to window {:?}: {}", hwnd, e);
+            },
+            WindowCommand::SnapLeft | WindowCommand::SnapRight => {
+                // A maximized or minimized window would keep its state and ignore the new position
+                if IsZoomed(hwnd).as_bool() || IsIconic(hwnd).as_bool() {
+                    let _ = ShowWindow(hwnd, SW_RESTORE);
+                }
+                let Some(work_area) = window_work_area(hwnd) else {
+                    log::warn!("Work area of window {:?} not found, not snapped", hwnd);
+                    return true;
+                };
+                let rect = work_area.half(command == WindowCommand::SnapLeft);
+                if let Err(e) = SetWindowPos(hwnd, None, rect.left, rect.top, rect.width(), rect.height(), SWP_NOZORDER | SWP_NOACTIVATE) {
+                    log::warn!("Window {:?} not snapped: {}", hwnd, e);
+                }
+            },
+        }
+        true
+    }
+}
+
 fn get_foreground_process() -> ProcessInfo {
+    unsafe { window_process(GetForegroundWindow()).unwrap() }
+}
+
+fn window_process(fg_hwnd: HWND) -> windows::core::Result<ProcessInfo> {
     unsafe {
         let pid: Option<*mut u32> = Some(&mut 0);
-        let fg_hwnd = GetForegroundWindow();
 
         // Get Window Rect
         let mut lprect = RECT::default();
@@ -146,7 +387,7 @@ fn get_foreground_process() -> ProcessInfo {
         GetWindowThreadProcessId(fg_hwnd, pid);
 
         let pid = *pid.unwrap();
-        let process_handle = ProcessHandle::open(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid).unwrap();
+        let process_handle = ProcessHandle::open(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid)?;
 
         let mut file_path: [u16; 500] = [0; 500];
         let file_path_len = K32GetProcessImageFileNameW(process_handle.handle(), &mut file_path) as usize;
@@ -164,34 +405,65 @@ fn get_foreground_process() -> ProcessInfo {
         };
         log::trace!("Foreground: {:?} {}", process_handle.handle(), proc_info);
 
-        proc_info
+        Ok(proc_info)
     }
 }
 
+/// Checks whether a process with the given executable name is running.
+/// Names are compared slugified, so "Code.exe" matches "code".
+pub fn is_process_running(process_name: &str) -> bool {
+    let expected = slugify_process_name(process_name);
+
+    let mut pids = vec![0u32; 1024];
+    let mut bytes_returned = 0u32;
+    let enumerated = unsafe {
+        K32EnumProcesses(pids.as_mut_ptr(), (pids.len() * mem::size_of::<u32>()) as u32, &mut bytes_returned).as_bool()
+    };
+    if !enumerated {
+        log::warn!("Failed to enumerate processes, assuming '{}' is running", process_name);
+        return true;
+    }
+
+    let count = bytes_returned as usize / mem::size_of::<u32>();
+    pids[..count].iter().any(|&pid| {
+        match ProcessHandle::open(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            Ok(process_handle) => {
+                let mut file_path: [u16; 500] = [0; 500];
+                let len = unsafe { K32GetProcessImageFileNameW(process_handle.handle(), &mut file_path) } as usize;
+                len > 0 && slugify_process_name(&file_name(file_path, len)) == expected
+            },
+            Err(_) => false, // System and protected processes can't be opened
+        }
+    })
+}
+
 fn title_name(title: [u16; 500], title_len: i32) -> String {
     OsString::from_wide(&title[0..title_len as usize]).to_string_lossy().to_string()
 }
 
 
 fn trigger_hook_event(fgproc: ProcessInfo) {
+    send_message(Message::HookEvt(fgproc));
+}
+
+fn send_message(message: Message) {
     let maybe_sender = {
         let sender_lock = SENDER.get_or_init(|| Mutex::new(None)).lock().unwrap();
         sender_lock.clone()
     };
 
     if let Some(tx) = maybe_sender {
-        let _ = tx.send(Message::HookEvt(fgproc));
+        let _ = tx.send(message);
     }
 }
 
 fn file_name(file_path: [u16; 500], len: usize) -> String {
+    // Paths are not guaranteed to be valid UTF-16, a lossy name still matches detections
     Path::new(
         &OsString::from_wide(&file_path[0..len])
     ).file_name()
-    .unwrap()
-    .to_str()
-    .unwrap()
-    .to_lowercase()
+    .map(|name| name.to_string_lossy().to_lowercase())
+    .unwrap_or_default()
 }
 
 impl Display for ProcessInfo {
@@ -207,8 +479,12 @@ pub mod win_icon {
     use windows::Win32::UI::WindowsAndMessaging::{GetIconInfo, ICONINFO, DrawIconEx, DI_NORMAL};
     use windows::Win32::Graphics::Gdi::{GetObjectW, DeleteObject, BITMAP, CreateCompatibleDC, CreateCompatibleBitmap, SelectObject, GetDC, ReleaseDC, DeleteDC, GetDIBits, BITMAPINFOHEADER, BITMAPINFO, DIB_RGB_COLORS};
     use windows::Win32::UI::WindowsAndMessaging::HICON;
+    use windows::Win32::UI::WindowsAndMessaging::DestroyIcon;
+    use windows::Win32::UI::Shell::{SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON, SHGFI_LARGEICON};
+    use windows::Win32::Storage::FileSystem::FILE_FLAGS_AND_ATTRIBUTES;
     use windows::Win32::Foundation::{WPARAM, LPARAM};
-    use std::{mem, path::PathBuf};
+    use windows::core::HSTRING;
+    use std::{mem, path::{Path, PathBuf}};
     use image::{RgbaImage}; // Rgba
 
     /// Get HICON dimensions, returns (width, height) or None if failed
@@ -398,5 +674,35 @@ pub mod win_icon {
         Err("No icon found for window".into())
     }
 
+    /// Saves the icon the shell shows for a file, for a shortcut the icon it was given
+    pub fn save_file_icon(file: &Path, png_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        unsafe {
+            let mut info = SHFILEINFOW::default();
+            let found = SHGetFileInfoW(
+                &HSTRING::from(file.as_os_str()),
+                FILE_FLAGS_AND_ATTRIBUTES::default(),
+                Some(&mut info as *mut _),
+                mem::size_of::<SHFILEINFOW>() as u32,
+                SHGFI_ICON | SHGFI_LARGEICON
+            );
+            if found == 0 || info.hIcon.0.is_null() {
+                return Err(format!("No icon found for {}", file.display()).into());
+            }
+
+            let image = get_icon_dimensions(info.hIcon)
+                .and_then(|(width, height)| hicon_to_rgba(info.hIcon, width, height));
+            let _ = DestroyIcon(info.hIcon);
+
+            match image {
+                Some(rgba_img) => {
+                    rgba_img.save(png_path)?;
+                    log::debug!("Saved icon of {} to {}", file.display(), png_path.display());
+                    Ok(())
+                },
+                None => Err(format!("Failed to convert the icon of {}", file.display()).into()),
+            }
+        }
+    }
+
 }
 
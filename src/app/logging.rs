@@ -0,0 +1,126 @@
+use std::{env, fs, path::{Path, PathBuf}, sync::{Mutex, OnceLock}, thread, time::{Duration, SystemTime}};
+
+use log::LevelFilter;
+use log4rs::{
+    append::file::FileAppender,
+    config::{Appender, Deserializers, Logger, RawConfig, Root},
+    encode::pattern::PatternEncoder,
+    Config, Handle,
+};
+
+/// Log file of the built-in configuration, next to the executable
+const DEFAULT_LOG_FILE: &str = "application.log";
+const DEFAULT_LOG_PATTERN: &str = "{d(%Y-%m-%d %H:%M:%S.%6f)} [{({l}):5.5}] {M}:{L} — {m}{n}";
+
+/// The running logger, the configuration file it was set up from (None for the built-in configuration)
+/// and the level replacing the configured ones
+static LOGGER: OnceLock<(Handle, Option<PathBuf>, Mutex<Option<LevelFilter>>)> = OnceLock::new();
+
+/// Sets up logging from the log configuration file. `level`, when given, replaces every level configured there.
+/// A missing or invalid file doesn't stop the application: the built-in configuration is used and the problem logged as a warning.
+/// With a `refresh_rate` in the file, changes to it are picked up while the application runs.
+pub fn init_logging(path: Option<&Path>, level: Option<LevelFilter>) -> Result<(), Box<dyn std::error::Error>> {
+    let loaded = path.map(|path| read_config(path)
+        .and_then(|raw| Ok((build_config(&raw, level)?, raw.refresh_rate()))));
+    let (config, path, refresh_rate, problem) = match loaded {
+        Some(Ok((config, refresh_rate))) => (config, path, refresh_rate, None),
+        Some(Err(e)) => (default_config(level)?, None, None, Some(e.to_string())),
+        None => (default_config(level)?, None, None, Some("file not found".to_string())),
+    };
+
+    let handle = log4rs::init_config(config)?;
+    let _ = LOGGER.set((handle, path.map(Path::to_path_buf), Mutex::new(level)));
+    if let (Some(path), Some(refresh_rate)) = (path, refresh_rate) {
+        watch_config(path.to_path_buf(), refresh_rate);
+    }
+
+    if let Some(problem) = problem {
+        log::warn!("Log configuration not used ({}), logging to {} at {} level",
+            problem, default_log_path().display(), level.unwrap_or(LevelFilter::Info));
+    }
+    Ok(())
+}
+
+/// Re-reads the log configuration file and applies it with `level` in place of its levels, None keeps them
+pub fn set_log_level(level: Option<LevelFilter>) -> Result<(), Box<dyn std::error::Error>> {
+    let (handle, path, current_level) = LOGGER.get().ok_or("Logging is not initialized")?;
+    let mut current_level = current_level.lock().unwrap();
+    handle.set_config(match path {
+        Some(path) => build_config(&read_config(path)?, level)?,
+        None => default_config(level)?,
+    });
+    *current_level = level;
+    match (level, path) {
+        (Some(level), _) => log::info!("Log level set to {}", level),
+        (None, Some(path)) => log::info!("Log levels reset to {}", path.display()),
+        (None, None) => log::info!("Log level reset to {}", LevelFilter::Info),
+    }
+    Ok(())
+}
+
+/// Checks the configuration file every `refresh_rate` and re-applies it when modified, keeping the level set at runtime
+fn watch_config(path: PathBuf, refresh_rate: Duration) {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut last_modified: Option<SystemTime> = modified(&path);
+    thread::spawn(move || loop {
+        thread::sleep(refresh_rate);
+        let current = modified(&path);
+        if current == last_modified {
+            continue;
+        }
+        last_modified = current;
+
+        let Some((handle, _, level)) = LOGGER.get() else { return };
+        let level = level.lock().unwrap();
+        match read_config(&path).and_then(|raw| build_config(&raw, *level)) {
+            Ok(config) => {
+                handle.set_config(config);
+                log::info!("Log configuration reloaded from {}", path.display());
+            },
+            Err(e) => log::warn!("Log configuration {} not reloaded: {}", path.display(), e),
+        }
+    });
+}
+
+fn read_config(path: &Path) -> Result<RawConfig, Box<dyn std::error::Error>> {
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn build_config(raw: &RawConfig, level: Option<LevelFilter>) -> Result<Config, Box<dyn std::error::Error>> {
+    let (appenders, errors) = raw.appenders_lossy(&Deserializers::default());
+    if !errors.is_empty() {
+        return Err(errors.into());
+    }
+
+    let root = raw.root();
+    let root = Root::builder()
+        .appenders(root.appenders().to_vec())
+        .build(level.unwrap_or(root.level()));
+    let loggers = raw.loggers().into_iter()
+        .map(|logger| Logger::builder()
+            .appenders(logger.appenders().to_vec())
+            .additive(logger.additive())
+            .build(logger.name(), level.unwrap_or(logger.level())));
+
+    Ok(Config::builder()
+        .appenders(appenders)
+        .loggers(loggers)
+        .build(root)?)
+}
+
+/// Everything at info level to a file next to the executable, used without a usable configuration file
+fn default_config(level: Option<LevelFilter>) -> Result<Config, Box<dyn std::error::Error>> {
+    let file = FileAppender::builder()
+        .encoder(Box::new(PatternEncoder::new(DEFAULT_LOG_PATTERN)))
+        .build(default_log_path())?;
+
+    Ok(Config::builder()
+        .appender(Appender::builder().build("file", Box::new(file)))
+        .build(Root::builder().appender("file").build(level.unwrap_or(LevelFilter::Info)))?)
+}
+
+fn default_log_path() -> PathBuf {
+    env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(DEFAULT_LOG_FILE)))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_LOG_FILE))
+}
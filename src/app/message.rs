@@ -1,6 +1,9 @@
 #[derive(Clone, Debug)]
 pub enum Message {
     HookEvt(ProcessInfo),
+    HotKeyEvt(usize),
+    ForegroundEvt(ProcessInfo),
+    KeyReleasedEvt(u16),
     WinCreated(isize),
     Quit,
 }
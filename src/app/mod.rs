@@ -5,12 +5,15 @@ mod hook;
 mod board_manager;
 mod action_factory;
 mod board_factory;
+mod logging;
 mod windows;
 
-use action_factory::ActionFactoryImpl;
+use action_factory::{ActionFactoryImpl, wait_for_clipboard_change, wrap_selection};
 use board_factory::BoardFactoryImpl;
 use board_manager::BoardManager;
 
 pub use app::Application;
+pub use logging::{init_logging, set_log_level};
+pub use hook::{is_process_running, win_icon::save_file_icon};
 pub use action_factory::{ ActionFactoryRegistry };
 pub use board_factory::{ BoardFactoryRegistry, BoardFactory, BoardRuntimeContext };
\ No newline at end of file
@@ -1,5 +1,6 @@
 use std::sync::Once;
 use std::ffi::c_void;
+use std::time::{Duration, Instant};
 
 
 use windows::{
@@ -9,9 +10,9 @@ use windows::{
         Graphics::Gdi::{InvalidateRect, HBRUSH},
         System::LibraryLoader::GetModuleHandleW,
         UI::{
-            Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_ESCAPE},
+            Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_0, VK_ADD, VK_ESCAPE, VK_NUMPAD0, VK_OEM_MINUS, VK_OEM_PLUS, VK_RETURN, VK_SUBTRACT},
             WindowsAndMessaging::{
-                CreateWindowExW, DefWindowProcW, DestroyWindow, KillTimer, LoadCursorW, LoadIconW, PostMessageW, RegisterClassW, SetTimer, ShowWindow, IDC_ARROW, SW_SHOW, WM_CLOSE, WM_CREATE, WM_DESTROY, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN, WM_MOVE, WM_PAINT, WM_RBUTTONDOWN, WM_SIZE, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_TIMER, WM_USER, WNDCLASSW
+                CreateWindowExW, DefWindowProcW, DestroyWindow, KillTimer, LoadCursorW, LoadIconW, PostMessageW, RegisterClassW, SetTimer, ShowWindow, IDC_ARROW, SW_SHOW, WM_CLOSE, WM_CREATE, WM_DESTROY, WM_DISPLAYCHANGE, WM_DPICHANGED, WM_KEYDOWN, WM_KEYUP, WM_KILLFOCUS, WM_LBUTTONDOWN, WM_MOVE, WM_PAINT, WM_RBUTTONDOWN, WM_SIZE, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_TIMER, WM_USER, WNDCLASSW
             }
         },
     }
@@ -19,29 +20,127 @@ use windows::{
 
 
 use crate::{
-    components::{BoardComponent, ChildWindowRequest, Direction, KeyboardEvent, MouseEvent, MouseEventTarget, PadMapping, SetWindowPosCommand, UiEvent, UiEventResult}, core::SettingsRepository, framework::{wnd_proc_router, Window}, input::{ModifierHandler, ModifierState}, model::PadId, ui::{components::painter, shared::{ layout::WindowLayout, utils::{reset_window_pos, set_window_rect}}}
+    components::{map_letter_key, BoardComponent, ChildWindowRequest, Direction, HelpOverlay, KeyboardEvent, MouseEvent, MouseEventTarget, PadMapping, SetWindowPosCommand, StackOverlay, UiEvent, UiEventResult}, core::{data::{clamp_zoom, DEFAULT_ZOOM, ZOOM_STEP}, PadBehavior, Renderer, SettingsRepository}, framework::{wnd_proc_router, Window}, input::{ModifierGrace, ModifierHandler, ModifierState}, model::{Board, PadId, PadSource}, ui::{components::painter, dialogs::FocusCycle, shared::{ layout::{dpi_scale, WindowLayout}, utils::{reset_window_pos, set_window_rect}}}
 };
 
 pub const WM_BOARD_COMMAND:u32 = WM_USER + 20;
 pub const WM_BOARD_FINISHED:u32 = WM_USER + 21;
 pub const WM_UPDATE_LAYOUT:u32 = WM_USER + 22;
 const WM_SHOW_CHILD_WINDOW:u32 = WM_USER + 23;
+pub const WM_SAVE_AND_CLOSE:u32 = WM_USER + 24;
+pub const WM_ZOOM_CHANGED:u32 = WM_USER + 25;
+pub const WM_GO_HOME:u32 = WM_USER + 26;
+/// Posted once the icons left out of a paint are rasterized, the board is painted again with them
+pub const WM_ICONS_WARMED:u32 = WM_USER + 27;
 
 const ID_TIMER_TIMEOUT: usize = 1;
 const ID_TIMER_FEEDBACK: usize = 2;
+const ID_TIMER_MODIFIER_GRACE: usize = 3;
+const ID_TIMER_COOLDOWN: usize = 4;
+
+/// Interval of the feedback timer, the selected pad is repainted every tick until the feedback delay is over
+const FEEDBACK_FRAME: Duration = Duration::from_millis(16);
+/// Interval of the cooldown timer, the cooldown wipes shrink every tick
+const COOLDOWN_FRAME: Duration = Duration::from_millis(50);
 
 static REGISTER_WINDOW_CLASS: Once = Once::new();
 static WINDOW_CLASS_NAME: &HSTRING = h!("HotKeys.Window");
 
+/// A pad that ran and can't run again until its cooldown is over, kept per board and padset
+#[derive(Clone, Debug)]
+pub struct PadCooldown {
+    pub board: String,
+    pub modifiers: ModifierState,
+    pub pad_id: PadId,
+    pub started: Instant,
+    pub length: Duration,
+}
+
+impl PadCooldown {
+    pub fn is_of(&self, source: &PadSource) -> bool {
+        self.board == source.board && self.modifiers == source.modifiers && self.pad_id == source.pad_id
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.started.elapsed() >= self.length
+    }
+
+    /// Part of the cooldown still to go, from 1 when the pad ran down to 0
+    pub fn remaining(&self) -> f64 {
+        1.0 - (self.started.elapsed().as_secs_f64() / self.length.as_secs_f64()).min(1.0)
+    }
+}
+
+/// When the pad key held back for a modifier is resolved, once a modifier changed the state
+#[derive(Debug, PartialEq)]
+enum HeldPad {
+    /// Before the state changes, the key waited out its grace period and the modifier isn't part of its press
+    Before,
+    /// With the new state, the modifier came within the grace period
+    After,
+}
+
+/// The modifier state a key pressed at `now` leads to, whether it is a modifier, and when it resolves the held pad key
+fn press_modifier<T>(grace: &ModifierGrace<T>, state: &ModifierState, vk_code: VIRTUAL_KEY, now: Instant) -> (bool, ModifierState, Option<HeldPad>) {
+    let mut modifier_handler = ModifierHandler::new(state.clone());
+    let is_modifier = modifier_handler.handle_key_press(vk_code);
+    let new_state = modifier_handler.state().clone();
+
+    let held_pad = match grace.held() {
+        Some(_) if is_modifier && new_state != *state => Some(if grace.expired(now) { HeldPad::Before } else { HeldPad::After }),
+        _ => None,
+    };
+    (is_modifier, new_state, held_pad)
+}
+
+/// Settings a board window is opened with, they don't change while it is displayed
+pub struct BoardWindowOptions {
+    pub feedback: u64,
+    pub flash_feedback: bool,
+    pub show_cooldown: bool,
+    pub save_and_close_key: Option<(ModifierState, u16)>,
+    pub fast_render: bool,
+    pub renderer: Renderer,
+    pub modifier_grace: u64,
+    pub help_key: Option<u16>,
+    pub stack_key: Option<(ModifierState, u16)>,
+    pub focus_key: Option<(ModifierState, u16)>,
+    pub home_key: Option<(ModifierState, u16)>,
+    pub home_board: String,
+}
+
 pub struct BoardWindow<R: SettingsRepository> {
     hwnd: HWND,
     layout: WindowLayout,
     board: Box<dyn BoardComponent>,
     timeout: u32,
     feedback: u64,
+    flash_feedback: bool,
     pad_mapping: PadMapping<R>,
     selected_pad: Option<PadId>,
+    /// Start and length of the feedback delay of the selected pad, it fades in over the delay
+    feedback_animation: Option<(Instant, Duration)>,
+    /// Pads of this board in their cooldown, they ignore selection until it is over
+    cooldowns: Vec<PadCooldown>,
+    show_cooldown: bool,
     modifier_state: ModifierState,
+    save_and_close_key: Option<(ModifierState, u16)>,
+    zoom: f64,
+    /// DPI scale of the monitor the window is on, applied on top of the zoom
+    dpi_scale: f64,
+    fast_render: bool,
+    renderer: Renderer,
+    last_input: Instant,
+    child_window_open: bool,
+    pending_release: Option<(PadId, VIRTUAL_KEY)>,
+    modifier_grace: ModifierGrace<(PadId, VIRTUAL_KEY)>,
+    help_key: Option<u16>,
+    show_help: bool,
+    stack_key: Option<(ModifierState, u16)>,
+    focus_key: Option<(ModifierState, u16)>,
+    show_stack: bool,
+    home_key: Option<(ModifierState, u16)>,
+    home_board: String,
 }
 
 impl<R: SettingsRepository> BoardWindow<R> {
@@ -66,13 +165,17 @@ impl<R: SettingsRepository> BoardWindow<R> {
         layout: WindowLayout,
         board: Box<dyn BoardComponent>,
         timeout: u32,
-        feedback: u64,
         pad_mapping: PadMapping<R>,
+        zoom: f64,
+        options: BoardWindowOptions,
     ) -> Result<Box<BoardWindow<R>>> {
 
         let hinstance = unsafe { GetModuleHandleW(None)? };
         Self::register_window_class(hinstance);
 
+        let dpi_scale = dpi_scale(layout.dpi());
+        log::debug!("Board window scaled by {} for the monitor DPI", dpi_scale);
+        let layout = layout.zoomed(zoom * dpi_scale);
         let style = layout.style.style();
         let ex_style = layout.style.ex_style();
         let rect = layout.get_adjusted_rect()?;
@@ -82,10 +185,30 @@ impl<R: SettingsRepository> BoardWindow<R> {
             layout: layout,
             board: board,
             timeout: timeout,
-            feedback: feedback,
+            feedback: options.feedback,
+            flash_feedback: options.flash_feedback,
             selected_pad: None,
+            feedback_animation: None,
+            cooldowns: vec![],
+            show_cooldown: options.show_cooldown,
             modifier_state: ModifierState::default(),
             pad_mapping: pad_mapping,
+            save_and_close_key: options.save_and_close_key,
+            zoom: zoom,
+            dpi_scale: dpi_scale,
+            fast_render: options.fast_render,
+            renderer: options.renderer,
+            last_input: Instant::now(),
+            child_window_open: false,
+            pending_release: None,
+            modifier_grace: ModifierGrace::new(options.modifier_grace),
+            help_key: options.help_key,
+            show_help: false,
+            stack_key: options.stack_key,
+            focus_key: options.focus_key,
+            show_stack: false,
+            home_key: options.home_key,
+            home_board: options.home_board,
         });
 
 
@@ -113,6 +236,10 @@ impl<R: SettingsRepository> BoardWindow<R> {
         Ok(this)
     }
 
+    pub fn hwnd(&self) -> isize {
+        self.hwnd.0 as isize
+    }
+
     pub fn hide(&mut self) {
         unsafe {
             DestroyWindow(self.hwnd).unwrap_or_default();
@@ -131,6 +258,29 @@ impl<R: SettingsRepository> BoardWindow<R> {
         &self.layout
     }
 
+    /// Layout without the zoom and DPI scale applied, this is what gets persisted and shared between boards
+    pub fn base_layout(&self) -> WindowLayout {
+        self.layout.zoomed(1.0 / self.scale())
+    }
+
+    /// How much larger than its base layout the board is drawn: the zoom on the monitor's DPI scale
+    fn scale(&self) -> f64 {
+        self.zoom * self.dpi_scale
+    }
+
+    /// Time since the last key or mouse input, `None` while a dialog is open over the board
+    pub fn idle_time(&self) -> Option<Duration> {
+        if self.child_window_open {
+            None
+        } else {
+            Some(self.last_input.elapsed())
+        }
+    }
+
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
     fn on_create(&self, hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
         unsafe {
             // Don't call SetLayeredWindowAttributes - we use UpdateLayeredWindow instead
@@ -152,6 +302,56 @@ impl<R: SettingsRepository> BoardWindow<R> {
         LRESULT(0)
     }
 
+    fn on_display_change(&mut self, hwnd: HWND) -> LRESULT {
+        if self.layout.fit_to_screen() {
+            log::info!("Board window was off-screen after a display change, moved to {:?}", self.layout.rect);
+            if let Ok(rect) = self.layout.get_adjusted_rect() {
+                unsafe { set_window_rect(hwnd, &rect); }
+                self.post_layout_update_msg(hwnd);
+            }
+        }
+        self.invalidate(hwnd)
+    }
+
+    /// The window moved onto a monitor with another DPI, it is rescaled from its base layout
+    /// and placed where Windows suggests, which keeps it under the cursor while it is dragged
+    fn on_dpi_changed(&mut self, hwnd: HWND, dpi: u32, suggested: &RECT) -> LRESULT {
+        let base_layout = self.base_layout();
+        self.dpi_scale = dpi_scale(dpi);
+        self.layout = base_layout.zoomed(self.scale());
+        log::debug!("Board window rescaled by {} for a monitor at {} DPI", self.dpi_scale, dpi);
+
+        if let Ok(rect) = self.layout.get_adjusted_rect() {
+            let width = self.layout.rect.width();
+            let height = self.layout.rect.height();
+            self.layout.rect.left += suggested.left - rect.left;
+            self.layout.rect.top += suggested.top - rect.top;
+            self.layout.rect.right = self.layout.rect.left + width;
+            self.layout.rect.bottom = self.layout.rect.top + height;
+        }
+        if let Ok(rect) = self.layout.get_adjusted_rect() {
+            unsafe { set_window_rect(hwnd, &rect); }
+        }
+        self.invalidate(hwnd)
+    }
+
+    fn on_zoom(&mut self, hwnd: HWND, step: f64) -> LRESULT {
+        let zoom = clamp_zoom(if step == 0.0 { DEFAULT_ZOOM } else { self.zoom + step });
+        if zoom == self.zoom {
+            return LRESULT(0);
+        }
+
+        // Rescale from the unzoomed size, the top left corner stays in place
+        let base_layout = self.base_layout();
+        self.zoom = zoom;
+        self.layout = base_layout.zoomed(self.scale());
+        if let Ok(rect) = self.layout.get_adjusted_rect() {
+            unsafe { set_window_rect(hwnd, &rect); }
+        }
+        self.post_zoom_changed_msg(hwnd);
+        self.invalidate(hwnd)
+    }
+
     fn on_paint(&self, hwnd: HWND) -> LRESULT {
         unsafe {
             // Need to call BeginPaint/EndPaint to satisfy Windows paint cycle
@@ -218,26 +418,48 @@ impl<R: SettingsRepository> BoardWindow<R> {
         let pixel_count = (width * height) as usize;
         let pixels = std::slice::from_raw_parts_mut(bits as *mut painter::RGBA, pixel_count);
 
-        // Initialize with transparent background based on board color scheme
+        // Initialize with transparent background based on board color scheme, row by row for a gradient
         let board = self.board.as_ref().data();
-        let (bg_r, bg_g, bg_b) = board.color_scheme().background().to_rgb();
-        let bg_alpha = (board.color_scheme().opacity() * 255.0) as u8;
-
-        for pixel in pixels.iter_mut() {
-            *pixel = painter::RGBA {
-                r: (bg_r as u16 * bg_alpha as u16 / 255) as u8,
-                g: (bg_g as u16 * bg_alpha as u16 / 255) as u8,
-                b: (bg_b as u16 * bg_alpha as u16 / 255) as u8,
-                a: bg_alpha, // Use board's opacity setting
-            };
+        let color_scheme = board.color_scheme();
+        let bg_alpha = (color_scheme.opacity() * 255.0) as u8;
+
+        for (y, row) in pixels.chunks_mut(width.max(1) as usize).enumerate() {
+            let (bg_r, bg_g, bg_b) = color_scheme.background_at(y as i32, height).to_rgb();
+            for pixel in row.iter_mut() {
+                *pixel = painter::RGBA {
+                    r: (bg_r as u16 * bg_alpha as u16 / 255) as u8,
+                    g: (bg_g as u16 * bg_alpha as u16 / 255) as u8,
+                    b: (bg_b as u16 * bg_alpha as u16 / 255) as u8,
+                    a: bg_alpha, // Use board's opacity setting
+                };
+            }
         }
 
         // Call existing painter with memory DC and pixels for blending
+        let started = Instant::now();
+        let help_overlay;
+        let stack_overlay;
+        let painted: &dyn Board = if self.show_help {
+            help_overlay = HelpOverlay::new(board);
+            &help_overlay
+        } else if self.show_stack {
+            stack_overlay = StackOverlay::new(board, self.board.state_stack());
+            &stack_overlay
+        } else {
+            board
+        };
         painter::BoardPainter {
-            board: board,
+            board: painted,
             timeout: self.timeout as u8,
             selected_pad: self.selected_pad,
+            selection_progress: self.selection_progress(),
+            cooldowns: self.cooldown_progress(),
+            zoom: self.scale(),
+            fast_render: self.fast_render,
+            renderer: self.renderer,
         }.paint(hwnd, mem_dc, pixels, width as usize, self.modifier_state.clone());
+        log::debug!("Board painted in {:?} ({}x{}, opacity {}, fast_render {}, renderer {:?})",
+            started.elapsed(), width, height, board.color_scheme().opacity(), self.fast_render, self.renderer);
 
         // Update layered window
         let window_pos = windows::Win32::Foundation::POINT {
@@ -290,24 +512,89 @@ impl<R: SettingsRepository> BoardWindow<R> {
     fn on_keydown(&mut self, hwnd: HWND, wparam: WPARAM) -> LRESULT {
         let vk_code = VIRTUAL_KEY(wparam.0 as u16);
 
+        // The board can be focused while a dialog is open over it (see FocusCycle), its handler is busy with the dialog
+        if self.child_window_open {
+            return LRESULT(0);
+        }
+
         // Stop timeout timer and queue redraw on any key press
         self.stop_timeout_timer(hwnd);
 
+        // Auto-repeat of the held key keeps the pad waiting, any other key cancels it
+        if let Some((_, held_key)) = self.pending_release {
+            if held_key == vk_code {
+                return LRESULT(0);
+            }
+            self.cancel_pending_release(hwnd);
+        }
+
+        // Auto-repeat of a pad key waiting for its modifier is ignored, any other key that isn't a modifier resolves it first
+        if let Some(&(_, held_key)) = self.modifier_grace.held() {
+            if held_key == vk_code {
+                return LRESULT(0);
+            }
+            if !ModifierHandler::is_modifier(vk_code) {
+                self.resolve_held_pad(hwnd);
+            }
+        }
+
+        // The help stays up while the key is held, its auto-repeat doesn't repaint
+        if self.help_key == Some(vk_code.0) {
+            if !self.show_help {
+                self.show_help = true;
+                self.invalidate(hwnd);
+            }
+            return LRESULT(0);
+        }
+
         // Handle modifier keys first
         let old_state = self.modifier_state.clone();
-        let mut modifier_handler = ModifierHandler::new(old_state.clone());
-        let is_modifier = modifier_handler.handle_key_press(vk_code);
-        let new_state = modifier_handler.state().clone();
+        let (is_modifier, new_state, held_pad) = press_modifier(&self.modifier_grace, &old_state, vk_code, Instant::now());
 
         if is_modifier {
             if new_state != old_state {
+                // Later than the grace period, even if its timer didn't fire yet
+                if held_pad == Some(HeldPad::Before) {
+                    self.resolve_held_pad(hwnd);
+                }
                 self.modifier_state = new_state;
                 self.invalidate(hwnd);
+                // The pad key came just before its modifier
+                if held_pad == Some(HeldPad::After) {
+                    return self.resolve_held_pad(hwnd);
+                }
             } else {
                 return LRESULT(0); // No state change, ignore
             }
         }
 
+        // Handled before the board, so it works the same in every (editing) state
+        if !is_modifier && self.save_and_close_key == Some((new_state, vk_code.0)) {
+            self.post_save_and_close_msg(hwnd);
+            return LRESULT(0);
+        }
+
+        if !is_modifier && self.stack_key == Some((new_state, vk_code.0)) {
+            self.show_stack = !self.show_stack;
+            if self.show_stack {
+                log::info!("State stack: {}", self.board.state_stack().join(" > "));
+            }
+            return self.invalidate(hwnd);
+        }
+
+        if !is_modifier && self.home_key == Some((new_state, vk_code.0)) {
+            return self.go_home(hwnd);
+        }
+
+        if !is_modifier && new_state == (ModifierState { ctrl: true, ..Default::default() }) {
+            match vk_code {
+                VK_OEM_PLUS | VK_ADD => return self.on_zoom(hwnd, ZOOM_STEP),
+                VK_OEM_MINUS | VK_SUBTRACT => return self.on_zoom(hwnd, -ZOOM_STEP),
+                VK_0 | VK_NUMPAD0 => return self.on_zoom(hwnd, 0.0),
+                _ => {}
+            }
+        }
+
         if let Some(handler) = self.board.as_mut().handler() {
             match handler.handle_ui_event(EventMapper::key_down(vk_code, new_state)) {
                 UiEventResult::Handled => return LRESULT(0),
@@ -346,11 +633,34 @@ impl<R: SettingsRepository> BoardWindow<R> {
             return LRESULT(0);
         }
 
-        // Handle numeric pad keys
-        let pad_id = self.pad_mapping.map(vk_code);
+        // Enter activates the board's default pad, if it has one
+        if vk_code == VK_RETURN && new_state.is_none() {
+            if let Some(pad_id) = self.board.data().default_pad() {
+                return self.on_pad_pressed(pad_id, vk_code, hwnd);
+            }
+        }
+
+        // Handle numeric pad keys, and the pad letters on boards using them
+        let pad_id = self.pad_mapping.map(vk_code)
+            .or_else(|| self.board.data().letter_keys().then(|| map_letter_key(vk_code)).flatten());
         match pad_id {
             None => LRESULT(0), // Unhandled key
-            Some(pad_id) => self.on_pad_selected(pad_id, hwnd)
+            Some(pad_id) => match self.modifier_grace.press((pad_id, vk_code)) {
+                Some((pad_id, vk_code)) => self.on_pad_pressed(pad_id, vk_code, hwnd),
+                None => {
+                    unsafe { SetTimer(Some(hwnd), ID_TIMER_MODIFIER_GRACE, self.modifier_grace.period() as u32, None); }
+                    LRESULT(0)
+                }
+            }
+        }
+    }
+
+    /// Resolves the pad key waiting for a modifier with the modifier state as it is now
+    fn resolve_held_pad(&mut self, hwnd: HWND) -> LRESULT {
+        unsafe { let _ = KillTimer(Some(hwnd), ID_TIMER_MODIFIER_GRACE); }
+        match self.modifier_grace.release() {
+            Some((pad_id, vk_code)) => self.on_pad_pressed(pad_id, vk_code, hwnd),
+            None => LRESULT(0),
         }
     }
 
@@ -359,6 +669,28 @@ impl<R: SettingsRepository> BoardWindow<R> {
 
         let vk_code = VIRTUAL_KEY(wparam.0 as u16);
 
+        if self.show_help && self.help_key == Some(vk_code.0) {
+            self.show_help = false;
+            return self.invalidate(hwnd);
+        }
+
+        // Released before its grace period ended, there is nothing left to hold
+        if self.modifier_grace.held().is_some_and(|(_, held_key)| *held_key == vk_code) {
+            unsafe { let _ = KillTimer(Some(hwnd), ID_TIMER_MODIFIER_GRACE); }
+            if let Some((pad_id, _)) = self.modifier_grace.release() {
+                return self.on_pad_selected(pad_id, hwnd);
+            }
+        }
+
+        if let Some((pad_id, held_key)) = self.pending_release {
+            if held_key == vk_code {
+                self.pending_release = None;
+                self.selected_pad = None;
+                self.post_board_command_msg(hwnd, pad_id, None);
+                return LRESULT(0);
+            }
+        }
+
         // Handle modifier key releases
         let old_state = self.modifier_state.clone();
         let mut modifier_handler = ModifierHandler::new(old_state.clone());
@@ -439,10 +771,26 @@ impl<R: SettingsRepository> BoardWindow<R> {
                     }
                 }
             },
+            ID_TIMER_MODIFIER_GRACE => {
+                return self.resolve_held_pad(hwnd);
+            },
+            ID_TIMER_COOLDOWN => {
+                self.cooldowns.retain(|cooldown| !cooldown.is_over());
+                if self.cooldowns.is_empty() {
+                    unsafe { let _ = KillTimer(Some(hwnd), ID_TIMER_COOLDOWN); }
+                }
+                return self.invalidate(hwnd);
+            },
             ID_TIMER_FEEDBACK => {
+                if self.selection_progress() < 1.0 {
+                    // Repainted right away, a WM_PAINT per frame could lag behind the timer
+                    unsafe { self.update_layered_window(hwnd); }
+                    return LRESULT(0);
+                }
+                self.feedback_animation = None;
                 self.kill_timers(hwnd);
                 if let Some(selected_pad) = self.selected_pad {
-                    self.post_board_command_msg(hwnd, selected_pad);
+                    self.post_board_command_msg(hwnd, selected_pad, None);
                 } else {
                     log::warn!("No pad selected for feedback timer");
                 }
@@ -464,13 +812,55 @@ impl<R: SettingsRepository> BoardWindow<R> {
         }
     }
 
-    fn post_board_command_msg(&self, hwnd: HWND, pad_id: PadId) {
+    /// Drops the states stacked on the home board when it is the root one, otherwise the application replaces the board with it
+    fn go_home(&mut self, hwnd: HWND) -> LRESULT {
+        if self.board.state_stack().first() == Some(&self.home_board) {
+            if self.board.reset_state() {
+                return self.invalidate(hwnd);
+            }
+            return LRESULT(0);
+        }
+        unsafe {
+            PostMessageW(
+                Some(hwnd),
+                WM_GO_HOME,
+                WPARAM(0),
+                LPARAM(0)
+            ).unwrap_or_default();
+        }
+        LRESULT(0)
+    }
+
+    fn post_save_and_close_msg(&self, hwnd: HWND) {
+        unsafe {
+            PostMessageW(
+                Some(hwnd),
+                WM_SAVE_AND_CLOSE,
+                WPARAM(0),
+                LPARAM(0)
+            ).unwrap_or_default();
+        }
+    }
+
+    /// The held key, if any, travels in LPARAM so momentary pads can wait for its release
+    fn post_board_command_msg(&self, hwnd: HWND, pad_id: PadId, held_key: Option<VIRTUAL_KEY>) {
         let hwnd_val = hwnd.0 as usize;
         unsafe {
             PostMessageW(
                 Some(HWND(hwnd_val as *mut c_void)),
                 WM_BOARD_COMMAND,
                 WPARAM(pad_id.as_keypad_int() as usize),
+                LPARAM(held_key.map(|vk| vk.0 as isize).unwrap_or(0))
+            ).unwrap_or_default();
+        }
+    }
+
+    fn post_zoom_changed_msg(&self, hwnd: HWND) {
+        unsafe {
+            PostMessageW(
+                Some(hwnd),
+                WM_ZOOM_CHANGED,
+                WPARAM(0),
                 LPARAM(0)
             ).unwrap_or_default();
         }
@@ -508,21 +898,84 @@ impl<R: SettingsRepository> BoardWindow<R> {
 
     fn on_pad_selected(&mut self, pad_id: PadId, hwnd: HWND) -> LRESULT {
         let pad = self.board.as_ref().data().padset(Some(self.modifier_state)).pad(pad_id);
-        if !pad.data.is_interactive() {
+        if !pad.data.is_interactive() || self.in_cooldown(pad_id) {
             return LRESULT(0);
         }
 
-        if self.feedback == 0 {
-            self.post_board_command_msg(hwnd, pad_id);
+        // The pad's own delay wins, e.g. 0 on navigation pads of a board that otherwise confirms the press
+        let feedback = pad.feedback().unwrap_or(self.feedback);
+        if feedback == 0 {
+            if self.flash_feedback {
+                // Painted right away, the command is handled before a WM_PAINT would come
+                self.selected_pad = Some(pad_id);
+                self.feedback_animation = None;
+                unsafe { self.update_layered_window(hwnd); }
+            }
+            self.post_board_command_msg(hwnd, pad_id, None);
             return LRESULT(0);
         }
 
         self.selected_pad = Some(pad_id);
+        self.feedback_animation = Some((Instant::now(), Duration::from_millis(feedback)));
         self.invalidate(hwnd);
-        self.set_timer(hwnd, ID_TIMER_FEEDBACK, self.feedback as f64 / 1000.0);
+        self.set_timer(hwnd, ID_TIMER_FEEDBACK, FEEDBACK_FRAME.as_secs_f64());
         LRESULT(0)
     }
 
+    /// How far the selected pad has faded in, 1 once the feedback delay is over and for pads selected without one
+    fn selection_progress(&self) -> f64 {
+        self.feedback_animation
+            .map(|(started, delay)| (started.elapsed().as_secs_f64() / delay.as_secs_f64()).min(1.0))
+            .unwrap_or(1.0)
+    }
+
+    fn in_cooldown(&self, pad_id: PadId) -> bool {
+        let cooling = self.cooldowns.iter()
+            .any(|cooldown| cooldown.pad_id == pad_id && cooldown.modifiers == self.modifier_state && !cooldown.is_over());
+        if cooling {
+            log::debug!("Pad {} is in its cooldown, not run", pad_id.to_string());
+        }
+        cooling
+    }
+
+    /// Remaining cooldown of the pads in the displayed padset, nothing is drawn unless `show_cooldown` is set
+    fn cooldown_progress(&self) -> Vec<(PadId, f64)> {
+        if !self.show_cooldown {
+            return vec![];
+        }
+        self.cooldowns.iter()
+            .filter(|cooldown| cooldown.modifiers == self.modifier_state && !cooldown.is_over())
+            .map(|cooldown| (cooldown.pad_id, cooldown.remaining()))
+            .collect()
+    }
+
+    /// Keyboard selection, momentary pads skip the feedback delay and hand over the key that is held,
+    /// on-release pads stay highlighted until that key goes up
+    fn on_pad_pressed(&mut self, pad_id: PadId, vk_code: VIRTUAL_KEY, hwnd: HWND) -> LRESULT {
+        if self.in_cooldown(pad_id) {
+            return LRESULT(0);
+        }
+        let pad = self.board.as_ref().data().padset(Some(self.modifier_state)).pad(pad_id);
+        if pad.data.is_interactive() && pad.behavior() == Some(PadBehavior::Momentary) {
+            self.post_board_command_msg(hwnd, pad_id, Some(vk_code));
+            return LRESULT(0);
+        }
+        if pad.data.is_interactive() && pad.on_release() {
+            self.pending_release = Some((pad_id, vk_code));
+            self.selected_pad = Some(pad_id);
+            self.feedback_animation = None;
+            return self.invalidate(hwnd);
+        }
+        self.on_pad_selected(pad_id, hwnd)
+    }
+
+    fn cancel_pending_release(&mut self, hwnd: HWND) {
+        if self.pending_release.take().is_some() {
+            self.selected_pad = None;
+            self.invalidate(hwnd);
+        }
+    }
+
     fn set_timer(&self, hwnd: HWND, id: usize, seconds: f64) {
         if seconds > 0.0 {
             unsafe {
@@ -534,6 +987,7 @@ impl<R: SettingsRepository> BoardWindow<R> {
     fn kill_timers(&self, hwnd: HWND) -> LRESULT {
         unsafe { let _ = KillTimer(Some(hwnd), ID_TIMER_TIMEOUT); }
         unsafe { let _ = KillTimer(Some(hwnd), ID_TIMER_FEEDBACK); }
+        unsafe { let _ = KillTimer(Some(hwnd), ID_TIMER_COOLDOWN); }
         LRESULT(0)
     }
 
@@ -590,6 +1044,22 @@ impl<R: SettingsRepository> BoardWindow<R> {
         self.invalidate(self.hwnd);
     }
 
+    /// Pads of the board still in their cooldown, repainted until the last one is over when they are shown
+    pub fn set_cooldowns(&mut self, cooldowns: Vec<PadCooldown>) {
+        self.cooldowns = cooldowns;
+        if self.show_cooldown && !self.cooldowns.is_empty() {
+            self.set_timer(self.hwnd, ID_TIMER_COOLDOWN, COOLDOWN_FRAME.as_secs_f64());
+            self.invalidate(self.hwnd);
+        }
+    }
+
+    /// Removes the highlight of the pad that was selected, for boards staying open after a pad
+    pub fn clear_selection(&mut self) {
+        self.selected_pad = None;
+        self.feedback_animation = None;
+        self.pending_release = None;
+    }
+
 }
 
 impl<R: SettingsRepository> Window for BoardWindow<R> {
@@ -612,6 +1082,9 @@ impl<R: SettingsRepository> Window for BoardWindow<R> {
             WM_PAINT => {
                 Some(self.on_paint(hwnd))
             },
+            WM_ICONS_WARMED => {
+                Some(self.invalidate(hwnd))
+            },
             WM_SYSKEYDOWN => {
                 let vk_code = VIRTUAL_KEY(wparam.0 as u16);
                 if ModifierHandler::is_modifier(vk_code) {
@@ -629,12 +1102,15 @@ impl<R: SettingsRepository> Window for BoardWindow<R> {
                 }
             },
             WM_KEYDOWN => {
+                self.last_input = Instant::now();
                 Some(self.on_keydown(hwnd, wparam))
             },
             WM_KEYUP => {
+                self.last_input = Instant::now();
                 Some(self.on_keyup(hwnd, wparam))
             },
             WM_RBUTTONDOWN | WM_LBUTTONDOWN => {
+                self.last_input = Instant::now();
                 Some(self.on_right_mouse_down(hwnd, wparam, lparam))
             },
             WM_SIZE => {
@@ -646,6 +1122,25 @@ impl<R: SettingsRepository> Window for BoardWindow<R> {
             WM_TIMER => {
                 Some(self.on_timer(hwnd, wparam))
             },
+            WM_DISPLAYCHANGE => {
+                Some(self.on_display_change(hwnd))
+            },
+            WM_DPICHANGED => {
+                let suggested = unsafe { &*(lparam.0 as *const RECT) };
+                Some(self.on_dpi_changed(hwnd, (wparam.0 & 0xffff) as u32, suggested))
+            },
+            WM_KILLFOCUS => {
+                // The key-up goes elsewhere now, a pad waiting for it must not fire later
+                self.cancel_pending_release(hwnd);
+                if self.modifier_grace.release().is_some() {
+                    unsafe { let _ = KillTimer(Some(hwnd), ID_TIMER_MODIFIER_GRACE); }
+                }
+                if self.show_help {
+                    self.show_help = false;
+                    self.invalidate(hwnd);
+                }
+                None
+            },
             WM_CLOSE => {
                 self.kill_timers(hwnd); // kill the timer, let the app handle WM_CLOSE
                 None
@@ -655,6 +1150,9 @@ impl<R: SettingsRepository> Window for BoardWindow<R> {
             },
             WM_SHOW_CHILD_WINDOW => {
                 let child_request = Self::decode_child_window_message(wparam);
+                // Dialogs run their own message loop, app timers keep firing meanwhile
+                self.child_window_open = true;
+                let _focus_cycle = FocusCycle::begin(hwnd, self.focus_key);
                 if let Some(handler) = self.board.as_mut().handler() {
                     match handler.create_child_window(child_request, hwnd) {
                         UiEventResult::RequiresRedraw => {
@@ -663,6 +1161,8 @@ impl<R: SettingsRepository> Window for BoardWindow<R> {
                         _ => {}
                     }
                 }
+                self.child_window_open = false;
+                self.last_input = Instant::now();
                 self.reset_window_pos(self.hwnd, false);
                 Some(LRESULT(0))
             },
@@ -734,4 +1234,30 @@ impl MouseEventTargetable for WindowLayout {
 
         None
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{VK_LSHIFT, VK_NUMPAD7, VK_NUMPAD8};
+
+    #[test]
+    fn test_modifier_after_held_pad_key() {
+        let shift = ModifierState { shift: true, ..Default::default() };
+        let mut grace = ModifierGrace::new(50);
+
+        // Nothing held: the modifier only changes the state, a pad key isn't a modifier
+        let now = Instant::now();
+        assert_eq!(press_modifier(&grace, &ModifierState::default(), VK_LSHIFT, now), (true, shift, None));
+        assert_eq!(press_modifier(&grace, &ModifierState::default(), VK_NUMPAD8, now), (false, ModifierState::default(), None));
+
+        // The pad key is held back, the modifier within the grace period is part of its press, a later one isn't
+        assert_eq!(grace.press((PadId::Seven, VK_NUMPAD7)), None);
+        let pressed = Instant::now();
+        assert_eq!(press_modifier(&grace, &ModifierState::default(), VK_LSHIFT, pressed), (true, shift, Some(HeldPad::After)));
+        assert_eq!(press_modifier(&grace, &ModifierState::default(), VK_LSHIFT, pressed + Duration::from_millis(80)), (true, shift, Some(HeldPad::Before)));
+
+        // A modifier already held doesn't change the state, nor resolve the key
+        assert_eq!(press_modifier(&grace, &shift, VK_LSHIFT, pressed), (true, shift, None));
+    }
+}
@@ -3,5 +3,5 @@ mod board;
 mod tray;
 
 pub use main::MainWindow;
-pub use board::{BoardWindow, WM_BOARD_COMMAND, WM_BOARD_FINISHED, WM_UPDATE_LAYOUT};
-pub use tray::{create as tray_item, WM_OPEN_SETTINGS, WM_RELOAD_SETTINGS, WM_SAVE_SETTINGS, WM_SHOW_APPLICATION};
+pub use board::{BoardWindow, BoardWindowOptions, PadCooldown, WM_BOARD_COMMAND, WM_BOARD_FINISHED, WM_GO_HOME, WM_ICONS_WARMED, WM_SAVE_AND_CLOSE, WM_UPDATE_LAYOUT, WM_ZOOM_CHANGED};
+pub use tray::{chosen_log_level, create as tray_item, TrayIcon, show_menu as tray_menu, WM_OPEN_SETTINGS, WM_RELOAD_ICONS, WM_RELOAD_SETTINGS, WM_SAVE_SETTINGS, WM_SET_LOG_LEVEL, WM_SHOW_APPLICATION, WM_TRAY_NOTIFY};
@@ -1,45 +1,157 @@
-use tray_item::{IconSource, TrayItem};
-use windows::Win32::{
-    Foundation::HWND,
-    UI::WindowsAndMessaging::{SendMessageW, WM_CLOSE, WM_USER},
+use log::LevelFilter;
+use windows::{
+    core::{w, HSTRING, PCWSTR},
+    Win32::{
+        Foundation::{HWND, POINT, WPARAM},
+        System::LibraryLoader::GetModuleHandleW,
+        UI::{
+            Shell::{Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE, NOTIFYICONDATAW},
+            WindowsAndMessaging::{
+                AppendMenuW, CreatePopupMenu, DestroyMenu, GetCursorPos, LoadIconW, RegisterWindowMessageW, SendMessageW, SetForegroundWindow, TrackPopupMenu, HMENU,
+                MF_CHECKED, MF_POPUP, MF_SEPARATOR, MF_STRING, MF_UNCHECKED, TPM_NONOTIFY, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_CLOSE, WM_USER
+            },
+        },
+    },
 };
 
 pub const WM_RELOAD_SETTINGS:u32 = WM_USER + 10;
 pub const WM_OPEN_SETTINGS:u32 = WM_USER + 11;
 pub const WM_SAVE_SETTINGS:u32 = WM_USER + 12;
 pub const WM_SHOW_APPLICATION:u32 = WM_USER + 13;
+pub const WM_TRAY_NOTIFY:u32 = WM_USER + 14;
+pub const WM_RELOAD_ICONS:u32 = WM_USER + 15;
+pub const WM_SET_LOG_LEVEL:u32 = WM_USER + 16;
 
-pub fn create(hwnd: isize) -> TrayItem {
-    let mut tray = TrayItem::new("Hotkeys", IconSource::Resource("id")).unwrap();
+// Menu items send the message used as their command id, 0 is a separator and WM_SET_LOG_LEVEL the log level submenu
+const MENU_ITEMS: [(u32, &str); 9] = [
+    (WM_SHOW_APPLICATION, "Open HotKeys"),
+    (0, ""),
+    (WM_OPEN_SETTINGS, "Settings"),
+    (WM_RELOAD_SETTINGS, "Reload"),
+    (WM_RELOAD_ICONS, "Reload icons"),
+    (WM_SAVE_SETTINGS, "Save"),
+    (WM_SET_LOG_LEVEL, "Log level"),
+    (0, ""),
+    (WM_CLOSE, "Quit"),
+];
 
-    tray.add_menu_item("Open HotKeys", move || unsafe {
-        SendMessageW(HWND(hwnd as *mut _), WM_SHOW_APPLICATION, None, None);
-    })
-    .unwrap();
+// Items of the log level submenu, None keeps the levels of the log configuration file
+const LOG_LEVELS: [(Option<LevelFilter>, &str); 6] = [
+    (None, "As configured"),
+    (Some(LevelFilter::Error), "Error"),
+    (Some(LevelFilter::Warn), "Warn"),
+    (Some(LevelFilter::Info), "Info"),
+    (Some(LevelFilter::Debug), "Debug"),
+    (Some(LevelFilter::Trace), "Trace"),
+];
 
-    tray.inner_mut().add_separator().unwrap();
+// Command id of the first log level item, the others follow in order. Chosen, they send WM_SET_LOG_LEVEL with their index.
+const LOG_LEVEL_COMMAND: u32 = 0x1000;
 
-    tray.add_menu_item("Settings", move || unsafe {
-        SendMessageW(HWND(hwnd as *mut _), WM_OPEN_SETTINGS, None, None);
-    })
-    .unwrap();
+/// Level chosen in the tray menu, from the WPARAM of WM_SET_LOG_LEVEL
+pub fn chosen_log_level(wparam: WPARAM) -> Option<LevelFilter> {
+    LOG_LEVELS.get(wparam.0).and_then(|(level, _)| *level)
+}
+
+/// Notification area icon of the main window, removed when dropped.
+/// Mouse input on it reaches the window as WM_TRAY_NOTIFY, with the mouse message in the low word of lparam.
+pub struct TrayIcon {
+    data: NOTIFYICONDATAW,
+    /// Broadcast when the taskbar is created again, e.g. after Explorer restarted, which drops the icons
+    taskbar_created: u32,
+}
 
-    tray.add_menu_item("Reload", move || unsafe {
-        SendMessageW(HWND(hwnd as *mut _), WM_RELOAD_SETTINGS, None, None);
-    })
-    .unwrap();
+impl TrayIcon {
+    /// Adds the icon again when `msg` is the taskbar being recreated, returns whether it was
+    pub fn on_taskbar_created(&self, msg: u32) -> bool {
+        if self.taskbar_created == 0 || msg != self.taskbar_created {
+            return false;
+        }
+        log::info!("Taskbar recreated, adding the tray icon again");
+        self.add();
+        true
+    }
 
-    tray.add_menu_item("Save", move || unsafe {
-        SendMessageW(HWND(hwnd as *mut _), WM_SAVE_SETTINGS, None, None);
-    })
-    .unwrap();
+    fn add(&self) {
+        if !unsafe { Shell_NotifyIconW(NIM_ADD, &self.data) }.as_bool() {
+            log::error!("Failed to add the tray icon");
+        }
+    }
+}
 
-    tray.inner_mut().add_separator().unwrap();
+pub fn create(hwnd: isize) -> TrayIcon {
+    let mut data = NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: HWND(hwnd as *mut _),
+        uID: 1,
+        uFlags: NIF_ICON | NIF_MESSAGE | NIF_TIP,
+        uCallbackMessage: WM_TRAY_NOTIFY,
+        ..Default::default()
+    };
 
-    tray.add_menu_item("Quit", move || unsafe {
-        SendMessageW(HWND(hwnd as *mut _), WM_CLOSE, None, None);
-    })
-    .unwrap();
+    unsafe {
+        if let Ok(instance) = GetModuleHandleW(None) {
+            data.hIcon = LoadIconW(Some(instance.into()), w!("id")).unwrap_or_default();
+        }
+    }
+    let tip: Vec<u16> = "Hotkeys".encode_utf16().collect();
+    data.szTip[..tip.len()].copy_from_slice(&tip);
 
+    let tray = TrayIcon { data, taskbar_created: unsafe { RegisterWindowMessageW(w!("TaskbarCreated")) } };
+    tray.add();
     tray
 }
+
+impl Drop for TrayIcon {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = Shell_NotifyIconW(NIM_DELETE, &self.data);
+        }
+    }
+}
+
+/// Shows the tray menu at the cursor and sends the chosen item's message to the window,
+/// `log_level` is checked in the log level submenu
+pub fn show_menu(hwnd: HWND, log_level: Option<LevelFilter>) {
+    unsafe {
+        let Ok(menu) = CreatePopupMenu() else {
+            log::error!("Failed to create the tray menu");
+            return;
+        };
+        for (command, text) in MENU_ITEMS {
+            let _ = match command {
+                0 => AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null()),
+                WM_SET_LOG_LEVEL => match log_level_menu(log_level) {
+                    Some(submenu) => AppendMenuW(menu, MF_POPUP, submenu.0 as usize, &HSTRING::from(text)),
+                    None => Ok(()),
+                },
+                _ => AppendMenuW(menu, MF_STRING, command as usize, &HSTRING::from(text)),
+            };
+        }
+
+        let mut point = POINT::default();
+        let _ = GetCursorPos(&mut point);
+
+        // Without it the menu doesn't close when clicking elsewhere
+        let _ = SetForegroundWindow(hwnd);
+        let command = TrackPopupMenu(menu, TPM_RETURNCMD | TPM_NONOTIFY | TPM_RIGHTBUTTON, point.x, point.y, None, hwnd, None);
+        let _ = DestroyMenu(menu);
+
+        let command = command.0 as u32;
+        if (LOG_LEVEL_COMMAND..LOG_LEVEL_COMMAND + LOG_LEVELS.len() as u32).contains(&command) {
+            SendMessageW(hwnd, WM_SET_LOG_LEVEL, Some(WPARAM((command - LOG_LEVEL_COMMAND) as usize)), None);
+        } else if command != 0 {
+            SendMessageW(hwnd, command, None, None);
+        }
+    }
+}
+
+/// Submenu of the log levels, destroyed along with the menu it is added to
+unsafe fn log_level_menu(log_level: Option<LevelFilter>) -> Option<HMENU> {
+    let submenu = CreatePopupMenu().ok()?;
+    for (index, (level, text)) in LOG_LEVELS.iter().enumerate() {
+        let check = if *level == log_level { MF_CHECKED } else { MF_UNCHECKED };
+        let _ = AppendMenuW(submenu, MF_STRING | check, (LOG_LEVEL_COMMAND as usize) + index, &HSTRING::from(*text));
+    }
+    Some(submenu)
+}
@@ -5,7 +5,7 @@ use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
 use crate::core::{self, SettingsRepository, SettingsRepositoryMut};
 use crate::input::{ModifierState, TextCapture};
-use crate::model::{Anchor, Board, BoardHandle, ColorScheme, Pad, PadId, PadSet, Tag, TextStyle};
+use crate::model::{Anchor, Board, BoardHandle, ColorScheme, Pad, PadId, PadSet, PadSource, Tag, TextStyle, WindowSizing};
 
 use super::{BoardComponent, UiEvent, UiEventHandler, UiEventResult, SetWindowPosCommand as Command, Direction, ChildWindowRequest, Tags, state_machine::BoardStateMachine};
 
@@ -78,6 +78,21 @@ pub trait DelegatingBoard: HasBoard {
     fn delegate_tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
         self.board().tags(modifier)
     }
+    fn delegate_default_pad(&self) -> Option<PadId> {
+        self.board().default_pad()
+    }
+    fn delegate_letter_keys(&self) -> bool {
+        self.board().letter_keys()
+    }
+    fn delegate_help_tags(&self) -> Vec<Tag> {
+        self.board().help_tags()
+    }
+    fn delegate_sizing(&self) -> WindowSizing {
+        self.board().sizing()
+    }
+    fn delegate_pad_source(&self, pad_id: PadId) -> Option<PadSource> {
+        self.board().pad_source(pad_id)
+    }
 }
 
 impl<T: DelegatingBoard> Board for T {
@@ -102,6 +117,21 @@ impl<T: DelegatingBoard> Board for T {
     fn tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
         self.delegate_tags(modifier)
     }
+    fn default_pad(&self) -> Option<PadId> {
+        self.delegate_default_pad()
+    }
+    fn letter_keys(&self) -> bool {
+        self.delegate_letter_keys()
+    }
+    fn help_tags(&self) -> Vec<Tag> {
+        self.delegate_help_tags()
+    }
+    fn sizing(&self) -> WindowSizing {
+        self.delegate_sizing()
+    }
+    fn pad_source(&self, pad_id: PadId) -> Option<PadSource> {
+        self.delegate_pad_source(pad_id)
+    }
 }
 
 
@@ -220,6 +250,26 @@ impl Board for StateMachineBoard {
     fn tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
         self.state_machine.current_board_ref().data().tags(modifier)
     }
+
+    fn default_pad(&self) -> Option<PadId> {
+        self.state_machine.current_board_ref().data().default_pad()
+    }
+
+    fn letter_keys(&self) -> bool {
+        self.state_machine.current_board_ref().data().letter_keys()
+    }
+
+    fn help_tags(&self) -> Vec<Tag> {
+        self.state_machine.current_board_ref().data().help_tags()
+    }
+
+    fn sizing(&self) -> WindowSizing {
+        self.state_machine.current_board_ref().data().sizing()
+    }
+
+    fn pad_source(&self, pad_id: PadId) -> Option<PadSource> {
+        self.state_machine.current_board_ref().data().pad_source(pad_id)
+    }
 }
 
 impl UiEventHandler for StateMachineBoard {
@@ -257,7 +307,20 @@ impl UiEventHandler for StateMachineBoard {
 
 }
 
-impl_board_component!(StateMachineBoard);
+impl BoardComponent for StateMachineBoard {
+    fn data(&self) -> &dyn Board {
+        self
+    }
+    fn handler(&mut self) -> Option<&mut dyn UiEventHandler> {
+        Some(self)
+    }
+    fn state_stack(&self) -> Vec<String> {
+        self.state_machine.stack()
+    }
+    fn reset_state(&mut self) -> bool {
+        self.state_machine.pop_to_root()
+    }
+}
 
 
 /// SimpleBoard - data only BoardComponent implementation that uses BoardHandle to fetch data from the repository on demand
@@ -309,15 +372,47 @@ impl<R: SettingsRepository + SettingsRepositoryMut> Board for SimpleBoard<R> {
     }
 
     fn padset(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet> {
-        if let Ok(padset_handle) = self.get_handle().padset(modifier) {
-            return padset_handle.pads().map(|p| Box::new(p) as Box<dyn PadSet>).unwrap_or_else(|_| Box::new(vec![] as Vec<Pad>));
-        }
-        Box::new(vec![] as Vec<Pad>)
+        let handle = self.get_handle();
+        let key_caps = handle.key_caps().unwrap_or(false);
+        handle.pads(modifier)
+            .map(|pads| if key_caps { pads.into_iter().map(with_key_caps).collect() } else { pads })
+            .map(|p| Box::new(p) as Box<dyn PadSet>)
+            .unwrap_or_else(|_| Box::new(vec![] as Vec<Pad>))
     }
 
     fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
         std::vec![]
     }
+
+    fn default_pad(&self) -> Option<PadId> {
+        self.get_handle().default_pad().ok().flatten()
+    }
+
+    fn letter_keys(&self) -> bool {
+        self.get_handle().letter_keys().unwrap_or(false)
+    }
+
+    fn help_tags(&self) -> Vec<Tag> {
+        self.get_handle().help().ok().flatten()
+            .map(|help| vec![Tag { text: help, anchor: Anchor::SW, font_idx: Some(0), ..Default::default() }])
+            .unwrap_or_default()
+    }
+
+    fn sizing(&self) -> WindowSizing {
+        self.get_handle().sizing().unwrap_or_default()
+    }
+}
+
+/// Tags the pad with its first shortcut, drawn as key caps below the text
+fn with_key_caps(pad: Pad) -> Pad {
+    let shortcut = pad.actions().iter().find_map(|action| match action {
+        core::ActionType::Shortcut(text) | core::ActionType::ShortcutKeys { text, .. } => Some(text.clone()),
+        _ => None,
+    });
+    match shortcut {
+        Some(text) => pad.with_tags(vec![Tag { text, anchor: Anchor::S, font_idx: Some(0), key_caps: true, ..Default::default() }]),
+        None => pad,
+    }
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> BoardComponent for SimpleBoard<R> {
@@ -404,7 +499,7 @@ pub fn string_editor_board(initial_text: String, board: &dyn BoardComponent, tag
         color_scheme: Some(data.color_scheme()),
         text_style: Some(data.text_style()),
         tags: vec![
-            Tag { text: tag, anchor: Anchor::NW, color_idx: Some(0), ..Default::default() },
+            Tag { text: tag, anchor: Anchor::NW, accent: true, ..Default::default() },
             Tags::EscEnter.default(),
         ],
     }
@@ -463,6 +558,9 @@ impl Board for YesNoBoard {
     fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
         vec![]
     }
+    fn sizing(&self) -> WindowSizing {
+        WindowSizing::Fit
+    }
 }
 
 impl UiEventHandler for YesNoBoard {
@@ -494,6 +592,74 @@ pub fn yes_no_warning_board(message: String, board: &dyn BoardComponent) -> YesN
     YesNoBoard::new(message, Some(board.data().color_scheme()), Some(board.data().text_style()), Some("warning.svg".to_string()))
 }
 
+/// ConfirmPadBoard - asks before a pad with a confirmation prompt runs
+/// Yes selects the confirmed pad, No closes the board
+pub struct ConfirmPadBoard {
+    question: YesNoBoard,
+    pad: core::Pad,
+}
+
+impl ConfirmPadBoard {
+    pub fn new(prompt: String, pad: core::Pad, color_scheme: ColorScheme, text_style: TextStyle) -> Self {
+        Self {
+            question: YesNoBoard::new(prompt, Some(color_scheme), Some(text_style), Some("warning.svg".to_string())),
+            pad: core::Pad { confirm: None, ..pad },
+        }
+    }
+}
+
+impl Board for ConfirmPadBoard {
+    fn name(&self) -> String {
+        "ConfirmPadBoard".to_string()
+    }
+    fn title(&self) -> String {
+        self.question.title()
+    }
+    fn icon(&self) -> Option<String> {
+        self.question.icon()
+    }
+    fn color_scheme(&self) -> ColorScheme {
+        self.question.color_scheme()
+    }
+    fn text_style(&self) -> TextStyle {
+        self.question.text_style()
+    }
+    fn padset(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        // The prompt pad carries the confirmed pad's actions, so selecting it runs them
+        let pads: Vec<Pad> = self.question.padset(modifier).pads().into_iter()
+            .map(|pad| if pad.pad_id() == PadId::Five {
+                let data = core::Pad {
+                    actions: self.pad.actions.clone(),
+                    board: self.pad.board.clone(),
+                    board_params: self.pad.board_params.clone(),
+                    ..pad.data.clone()
+                };
+                pad.with_data(data)
+            } else {
+                pad
+            })
+            .collect();
+        Box::new(pads)
+    }
+    fn sizing(&self) -> WindowSizing {
+        self.question.sizing()
+    }
+}
+
+impl UiEventHandler for ConfirmPadBoard {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match self.question.handle_ui_event(event) {
+            UiEventResult::PopState { result } => match result.downcast_ref::<bool>() {
+                Some(true) => UiEventResult::PadSelected(PadId::Five),
+                _ => UiEventResult::CloseWindow,
+            },
+            other => other,
+        }
+    }
+}
+
+impl_board_component!(ConfirmPadBoard);
+
 /// MessageBoard - a simple message display board component
 /// Returns () on any key press
 pub struct MessageBoard {
@@ -546,6 +712,9 @@ impl Board for MessageBoard {
     fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
         vec![]
     }
+    fn sizing(&self) -> WindowSizing {
+        WindowSizing::Fit
+    }
 }
 
 
@@ -660,7 +829,7 @@ impl LayoutBoard {
 impl DelegatingBoard for LayoutBoard {
     fn delegate_tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
         let mut tags =vec![
-            Tag{ text: format!("{} window", self.mode.as_str()), anchor: Anchor::NW, color_idx: Some(0), ..Default::default() },
+            Tag{ text: format!("{} window", self.mode.as_str()), anchor: Anchor::NW, accent: true, ..Default::default() },
             Tag{ text: format!("x: {}, esc/enter", self.mode.toggle().as_str().to_lowercase()), anchor: Anchor::SW, font_idx: Some(1), color_idx: None, ..Default::default() },
         ];
         tags.extend(vec![
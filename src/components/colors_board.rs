@@ -1,17 +1,17 @@
 use std::{cell::RefCell, rc::Rc};
 
-use windows::Win32::{Foundation::RECT, Graphics::Gdi::{DrawTextW, SelectObject, DT_CALCRECT, DT_NOPREFIX, HDC}, UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_C, VK_D, VK_DELETE, VK_DOWN, VK_E, VK_ESCAPE, VK_F2, VK_LEFT, VK_R, VK_RETURN, VK_RIGHT, VK_S, VK_UP}};
+use windows::Win32::{Foundation::RECT, Graphics::Gdi::{DrawTextW, SelectObject, DT_CALCRECT, DT_NOPREFIX, HDC}, UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_A, VK_C, VK_D, VK_DELETE, VK_DOWN, VK_E, VK_ESCAPE, VK_F2, VK_LEFT, VK_M, VK_R, VK_RETURN, VK_RIGHT, VK_S, VK_UP}};
 
 use super::{
     BoardComponent, ChildWindowRequest, DelegatingBoard, HasBoard, UiEvent, UiEventHandler, UiEventResult, EnumAll, EnumTraversal,
-    apply_bool, apply_string, string_editor_board, yes_no_question_board,
-    HSlider, NumericSpinnerPad, Tags,
+    apply_bool, apply_string, error_board, string_editor_board, success_board, yes_no_question_board,
+    HSlider, Tags,
 };
 
 use crate::{
     core::{self, SettingsRepository, SettingsRepositoryMut}, impl_board_component, impl_board_component_generic, impl_has_board,
     input::{ModifierState},
-    model::{Anchor, AnchorPin, Board, Color, ColorScheme, ColorSchemeHandle, Pad, PadId, PadSet, Tag, TextStyle},
+    model::{Anchor, AnchorPin, Board, Color, ColorScheme, ColorSchemeHandle, MergeColorSchemesUseCase, Pad, PadId, PadSet, ReassignColorSchemeUseCase, Tag, TextStyle},
     ui::dialogs::open_color_picker
 };
 
@@ -63,6 +63,32 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> ColorSchemeEditorB
                     return UiEventResult::NotHandled
                 }
             }
+            VK_A => {
+                if let Ok(cs) = self.handle.as_data() {
+                    UiEventResult::PushState {
+                        board: Box::new(ReassignColorSchemeBoard::new(self.repository.clone(), cs.name)),
+                        context: Box::new(()),
+                    }
+                } else {
+                    UiEventResult::NotHandled
+                }
+            }
+            VK_M => {
+                let duplicates = MergeColorSchemesUseCase::new(self.repository.clone()).duplicates();
+                if duplicates.is_empty() {
+                    return UiEventResult::PushState {
+                        board: Box::new(success_board("No duplicate\ncolor schemes".to_string(), self)),
+                        context: Box::new(()),
+                    };
+                }
+                let count: usize = duplicates.iter().map(|(_, names)| names.len()).sum();
+                UiEventResult::PushState {
+                    board: Box::new(yes_no_question_board(
+                        format!("Merge {} duplicate\ncolor scheme(s)?\n{}", count, merge_summary(&duplicates)), self
+                    )),
+                    context: Box::new("Merge"),
+                }
+            }
             VK_DOWN | VK_RETURN => {
                 let edit_board = EditModeBoard::new(self.repository.clone(), self.handle.as_data().unwrap());
                 UiEventResult::PushState {
@@ -107,6 +133,40 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> ColorSchemeEditorB
         }
         Ok(())
     }
+
+    fn merge_duplicates(&mut self) -> UiEventResult {
+        match MergeColorSchemesUseCase::new(self.repository.clone()).merge() {
+            Ok(merged) => {
+                // The shown scheme may be gone, show the one it was merged into
+                let current = self.handle.name().to_string();
+                if let Some((kept, _)) = merged.iter().find(|(_, names)| names.contains(&current)) {
+                    self.handle.select(kept.clone());
+                }
+                let count: usize = merged.iter().map(|(_, names)| names.len()).sum();
+                log::info!("Merged {} duplicate color scheme(s): {:?}", count, merged);
+                UiEventResult::PushState {
+                    board: Box::new(success_board(format!("Merged {} color scheme(s)\n{}", count, merge_summary(&merged)), self)),
+                    context: Box::new(()),
+                }
+            },
+            Err(e) => UiEventResult::PushState {
+                board: Box::new(error_board(format!("{}", e), self)),
+                context: Box::new(()),
+            },
+        }
+    }
+}
+
+/// One line per duplicate naming the scheme it is merged into, longer lists are cut off
+fn merge_summary(groups: &[(String, Vec<String>)]) -> String {
+    let lines: Vec<String> = groups.iter()
+        .flat_map(|(kept, duplicates)| duplicates.iter().map(move |name| format!("\"{}\" → \"{}\"", name, kept)))
+        .collect();
+    if lines.len() > 4 {
+        format!("{}\n... and {} more", lines[..3].join("\n"), lines.len() - 3)
+    } else {
+        lines.join("\n")
+    }
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for ColorSchemeEditorBoard<R> {
@@ -140,7 +200,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for ColorSch
             Tags::LeftRight.default(),
             Tags::EscEnter.default(),
             Tag{ text: "Colors Schemes".to_string(), anchor: Anchor::NW, ..Default::default() },
-            Tag{ text: "c: copy, d: delete, f2: rename".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+            Tag{ text: "c: copy, d: delete, f2: rename\na: reassign boards, m: merge duplicates".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
         ]
     }
 }
@@ -158,6 +218,8 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
         if let Some(context) = context.downcast_ref::<&str>() {
             if *context == "Title" {
                 return apply_string(result, |new_name| self.rename_color_scheme(new_name))
+            } else if *context == "Merge" && result.downcast_ref::<bool>() == Some(&true) {
+                return self.merge_duplicates();
             } else if *context == "Delete" {
                 return apply_bool(result, |confirm| {
                     if confirm {
@@ -178,13 +240,167 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
 impl_board_component_generic!(ColorSchemeEditorBoard<R>);
 
 
+/// Moves all boards using one color scheme over to another one (Left/Right selects the target)
+struct ReassignColorSchemeBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    source: String,
+    target: ColorSchemeHandle<R>,
+    repository: Rc<R>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> ReassignColorSchemeBoard<R> {
+    pub fn new(repository: Rc<R>, source: String) -> Self {
+        let mut target = ColorSchemeHandle::new(repository.clone(), Some(source.clone()));
+        target.move_next();
+        Self {
+            source,
+            target,
+            repository,
+        }
+    }
+
+    fn uc(&self) -> ReassignColorSchemeUseCase<R> {
+        ReassignColorSchemeUseCase::new(self.repository.clone(), self.source.clone(), self.target.name().to_string())
+    }
+
+    fn move_target(&mut self, forward: bool) {
+        for _ in 0..2 {
+            if forward {
+                self.target.move_next();
+            } else {
+                self.target.move_prev();
+            }
+            if self.target.name() != self.source {
+                break;
+            }
+        }
+    }
+
+    fn references_pad(&self) -> Pad {
+        let board_names = self.uc().referencing_boards();
+        let mut listed: Vec<String> = board_names.iter()
+            .take(6)
+            .map(|name| self.repository.get_board(name).map(|b| b.title().to_string()).unwrap_or(name.clone()))
+            .collect();
+        if board_names.len() > listed.len() {
+            listed.push(format!("... and {} more", board_names.len() - listed.len()));
+        }
+
+        PadId::Eight.with_data(core::Pad {
+            header: Some(format!("Used by {} board(s)", board_names.len())),
+            ..Default::default()
+        }).with_tags(vec![
+            Tag { text: listed.join("\n"), anchor: Anchor::Rel(0.05, 0.35), pin: Some(AnchorPin::NW), font_idx: Some(0), ..Default::default() },
+        ])
+    }
+
+    fn request_confirm(&self) -> UiEventResult {
+        let uc = self.uc();
+        if let Err(e) = uc.validate() {
+            return UiEventResult::PushState {
+                board: Box::new(error_board(e, self)),
+                context: Box::new(()),
+            };
+        }
+        let count = uc.referencing_boards().len();
+        if count == 0 {
+            return UiEventResult::PushState {
+                board: Box::new(error_board(format!("No boards use\n\"{}\"", self.source), self)),
+                context: Box::new(()),
+            };
+        }
+        UiEventResult::PushState {
+            board: Box::new(yes_no_question_board(
+                format!("Reassign {} board(s)\nfrom \"{}\"\nto \"{}\"?", count, self.source, self.target.name()), self
+            )),
+            context: Box::new("Reassign"),
+        }
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for ReassignColorSchemeBoard<R> {
+    fn title(&self) -> String {
+        format!("{} → {}", self.source, self.target.name())
+    }
+
+    fn name(&self) -> String {
+        "ColorSchemeReassign".to_string()
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.target.as_data().unwrap_or_else(|_| self.repository.resolve_color_scheme(&None))
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.repository.resolve_text_style(&None)
+    }
+
+    fn icon(&self) -> Option<String> {
+        None
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        Box::new(vec![ self.references_pad(), EditModeBoard::<R>::preview_pad() ])
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![
+            Tags::LeftRight.default(),
+            Tags::EscEnter.default(),
+            Tag{ text: "Reassign Boards".to_string(), anchor: Anchor::NW, ..Default::default() },
+        ]
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for ReassignColorSchemeBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match event {
+            UiEvent::KeyDown(ke) => {
+                let vk_code = VIRTUAL_KEY(ke.key as u16);
+                match vk_code {
+                    VK_LEFT | VK_RIGHT => {
+                        self.move_target(vk_code == VK_RIGHT);
+                        UiEventResult::RequiresRedraw
+                    }
+                    VK_RETURN => self.request_confirm(),
+                    VK_ESCAPE => UiEventResult::PopState { result: Box::new(()) },
+                    _ => UiEventResult::NotHandled,
+                }
+            },
+            _ => UiEventResult::NotHandled,
+        }
+    }
+
+    fn handle_child_result(&mut self, context: Box<dyn std::any::Any>, result: Box<dyn std::any::Any>) -> UiEventResult {
+        if context.downcast_ref::<&str>() == Some(&"Reassign") && result.downcast_ref::<bool>() == Some(&true) {
+            return match self.uc().reassign() {
+                Ok(count) => UiEventResult::PushState {
+                    board: Box::new(success_board(
+                        format!("Reassigned {} board(s)\nto \"{}\"", count, self.target.name()), self
+                    )),
+                    context: Box::new(()),
+                },
+                Err(e) => UiEventResult::PushState {
+                    board: Box::new(error_board(format!("{}", e), self)),
+                    context: Box::new(()),
+                },
+            };
+        }
+        UiEventResult::RequiresRedraw
+    }
+}
+
+impl_board_component_generic!(ReassignColorSchemeBoard<R>);
+
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum EditMode {
     Background,
+    Background2,
     Opacity,
     Lines,
     Text,
     Tag,
+    Accent,
     Palette(i32),
 }
 
@@ -192,10 +408,12 @@ impl EnumAll<EditMode> for EditMode {
     fn all() -> Vec<EditMode> {
         vec![
             EditMode::Background,
+            EditMode::Background2,
             EditMode::Opacity,
             EditMode::Lines,
             EditMode::Text,
             EditMode::Tag,
+            EditMode::Accent,
             EditMode::Palette(0),
             EditMode::Palette(1),
             EditMode::Palette(2),
@@ -203,21 +421,30 @@ impl EnumAll<EditMode> for EditMode {
     }
 }
 
+/// WCAG AA minimum contrast against the background for text
+const MIN_TEXT_CONTRAST: f64 = 4.5;
+/// WCAG minimum contrast for non-text graphics, such as the pad lines
+const MIN_GRAPHICS_CONTRAST: f64 = 3.0;
+
 impl EditMode {
 
     fn rows(&self, cs: &ColorScheme) -> Vec<TableRow> {
         let label = |mode| if *self == mode { "■■■■■■" } else { "■■■" };
         let font = |mode| if *self == mode { None } else { Some(0) };
+        let background = cs.background();
+        let contrast = |color: Option<Color>| color.map(|color| color.contrast_ratio(&background));
         use EditMode::*;
         vec![
             TableRow::from_str("Background", cs.background().to_hex().as_str(), None, font(Background)),
+            TableRow::from_str("Background 2", cs.background2().map(|color| color.to_hex()).unwrap_or_else(|| "none".to_string()).as_str(), None, font(Background2)),
             TableRow::from_str("Opacity", format!("{:0.2}", cs.opacity).as_str(), None, font(Opacity)),
-            TableRow::from_str("Lines", label(Lines), Some(4), font(Lines)),
-            TableRow::from_str("Text", label(Text), Some(5), font(Text)),
-            TableRow::from_str("Tag", label(Tag), None, font(Tag)),
-            TableRow::from_str("Palette 0", label(Palette(0)), Some(0), font(Palette(0))),
-            TableRow::from_str("Palette 1", label(Palette(1)), Some(1), font(Palette(1))),
-            TableRow::from_str("Palette 2", label(Palette(2)), Some(2), font(Palette(2))),
+            TableRow::from_str("Lines", label(Lines), Some(4), font(Lines)).with_contrast(contrast(Some(cs.foreground1())), MIN_GRAPHICS_CONTRAST),
+            TableRow::from_str("Text", label(Text), Some(5), font(Text)).with_contrast(contrast(Some(cs.foreground2())), MIN_TEXT_CONTRAST),
+            TableRow::from_str("Tag", label(Tag), None, font(Tag)).with_contrast(contrast(Some(cs.tag_foreground())), MIN_TEXT_CONTRAST),
+            TableRow::from_str("Accent", label(Accent), None, font(Accent)).with_accent().with_contrast(contrast(Some(cs.accent())), MIN_TEXT_CONTRAST),
+            TableRow::from_str("Palette 0", label(Palette(0)), Some(0), font(Palette(0))).with_contrast(contrast(cs.palette_color(0)), MIN_TEXT_CONTRAST),
+            TableRow::from_str("Palette 1", label(Palette(1)), Some(1), font(Palette(1))).with_contrast(contrast(cs.palette_color(1)), MIN_TEXT_CONTRAST),
+            TableRow::from_str("Palette 2", label(Palette(2)), Some(2), font(Palette(2))).with_contrast(contrast(cs.palette_color(2)), MIN_TEXT_CONTRAST),
         ]
     }
 
@@ -344,9 +571,11 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for EditMode
         let tag_color = match &self.mode {
             EditMode::Opacity => self.color_scheme.foreground2(),
             EditMode::Background => self.color_scheme.background(),
+            EditMode::Background2 => self.color_scheme.background2().unwrap_or(self.color_scheme.background()),
             EditMode::Lines => self.color_scheme.foreground1(),
             EditMode::Text => self.color_scheme.foreground2(),
             EditMode::Tag => self.color_scheme.tag_foreground(),
+            EditMode::Accent => self.color_scheme.accent(),
             EditMode::Palette(idx) => self.color_scheme.palette_color(*idx as usize).unwrap_or(self.color_scheme.foreground2()),
         };
         cs.palette.push(tag_color.to_hex());
@@ -373,12 +602,14 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for EditMode
                     ..Default::default()
                 }));
             },
-            EditMode::Background | EditMode::Text | EditMode::Lines | EditMode::Tag | EditMode::Palette(_) => {
+            EditMode::Background | EditMode::Background2 | EditMode::Text | EditMode::Lines | EditMode::Tag | EditMode::Accent | EditMode::Palette(_) => {
                 let system_color = match &self.mode {
                     EditMode::Background => SystemColor::Background,
+                    EditMode::Background2 => SystemColor::Background2,
                     EditMode::Text => SystemColor::Text,
                     EditMode::Lines => SystemColor::Lines,
                     EditMode::Tag => SystemColor::Tag,
+                    EditMode::Accent => SystemColor::Accent,
                     EditMode::Palette(i) if *i == 0 => SystemColor::PalleteR,
                     EditMode::Palette(i) if *i == 1 => SystemColor::PalleteG,
                     EditMode::Palette(i) if *i == 2 => SystemColor::PalleteB,
@@ -398,21 +629,26 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for EditMode
 
 
     fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
-        if self.inactive_menu || !self.is_dirty() {
-            vec![
-                Tags::DownUp.default(),
-                Tags::EscEnter.default(),
-                Tag{ text: "Colors Schemes".to_string(), anchor: Anchor::NW, ..Default::default() },
-            ]
-        } else {
-            vec![
-                Tags::DownUp.default(),
-                Tags::EscEnter.default(),
-                Tag{ text: "Colors Schemes".to_string(), anchor: Anchor::NW, ..Default::default() },
-                Tag{ text: "s: save".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
-            ]
+        let mut tags = vec![
+            Tags::DownUp.default(),
+            Tags::EscEnter.default(),
+            Tag{ text: "Colors Schemes".to_string(), anchor: Anchor::NW, ..Default::default() },
+        ];
+        if self.inactive_menu {
+            return tags;
+        }
 
+        let mut hints = vec![];
+        if self.is_dirty() {
+            hints.push("s: save");
         }
+        if self.mode == EditMode::Background2 && self.color_scheme.background2.is_some() {
+            hints.push("del: flat background");
+        }
+        if !hints.is_empty() {
+            tags.push(Tag{ text: hints.join(", "), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() });
+        }
+        tags
     }
 }
 
@@ -430,6 +666,11 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
                         }
                         UiEventResult::RequiresRedraw
                     }
+                    VK_DELETE if self.mode == EditMode::Background2 => {
+                        // Back to a flat background
+                        self.color_scheme.background2 = None;
+                        UiEventResult::RequiresRedraw
+                    }
                     VK_S => {
                         self.repository.set_color_scheme(self.color_scheme.clone())
                             .unwrap_or_else(|e| log::error!("Failed to save color scheme: {}", e));
@@ -447,12 +688,14 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
                                     context: Box::new(EditMode::Opacity),
                                 }
                             },
-                            EditMode::Background | EditMode::Text | EditMode::Lines | EditMode::Tag | EditMode::Palette(_) => {
+                            EditMode::Background | EditMode::Background2 | EditMode::Text | EditMode::Lines | EditMode::Tag | EditMode::Accent | EditMode::Palette(_) => {
                                 let system_color = match &self.mode {
                                     EditMode::Background => SystemColor::Background,
+                                    EditMode::Background2 => SystemColor::Background2,
                                     EditMode::Text => SystemColor::Text,
                                     EditMode::Lines => SystemColor::Lines,
                                     EditMode::Tag => SystemColor::Tag,
+                                    EditMode::Accent => SystemColor::Accent,
                                     EditMode::Palette(i) if *i == 0 => SystemColor::PalleteR,
                                     EditMode::Palette(i) if *i == 1 => SystemColor::PalleteG,
                                     EditMode::Palette(i) if *i == 2 => SystemColor::PalleteB,
@@ -500,7 +743,7 @@ impl_board_component_generic!(EditModeBoard<R>);
 
 struct OpacityEditor<R: SettingsRepository + SettingsRepositoryMut> {
     inner: Box<dyn Board>,
-    spinner: NumericSpinnerPad<f64>,
+    slider: HSlider<f64>,
     #[allow(dead_code)]
     repository: Rc<R>,
 }
@@ -511,7 +754,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut> OpacityEditor<R> {
         let initial = inner.color_scheme().opacity;
         Self {
             inner,
-            spinner: NumericSpinnerPad::new(PadId::Eight, initial, 0.0, 1.0, 0.01, Some(format)),
+            slider: HSlider::new("A".to_string(), initial, 0.0, 1.0, 0.01, Some(format)),
             repository,
         }
     }
@@ -522,12 +765,14 @@ impl_has_board!(OpacityEditor<R>);
 impl<R: SettingsRepository + SettingsRepositoryMut> DelegatingBoard for OpacityEditor<R> {
     fn delegate_color_scheme(&self) -> ColorScheme {
         let mut cs = self.inner.color_scheme();
-        cs.opacity = self.spinner.parsed_formatted_value().unwrap_or(self.spinner.value());
+        cs.opacity = self.slider.value();
         cs
     }
 
     fn delegate_padset(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet> {
-        Box::new(self.inner.padset(modifier).overlay(vec![self.spinner.get_pad()]))
+        let pad = PadId::Eight.with_data(core::Pad::default())
+            .with_tags(vec![self.slider.get_tag(Anchor::C), Tags::LeftRight.tag(Anchor::SE)]);
+        Box::new(self.inner.padset(modifier).overlay(vec![pad]))
     }
 }
 
@@ -537,13 +782,12 @@ impl<R: SettingsRepository + SettingsRepositoryMut> UiEventHandler for OpacityEd
             UiEvent::KeyDown(ke) => {
                 let vk_code = VIRTUAL_KEY(ke.key as u16);
                 match vk_code {
-                    VK_UP | VK_DOWN => self.spinner.key_down(ke),
+                    VK_LEFT | VK_RIGHT => self.slider.key_down(ke),
                     VK_RETURN => UiEventResult::PopState { result: Box::new(self.delegate_color_scheme()) },
                     VK_ESCAPE => UiEventResult::PopState { result: Box::new(()) },
                     _ => UiEventResult::NotHandled,
                 }
             },
-            UiEvent::KeyUp(ke) => self.spinner.key_up(ke),
             _ => UiEventResult::NotHandled,
         }
     }
@@ -553,9 +797,11 @@ impl_board_component_generic!(OpacityEditor<R>);
 
 enum SystemColor {
     Background,
+    Background2,
     Text,
     Lines,
     Tag,
+    Accent,
     PalleteR,
     PalleteG,
     PalleteB,
@@ -565,9 +811,11 @@ impl SystemColor {
     fn to_color(&self, cs: &ColorScheme) -> Color {
         match self {
             SystemColor::Background => cs.background(),
+            SystemColor::Background2 => cs.background2().unwrap_or(cs.background()),
             SystemColor::Text => cs.foreground2(),
             SystemColor::Lines => cs.foreground1(),
             SystemColor::Tag => cs.tag_foreground(),
+            SystemColor::Accent => cs.accent(),
             SystemColor::PalleteR => cs.palette_color(0).unwrap_or(cs.foreground2()),
             SystemColor::PalleteG => cs.palette_color(1).unwrap_or(cs.foreground2()),
             SystemColor::PalleteB => cs.palette_color(2).unwrap_or(cs.foreground2()),
@@ -577,9 +825,11 @@ impl SystemColor {
     fn set_color(&self, cs: &mut ColorScheme, color: &Color) {
         match self {
             SystemColor::Background => cs.background = color.to_hex(),
+            SystemColor::Background2 => cs.background2 = Some(color.to_hex()),
             SystemColor::Text => cs.foreground2 = color.to_hex(),
             SystemColor::Lines => cs.foreground1 = color.to_hex(),
             SystemColor::Tag => cs.tag_foreground = color.to_hex(),
+            SystemColor::Accent => cs.accent = Some(color.to_hex()),
             SystemColor::PalleteR => { if cs.palette.len() > 0 { cs.palette[0] = color.to_hex(); } },
             SystemColor::PalleteG => { if cs.palette.len() > 1 { cs.palette[1] = color.to_hex(); } },
             SystemColor::PalleteB => { if cs.palette.len() > 2 { cs.palette[2] = color.to_hex(); } },
@@ -730,6 +980,7 @@ impl_board_component!(ColorEditor);
 struct TableRow {
     col1: Tag,
     col2: Tag,
+    col3: Option<Tag>,
 }
 
 impl TableRow {
@@ -741,6 +992,9 @@ impl TableRow {
                 anchor: Anchor::NW,
                 color_idx: None,
                 font_idx: None,
+                accent: false,
+                background: None,
+                key_caps: false,
             },
             col2: Tag {
                 text: col2.to_string(),
@@ -748,9 +1002,33 @@ impl TableRow {
                 anchor: Anchor::NE,
                 color_idx: col2_color_idx,
                 font_idx: col2_font_idx,
+                accent: false,
+                background: None,
+                key_caps: false,
             },
+            col3: None,
         }
     }
+
+    pub fn with_accent(mut self) -> Self {
+        self.col2.accent = true;
+        self
+    }
+
+    /// Contrast ratio column, with a warning glyph when the ratio is below `minimum`
+    pub fn with_contrast(mut self, ratio: Option<f64>, minimum: f64) -> Self {
+        self.col3 = ratio.map(|ratio| Tag {
+            text: if ratio < minimum { format!("⚠{:.1}", ratio) } else { format!("{:.1}", ratio) },
+            pin: Some(AnchorPin::NE),
+            anchor: Anchor::NE,
+            color_idx: None,
+            font_idx: Some(0),
+            accent: false,
+            background: None,
+            key_caps: false,
+        });
+        self
+    }
 }
 
 struct TableView {
@@ -767,7 +1045,7 @@ impl TableView {
         for (i, row) in self.rows.iter().enumerate() {
             let y = padding_top + i as i32 * line_spacing;
 
-            // 3 columns: col1, col2, selection indicator
+            // 4 columns: col1, col3 (right-aligned before col2), col2, selection indicator
             let mut col1 = row.col1.clone();
             let mut col2 = row.col2.clone();
 
@@ -776,18 +1054,27 @@ impl TableView {
             col2.anchor = Anchor::Abs2(-padding_right, y);
             col2.pin = Some(AnchorPin::NE);
 
+            let mut row_tags = vec![col1, col2];
+            if let Some(mut col3) = row.col3.clone() {
+                // Room for the widest value, a 7-character hex color
+                col3.anchor = Anchor::Abs2(-(padding_right + line_spacing * 9 / 2), y);
+                col3.pin = Some(AnchorPin::NE);
+                row_tags.push(col3);
+            }
+
             if self.index == Some(i) {
-                let indicator = Tag {
+                row_tags.push(Tag {
                     text: "▶".to_string(),
                     pin: Some(AnchorPin::NW),
                     anchor: Anchor::Abs2(padding_left, y),
-                    color_idx: if inactive { None } else { Some(0) },
+                    color_idx: None,
                     font_idx: None,
-                };
-                tags.push(vec![col1, col2, indicator]);
-            } else {
-                tags.push(vec![col1, col2]);
+                    accent: !inactive,
+                    background: None,
+                    key_caps: false,
+                });
             }
+            tags.push(row_tags);
         }
 
         tags
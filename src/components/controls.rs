@@ -1,117 +1,13 @@
-use num_traits::ToPrimitive;
-use windows::Win32::UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_DOWN, VK_LEFT, VK_RIGHT, VK_UP};
+use num_traits::{NumCast, ToPrimitive};
+use windows::Win32::UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_LEFT, VK_RIGHT};
 
-use crate::core;
-use crate::model::{Anchor, Pad, PadId, Tag};
+use crate::model::{Anchor, Tag};
 use super::{KeyboardEvent, UiEventResult};
 
 use std::ops::{Add, Div, Mul, Sub};
 
-pub struct NumericSpinnerPad<T> {
-    pad_id: PadId,
-    value: T,
-    min: T,
-    max: T,
-    step: T,
-    direction: i32,
-    format: fn(T) -> String,
-}
-
-impl<T> NumericSpinnerPad<T>
-where
-    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + std::fmt::Display,
-{
-    pub fn new(pad_id: PadId, value: T, min: T, max: T, step: T, format: Option<fn(T) -> String>) -> Self {
-        Self {
-            pad_id,
-            value,
-            min,
-            max,
-            step,
-            format : format.unwrap_or(|v| format!("{}", v)),
-            direction: 0,
-        }
-    }
-
-    pub fn value(&self) -> T {
-        self.value
-    }
-
-    pub fn parsed_formatted_value(&self) -> Result<T, Box<dyn std::error::Error>>
-    where
-        T: std::str::FromStr,
-        T::Err: std::error::Error + 'static,
-    {
-        let formatted_string = (self.format)(self.value);
-        let parsed_value = formatted_string.parse::<T>()?;
-        Ok(parsed_value)
-    }
-}
-
-impl<T> NumericSpinnerPad<T>
-where
-    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + std::fmt::Display,
-{
-    pub fn key_down(&mut self, event: KeyboardEvent) -> UiEventResult {
-        match event.into() {
-            VK_UP | VK_DOWN => {
-                self.direction = if VIRTUAL_KEY::from(event) == VK_UP { 1 } else { -1 };
-                let inc = if event.modifiers.shift {
-                    // If shift is held, increase step by 10x
-                    let mut inc = self.step;
-                    for _ in 1..10 {
-                        inc = inc + self.step;
-                    }
-                    inc
-                } else {
-                    self.step
-                };
-                let new_val = if self.direction == 1 {
-                    self.value + inc
-                } else {
-                    self.value - inc
-                };
-                if new_val > self.max {
-                    self.value = self.max;
-                } else if new_val < self.min {
-                    self.value = self.min;
-                } else {
-                    self.value = new_val;
-                }
-                UiEventResult::RequiresRedraw
-            }
-            _ => UiEventResult::NotHandled,
-        }
-    }
-
-    pub fn key_up(&mut self, event: KeyboardEvent) -> UiEventResult {
-        match event.into() {
-            VK_UP | VK_DOWN => {
-                self.direction = 0;
-                UiEventResult::RequiresRedraw
-            }
-            _ => UiEventResult::NotHandled,
-        }
-    }
-
-
-    pub fn get_pad(&self) -> Pad {
-
-        let text = (self.format)(self.value);
-
-        Pad::from(self.pad_id).with_data(core::Pad {
-            text: Some(text),
-            ..Default::default()
-        })
-        .with_tags(vec![
-            Tags::UpWhite.with(Anchor::N, if self.direction == 1 { Some(0) } else { None }, Some(2)),
-            Tags::DownWhite.with(Anchor::S, if self.direction == -1 { Some(0) } else { None }, Some(2)),
-            Tags::RightBlack.tag(Anchor::W),
-        ])
-    }
-}
-
-
+/// Horizontal slider over a numeric range, stepped with the left/right arrows.
+/// Works for integer (RGB components) as well as float (opacity, zoom) parameterizations.
 pub struct HSlider<T> {
     label: String,
     value: T,
@@ -123,7 +19,7 @@ pub struct HSlider<T> {
 
 impl<T> HSlider<T>
 where
-    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + ToPrimitive + std::fmt::Display,
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + ToPrimitive + NumCast + std::fmt::Display,
 {
     pub fn new(label: String, value: T, min: T, max: T, step: T, format: Option<fn(T) -> String>) -> Self {
         Self {
@@ -139,11 +35,23 @@ where
     pub fn value(&self) -> T {
         self.value
     }
+
+    /// Snaps the value onto the step grid starting at min, so repeated float steps don't accumulate rounding errors
+    fn snap(&self, value: T) -> T {
+        let (Some(min), Some(step), Some(val)) = (self.min.to_f64(), self.step.to_f64(), value.to_f64()) else {
+            return value;
+        };
+        if step <= 0.0 {
+            return value;
+        }
+        let snapped = min + ((val - min) / step).round() * step;
+        NumCast::from(snapped).unwrap_or(value)
+    }
 }
 
 impl<T> HSlider<T>
 where
-    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + ToPrimitive + std::fmt::Display,
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + ToPrimitive + NumCast + std::fmt::Display,
 {
     pub fn key_down(&mut self, event: KeyboardEvent) -> UiEventResult {
         match event.into() {
@@ -163,6 +71,7 @@ where
                 } else {
                     self.value - inc
                 };
+                let new_val = self.snap(new_val);
                 if new_val > self.max {
                     self.value = self.max;
                 } else if new_val < self.min {
@@ -176,18 +85,23 @@ where
         }
     }
 
-    // Creates a Tag representing the current value and ascii representation of the slider (10 segments): "<label> |■■■■■□□□□□| <formatted_value>"
-    // Segments are filled based on the value's position between min and max (use f32 for internal calculation, then convert back to T for display)
-    pub fn get_tag(&self, anchor: Anchor) -> Tag {
-       let segments = 16;
+    /// Number of filled segments out of `segments`, based on the value's position between min and max
+    fn filled_segments(&self, segments: usize) -> usize {
+        let min = self.min.to_f64().unwrap_or(0.0);
+        let max = self.max.to_f64().unwrap_or(0.0);
+        let value = self.value.to_f64().unwrap_or(min);
 
-        // Convert to f32 safely
-        let min = self.min.to_f32().unwrap();
-        let max = self.max.to_f32().unwrap();
-        let value = self.value.to_f32().unwrap();
+        if max <= min {
+            return segments;
+        }
+        let ratio = ((value - min) / (max - min)).clamp(0.0, 1.0);
+        ((ratio * segments as f64).round() as usize).min(segments)
+    }
 
-        let ratio = (value - min) / (max - min);
-        let filled_segments = (ratio * segments as f32).round() as usize;
+    // Creates a Tag representing the current value and ascii representation of the slider (16 segments): "<label> |■■■■■□□□□□| <formatted_value>"
+    pub fn get_tag(&self, anchor: Anchor) -> Tag {
+        let segments = 16;
+        let filled_segments = self.filled_segments(segments);
         let empty_segments = segments - filled_segments;
 
         let filled_bar = "■".repeat(filled_segments);
@@ -248,10 +162,10 @@ impl Tags {
 
     pub fn default(&self) -> Tag {
         match self {
-            Tags::RightBlack => Tag { text: self.to_string(), anchor: Anchor::W, font_idx: None, color_idx: Some(0), ..Default::default() },
-            Tags::LeftBlack => Tag { text: self.to_string(), anchor: Anchor::E, font_idx: None, color_idx: Some(0), ..Default::default() },
-            Tags::UpBlack => Tag { text: self.to_string(), anchor: Anchor::S, font_idx: None, color_idx: Some(0), ..Default::default() },
-            Tags::DownBlack => Tag { text: self.to_string(), anchor: Anchor::N, font_idx: None, color_idx: Some(0), ..Default::default() },
+            Tags::RightBlack => Tag { text: self.to_string(), anchor: Anchor::W, font_idx: None, color_idx: None, accent: true, ..Default::default() },
+            Tags::LeftBlack => Tag { text: self.to_string(), anchor: Anchor::E, font_idx: None, color_idx: None, accent: true, ..Default::default() },
+            Tags::UpBlack => Tag { text: self.to_string(), anchor: Anchor::S, font_idx: None, color_idx: None, accent: true, ..Default::default() },
+            Tags::DownBlack => Tag { text: self.to_string(), anchor: Anchor::N, font_idx: None, color_idx: None, accent: true, ..Default::default() },
             Tags::RightWhite => Tag { text: self.to_string(), anchor: Anchor::W, font_idx: Some(2), color_idx: None, ..Default::default() },
             Tags::LeftWhite => Tag { text: self.to_string(), anchor: Anchor::E, font_idx: Some(2), color_idx: None, ..Default::default() },
             Tags::UpWhite => Tag { text: self.to_string(), anchor: Anchor::S, font_idx: Some(2), color_idx: None, ..Default::default() },
@@ -277,3 +191,46 @@ impl Tags {
         tag
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::UI::Input::KeyboardAndMouse::VK_UP;
+    use crate::input::ModifierState;
+
+    fn key(vk: VIRTUAL_KEY, shift: bool) -> KeyboardEvent {
+        KeyboardEvent { key: vk.0 as u32, modifiers: ModifierState { shift, ..Default::default() } }
+    }
+
+    #[test]
+    fn test_hslider_int_stepping() {
+        let mut slider = HSlider::new("R".to_string(), 250, 0, 255, 1, None);
+        slider.key_down(key(VK_RIGHT, false));
+        assert_eq!(slider.value(), 251);
+        slider.key_down(key(VK_RIGHT, true));
+        assert_eq!(slider.value(), 255);
+        slider.key_down(key(VK_LEFT, true));
+        assert_eq!(slider.value(), 245);
+        assert!(matches!(slider.key_down(key(VK_UP, false)), UiEventResult::NotHandled));
+        assert!(slider.get_tag(Anchor::C).text.ends_with("| 245"));
+    }
+
+    #[test]
+    fn test_hslider_float_stepping() {
+        let mut slider = HSlider::new("Opacity".to_string(), 0.5, 0.0, 1.0, 0.01, Some(|v| format!("{:0.2}", v)));
+        for _ in 0..3 {
+            slider.key_down(key(VK_RIGHT, false));
+        }
+        assert!((slider.value() - 0.53).abs() < 1e-9);
+        slider.key_down(key(VK_RIGHT, true));
+        assert!((slider.value() - 0.63).abs() < 1e-9);
+        for _ in 0..10 {
+            slider.key_down(key(VK_LEFT, true));
+        }
+        assert_eq!(slider.value(), 0.0);
+
+        let tag = slider.get_tag(Anchor::C);
+        assert_eq!(tag.text, format!("Opacity |{}| 0.00", "□".repeat(16)));
+    }
+}
@@ -0,0 +1,324 @@
+use std::{fs, rc::Rc};
+
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::core::{self, BoardExport, ImportOutcome, ImportPolicy, Resources, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::model::{Anchor, Board, ColorScheme, ImportBoardUseCase, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+
+use super::{BoardComponent, error_board, success_board, yes_no_question_board, MouseEventTarget, PadMapping, UiEvent, UiEventHandler, UiEventResult};
+
+/// Example boards built into the application, each in the shape of a board export.
+/// Used when the resources have no templates folder.
+const TEMPLATES: [&str; 3] = [
+    include_str!("../../resources/templates/media.json"),
+    include_str!("../../resources/templates/dev.json"),
+    include_str!("../../resources/templates/windows.json"),
+];
+
+/// The templates in the templates folder of the resources, or the built-in ones without it.
+/// Ones that can't be read or don't parse are left out, the first nine fill the pads top-down.
+fn templates(resources: &Resources) -> Vec<BoardExport> {
+    let files = resources.templates();
+    if files.is_empty() {
+        return builtin_templates();
+    }
+
+    files.iter()
+        .filter_map(|path| match fs::read_to_string(path) {
+            Ok(json) => parse_template(&json, &path.display().to_string()),
+            Err(e) => {
+                log::error!("Board template {} not read: {}", path.display(), e);
+                None
+            }
+        })
+        .take(PadId::reading_order().len())
+        .collect()
+}
+
+fn builtin_templates() -> Vec<BoardExport> {
+    TEMPLATES.iter().filter_map(|json| parse_template(json, "built-in")).collect()
+}
+
+fn parse_template(json: &str, source: &str) -> Option<BoardExport> {
+    match BoardExport::from_json(json) {
+        Ok(template) => Some(template),
+        Err(e) => {
+            log::error!("Invalid board template ({}): {}", source, e);
+            None
+        }
+    }
+}
+
+/// Steps of installing the template at the index
+enum InstallStep {
+    Confirm(usize),
+    Policy(usize),
+}
+
+/// GalleryBoard - one pad per template, selecting one asks and installs its boards next to the existing ones.
+/// When a board name is in use, the user picks whether the template's board is renamed, overwrites it or is skipped.
+pub struct GalleryBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    board: core::Board,
+    resources: Resources,
+    repository: Rc<R>,
+    templates: Vec<BoardExport>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> GalleryBoard<R> {
+    pub fn new(board: core::Board, resources: Resources, repository: Rc<R>) -> Self {
+        let templates = templates(&resources);
+        Self { board, resources, repository, templates }
+    }
+
+    fn template_index(&self, pad_id: PadId) -> Option<usize> {
+        PadId::reading_order().iter().position(|id| *id == pad_id).filter(|index| *index < self.templates.len())
+    }
+
+    fn import_use_case(&self, index: usize, policy: ImportPolicy) -> ImportBoardUseCase<R> {
+        ImportBoardUseCase::new(self.repository.clone(), self.resources.clone(), self.templates[index].clone(), policy)
+    }
+
+    fn request_install(&self, index: usize) -> UiEventResult {
+        UiEventResult::PushState {
+            board: Box::new(yes_no_question_board(format!("Install template\n\"{}\"?", self.templates[index].boards[0].title()), self)),
+            context: Box::new(InstallStep::Confirm(index)),
+        }
+    }
+
+    fn request_policy_or_install(&self, index: usize) -> UiEventResult {
+        let conflicts = self.import_use_case(index, ImportPolicy::default()).conflicts();
+        if conflicts.is_empty() {
+            return self.install(index, ImportPolicy::default());
+        }
+        UiEventResult::PushState {
+            board: Box::new(ImportPolicyBoard::new(conflicts, self, self.repository.clone())),
+            context: Box::new(InstallStep::Policy(index)),
+        }
+    }
+
+    fn install(&self, index: usize, policy: ImportPolicy) -> UiEventResult {
+        let board: Box<dyn BoardComponent> = match self.import_use_case(index, policy).import() {
+            Ok(outcomes) => {
+                let installed = outcomes.iter().any(|outcome| !matches!(outcome, ImportOutcome::Skipped(_)));
+                let lines: Vec<String> = outcomes.iter().map(|outcome| outcome.to_string()).collect();
+                let title = match installed {
+                    true => "Installed",
+                    false => "Nothing installed",
+                };
+                Box::new(success_board(format!("{}\n{}", title, lines.join("\n")), self))
+            },
+            Err(err) => Box::new(error_board(format!("{}", err), self)),
+        };
+        UiEventResult::PushState { board, context: Box::new(()) }
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for GalleryBoard<R> {
+    fn name(&self) -> String {
+        "template_gallery".to_string()
+    }
+
+    fn title(&self) -> String {
+        "Templates".to_string()
+    }
+
+    fn icon(&self) -> Option<String> {
+        Some("gear.svg".to_string())
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.board.with_opacity(self.repository.resolve_color_scheme(&self.board.color_scheme))
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.repository.resolve_text_style(&self.board.text_style)
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        let pads: Vec<Pad> = self.templates.iter()
+            .zip(PadId::reading_order().iter())
+            .map(|(template, pad_id)| {
+                let header = match template.boards.len() {
+                    1 => String::new(),
+                    count => format!("{} boards", count),
+                };
+                pad_id.with_data(core::Pad {
+                    header: Some(header),
+                    text: Some(template.boards[0].title().to_string()),
+                    icon: template.boards[0].icon.clone(),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        Box::new(pads)
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![
+            Tag { text: "esc".to_string(), anchor: Anchor::NW, font_idx: Some(0), ..Default::default() },
+            Tag { text: "1-9: install".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+        ]
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for GalleryBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        let pad_id = match event {
+            UiEvent::KeyDown(key_event) => match VIRTUAL_KEY(key_event.key as u16) {
+                VK_ESCAPE => return UiEventResult::PopState { result: Box::new(()) },
+                vk_code => PadMapping::new(self.repository.clone()).map(vk_code),
+            },
+            UiEvent::RightMouseDown(me) => match me.target {
+                MouseEventTarget::Pad(pad_id) => Some(pad_id),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match pad_id.and_then(|pad_id| self.template_index(pad_id)) {
+            Some(index) => self.request_install(index),
+            None => UiEventResult::NotHandled,
+        }
+    }
+
+    fn handle_child_result(&mut self, context: Box<dyn std::any::Any>, result: Box<dyn std::any::Any>) -> UiEventResult {
+        match context.downcast_ref::<InstallStep>() {
+            Some(InstallStep::Confirm(index)) if result.downcast_ref::<bool>() == Some(&true) => self.request_policy_or_install(*index),
+            Some(InstallStep::Policy(index)) => match result.downcast_ref::<ImportPolicy>() {
+                Some(policy) => self.install(*index, *policy),
+                None => UiEventResult::RequiresRedraw,
+            },
+            _ => UiEventResult::RequiresRedraw,
+        }
+    }
+}
+
+impl_board_component_generic!(GalleryBoard<R>);
+
+/// The policies on the middle row, left to right
+const POLICY_PADS: [(PadId, ImportPolicy, &str); 3] = [
+    (PadId::Four, ImportPolicy::Rename, "add as _2"),
+    (PadId::Five, ImportPolicy::Overwrite, "back up, replace"),
+    (PadId::Six, ImportPolicy::Skip, "keep existing"),
+];
+
+/// ImportPolicyBoard - asks what to do with imported boards whose names are in use, returns the ImportPolicy picked.
+/// Escape cancels the import.
+pub struct ImportPolicyBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    conflicts: Vec<String>,
+    color_scheme: ColorScheme,
+    text_style: TextStyle,
+    repository: Rc<R>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> ImportPolicyBoard<R> {
+    pub fn new(conflicts: Vec<String>, board: &dyn BoardComponent, repository: Rc<R>) -> Self {
+        Self { conflicts, color_scheme: board.data().color_scheme(), text_style: board.data().text_style(), repository }
+    }
+
+    fn policy(&self, pad_id: PadId) -> Option<ImportPolicy> {
+        POLICY_PADS.iter().find(|(id, _, _)| *id == pad_id).map(|(_, policy, _)| *policy)
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for ImportPolicyBoard<R> {
+    fn name(&self) -> String {
+        "import_policy".to_string()
+    }
+
+    fn title(&self) -> String {
+        "Name in use".to_string()
+    }
+
+    fn icon(&self) -> Option<String> {
+        Some("question.svg".to_string())
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.color_scheme.clone()
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.text_style.clone()
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        let mut pads = vec![PadId::Eight.with_data(core::Pad {
+            header: Some("boards exist".to_string()),
+            text: Some(format!("\"{}\"", self.conflicts.join("\", \""))),
+            label: true,
+            ..Default::default()
+        })];
+        pads.extend(POLICY_PADS.iter().map(|(pad_id, policy, header)| pad_id.with_data(core::Pad {
+            header: Some(header.to_string()),
+            text: Some(policy.to_string()),
+            ..Default::default()
+        })));
+        Box::new(pads)
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![
+            Tag { text: "esc: cancel".to_string(), anchor: Anchor::NW, font_idx: Some(0), ..Default::default() },
+        ]
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for ImportPolicyBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        let pad_id = match event {
+            UiEvent::KeyDown(key_event) => match VIRTUAL_KEY(key_event.key as u16) {
+                VK_ESCAPE => return UiEventResult::PopState { result: Box::new(()) },
+                vk_code => PadMapping::new(self.repository.clone()).map(vk_code),
+            },
+            UiEvent::RightMouseDown(me) => match me.target {
+                MouseEventTarget::Pad(pad_id) => Some(pad_id),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match pad_id.and_then(|pad_id| self.policy(pad_id)) {
+            Some(policy) => UiEventResult::PopState { result: Box::new(policy) },
+            None => UiEventResult::Handled,
+        }
+    }
+}
+
+impl_board_component_generic!(ImportPolicyBoard<R>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_are_valid() {
+        let templates = builtin_templates();
+        assert_eq!(templates.len(), TEMPLATES.len());
+        assert!(templates.len() <= PadId::reading_order().len());
+        for template in &templates {
+            template.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_templates_from_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let resources = Resources::new(vec![dir.path().to_path_buf()]);
+        assert_eq!(templates(&resources).len(), TEMPLATES.len());
+
+        let templates_dir = dir.path().join("templates");
+        fs::create_dir(&templates_dir).unwrap();
+        fs::write(templates_dir.join("b.json"), TEMPLATES[1]).unwrap();
+        fs::write(templates_dir.join("a.json"), TEMPLATES[0]).unwrap();
+        fs::write(templates_dir.join("broken.json"), "{").unwrap();
+        fs::write(templates_dir.join("notes.txt"), "not a template").unwrap();
+
+        let expected: Vec<String> = TEMPLATES[..2].iter()
+            .map(|json| BoardExport::from_json(json).unwrap().boards[0].title().to_string())
+            .collect();
+        let titles: Vec<String> = templates(&resources).iter().map(|template| template.boards[0].title().to_string()).collect();
+        assert_eq!(titles, expected);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::model::{Anchor, Board, ModifierState, Pad, PadSet, Tag};
+
+use super::{palette_board::action_text, DelegatingBoard, HasBoard};
+
+/// Lines of a pad's summary, longer lists of actions are cut off
+const MAX_SUMMARY_LINES: usize = 3;
+
+/// HelpOverlay - the displayed board as shown while the help key is held:
+/// the board's help tags instead of its own, and each pad tagged with what it does
+pub struct HelpOverlay<'a> {
+    inner: &'a dyn Board,
+}
+
+impl<'a> HelpOverlay<'a> {
+    pub fn new(inner: &'a dyn Board) -> Self {
+        Self { inner }
+    }
+}
+
+impl HasBoard for HelpOverlay<'_> {
+    fn board(&self) -> &dyn Board {
+        self.inner
+    }
+}
+
+impl DelegatingBoard for HelpOverlay<'_> {
+    fn delegate_padset(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        let pads: Vec<Pad> = self.inner.padset(modifier).pads().into_iter()
+            .map(|pad| {
+                let tags = pad_summary(&pad)
+                    .map(|text| vec![Tag { text, anchor: Anchor::S, font_idx: Some(0), accent: true, ..Default::default() }])
+                    .unwrap_or_default();
+                pad.with_tags(tags)
+            })
+            .collect();
+        Box::new(pads)
+    }
+
+    fn delegate_tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        self.inner.help_tags()
+    }
+}
+
+/// What selecting the pad does, one line per action and one for the board it opens
+fn pad_summary(pad: &Pad) -> Option<String> {
+    if !pad.data.is_interactive() {
+        return None;
+    }
+    let mut lines: Vec<String> = pad.actions().iter()
+        .map(action_text)
+        .filter(|text| !text.is_empty())
+        .collect();
+    if let Some(board) = pad.board().filter(|board| !board.is_empty()) {
+        lines.push(format!("→ {}", board));
+    }
+    if lines.len() > MAX_SUMMARY_LINES {
+        lines.truncate(MAX_SUMMARY_LINES - 1);
+        lines.push("…".to_string());
+    }
+    Some(lines.join("\n")).filter(|summary| !summary.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{self, ActionType};
+    use crate::model::PadId;
+
+    #[test]
+    fn test_pad_summary() {
+        let pad = |actions: Vec<ActionType>, board: Option<&str>| PadId::Five.with_data(core::Pad {
+            actions,
+            board: board.map(|b| b.to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(pad_summary(&pad(vec![], None)), None);
+        assert_eq!(pad_summary(&pad(vec![ActionType::Pause(100)], None)), None);
+        assert_eq!(pad_summary(&pad(vec![ActionType::Shortcut("Ctrl C".to_string())], Some("git"))).as_deref(), Some("Ctrl C\n→ git"));
+
+        let actions = (1..=5).map(|i| ActionType::Text(format!("line {}", i))).collect();
+        assert_eq!(pad_summary(&pad(actions, None)).as_deref(), Some("line 1\nline 2\n…"));
+    }
+}
@@ -145,7 +145,7 @@ impl <R: SettingsRepository + SettingsRepositoryMut + 'static> Board for HomeBoa
     }
 
     fn color_scheme(&self) -> ColorScheme {
-        self.repository.resolve_color_scheme(&self.board.color_scheme)
+        self.board.with_opacity(self.repository.resolve_color_scheme(&self.board.color_scheme))
     }
 
     fn text_style(&self) -> TextStyle {
@@ -369,7 +369,7 @@ impl <R: SettingsRepository + SettingsRepositoryMut> Board for TourBoard<R> {
     }
 
     fn color_scheme(&self) -> ColorScheme {
-        let mut cs = self.repository.resolve_color_scheme(&self.board.color_scheme);
+        let mut cs = self.board.with_opacity(self.repository.resolve_color_scheme(&self.board.color_scheme));
         cs.palette.push(cs.foreground2().equidistant(&cs.background()).to_hex());
         cs
     }
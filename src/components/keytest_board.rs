@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::core::{self, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::input::keys::{keyboard_api, vkey};
+use crate::model::{Anchor, Board, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+
+use super::{map_pad_id, BoardComponent, KeyboardEvent, UiEvent, UiEventHandler, UiEventResult};
+
+/// Keys listed in the history pad, the latest first
+const HISTORY_LEN: usize = 5;
+
+/// The keys pressed on the key tester, Escape only closes it when held, so a single press can be tested as well
+#[derive(Default)]
+struct KeyLog {
+    last: Option<KeyboardEvent>,
+    held: Option<(u32, ModifierState)>,
+    history: VecDeque<String>,
+}
+
+impl KeyLog {
+    /// Records a key press, returns whether it was the auto-repeat of a held Escape
+    fn key_down(&mut self, event: KeyboardEvent) -> bool {
+        let repeated = self.held == Some((event.key, event.modifiers));
+        if repeated && VIRTUAL_KEY(event.key as u16) == VK_ESCAPE {
+            return true;
+        }
+        if !repeated {
+            self.history.push_front(key_name(event.key as u16));
+            self.history.truncate(HISTORY_LEN);
+        }
+        self.held = Some((event.key, event.modifiers));
+        self.last = Some(event);
+        false
+    }
+
+    fn key_up(&mut self, event: KeyboardEvent) {
+        if self.held.is_some_and(|(key, _)| key == event.key) {
+            self.held = None;
+        }
+    }
+}
+
+/// Name of a key as shortcuts spell it, its code for keys without one
+fn key_name(vk_code: u16) -> String {
+    vkey::find_vkey_by_code(vk_code)
+        .map(|vk| vk.title.to_string())
+        .or_else(|| keyboard_api::vkey_to_string(vk_code, &ModifierState::default(), false))
+        .unwrap_or_else(|| format!("0x{:02X}", vk_code))
+}
+
+/// KeyTesterBoard - hidden diagnostic board opened with `--board __keytest`, showing how each key pressed on it is seen:
+/// its virtual key code, the pad it selects, the character it types on the current layout and the modifiers held
+pub struct KeyTesterBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    repository: Rc<R>,
+    log: KeyLog,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> KeyTesterBoard<R> {
+    pub fn new(repository: Rc<R>) -> Self {
+        Self { repository, log: KeyLog::default() }
+    }
+
+    fn key_pads(&self, event: &KeyboardEvent) -> Vec<Pad> {
+        let vk_code = event.key as u16;
+        let pad_id = map_pad_id(VIRTUAL_KEY(vk_code), self.repository.natural_key_order(), self.repository.pad_keys());
+        let character = keyboard_api::vkey_to_string(vk_code, &event.modifiers, false)
+            .filter(|ch| !ch.chars().all(char::is_control));
+        let modifiers = match event.modifiers.is_none() {
+            true => "none".to_string(),
+            false => event.modifiers.to_string(),
+        };
+
+        vec![
+            info_pad(PadId::Seven, "virtual key", format!("0x{:02X}\n({})", vk_code, vk_code)),
+            info_pad(PadId::Eight, "key", key_name(vk_code)),
+            info_pad(PadId::Nine, "character", character.map(|ch| format!("\"{}\"", ch)).unwrap_or_else(|| "none".to_string())),
+            info_pad(PadId::Four, "pad", pad_id.map(|pad_id| pad_id.to_string()).unwrap_or_else(|| "none".to_string())),
+            info_pad(PadId::Five, "modifiers", modifiers),
+        ]
+    }
+}
+
+fn info_pad(pad_id: PadId, header: &str, text: String) -> Pad {
+    pad_id.with_data(core::Pad {
+        header: Some(header.to_string()),
+        text: Some(text),
+        label: true,
+        ..Default::default()
+    })
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for KeyTesterBoard<R> {
+    fn name(&self) -> String {
+        core::data::KEYTEST_BOARD_NAME.to_string()
+    }
+
+    fn title(&self) -> String {
+        "Key tester".to_string()
+    }
+
+    fn icon(&self) -> Option<String> {
+        Some("info.svg".to_string())
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.repository.resolve_color_scheme(&None)
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.repository.resolve_text_style(&None)
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        let mut pads = match &self.log.last {
+            Some(event) => self.key_pads(event),
+            None => vec![info_pad(PadId::Five, "", "Press any key".to_string())],
+        };
+        if !self.log.history.is_empty() {
+            pads.push(info_pad(PadId::Two, "history", self.log.history.iter().cloned().collect::<Vec<_>>().join("  ")));
+        }
+        Box::new(pads)
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![
+            Tag { text: "hold esc: exit".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+        ]
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for KeyTesterBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match event {
+            UiEvent::KeyDown(key_event) => match self.log.key_down(key_event) {
+                true => UiEventResult::CloseWindow,
+                false => UiEventResult::RequiresRedraw,
+            },
+            UiEvent::KeyUp(key_event) => {
+                self.log.key_up(key_event);
+                UiEventResult::Handled
+            },
+            UiEvent::RightMouseDown(_) => UiEventResult::Handled,
+        }
+    }
+}
+
+impl_board_component_generic!(KeyTesterBoard<R>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(vk_code: VIRTUAL_KEY, modifiers: ModifierState) -> KeyboardEvent {
+        KeyboardEvent { key: vk_code.0 as u32, modifiers }
+    }
+
+    #[test]
+    fn test_key_log() {
+        let ctrl = ModifierState { ctrl: true, ..Default::default() };
+        let mut log = KeyLog::default();
+
+        // A held key is listed once, pressed again it is listed again
+        assert!(!log.key_down(key(VK_F5, ModifierState::default())));
+        assert!(!log.key_down(key(VK_F5, ModifierState::default())));
+        log.key_up(key(VK_F5, ModifierState::default()));
+        assert!(!log.key_down(key(VK_F5, ModifierState::default())));
+        assert!(!log.key_down(key(VK_F5, ctrl)));
+        assert_eq!(log.history.len(), 3);
+        assert_eq!(log.last.map(|last| last.modifiers), Some(ctrl));
+
+        // Escape is tested like any key, only its auto-repeat closes the board
+        assert!(!log.key_down(key(VK_ESCAPE, ModifierState::default())));
+        log.key_up(key(VK_ESCAPE, ModifierState::default()));
+        assert!(!log.key_down(key(VK_ESCAPE, ModifierState::default())));
+        assert!(log.key_down(key(VK_ESCAPE, ModifierState::default())));
+
+        for _ in 0..10 {
+            log.key_down(key(VK_F6, ModifierState::default()));
+            log.key_up(key(VK_F6, ModifierState::default()));
+        }
+        assert_eq!(log.history.len(), HISTORY_LEN);
+    }
+}
@@ -6,18 +6,21 @@ use windows::Win32::Foundation::WPARAM;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
 
-use crate::components::PadMapping;
-use crate::core::{ActionType, Param, Params, PathString, Resources, SettingsRepository, SettingsRepositoryMut};
-use crate::model::{DeleteBoardUseCase, create_modifier_pad_set, delete_modifier_pad_set, Anchor, Board, BoardHandle, ColorScheme, ColorSchemeHandle, Pad, PadId, PadSet, Tag, TextStyle, TextStyleHandle};
-use crate::input::{ModifierState, TextCapture, KeyCombinationCapture, capture::{DisplayFormats, DisplayFormatable}};
+use crate::components::{map_letter_key, PadMapping};
+use crate::core::{slugify_process_name, ActionType, KeyChord, Param, Params, PathString, Resources, SettingsRepository, SettingsRepositoryMut};
+use crate::model::{DeleteBoardUseCase, RenameBoardUseCase, create_modifier_pad_set, delete_modifier_pad_set, export_board, Anchor, Board, BoardHandle, ColorScheme, ColorSchemeHandle, Pad, PadId, PadSet, Tag, TextStyle, TextStyleHandle};
+use crate::input::{ModifierHandler, ModifierState, TextCapture, KeyCombinationCapture, capture::{Combination, DisplayFormats, DisplayFormatable}, script::{parse_hotkey, parse_modifiers}};
 use crate::{impl_board_component, impl_board_component_generic, impl_has_board};
-use crate::ui::dialogs::open_pad_editor;
+use crate::ui::dialogs::{open_pad_editor, pick_shortcuts, Shortcut};
+use crate::app::{is_process_running, save_file_icon};
 
 use super::{
-    BoardComponent, ChildWindowRequest, DelegatingBoard, HasBoard, KeyboardEvent, MouseEventTarget, LayoutAction, UiEvent, UiEventHandler, UiEventResult, SimpleBoard, LayoutBoard, SettingsBoard, EnumAll, EnumTraversal, Tags,
-    apply_string, error_board, string_editor_board, success_board, yes_no_warning_board, INITIAL_PATH_PARAM
+    BoardComponent, ChildWindowRequest, DelegatingBoard, HasBoard, KeyboardEvent, MouseEventTarget, LayoutAction, UiEvent, UiEventHandler, UiEventResult, SimpleBoard, LayoutBoard, SettingsBoard, CommandPaletteBoard, EnumAll, EnumTraversal, Tags,
+    apply_capture, apply_string, error_board, string_editor_board, success_board, yes_no_warning_board, INITIAL_PATH_PARAM
 };
 
+const MAIN_BOARD_COMMANDS: &str = "e: edit    x: layout\nd: delete  s: settings\np: palette t: text style\nctrl z/y: undo/redo";
+
 enum MainBoardContext {
     DeleteBoard,
     DeleteBoardSuccess,
@@ -28,7 +31,8 @@ pub struct MainBoard<R: SettingsRepository + SettingsRepositoryMut> {
     inner: Box<dyn Board>,
     params: Vec<Param>,
     resources: Resources,
-    repository: Rc<R>
+    repository: Rc<R>,
+    missing_process: Option<String>,
 }
 
 
@@ -36,11 +40,17 @@ impl_has_board!(MainBoard<R>);
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoard<R> {
     pub fn new(board_name: String, params: Vec<Param>, resources: Resources, repository: Rc<R>) -> Self {
+        // Checked once when the board opens, the warning is informational only
+        let missing_process = repository.get_board(&board_name).ok()
+            .and_then(|board| board.requires_process)
+            .filter(|process_name| !is_process_running(process_name));
+
         Self {
             inner: SimpleBoard::new_box(repository.clone(), board_name),
             params,
             resources,
-            repository
+            repository,
+            missing_process,
         }
     }
 
@@ -51,6 +61,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoard<R> {
     fn request_edit_mode(&mut self, params: Vec<Param>) -> UiEventResult {
         let edit_board = Box::new(EditModeBoard::new(
             self.create_simple_board(),
+            self.resources.clone(),
             self.repository.clone(),
             params
         ));
@@ -80,6 +91,18 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoard<R> {
 
     }
 
+    fn request_command_palette(&self) -> UiEventResult {
+        let palette_board = Box::new(CommandPaletteBoard::new(
+            self.inner.color_scheme(),
+            self.inner.text_style(),
+            self.repository.clone()
+        ));
+        UiEventResult::PushState {
+            board: palette_board,
+            context: Box::new(()),
+        }
+    }
+
     fn request_move_or_size(&self) -> UiEventResult {
         let move_or_size_board = Box::new(LayoutBoard::new(
             self.create_simple_board(),
@@ -105,6 +128,12 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoard<R> {
 
     fn key_down(&mut self, ke: KeyboardEvent) -> UiEventResult {
         let vk_code = VIRTUAL_KEY(ke.key as u16);
+
+        // Pad letters win over the board commands, which stay available while a modifier is held
+        if self.letter_keys() && !ke.modifiers.is_any() && map_letter_key(vk_code).is_some() {
+            return UiEventResult::NotHandled
+        }
+
         match vk_code {
             VK_E => {
                 self.request_edit_mode(vec![])
@@ -115,15 +144,31 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoard<R> {
             VK_X => {
                 self.request_move_or_size()
             }
+            VK_P => {
+                self.request_command_palette()
+            }
             VK_D => {
                 self.request_confirm_delete()
             },
+            VK_T => {
+                self.cycle_text_style()
+            },
             VK_W => {
                 if self.repository.is_dirty() && self.repository.flush().is_ok() {
                     return UiEventResult::RequiresRedraw
                 }
                 UiEventResult::NotHandled
             }
+            VK_Z | VK_Y if ke.modifiers.ctrl => {
+                let changed = match vk_code {
+                    VK_Z => self.repository.undo(),
+                    _ => self.repository.redo(),
+                };
+                match changed {
+                    true => UiEventResult::RequiresRedraw,
+                    false => UiEventResult::NotHandled,
+                }
+            }
             _ => UiEventResult::NotHandled
         }
     }
@@ -132,6 +177,20 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoard<R> {
         UiEventResult::RequiresRedraw
     }
 
+    /// Switches the board to the next text style right away, the same as a SwitchTextStyle pad
+    fn cycle_text_style(&mut self) -> UiEventResult {
+        let Some(next) = TextStyleHandle::<R>::new(self.repository.clone(), Some(self.inner.text_style().name)).next_name() else {
+            return UiEventResult::NotHandled;
+        };
+        match BoardHandle::<R>::new(self.repository.clone(), self.name()).set_text_style(Some(next)) {
+            Ok(()) => UiEventResult::RequiresRedraw,
+            Err(e) => UiEventResult::PushState {
+                board: Box::new(error_board(format!("{}", e), self)),
+                context: Box::new(()),
+            },
+        }
+    }
+
     fn uc(&self) -> DeleteBoardUseCase<R> {
         DeleteBoardUseCase::new(self.repository.clone(), self.name().to_string())
     }
@@ -145,6 +204,17 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoard<R> {
 
 impl<R: SettingsRepository + SettingsRepositoryMut> DelegatingBoard for MainBoard<R> {
 
+    /// The board's own help next to the commands, which need a modifier on boards selecting pads with letters
+    fn delegate_help_tags(&self) -> Vec<Tag> {
+        let commands = match self.letter_keys() {
+            true => format!("{}\n(with a modifier)", MAIN_BOARD_COMMANDS),
+            false => MAIN_BOARD_COMMANDS.to_string(),
+        };
+        let mut tags = self.inner.help_tags();
+        tags.push(Tag { text: commands, anchor: Anchor::SE, font_idx: Some(0), ..Default::default() });
+        tags
+    }
+
     fn delegate_tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
         let mut tags = Vec::<Tag>::new();
 
@@ -153,7 +223,15 @@ impl<R: SettingsRepository + SettingsRepositoryMut> DelegatingBoard for MainBoar
                 Tag { text: modifier.to_string(), anchor: Anchor::SE, font_idx: Some(0), ..Default::default() }
             );
 
-            tags.push(Tag { text: "e: edit    x: layout\nd: delete  s: settings".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() });
+            // The board's own help takes the place of the edit commands
+            let help = BoardHandle::<R>::new(self.repository.clone(), self.inner.name()).help().ok().flatten()
+                .filter(|_| parse_modifiers(&self.repository.help_modifier()) == Some(modifier));
+            if let Some(help) = help {
+                tags.push(Tag { text: help, anchor: Anchor::SW, font_idx: Some(0), ..Default::default() });
+                return tags;
+            }
+
+            tags.push(Tag { text: MAIN_BOARD_COMMANDS.to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() });
             if self.repository.is_dirty() {
                 tags.push(Tag { text: "w: save".to_string(), anchor: Anchor::NE, font_idx: Some(0), ..Default::default() });
             }
@@ -164,6 +242,10 @@ impl<R: SettingsRepository + SettingsRepositoryMut> DelegatingBoard for MainBoar
             tags.push(Tag { text: "(*)".to_string(), anchor: Anchor::NE, ..Default::default() });
         }
 
+        if let Some(process_name) = &self.missing_process {
+            tags.push(Tag { text: format!("⚠ {} is not running", process_name), anchor: Anchor::SW, font_idx: Some(0), color_idx: Some(0), ..Default::default() });
+        }
+
         tags
     }
 }
@@ -230,6 +312,9 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
                 }
             }
         }
+        if self.repository.open_in_edit_mode() {
+            return self.request_edit_mode(vec![]);
+        }
         UiEventResult::NotHandled
     }
 }
@@ -351,6 +436,9 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> ColorSchemeSelecto
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard for ColorSchemeSelectorBoard<R> {
+    fn delegate_letter_keys(&self) -> bool {
+        false
+    }
     fn delegate_title(&self) -> String {
         self.delegate_color_scheme().name
     }
@@ -384,6 +472,9 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> TextStyleSelectorB
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard for TextStyleSelectorBoard<R> {
+    fn delegate_letter_keys(&self) -> bool {
+        false
+    }
     fn delegate_title(&self) -> String {
         self.delegate_text_style().name
     }
@@ -401,20 +492,24 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard fo
 
 enum EditOperation {
     TitleEdit,
+    NameEdit,
+    NameEditSuccess,
     DeletePadSet(ModifierState),
 }
 
 pub struct EditModeBoard<R: SettingsRepository + SettingsRepositoryMut> {
     inner: Box<dyn Board>,
+    resources: Resources,
     repository: Rc<R>,
-    params: Vec<Param>
+    params: Vec<Param>,
+    notice: Option<String>,
 }
 
 impl_has_board!(EditModeBoard<R>);
 
 impl <R: SettingsRepository + SettingsRepositoryMut + 'static> EditModeBoard<R> {
-    pub fn new(inner: Box<dyn Board>, repository: Rc<R>, params: Vec<Param>) -> Self {
-        Self { inner, repository, params }
+    pub fn new(inner: Box<dyn Board>, resources: Resources, repository: Rc<R>, params: Vec<Param>) -> Self {
+        Self { inner, resources, repository, params, notice: None }
     }
 
     fn create_simple_board(&self) -> Box<SimpleBoard<R>> {
@@ -431,6 +526,29 @@ impl <R: SettingsRepository + SettingsRepositoryMut + 'static> EditModeBoard<R>
         }
     }
 
+    fn request_name_editor(&mut self) -> UiEventResult {
+        UiEventResult::PushState {
+            board: Box::new(string_editor_board(self.name(), self, "Name".to_string())),
+            context: Box::new(EditOperation::NameEdit),
+        }
+    }
+
+    /// The boards below this one still go by the old name, the window is closed once the rename is confirmed
+    fn rename_board(&mut self, new_name: &str) -> UiEventResult {
+        let board: Box<dyn BoardComponent> = match RenameBoardUseCase::new(self.repository.clone(), self.name(), new_name.to_string()).rename() {
+            Ok(references) => {
+                self.inner = SimpleBoard::new_box(self.repository.clone(), new_name.trim().to_string());
+                let message = format!("Renamed board to\n\"{}\"\n\n{} references updated", self.name(), references);
+                return UiEventResult::PushState {
+                    board: Box::new(success_board(message, self)),
+                    context: Box::new(EditOperation::NameEditSuccess),
+                };
+            },
+            Err(e) => Box::new(error_board(format!("{}", e), self)),
+        };
+        UiEventResult::PushState { board, context: Box::new(()) }
+    }
+
     fn request_color_scheme_selector(&self) -> UiEventResult {
         let selector_board = Box::new(ColorSchemeSelectorBoard::new(
             self.create_simple_board(),
@@ -519,16 +637,107 @@ impl <R: SettingsRepository + SettingsRepositoryMut + 'static> EditModeBoard<R>
         }
     }
 
+    fn is_copy_board_key(&self, vk_code: VIRTUAL_KEY, modifiers: ModifierState) -> bool {
+        parse_hotkey(&self.repository.copy_board_key()) == Some((modifiers, vk_code.0))
+    }
+
+    /// Copies the board and its padsets as JSON, in the form accepted as an include file
+    fn copy_board_to_clipboard(&mut self) -> UiEventResult {
+        let json = export_board(self.repository.as_ref(), &self.name())
+            .and_then(|export| Ok(export.to_json()?));
+
+        let result = json.and_then(|json| {
+            clipboard_win::set_clipboard_string(&json).map_err(|e| format!("Failed to set clipboard text: {}", e).into())
+        });
+
+        self.notice = Some(match result {
+            Ok(()) => "Copied to clipboard".to_string(),
+            Err(e) => {
+                log::error!("Failed to copy board '{}': {}", self.name(), e);
+                "Copy failed".to_string()
+            }
+        });
+        UiEventResult::RequiresRedraw
+    }
+
+    /// Shortcuts picked in the open dialog become pads on the empty pads of the board, with the icons they show in the shell
+    fn import_shortcuts(&mut self, folder: bool, parent_hwnd: windows::Win32::Foundation::HWND) -> UiEventResult {
+        let shortcuts = pick_shortcuts(Some(parent_hwnd), folder);
+        if shortcuts.is_empty() {
+            return UiEventResult::RequiresRedraw;
+        }
+
+        self.notice = Some(match self.place_shortcuts(&shortcuts) {
+            Ok(count) if count < shortcuts.len() => format!("Imported {} of {} shortcuts, no empty pads left", count, shortcuts.len()),
+            Ok(count) => format!("Imported {} shortcuts", count),
+            Err(e) => {
+                log::error!("Failed to import shortcuts to board '{}': {}", self.name(), e);
+                "Import failed".to_string()
+            }
+        });
+        UiEventResult::RequiresRedraw
+    }
+
+    fn place_shortcuts(&self, shortcuts: &[Shortcut]) -> Result<usize, Box<dyn std::error::Error>> {
+        let padset = BoardHandle::new(self.repository.clone(), self.name()).padset(None)?;
+        let room = padset.pads()?.empty_pad_ids().len();
+        let pads = shortcuts.iter()
+            .take(room)
+            .map(|shortcut| shortcut.to_pad(self.save_shortcut_icon(shortcut)))
+            .collect();
+        padset.fill_empty_pads(pads)
+    }
+
+    /// Name of the icon saved for the shortcut, None when it has none the shell can give
+    fn save_shortcut_icon(&self, shortcut: &Shortcut) -> Option<String> {
+        let name = format!("shortcut_{}.png", slugify_process_name(&shortcut.name));
+        let png_path = self.resources.local_icon(&name);
+        let saved = png_path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .map_err(|e| e.into())
+            .and_then(|_| save_file_icon(&shortcut.path, &png_path));
+        match saved {
+            Ok(()) => Some(name),
+            Err(e) => {
+                log::warn!("No icon imported for shortcut '{}': {}", shortcut.name, e);
+                None
+            }
+        }
+    }
+
     fn key_down(&mut self, key: u32, modifiers: ModifierState) -> UiEventResult {
         use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
         let vk_code = VIRTUAL_KEY(key as u16);
 
-        // Handle 'f2' key for title editing
+        // The confirmation stays until the next key press
+        if !ModifierHandler::is_modifier(vk_code) {
+            self.notice = None;
+        }
+
+        if self.is_copy_board_key(vk_code, modifiers) {
+            return self.copy_board_to_clipboard()
+        }
+
+        // Handle 'o' key for opening boards directly in edit mode
+        if vk_code == VK_O && modifiers.is_none() {
+            return self.toggle_open_in_edit_mode()
+        }
+
+        // Handle 'shift+f2' for renaming the board, 'f2' for title editing
+        if vk_code == VK_F2 && modifiers.shift {
+            return self.request_name_editor()
+        }
         if vk_code == VK_F2 {
             return self.request_title_editor()
         }
 
+        // Handle 'i' key for importing shortcuts, 'shift+i' for a folder of them
+        let folder = modifiers == ModifierState { shift: true, ..Default::default() };
+        if vk_code == VK_I && (modifiers.is_none() || folder) {
+            return UiEventResult::RequestChildWindow(ChildWindowRequest::ShortcutImport { folder })
+        }
+
         // Handle 'c' key for color scheme selector
         if vk_code == VK_C {
             return self.request_color_scheme_selector()
@@ -560,6 +769,16 @@ impl <R: SettingsRepository + SettingsRepositoryMut + 'static> EditModeBoard<R>
         return UiEventResult::RequiresRedraw
     }
 
+    fn toggle_open_in_edit_mode(&mut self) -> UiEventResult {
+        let enabled = !self.repository.open_in_edit_mode();
+        self.repository.set_open_in_edit_mode(enabled);
+        self.notice = Some(match enabled {
+            true => "Boards open in edit mode".to_string(),
+            false => "Boards open live".to_string(),
+        });
+        UiEventResult::RequiresRedraw
+    }
+
     fn right_mouse_down(&mut self, target: MouseEventTarget, modifiers: ModifierState) -> UiEventResult {
         match target {
             MouseEventTarget::Header => {
@@ -583,14 +802,30 @@ impl <R: SettingsRepository + SettingsRepositoryMut + 'static> EditModeBoard<R>
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard for EditModeBoard<R> {
+    // Letters are edit commands here (c: colors, f: fonts), pads are edited with the number keys
+    fn delegate_letter_keys(&self) -> bool {
+        false
+    }
+    // The edit commands are always on display, the help only adds the modifier ones to the SW tag
+    fn delegate_help_tags(&self) -> Vec<Tag> {
+        let mut tags = self.delegate_tags(None);
+        if let Some(commands) = tags.iter_mut().find(|tag| matches!(tag.anchor, Anchor::SW)) {
+            commands.text = "1-9: pad, F2: title\nshift F2: name\nmodifier +/-: its pads\nshift I: shortcuts from a folder".to_string();
+        }
+        tags
+    }
     fn delegate_tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
         let mut tags = vec![
-            Tag { text: "Editing".to_string(), anchor: Anchor::NW, font_idx: None, color_idx: Some(0), ..Default::default() },
+            Tag { text: "Editing".to_string(), anchor: Anchor::NW, font_idx: None, accent: true, ..Default::default() },
             Tags::EscEnter.default(),
-            Tag { text: "c: colors, f: fonts".to_string(), anchor: Anchor::SE, font_idx: Some(0), ..Default::default() },
-            Tag { text: "1-9: pad, F2: rename".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() }
+            Tag { text: "c: colors, f: fonts, i: shortcuts\no: open in edit mode".to_string(), anchor: Anchor::SE, font_idx: Some(0), ..Default::default() },
+            Tag { text: "1-9: pad, F2: title\nshift F2: name".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() }
         ];
 
+        if let Some(notice) = &self.notice {
+            tags.insert(0, Tag { text: notice.clone(), anchor: Anchor::N, font_idx: Some(0), accent: true, ..Default::default() });
+        }
+
         let modifier = modifier.unwrap_or_default();
         if modifier.is_none() {
             return tags;
@@ -600,9 +835,10 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard fo
             true => format!("+: add ({})", modifier.to_string()),
             false => format!("-: delete ({})", modifier.to_string()),
         };
-        // Replace the SW tag with the modifier command
-        tags.pop();
-        tags.push(Tag { text: modifier_cmd, anchor: Anchor::SW, font_idx: Some(0), ..Default::default() });
+        // The modifier command takes the place of the SW tag
+        if let Some(commands) = tags.iter_mut().find(|tag| matches!(tag.anchor, Anchor::SW)) {
+            commands.text = modifier_cmd;
+        }
         tags
     }
 }
@@ -666,6 +902,15 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
             EditOperation::TitleEdit => {
                 apply_string(result, |title| self.update_board_title(title.to_string()))
             }
+            EditOperation::NameEdit => {
+                match result.downcast_ref::<String>() {
+                    Some(new_name) if *new_name != self.name() => self.rename_board(new_name),
+                    _ => UiEventResult::NotHandled,
+                }
+            }
+            EditOperation::NameEditSuccess => {
+                UiEventResult::CloseWindow
+            }
             EditOperation::DeletePadSet(modifier) => {
                 if let Some(confirmed) = result.downcast_ref::<bool>() {
                     if *confirmed {
@@ -676,6 +921,13 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
             }
         }
     }
+
+    fn create_child_window(&mut self, request: ChildWindowRequest, parent_hwnd: windows::Win32::Foundation::HWND) -> UiEventResult {
+        match request {
+            ChildWindowRequest::ShortcutImport { folder } => self.import_shortcuts(folder, parent_hwnd),
+            _ => UiEventResult::NotHandled,
+        }
+    }
 }
 
 impl_board_component_generic!(EditModeBoard<R>);
@@ -749,8 +1001,13 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> PadEditorBoard<R>
         if let Some(pad) = self.item.borrow().as_ref() {
             return pad.clone();
         }
-        let padset = self.inner.padset(Some(self.modifier_state.clone()));
-        self.set_pad(padset.pad(self.pad_id));
+        // Edit the modifier layer itself, not the stacked view of it
+        let pad = BoardHandle::<R>::new(self.repository.clone(), self.name())
+            .padset(Some(self.modifier_state.clone()))
+            .and_then(|padset_handle| padset_handle.pads())
+            .map(|pads| pads.pad(self.pad_id))
+            .unwrap_or_else(|_| Pad::from(self.pad_id));
+        self.set_pad(pad);
         self.get_pad()
     }
 
@@ -785,7 +1042,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> PadEditorBoard<R>
             if pad.actions().is_empty() {
                 return true;
             } else if pad.actions().len() == 1 {
-                if let ActionType::Shortcut(_) = &pad.actions()[0] {
+                if let ActionType::Shortcut(_) | ActionType::ShortcutKeys { .. } = &pad.actions()[0] {
                     return true;
                 }
             }
@@ -799,13 +1056,22 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> PadEditorBoard<R>
         }
     }
 
-    fn set_first_action_shortcut(&self, value: String) -> Result<(), Box<dyn std::error::Error>> {
-        let value = value.trim().to_string();
-        if value.is_empty() {
+    fn set_first_action_shortcut(&self, capture: &[Combination]) -> Result<(), Box<dyn std::error::Error>> {
+        if capture.is_empty() {
             return Ok(());
         }
+        let value = capture.to_vec().display_format(DisplayFormats::InverseSpaced.get_format());
+        // Modifier-only combinations have no key code to store
+        let keys: Option<Vec<KeyChord>> = capture.iter()
+            .map(|combination| combination.key.map(|key| KeyChord::new(combination.modifiers, key)))
+            .collect();
+
+        let action = match keys {
+            Some(keys) if self.repository.structured_shortcuts() => ActionType::ShortcutKeys { text: value.clone(), keys },
+            _ => ActionType::Shortcut(value.clone()),
+        };
         let mut pad = self.get_pad();
-        pad.data.actions = vec![ActionType::Shortcut(value.clone())];
+        pad.data.actions = vec![action];
         pad.data.header = Some(value);
         self.set_pad(pad);
         Ok(())
@@ -814,6 +1080,9 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> PadEditorBoard<R>
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard for PadEditorBoard<R> {
+    fn delegate_letter_keys(&self) -> bool {
+        false
+    }
     fn delegate_title(&self) -> String {
         match self.edit {
             PadEditorMode::Header => "Pad header".to_string(),
@@ -845,18 +1114,18 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard fo
             PadEditorMode::Action => (Anchor::SW, format!("{} {}", Tags::RightBlack.to_string(), action_str)),
             PadEditorMode::Board => (Anchor::SW, format!("{} {}", Tags::RightBlack.to_string(), pad.board().unwrap_or("-".to_string()))),
         };
-        pad.tags.extend(vec![Tag{ text: tag_text, anchor, color_idx: Some(0), ..Default::default() }]);
+        pad.tags.extend(vec![Tag{ text: tag_text, anchor, accent: true, ..Default::default() }]);
 
         Box::new(vec![pad])
     }
     fn delegate_tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
         let mut tags = vec![
-            Tag{ text: format!("Pad {}", self.pad_id.to_string()), anchor: Anchor::NW, font_idx: None, color_idx: Some(0), ..Default::default() },
+            Tag{ text: format!("Pad {}", self.pad_id.to_string()), anchor: Anchor::NW, font_idx: None, accent: true, ..Default::default() },
             Tags::EscEnter.default()
         ];
         if self.edit == PadEditorMode::Action || self.edit == PadEditorMode::Board {
             tags.push(Tag{ text: "c: clear pad, e: edit, s: shortcut".to_string(), anchor: Anchor::SW, font_idx: Some(1), color_idx: None, ..Default::default() });
-            tags.push(Tag{ text: "▷   ".to_string(), anchor: Anchor::SE, font_idx: Some(2), color_idx: Some(0), ..Default::default() });
+            tags.push(Tag{ text: "▷   ".to_string(), anchor: Anchor::SE, font_idx: Some(2), accent: true, ..Default::default() });
         }
         tags.push(Tags::DownUp.default());
 
@@ -942,7 +1211,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
     fn create_child_window(&mut self, request: ChildWindowRequest, parent_hwnd: windows::Win32::Foundation::HWND) -> UiEventResult {
         match request {
             ChildWindowRequest::PadEditor => {
-                if let Some(pad) = open_pad_editor(self.get_pad(), Some(parent_hwnd), self.repository.boards(), self.edit == PadEditorMode::Board) {
+                if let Some(pad) = open_pad_editor(self.get_pad(), Some(parent_hwnd), self.repository.boards(), self.inner.color_scheme().background(), self.edit == PadEditorMode::Board) {
                     self.set_pad(pad);
                     UiEventResult::RequiresRedraw
                 } else {
@@ -960,7 +1229,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for
         };
         match operation {
             PadEditOperation::ShortcutEdit => {
-                apply_string(result, |title| self.set_first_action_shortcut(title.to_string()))
+                apply_capture(result, |capture| self.set_first_action_shortcut(capture))
             }
         }
     }
@@ -1008,7 +1277,7 @@ impl Board for ShortcutEditorBoard {
     }
     fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
         let mut tags = vec![
-            Tag { text: "Shortcut".to_string(), anchor: Anchor::NW, color_idx: Some(0), ..Default::default() },
+            Tag { text: "Shortcut".to_string(), anchor: Anchor::NW, accent: true, ..Default::default() },
         ];
         if !self.is_finished {
             tags.push(Tags::EscEnter.default());
@@ -1028,14 +1297,7 @@ impl UiEventHandler for ShortcutEditorBoard {
                 if self.is_finished {
                     if vk_code == VK_RETURN {
                         let final_capture = self.capture.get_current_capture();
-
-                        if final_capture.is_empty() {
-                            return UiEventResult::PopState { result: Box::new(String::new()) };
-                        } else {
-                            let display_format = DisplayFormats::InverseSpaced;
-                            let display_text = final_capture.display_format(&display_format.get_format());
-                            return UiEventResult::PopState { result: Box::new(display_text) };
-                        }
+                        return UiEventResult::PopState { result: Box::new(final_capture) };
 
                     }
                     if vk_code == VK_ESCAPE {
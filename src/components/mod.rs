@@ -6,8 +6,19 @@ mod controls;
 mod colors_board;
 mod fonts_board;
 mod settings_board;
+mod search_board;
+mod palette_board;
+mod whatsnew_board;
+mod gallery_board;
+mod safe_mode_board;
 mod state_machine;
 mod board_chain;
+mod template_board;
+mod recent_board;
+mod selftest_board;
+mod keytest_board;
+mod help_overlay;
+mod stack_overlay;
 mod result_helpers;
 
 pub struct PadMapping<R: SettingsRepository> {
@@ -25,36 +36,71 @@ use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
 
 impl <R: SettingsRepository> PadMapping<R> {
     pub fn map(&self, vk_code: VIRTUAL_KEY) -> Option<PadId> {
-        map_pad_id(vk_code, self.repository.natural_key_order())
+        map_pad_id(vk_code, self.repository.natural_key_order(), self.repository.pad_keys())
     }
 }
 
-pub fn map_pad_id(vk_code: VIRTUAL_KEY, natural_key_order: bool) -> Option<PadId> {
+/// Pad selected by a number key, None for other keys and for the number keys excluded by `pad_keys`
+pub fn map_pad_id(vk_code: VIRTUAL_KEY, natural_key_order: bool, pad_keys: PadKeys) -> Option<PadId> {
+    let numpad = match pad_keys {
+        PadKeys::Digits => None,
+        _ => map_numpad_key(vk_code),
+    };
+    numpad.or_else(|| match pad_keys {
+        PadKeys::Numpad => None,
+        _ => map_digit_key(vk_code, natural_key_order),
+    })
+}
+
+/// Pad selected by the letters a-i on boards with `letter_keys`, in the order of [PadId::as_letter]
+pub fn map_letter_key(vk_code: VIRTUAL_KEY) -> Option<PadId> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::VK_A;
+    let offset = vk_code.0.checked_sub(VK_A.0)?;
+    PadId::all().into_iter().find(|pad_id| (pad_id.as_letter() as u16 - 'a' as u16) == offset)
+}
+
+fn map_numpad_key(vk_code: VIRTUAL_KEY) -> Option<PadId> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+    match vk_code {
+        VK_NUMPAD1 => Some(PadId::One),
+        VK_NUMPAD2 => Some(PadId::Two),
+        VK_NUMPAD3 => Some(PadId::Three),
+        VK_NUMPAD4 => Some(PadId::Four),
+        VK_NUMPAD5 => Some(PadId::Five),
+        VK_NUMPAD6 => Some(PadId::Six),
+        VK_NUMPAD7 => Some(PadId::Seven),
+        VK_NUMPAD8 => Some(PadId::Eight),
+        VK_NUMPAD9 => Some(PadId::Nine),
+        _ => None,
+    }
+}
+
+fn map_digit_key(vk_code: VIRTUAL_KEY, natural_key_order: bool) -> Option<PadId> {
     use windows::Win32::UI::Input::KeyboardAndMouse::*;
     if ! natural_key_order {
         match vk_code {
-            VK_NUMPAD1 | VK_1 => Some(PadId::One),
-            VK_NUMPAD2 | VK_2 => Some(PadId::Two),
-            VK_NUMPAD3 | VK_3 => Some(PadId::Three),
-            VK_NUMPAD4 | VK_4 => Some(PadId::Four),
-            VK_NUMPAD5 | VK_5 => Some(PadId::Five),
-            VK_NUMPAD6 | VK_6 => Some(PadId::Six),
-            VK_NUMPAD7 | VK_7 => Some(PadId::Seven),
-            VK_NUMPAD8 | VK_8 => Some(PadId::Eight),
-            VK_NUMPAD9 | VK_9 => Some(PadId::Nine),
+            VK_1 => Some(PadId::One),
+            VK_2 => Some(PadId::Two),
+            VK_3 => Some(PadId::Three),
+            VK_4 => Some(PadId::Four),
+            VK_5 => Some(PadId::Five),
+            VK_6 => Some(PadId::Six),
+            VK_7 => Some(PadId::Seven),
+            VK_8 => Some(PadId::Eight),
+            VK_9 => Some(PadId::Nine),
             _ => None,
         }
     } else {
         match vk_code {
-            VK_NUMPAD1 | VK_7 => Some(PadId::One),
-            VK_NUMPAD2 | VK_8 => Some(PadId::Two),
-            VK_NUMPAD3 | VK_9 => Some(PadId::Three),
-            VK_NUMPAD4 | VK_4 => Some(PadId::Four),
-            VK_NUMPAD5 | VK_5 => Some(PadId::Five),
-            VK_NUMPAD6 | VK_6 => Some(PadId::Six),
-            VK_NUMPAD7 | VK_1 => Some(PadId::Seven),
-            VK_NUMPAD8 | VK_2 => Some(PadId::Eight),
-            VK_NUMPAD9 | VK_3 => Some(PadId::Nine),
+            VK_7 => Some(PadId::One),
+            VK_8 => Some(PadId::Two),
+            VK_9 => Some(PadId::Three),
+            VK_4 => Some(PadId::Four),
+            VK_5 => Some(PadId::Five),
+            VK_6 => Some(PadId::Six),
+            VK_1 => Some(PadId::Seven),
+            VK_2 => Some(PadId::Eight),
+            VK_3 => Some(PadId::Nine),
             _ => None,
         }
     }
@@ -102,10 +148,55 @@ pub use traits::*;
 pub use boards::*;
 pub use controls::*;
 pub use board_chain::*;
+pub use template_board::TemplateBoard;
+pub use recent_board::RecentBoardsBoard;
 pub use main_board::MainBoard;
 pub use home_board::HomeBoard;
 pub use settings_board::SettingsBoard;
+pub use palette_board::CommandPaletteBoard;
+pub use whatsnew_board::{WhatsNewBoard, unseen_entries};
+pub use safe_mode_board::SafeModeBoard;
+pub use selftest_board::SelfTestBoard;
+pub use keytest_board::KeyTesterBoard;
+pub use help_overlay::HelpOverlay;
+pub use stack_overlay::StackOverlay;
+
+use crate::{core::{PadKeys, SettingsRepository}, model::PadId};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{VK_1, VK_7, VK_A, VK_C, VK_G, VK_I, VK_J, VK_NUMPAD1};
+
+    #[test]
+    fn test_map_pad_id() {
+        assert_eq!(map_pad_id(VK_NUMPAD1, true, PadKeys::Both), Some(PadId::One));
+        assert_eq!(map_pad_id(VK_1, false, PadKeys::Both), Some(PadId::One));
+        assert_eq!(map_pad_id(VK_7, true, PadKeys::Both), Some(PadId::One));
+        assert_eq!(map_pad_id(VK_A, false, PadKeys::Both), None);
+
+        assert_eq!(map_pad_id(VK_NUMPAD1, false, PadKeys::Numpad), Some(PadId::One));
+        assert_eq!(map_pad_id(VK_1, false, PadKeys::Numpad), None);
+
+        assert_eq!(map_pad_id(VK_NUMPAD1, false, PadKeys::Digits), None);
+        assert_eq!(map_pad_id(VK_1, false, PadKeys::Digits), Some(PadId::One));
+    }
 
-use crate::{core::SettingsRepository, model::PadId};
+    #[test]
+    fn test_map_letter_key() {
+        // Top-down, the same as the grid
+        assert_eq!(map_letter_key(VK_A), Some(PadId::Seven));
+        assert_eq!(map_letter_key(VK_C), Some(PadId::Nine));
+        assert_eq!(map_letter_key(VK_G), Some(PadId::One));
+        assert_eq!(map_letter_key(VK_I), Some(PadId::Three));
+        assert_eq!(map_letter_key(VK_J), None);
+        assert_eq!(map_letter_key(VK_1), None);
+
+        for pad_id in PadId::all() {
+            let vk_code = VIRTUAL_KEY(VK_A.0 + (pad_id.as_letter() as u16 - 'a' as u16));
+            assert_eq!(map_letter_key(vk_code), Some(pad_id));
+        }
+    }
+}
 
 
@@ -0,0 +1,368 @@
+use std::rc::Rc;
+
+use windows::Win32::Foundation::WPARAM;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::components::map_pad_id;
+use crate::core::{self, ActionType, PadKeys, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::input::{script::parse_modifiers, TextCapture};
+use crate::model::{Anchor, Board, ColorScheme, ModifierState, Pad, PadId, PadSet, PadSource, Tag, TextStyle};
+
+use super::{BoardComponent, UiEvent, UiEventHandler, UiEventResult, MouseEventTarget, Tags};
+
+/// An interactive pad found on one of the boards, together with the text it is matched against
+struct PaletteEntry {
+    location: String,
+    source: PadSource,
+    pad: core::Pad,
+    haystack: String,
+}
+
+/// CommandPaletteBoard - searches pads and actions of all boards as the user types
+pub struct CommandPaletteBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    text_capture: TextCapture,
+    color_scheme: ColorScheme,
+    text_style: TextStyle,
+    repository: Rc<R>,
+    entries: Vec<PaletteEntry>,
+    matches: Vec<usize>,
+    current_page: usize,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> CommandPaletteBoard<R> {
+    pub fn new(color_scheme: ColorScheme, text_style: TextStyle, repository: Rc<R>) -> Self {
+        let mut palette = Self {
+            text_capture: TextCapture::new(None, false),
+            color_scheme,
+            text_style,
+            entries: collect_entries(repository.as_ref()),
+            repository,
+            matches: vec![],
+            current_page: 0,
+        };
+        palette.update_matches();
+        palette
+    }
+
+    fn query(&self) -> String {
+        self.text_capture.text().unwrap_or_default()
+    }
+
+    fn update_matches(&mut self) {
+        let query = self.query();
+        let mut scored: Vec<(i32, usize)> = self.entries.iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| fuzzy_score(&query, &entry.haystack).map(|score| (score, idx)))
+            .collect();
+
+        // Stable sort keeps the repository order for equal scores
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        self.matches = scored.into_iter().take(self.repository.limits().palette_results).map(|(_, idx)| idx).collect();
+        self.current_page = 0;
+    }
+
+    fn max_page(&self) -> usize {
+        self.matches.len().saturating_sub(1) / PadId::reading_order().len()
+    }
+
+    fn get_pads(&self) -> Vec<Pad> {
+        self.matches.iter()
+            .skip(self.current_page * PadId::reading_order().len())
+            .zip(PadId::reading_order().iter())
+            .map(|(idx, pad_id)| {
+                let entry = &self.entries[*idx];
+                // Only shows the pad, selecting it runs the pad on its board
+                pad_id.with_data(core::Pad {
+                    header: Some(entry.location.clone()),
+                    text: entry.pad.text.clone().or_else(|| entry.pad.header.clone()),
+                    icon: entry.pad.icon.clone(),
+                    actions: entry.pad.actions.clone(),
+                    board: entry.pad.board.clone(),
+                    vertical_align: entry.pad.vertical_align,
+                    shrink_text: entry.pad.shrink_text,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    fn entry_at(&self, pad_id: PadId) -> Option<&PaletteEntry> {
+        let position = PadId::reading_order().iter().position(|id| *id == pad_id)?;
+        self.matches.get(self.current_page * PadId::reading_order().len() + position)
+            .map(|idx| &self.entries[*idx])
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for CommandPaletteBoard<R> {
+    fn name(&self) -> String {
+        "command_palette".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("{}|", self.query())
+    }
+
+    fn icon(&self) -> Option<String> {
+        None
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.color_scheme.clone()
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.text_style.clone()
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        Box::new(self.get_pads())
+    }
+
+    fn pad_source(&self, pad_id: PadId) -> Option<PadSource> {
+        self.entry_at(pad_id).map(|entry| entry.source.clone())
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        let mut tags = vec![
+            Tag { text: "esc".to_string(), anchor: Anchor::NW, font_idx: Some(0), ..Default::default() },
+            Tag { text: format!("{} matches", self.matches.len()), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+        ];
+
+        if self.current_page > 0 {
+            tags.push(Tags::UpWhite.tag(Anchor::NE));
+        }
+        if self.current_page < self.max_page() {
+            tags.push(Tags::DownWhite.tag(Anchor::SE));
+        }
+
+        tags
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for CommandPaletteBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match event {
+            UiEvent::KeyDown(ke) => {
+                let vk_code = VIRTUAL_KEY(ke.key as u16);
+                match vk_code {
+                    VK_ESCAPE => UiEventResult::PopState { result: Box::new(()) },
+                    VK_RETURN => {
+                        // Enter activates the best match on the current page
+                        if self.get_pads().is_empty() {
+                            UiEventResult::Handled
+                        } else {
+                            UiEventResult::PadSelected(PadId::reading_order()[0])
+                        }
+                    },
+                    VK_UP => {
+                        if self.current_page > 0 {
+                            self.current_page -= 1;
+                            UiEventResult::RequiresRedraw
+                        } else {
+                            UiEventResult::Handled
+                        }
+                    },
+                    VK_DOWN => {
+                        if self.current_page < self.max_page() {
+                            self.current_page += 1;
+                            UiEventResult::RequiresRedraw
+                        } else {
+                            UiEventResult::Handled
+                        }
+                    },
+                    // Top-row digits are part of the query, numpad keys select the results whatever pad_keys is set to
+                    VK_NUMPAD1 | VK_NUMPAD2 | VK_NUMPAD3 | VK_NUMPAD4 | VK_NUMPAD5 | VK_NUMPAD6 | VK_NUMPAD7 | VK_NUMPAD8 | VK_NUMPAD9 => {
+                        match map_pad_id(vk_code, false, PadKeys::Numpad) {
+                            Some(pad_id) => UiEventResult::PadSelected(pad_id),
+                            None => UiEventResult::Handled,
+                        }
+                    },
+                    _ => {
+                        let before = self.query();
+                        self.text_capture.on_keydown(WPARAM(ke.key as usize), ke.modifiers);
+                        if self.query() != before {
+                            self.update_matches();
+                        }
+                        UiEventResult::RequiresRedraw
+                    }
+                }
+            },
+            UiEvent::KeyUp(ke) => {
+                self.text_capture.on_keyup(WPARAM(ke.key as usize), ke.modifiers);
+                UiEventResult::Handled
+            },
+            UiEvent::RightMouseDown(me) => match me.target {
+                MouseEventTarget::Pad(pad_id) => UiEventResult::PadSelected(pad_id),
+                _ => UiEventResult::NotHandled,
+            },
+        }
+    }
+}
+
+impl_board_component_generic!(CommandPaletteBoard<R>);
+
+
+/// Collects all interactive pads from the base and modifier pad sets of every board
+fn collect_entries<R: SettingsRepository>(repository: &R) -> Vec<PaletteEntry> {
+    let mut entries = vec![];
+
+    for board in repository.boards().iter().filter_map(|name| repository.get_board(name).ok()) {
+        let mut padsets: Vec<(Option<String>, String)> = board.base_pads.iter()
+            .map(|name| (None, name.clone()))
+            .collect();
+        let mut modifier_pads: Vec<(&String, &String)> = board.modifier_pads.iter().collect();
+        modifier_pads.sort();
+        padsets.extend(modifier_pads.into_iter().map(|(modifier, name)| (Some(modifier.clone()), name.clone())));
+
+        for (modifier, padset_name) in padsets {
+            let padset = match repository.get_padset(&padset_name) {
+                Ok(padset) => padset,
+                Err(_) => continue,
+            };
+            let location = match &modifier {
+                Some(modifier) => format!("{} ({})", board.title(), modifier),
+                None => board.title().to_string(),
+            };
+            // Modifier padsets are keyed by the modifiers' display names, e.g. "Ctrl+Shift"
+            let modifiers = match &modifier {
+                Some(modifier) => match parse_modifiers(modifier) {
+                    Some(modifiers) => modifiers,
+                    None => continue,
+                },
+                None => ModifierState::default(),
+            };
+
+            let pads = padset.items.into_iter()
+                .zip(PadId::all())
+                .filter(|(pad, _)| pad.is_interactive());
+            for (pad, pad_id) in pads {
+                let mut haystack = vec![location.clone()];
+                haystack.extend(pad.header.iter().cloned());
+                haystack.extend(pad.text.iter().cloned());
+                haystack.extend(pad.actions.iter().map(action_text));
+
+                entries.push(PaletteEntry {
+                    location: location.clone(),
+                    source: PadSource { board: board.name.clone(), modifiers, pad_id },
+                    haystack: haystack.join(" "),
+                    pad,
+                });
+            }
+        }
+    }
+    entries
+}
+
+pub(super) fn action_text(action: &ActionType) -> String {
+    match action {
+        ActionType::Shortcut(text)
+        | ActionType::ShortcutKeys { text, .. }
+        | ActionType::Text(text)
+        | ActionType::Line(text)
+        | ActionType::Snippet(text)
+        | ActionType::Paste(text)
+        | ActionType::PasteEnter(text)
+        | ActionType::PasteKeep(text)
+        | ActionType::PasteEnterKeep(text)
+        | ActionType::OpenUrl(text)
+        | ActionType::SwitchTextStyle(text) => text.clone(),
+        ActionType::OpenQuery { base, .. } => base.clone(),
+        ActionType::LaunchApp { path, args, .. } => format!("{} {}", path, args.join(" ")).trim_end().to_string(),
+        ActionType::Pause(_) | ActionType::TypeClipboard(_) | ActionType::WaitForClipboard { .. } | ActionType::FocusPrevious => String::new(),
+        ActionType::AppendFile { path, text, .. } => format!("{} {}", path, text),
+        ActionType::Audio { command } => command.to_string(),
+        ActionType::MediaKey(key) => key.to_string(),
+        ActionType::WindowCommand(command) => command.to_string(),
+        ActionType::TypeTransformed { text, transform } => format!("{} {}", transform, text).trim_end().to_string(),
+        ActionType::WrapSelection { prefix, suffix } => format!("{} {}", prefix, suffix),
+        ActionType::IfTime { from, to, .. } => format!("{}-{}", from, to),
+        ActionType::Custom(params) => params.action_type.clone(),
+    }
+}
+
+/// Case-insensitive subsequence match. Returns None when not all query characters are found in order,
+/// otherwise a score favouring consecutive characters and matches at word starts.
+/// An empty query matches everything with a score of 0.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+
+    for (ci, ch) in candidate.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if *ch != query[qi] {
+            continue;
+        }
+
+        score += 1;
+        if prev_match.is_some_and(|prev| prev + 1 == ci) {
+            score += 5;
+        }
+        if ci == 0 || !candidate[ci - 1].is_alphanumeric() {
+            score += 3;
+        }
+        prev_match = Some(ci);
+        qi += 1;
+    }
+
+    if qi == query.len() {
+        Some(score)
+    } else {
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Resources;
+    use crate::settings::Settings;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("cpy", "Copy").is_some());
+        assert!(fuzzy_score("COPY", "copy line").is_some());
+        assert!(fuzzy_score("yc", "copy").is_none());
+        assert!(fuzzy_score("copyx", "copy").is_none());
+
+        // Consecutive and word-start matches rank higher than scattered ones
+        let contiguous = fuzzy_score("paste", "Paste text").unwrap();
+        let scattered = fuzzy_score("paste", "primary address set time").unwrap();
+        assert!(contiguous > scattered);
+
+        let word_start = fuzzy_score("st", "Save Text").unwrap();
+        let mid_word = fuzzy_score("st", "fastest").unwrap();
+        assert!(word_start > mid_word);
+    }
+
+    #[test]
+    fn test_pad_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::safe_mode(Resources::new(vec![dir.path().to_path_buf()]), String::new());
+        let pad = |text: &str| core::Pad { text: Some(text.to_string()), actions: vec![ActionType::Text(text.to_string())], ..Default::default() };
+        settings.add_padset(core::PadSet::new("dev", vec![core::Pad::default(), pad("build")])).unwrap();
+        settings.add_padset(core::PadSet::new("dev/ctrl", vec![pad("test")])).unwrap();
+        let mut dev = core::Board { name: "dev".to_string(), base_pads: Some("dev".to_string()), ..Default::default() };
+        dev.modifier_pads.insert("Ctrl".to_string(), "dev/ctrl".to_string());
+        settings.add_board(dev).unwrap();
+
+        // The results stand for the pads where their boards have them
+        let palette = CommandPaletteBoard::new(ColorScheme::default(), TextStyle::default(), settings);
+        let sources: Vec<PadSource> = PadId::reading_order().into_iter()
+            .filter_map(|pad_id| palette.pad_source(pad_id))
+            .filter(|source| source.board == "dev")
+            .collect();
+        assert_eq!(sources, vec![
+            PadSource { board: "dev".to_string(), modifiers: ModifierState::default(), pad_id: PadId::Two },
+            PadSource { board: "dev".to_string(), modifiers: ModifierState { ctrl: true, ..Default::default() }, pad_id: PadId::One },
+        ]);
+    }
+}
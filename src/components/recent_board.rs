@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+use crate::core::{self, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::model::{Board, BoardHandle, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+
+use super::settings_board::board_list_pad;
+use super::{BoardComponent, UiEvent, UiEventHandler, UiEventResult};
+
+/// RecentBoardsBoard - the boards opened most recently, one pad each, rendered as in the board list.
+/// The pads open their board through the regular pad command, which records it as recent again.
+pub struct RecentBoardsBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    handle: BoardHandle<R>,
+    repository: Rc<R>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> RecentBoardsBoard<R> {
+    pub fn new(board_name: String, repository: Rc<R>) -> Self {
+        Self {
+            handle: BoardHandle::new(repository.clone(), board_name),
+            repository,
+        }
+    }
+
+    fn get_pads(&self) -> Vec<Pad> {
+        let pads: Vec<Pad> = self.repository.recent_boards().iter()
+            .filter_map(|name| self.repository.get_board(name).ok())
+            .zip(PadId::reading_order().iter())
+            .map(|(board, pad_id)| board_list_pad(self.repository.as_ref(), &board, *pad_id))
+            .collect();
+
+        match pads.is_empty() {
+            true => vec![PadId::Five.with_data(core::Pad {
+                text: Some("No boards opened yet".to_string()),
+                label: true,
+                ..Default::default()
+            })],
+            false => pads,
+        }
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for RecentBoardsBoard<R> {
+    fn name(&self) -> String {
+        self.handle.name().to_string()
+    }
+
+    fn title(&self) -> String {
+        self.handle.title().unwrap_or_else(|_| "Recent boards".to_string())
+    }
+
+    fn icon(&self) -> Option<String> {
+        self.handle.icon().ok().flatten()
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.handle.color_scheme().unwrap_or_default()
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.handle.text_style().unwrap_or_default()
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        Box::new(self.get_pads())
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![]
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for RecentBoardsBoard<R> {
+    fn handle_ui_event(&mut self, _event: UiEvent) -> UiEventResult {
+        UiEventResult::NotHandled
+    }
+}
+
+impl_board_component_generic!(RecentBoardsBoard<R>);
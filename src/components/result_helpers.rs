@@ -1,5 +1,6 @@
 use std::any::Any;
 use super::UiEventResult;
+use crate::input::capture::Combination;
 use crate::model::Pad;
 
 /// Apply a String result using the provided handler function
@@ -20,6 +21,24 @@ where F: FnOnce(&str) -> Result<(), Box<dyn std::error::Error>>
     }
 }
 
+/// Apply a captured shortcut result using the provided handler function
+pub fn apply_capture<F>(result: Box<dyn Any>, handler: F) -> UiEventResult
+where F: FnOnce(&[Combination]) -> Result<(), Box<dyn std::error::Error>>
+{
+    if let Some(capture_result) = result.downcast_ref::<Vec<Combination>>() {
+        match handler(capture_result) {
+            Ok(()) => UiEventResult::RequiresRedraw,
+            Err(e) => {
+                log::error!("Capture handler failed: {}", e);
+                UiEventResult::RequiresRedraw
+            }
+        }
+    } else {
+        log::warn!("Expected captured shortcut result but got different type");
+        UiEventResult::NotHandled
+    }
+}
+
 /// Apply a bool result using the provided handler function
 pub fn apply_bool<F>(result: Box<dyn Any>, handler: F) -> UiEventResult
 where F: FnOnce(bool) -> Result<(), Box<dyn std::error::Error>>
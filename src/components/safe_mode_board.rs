@@ -0,0 +1,118 @@
+use std::rc::Rc;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::core::{self, ActionType, Resources, SettingsRepository, SettingsRepositoryMut};
+use crate::core::data::HOME_BOARD_NAME;
+use crate::impl_board_component_generic;
+use crate::model::{Anchor, Board, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+
+use super::{error_board, BoardComponent, KeyboardEvent, MouseEventTarget, PadMapping, UiEvent, UiEventHandler, UiEventResult};
+
+/// SafeModeBoard - shown on startup when the settings file failed to load and the built-in defaults are used
+pub struct SafeModeBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    error: String,
+    resources: Resources,
+    repository: Rc<R>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> SafeModeBoard<R> {
+    pub fn new(error: String, resources: Resources, repository: Rc<R>) -> Self {
+        Self { error, resources, repository }
+    }
+
+    fn reload(&mut self) -> UiEventResult {
+        match self.repository.reload() {
+            // The reload pad navigates to the home board of the real settings
+            Ok(()) => UiEventResult::PadSelected(PadId::Five),
+            Err(e) => {
+                log::error!("Failed to reload settings: {}", e);
+                self.error = e.to_string();
+                UiEventResult::RequiresRedraw
+            }
+        }
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for SafeModeBoard<R> {
+    fn name(&self) -> String {
+        "safe_mode".to_string()
+    }
+
+    fn title(&self) -> String {
+        "Safe mode".to_string()
+    }
+
+    fn icon(&self) -> Option<String> {
+        Some("warning.svg".to_string())
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.repository.resolve_color_scheme(&None)
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.repository.resolve_text_style(&None)
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        let pads: Vec<Pad> = vec![
+            PadId::Eight.with_data(core::Pad {
+                header: Some(self.error.clone()),
+                text: Some("Details".to_string()),
+                ..Default::default()
+            }),
+            PadId::Four.with_data(core::Pad {
+                text: Some("Config file".to_string()),
+                actions: vec![ ActionType::OpenUrl(
+                    self.resources.settings_json()
+                        .and_then(|p| p.to_str().map(|s| s.to_string()))
+                        .unwrap_or_else(|| self.resources.names().settings_json())
+                )],
+                ..Default::default()
+            }),
+            PadId::Five.with_data(core::Pad {
+                text: Some("Reload".to_string()),
+                board: Some(HOME_BOARD_NAME.to_string()),
+                ..Default::default()
+            }),
+        ];
+        Box::new(pads)
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![
+            Tag { text: "Settings failed to load, running with defaults".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+        ]
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for SafeModeBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match event {
+            UiEvent::KeyDown(key_event) => {
+                let vk_code = VIRTUAL_KEY(key_event.key as u16);
+                let pad_id = PadMapping::new(self.repository.clone()).map(vk_code);
+
+                match (pad_id, vk_code) {
+                    (Some(PadId::Eight), _) | (_, VK_D) => UiEventResult::PushState {
+                        board: Box::new(error_board(self.error.clone(), self)),
+                        context: Box::new(()),
+                    },
+                    (Some(PadId::Five), _) | (_, VK_R) => self.reload(),
+                    _ => UiEventResult::NotHandled,
+                }
+            },
+            UiEvent::RightMouseDown(me) => match me.target {
+                MouseEventTarget::Pad(pad_id) => {
+                    let key = VK_NUMPAD0.0 as u32 + pad_id.as_keypad_int() as u32;
+                    self.handle_ui_event(UiEvent::KeyDown(KeyboardEvent { key, modifiers: me.modifiers }))
+                },
+                _ => UiEventResult::NotHandled,
+            },
+            _ => UiEventResult::NotHandled,
+        }
+    }
+}
+
+impl_board_component_generic!(SafeModeBoard<R>);
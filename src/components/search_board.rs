@@ -0,0 +1,124 @@
+use std::rc::Rc;
+
+use windows::Win32::Foundation::WPARAM;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::core::{self, PadKeys, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::input::TextCapture;
+use crate::model::{Anchor, Board, ModifierState, PadId, Tag};
+
+use super::settings_board::BoardListBase;
+use super::{map_pad_id, BoardComponent, DelegatingBoard, DelegatingHandler, HasBoard, HasHandler, UiEvent, UiEventHandler, UiEventResult};
+
+/// Board search screen, lists the boards whose title or name contains the typed text
+pub(super) struct SearchBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    inner: BoardListBase<R>,
+    text_capture: TextCapture,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> SearchBoard<R> {
+    pub fn new(board: core::Board, repository: Rc<R>) -> Self {
+        Self {
+            inner: BoardListBase::new(board, repository, |_| true),
+            text_capture: TextCapture::new(None, false),
+        }
+    }
+
+    fn query(&self) -> String {
+        self.text_capture.text().unwrap_or_default()
+    }
+
+    fn update_filter(&mut self) {
+        let query = self.query();
+        self.inner.set_filter(move |board| matches_query(board, &query));
+    }
+}
+
+/// Case-insensitive match of `query` within the title or the name, an empty query matches every board
+fn matches_query(board: &core::Board, query: &str) -> bool {
+    let query = query.to_lowercase();
+    board.title().to_lowercase().contains(&query) || board.name.to_lowercase().contains(&query)
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> HasBoard for SearchBoard<R> {
+    fn board(&self) -> &dyn Board {
+        &self.inner
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> HasHandler for SearchBoard<R> {
+    fn handler(&mut self) -> Option<&mut dyn UiEventHandler> {
+        Some(&mut self.inner)
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard for SearchBoard<R> {
+    fn delegate_title(&self) -> String {
+        format!("Search: {}|", self.query())
+    }
+
+    fn delegate_tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
+        let mut tags = self.inner.tags(modifier);
+        tags.push(Tag { text: format!("{} boards", self.inner.boards().len()), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() });
+        tags
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler for SearchBoard<R> {
+    fn delegate_handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match event {
+            UiEvent::KeyDown(key_event) => {
+                let vk_code = VIRTUAL_KEY(key_event.key as u16);
+                match vk_code {
+                    VK_ESCAPE | VK_UP | VK_DOWN => self.inner.handle_ui_event(event),
+                    // Enter opens the first board found
+                    VK_RETURN => match self.inner.boards().is_empty() {
+                        true => UiEventResult::Handled,
+                        false => UiEventResult::PadSelected(PadId::Seven),
+                    },
+                    // Letters and top-row digits are part of the query, numpad keys select the boards found
+                    VK_NUMPAD1 | VK_NUMPAD2 | VK_NUMPAD3 | VK_NUMPAD4 | VK_NUMPAD5 | VK_NUMPAD6 | VK_NUMPAD7 | VK_NUMPAD8 | VK_NUMPAD9 => {
+                        match map_pad_id(vk_code, false, PadKeys::Numpad) {
+                            Some(pad_id) => UiEventResult::PadSelected(pad_id),
+                            None => UiEventResult::Handled,
+                        }
+                    },
+                    _ => {
+                        let before = self.query();
+                        self.text_capture.on_keydown(WPARAM(key_event.key as usize), key_event.modifiers);
+                        if self.query() != before {
+                            self.update_filter();
+                        }
+                        UiEventResult::RequiresRedraw
+                    }
+                }
+            },
+            UiEvent::KeyUp(key_event) => {
+                self.text_capture.on_keyup(WPARAM(key_event.key as usize), key_event.modifiers);
+                UiEventResult::Handled
+            },
+            _ => self.inner.handle_ui_event(event),
+        }
+    }
+}
+
+impl_board_component_generic!(SearchBoard<R>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_query() {
+        let board = core::Board { name: "dev/vscode".to_string(), title: Some("VS Code".to_string()), ..Default::default() };
+        assert!(matches_query(&board, ""));
+        assert!(matches_query(&board, "vs c"));
+        assert!(matches_query(&board, "dev/"));
+        assert!(matches_query(&board, "VSC"));
+        assert!(!matches_query(&board, "git"));
+
+        let untitled = core::Board { name: "Git".to_string(), ..Default::default() };
+        assert!(matches_query(&untitled, "git"));
+    }
+}
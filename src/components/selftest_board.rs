@@ -0,0 +1,125 @@
+use std::rc::Rc;
+
+use crate::core::{self, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::model::{Anchor, AnchorPin, Board, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+
+use super::{BoardComponent, UiEventHandler};
+
+/// Pads are laid out top-down, so each pad's pin matches its place in the grid
+const PINS: [(AnchorPin, &str); 9] = [
+    (AnchorPin::NW, "NW"), (AnchorPin::N, "N"), (AnchorPin::NE, "NE"),
+    (AnchorPin::W, "W"), (AnchorPin::C, "C"), (AnchorPin::E, "E"),
+    (AnchorPin::SW, "SW"), (AnchorPin::S, "S"), (AnchorPin::SE, "SE"),
+];
+
+/// Built-in icons, alternating between the SVG and PNG renderers
+const ICONS: [Option<&str>; 9] = [
+    Some("info.svg"), None, Some("icon.png"),
+    None, Some("gear.svg"), None,
+    Some("icon.png"), None, Some("warning.svg"),
+];
+
+fn anchors() -> Vec<(Anchor, &'static str)> {
+    vec![
+        (Anchor::NW, "NW"), (Anchor::NNW, "NNW"), (Anchor::N, "N"), (Anchor::NNE, "NNE"), (Anchor::NE, "NE"),
+        (Anchor::WNW, "WNW"), (Anchor::CN, "CN"), (Anchor::ENE, "ENE"),
+        (Anchor::W, "W"), (Anchor::C, "C"), (Anchor::E, "E"),
+        (Anchor::WSW, "WSW"), (Anchor::CS, "CS"), (Anchor::ESE, "ESE"),
+        (Anchor::SW, "SW"), (Anchor::SSW, "SSW"), (Anchor::S, "S"), (Anchor::SSE, "SSE"), (Anchor::SE, "SE"),
+    ]
+}
+
+/// One pad per anchor pin, each also carrying a sample icon and the palette colors whose index % 9 is its position
+fn selftest_pads(palette_len: usize) -> Vec<Pad> {
+    PadId::reading_order().iter().zip(PINS.iter()).zip(ICONS.iter()).enumerate()
+        .map(|(i, ((pad_id, (pin, pin_name)), icon))| {
+            let mut tags = vec![
+                Tag { text: format!("+{}", pin_name), anchor: Anchor::C, pin: Some(*pin), font_idx: Some(0), ..Default::default() },
+            ];
+            tags.extend((i..palette_len).step_by(PadId::reading_order().len()).enumerate().map(|(row, color_idx)| Tag {
+                text: format!("■ {}", color_idx),
+                anchor: Anchor::Rel(0.0, 1.0 - 0.15 * row as f32),
+                pin: Some(AnchorPin::SW),
+                color_idx: Some(color_idx),
+                font_idx: Some(0),
+                ..Default::default()
+            }));
+
+            pad_id.with_data(core::Pad {
+                header: Some(format!("pin {}", pin_name)),
+                text: icon.map(|name| name.to_string()),
+                icon: icon.map(|name| name.to_string()),
+                ..Default::default()
+            }).with_tags(tags)
+        })
+        .collect()
+}
+
+/// SelfTestBoard - hidden diagnostic board opened with `--board __selftest`, drawing every tag anchor,
+/// anchor pin, icon renderer and palette color of the default scheme for screenshots in bug reports
+pub struct SelfTestBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    repository: Rc<R>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> SelfTestBoard<R> {
+    pub fn new(repository: Rc<R>) -> Self {
+        Self { repository }
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for SelfTestBoard<R> {
+    fn name(&self) -> String {
+        core::data::SELFTEST_BOARD_NAME.to_string()
+    }
+
+    fn title(&self) -> String {
+        "Self-test".to_string()
+    }
+
+    fn icon(&self) -> Option<String> {
+        Some("info.svg".to_string())
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.repository.resolve_color_scheme(&None)
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.repository.resolve_text_style(&None)
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        Box::new(selftest_pads(self.color_scheme().palette().len()))
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        anchors().into_iter()
+            .map(|(anchor, name)| Tag { text: name.to_string(), anchor, font_idx: Some(0), ..Default::default() })
+            .collect()
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for SelfTestBoard<R> {}
+
+impl_board_component_generic!(SelfTestBoard<R>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selftest_pads() {
+        let pads = selftest_pads(0);
+        assert_eq!(pads.len(), 9);
+        assert!(pads.iter().all(|pad| pad.tags().len() == 1));
+        assert!(pads.iter().any(|pad| pad.icon().ends_with(".svg")));
+        assert!(pads.iter().any(|pad| pad.icon().ends_with(".png")));
+
+        // Twelve colors: the first three pads show a second row
+        let pads = selftest_pads(12);
+        let color_tags: Vec<usize> = pads.iter().map(|pad| pad.tags().len() - 1).collect();
+        assert_eq!(color_tags, vec![2, 2, 2, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(anchors().len(), 19);
+    }
+}
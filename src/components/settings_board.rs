@@ -1,17 +1,18 @@
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use super::{
     BoardComponent, UiEventHandler, LayoutAction, LayoutBoard, UiEvent, UiEventResult, Tags, KeyboardEvent, MouseEventTarget, HasBoard,
     error_board, string_editor_board, success_board,
-    colors_board::ColorSchemeEditorBoard, fonts_board::TextStyleEditorBoard,
+    colors_board::ColorSchemeEditorBoard, fonts_board::TextStyleEditorBoard, gallery_board::{GalleryBoard, ImportPolicyBoard}, search_board::SearchBoard,
 };
 
 use crate::components::{yes_no_question_board, yes_no_warning_board, ChildWindowRequest, DelegatingBoard, DelegatingHandler, HasHandler, PadMapping};
 use crate::core::integration::ChainParams;
-use crate::core::{self, ActionType, BoardType, Detection, Param, Resources, SettingsRepository, SettingsRepositoryMut };
-use crate::ui::dialogs::open_chain_editor;
+use crate::core::{self, ActionType, BoardType, Detection, ImportOutcome, ImportPolicy, Param, Resources, SettingsRepository, SettingsRepositoryMut };
+use crate::ui::dialogs::{open_chain_editor, pick_board_file};
 use crate::{impl_board_component_generic};
-use crate::model::{ConvertToBoardChainUseCase, DeleteBoardUseCase, create_board, create_new_chain_with_board, Anchor, Board, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+use crate::model::{ConvertToBoardChainUseCase, DeleteBoardUseCase, ImportBoardUseCase, chain_members, create_board, create_new_chain_with_board, duplicate_board, export_board_to_file, Anchor, Board, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
 
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
@@ -59,7 +60,7 @@ impl <R: SettingsRepository + SettingsRepositoryMut> Board for SettingsBoard<R>
     }
 
     fn color_scheme(&self) -> ColorScheme {
-        self.repository.resolve_color_scheme(&self.board.color_scheme)
+        self.board.with_opacity(self.repository.resolve_color_scheme(&self.board.color_scheme))
     }
 
     fn text_style(&self) -> TextStyle {
@@ -132,6 +133,10 @@ impl <R: SettingsRepository + SettingsRepositoryMut> Board for SettingsBoard<R>
         }
     }
 
+    fn help_tags(&self) -> Vec<Tag> {
+        vec![ Tag { text: "f: find a board\nctrl z/y: undo/redo".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() } ]
+    }
+
 }
 
 impl <R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for SettingsBoard<R> {
@@ -142,6 +147,23 @@ impl <R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler fo
                 let pad_id = PadMapping { repository: self.repository.clone() }.map(vk_code);
 
                 match (pad_id, vk_code) {
+                    // Before the resize key, z without ctrl
+                    (_, VK_Z) if key_event.modifiers.ctrl => match self.repository.undo() {
+                        true => UiEventResult::RequiresRedraw,
+                        false => UiEventResult::Handled,
+                    },
+                    (_, VK_Y) if key_event.modifiers.ctrl => match self.repository.redo() {
+                        true => UiEventResult::RequiresRedraw,
+                        false => UiEventResult::Handled,
+                    },
+                    // Before the pads, so it finds boards whichever keys select them
+                    (_, VK_F) => {
+                        let board = SearchBoard::new(self.board.clone(), self.repository.clone());
+                        UiEventResult::PushState {
+                            board: Box::new(board),
+                            context: Box::new(()),
+                        }
+                    },
                     (Some(PadId::Two), _) | (_, VK_M) => {
                         let board = LayoutBoard::new(Box::new(self.clone()), LayoutAction::Move);
                         UiEventResult::PushState {
@@ -168,7 +190,7 @@ impl <R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler fo
                     },
 
                     (Some(PadId::Seven), _) | (_, VK_B) => {
-                        let board = MainBoardList::new(self.board.clone(), self.repository.clone());
+                        let board = MainBoardList::new(self.board.clone(), self.resources.clone(), self.repository.clone());
                         UiEventResult::PushState {
                             board: Box::new(board),
                             context: Box::new(()),
@@ -213,7 +235,7 @@ impl_board_component_generic!(SettingsBoard<R>);
 
 /// Base board list implementation with filtering
 
-struct BoardListBase<R: SettingsRepository + SettingsRepositoryMut> {
+pub(super) struct BoardListBase<R: SettingsRepository + SettingsRepositoryMut> {
     board: core::Board,
     repository: Rc<R>,
     filter_function: Rc<dyn Fn(&core::Board) -> bool>,
@@ -245,13 +267,19 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> BoardListBase<R> {
         }
     }
 
-    fn boards(&self) -> Vec<core::Board> {
+    pub(super) fn boards(&self) -> Vec<core::Board> {
         self.repository.boards().iter()
             .filter_map(|name| self.repository.get_board(name).ok())
             .filter(|b| (self.filter_function)(b))
             .collect()
     }
 
+    /// Lists the boards passing `filter_function` instead, from the first page
+    pub(super) fn set_filter<F: 'static + Fn(&core::Board) -> bool>(&mut self, filter_function: F) {
+        self.filter_function = Rc::new(filter_function);
+        self.current_page = 0;
+    }
+
     /// 0-9 boards: 0 pages
     /// 10-12 boards: 1 page
     /// 13-15 boards: 2 pages
@@ -298,45 +326,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> BoardListBase<R> {
                 _ => unreachable!(),
             };
 
-            let mut header = "".to_string();
-            let title = board.title().to_owned();
-
-            if board.name.contains("/") {
-                let parent_name = board.name.rsplit_once('/').map(|(p, _)| p).unwrap_or("");
-                if let Ok(parent_board) = self.repository.get_board(parent_name) {
-                    header = parent_board.title().to_string();
-                }
-            }
-
-            let tags = match &board.board_type {
-                BoardType::Home => match board.name.as_str() {
-                    "home" => vec!["🏠"],
-                    "settings" => vec!["⚙"],
-                    _ => vec![]
-                },
-                BoardType::Chain(_) => match board.detection {
-                    Detection::None => vec!["🔗"],
-                    _ => vec!["🔗", "🎯"],
-                },
-                BoardType::Static => match board.detection {
-                    Detection::None => vec!["📋"],
-                    _ => vec!["🎯"],
-                },
-                _ => vec![],
-            };
-
-            pads.push(pad_id
-                .with_data(core::Pad {
-                    header: Some(header),
-                    text: Some(title),
-                    icon: board.icon.clone(),
-                    board: Some(board.name.clone()),
-                    ..Default::default()
-                })
-                .with_tags(vec![
-                    Tag { text: tags.join(""), anchor: Anchor::NW, font_idx: Some(0), ..Default::default() }
-                ])
-            );
+            pads.push(board_list_pad(self.repository.as_ref(), board, pad_id));
         }
         pads
     }
@@ -346,6 +336,50 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> BoardListBase<R> {
     }
 }
 
+/// Pad opening the board, titled with the board and its parent, tagged with the kind of board
+pub(super) fn board_list_pad<R: SettingsRepository>(repository: &R, board: &core::Board, pad_id: PadId) -> Pad {
+    let mut header = "".to_string();
+    let title = board.title().to_owned();
+
+    if board.name.contains("/") {
+        let parent_name = board.name.rsplit_once('/').map(|(p, _)| p).unwrap_or("");
+        if let Ok(parent_board) = repository.get_board(parent_name) {
+            header = parent_board.title().to_string();
+        }
+    }
+
+    let tags = match &board.board_type {
+        BoardType::Home => match board.name.as_str() {
+            "home" => vec!["🏠"],
+            "settings" => vec!["⚙"],
+            _ => vec![]
+        },
+        BoardType::Chain(_) => match board.detection {
+            Detection::None => vec!["🔗"],
+            _ => vec!["🔗", "🎯"],
+        },
+        BoardType::Static => match board.detection {
+            Detection::None => vec!["📋"],
+            _ => vec!["🎯"],
+        },
+        BoardType::Template(_) => vec!["🧩"],
+        BoardType::Recent => vec!["🕘"],
+        _ => vec![],
+    };
+
+    pad_id
+        .with_data(core::Pad {
+            header: Some(header),
+            text: Some(title),
+            icon: board.icon.clone(),
+            board: Some(board.name.clone()),
+            ..Default::default()
+        })
+        .with_tags(vec![
+            Tag { text: tags.join(""), anchor: Anchor::NW, font_idx: Some(0), ..Default::default() }
+        ])
+}
+
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for BoardListBase<R> {
     fn name(&self) -> String {
         "board_list".to_string()
@@ -360,7 +394,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for BoardLis
     }
 
     fn color_scheme(&self) -> ColorScheme {
-        self.repository.resolve_color_scheme(&self.board.color_scheme)
+        self.board.with_opacity(self.repository.resolve_color_scheme(&self.board.color_scheme))
     }
 
     fn text_style(&self) -> TextStyle {
@@ -441,18 +475,51 @@ impl_board_component_generic!(BoardListBase<R>);
 
 /// Main boards overview screen
 
+/// Board file waiting for the import policy the user picks
+struct ImportFile(PathBuf);
+
 struct MainBoardList<R: SettingsRepository + SettingsRepositoryMut> {
     inner: BoardListBase<R>,
+    resources: Resources,
     repository: Rc<R>,
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> MainBoardList<R> {
-    pub fn new(board: core::Board, repository: Rc<R>) -> Self {
+    pub fn new(board: core::Board, resources: Resources, repository: Rc<R>) -> Self {
         Self {
             inner: BoardListBase::new(board, repository.clone(), |_| true),
+            resources,
             repository
         }
     }
+
+    /// Adds the boards of a board file picked by the user. When board names are in use, the user picks
+    /// whether they are added as `_2`, `_3`..., overwrite the existing boards or are skipped, as for templates.
+    fn import_board_file(&mut self, parent_hwnd: windows::Win32::Foundation::HWND) -> UiEventResult {
+        let Some(path) = pick_board_file(Some(parent_hwnd), None) else {
+            return UiEventResult::NotHandled;
+        };
+        match ImportBoardUseCase::from_file(self.repository.clone(), self.resources.clone(), &path, ImportPolicy::default()) {
+            Ok(uc) if !uc.conflicts().is_empty() => UiEventResult::PushState {
+                board: Box::new(ImportPolicyBoard::new(uc.conflicts(), self, self.repository.clone())),
+                context: Box::new(ImportFile(path)),
+            },
+            _ => self.import(&path, ImportPolicy::default()),
+        }
+    }
+
+    fn import(&self, path: &Path, policy: ImportPolicy) -> UiEventResult {
+        let imported = ImportBoardUseCase::from_file(self.repository.clone(), self.resources.clone(), path, policy)
+            .and_then(|uc| uc.import());
+        let board: Box<dyn BoardComponent> = match imported {
+            Ok(outcomes) => {
+                let lines: Vec<String> = outcomes.iter().map(ImportOutcome::to_string).collect();
+                Box::new(success_board(format!("Imported\n{}", lines.join("\n")), self))
+            },
+            Err(err) => Box::new(error_board(format!("{}", err), self)),
+        };
+        UiEventResult::PushState { board, context: Box::new(()) }
+    }
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> HasBoard for MainBoardList<R> {
@@ -472,7 +539,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard fo
         let mut tags = self.inner.tags(modifier);
         if modifier.unwrap_or_default().ctrl {
             tags.push(
-                Tag { text: "(+) create    (c) collections\n(-) delete    (m) main boards".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+                Tag { text: "(+) create    (c) collections    (g) templates    (e) export\n(-) delete    (m) main boards    (i) import".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
             );
         }
         tags
@@ -509,6 +576,19 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler
                             context: Box::new(()),
                         }
                     },
+                    VK_G => {
+                        UiEventResult::PushState {
+                            board: Box::new(GalleryBoard::new(self.inner.board.clone(), self.resources.clone(), self.repository.clone())),
+                            context: Box::new(()),
+                        }
+                    },
+                    VK_E => {
+                        UiEventResult::PushState {
+                            board: Box::new(ExportBoardList::new(self.inner.board.clone(), self.repository.clone())),
+                            context: Box::new(()),
+                        }
+                    },
+                    VK_I => UiEventResult::RequestChildWindow(ChildWindowRequest::BoardImport),
                     _ => self.inner.handle_ui_event(event),
                 }
             },
@@ -516,7 +596,17 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler
         }
     }
 
-    fn delegate_handle_child_result(&mut self, _context: Box<dyn std::any::Any>, _result: Box<dyn std::any::Any>) -> UiEventResult {
+    fn delegate_create_child_window(&mut self, request: ChildWindowRequest, parent_hwnd: windows::Win32::Foundation::HWND) -> UiEventResult {
+        match request {
+            ChildWindowRequest::BoardImport => self.import_board_file(parent_hwnd),
+            _ => UiEventResult::NotHandled,
+        }
+    }
+
+    fn delegate_handle_child_result(&mut self, context: Box<dyn std::any::Any>, result: Box<dyn std::any::Any>) -> UiEventResult {
+        if let (Some(ImportFile(path)), Some(policy)) = (context.downcast_ref::<ImportFile>(), result.downcast_ref::<ImportPolicy>()) {
+            return self.import(path, *policy);
+        }
         self.inner.clamp_current_page();
         UiEventResult::RequiresRedraw
     }
@@ -529,11 +619,13 @@ impl_board_component_generic!(MainBoardList<R>);
 
 struct DeleteBoardList<R: SettingsRepository + SettingsRepositoryMut> {
     inner: BoardListBase<R>,
-    repository: Rc<R>
+    repository: Rc<R>,
+    marked: Vec<String>,
 }
 
 enum DeleteBoardListContext {
     Confirmation(String),
+    BatchConfirmation,
     Success,
 }
 
@@ -545,7 +637,8 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DeleteBoardList<R>
                 repository.clone(),
                 |b| { !matches!(b.board_type, BoardType::Home) }
             ),
-            repository
+            repository,
+            marked: vec![],
         }
     }
 
@@ -565,6 +658,70 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DeleteBoardList<R>
         }
     }
 
+    fn toggle_mark(&mut self, pad_id: PadId, modifiers: ModifierState) -> UiEventResult {
+        match self.padset(Some(modifiers)).pad(pad_id).board() {
+            Some(board_name) => {
+                match self.marked.iter().position(|name| *name == board_name) {
+                    Some(index) => { self.marked.remove(index); },
+                    None => self.marked.push(board_name),
+                }
+                UiEventResult::RequiresRedraw
+            },
+            None => UiEventResult::NotHandled
+        }
+    }
+
+    fn request_delete_marked(&self) -> UiEventResult {
+        UiEventResult::PushState {
+            board: Box::new(yes_no_warning_board(format!("Delete {} boards\n{}?", self.marked.len(), self.marked.join(", ")), self)),
+            context: Box::new(DeleteBoardListContext::BatchConfirmation),
+        }
+    }
+
+    /// Deletes the marked boards, repeating while progress is made, so boards only referenced
+    /// by other marked boards go as well. Every board is reported, nothing is rolled back.
+    fn delete_marked(&mut self) -> UiEventResult {
+        let mut pending = std::mem::take(&mut self.marked);
+        let mut deleted = vec![];
+        let mut failed = vec![];
+
+        loop {
+            let before = pending.len();
+            failed.clear();
+            for board_name in std::mem::take(&mut pending) {
+                match self.uc(&board_name).delete() {
+                    Ok(_) => deleted.push(board_name),
+                    Err(err) => {
+                        failed.push(format!("\"{}\": {}", board_name, err.to_string().replace('\n', " ")));
+                        pending.push(board_name);
+                    }
+                }
+            }
+            if pending.is_empty() || pending.len() == before {
+                break;
+            }
+        }
+
+        let mut report = vec![];
+        if !deleted.is_empty() {
+            report.push(format!("Deleted {}: {}", deleted.len(), deleted.join(", ")));
+        }
+        report.extend(failed);
+        if !deleted.is_empty() {
+            report.push("Reload settings without saving to undo".to_string());
+        }
+
+        let board: Box<dyn BoardComponent> = if pending.is_empty() {
+            Box::new(success_board(report.join("\n"), self))
+        } else {
+            Box::new(error_board(report.join("\n"), self))
+        };
+        UiEventResult::PushState {
+            board,
+            context: Box::new(DeleteBoardListContext::Success),
+        }
+    }
+
     fn delete_board(&mut self, board_name: &str) -> UiEventResult {
         match self.uc(board_name).delete() {
             Ok(_) => {
@@ -599,10 +756,26 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard fo
     fn delegate_title(&self) -> String {
         "Delete Board".to_string()
     }
+    fn delegate_padset(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        let pads = self.inner.padset(modifier).pads().into_iter()
+            .map(|pad| match pad.board() {
+                Some(board_name) if self.marked.contains(&board_name) => pad.with_tags(vec![
+                    Tag { text: "✔".to_string(), anchor: Anchor::NE, font_idx: Some(0), ..Default::default() }
+                ]),
+                _ => pad,
+            })
+            .collect::<Vec<Pad>>();
+        Box::new(pads)
+    }
     fn delegate_tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
+        let help = if self.marked.is_empty() {
+            "1-9: delete    ctrl 1-9: mark".to_string()
+        } else {
+            format!("ctrl 1-9: mark    enter: delete {} marked", self.marked.len())
+        };
         self.inner.tags(modifier).into_iter().chain(
             vec![
-                Tag { text: "1-9: delete".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+                Tag { text: help, anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
             ]
         ).collect()
     }
@@ -610,7 +783,23 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard fo
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler for DeleteBoardList<R> {
     fn delegate_handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        if let UiEvent::KeyDown(key_event) = &event {
+            if !self.marked.is_empty() {
+                match VIRTUAL_KEY(key_event.key as u16) {
+                    VK_RETURN => return self.request_delete_marked(),
+                    VK_ESCAPE => {
+                        self.marked.clear();
+                        return UiEventResult::RequiresRedraw;
+                    },
+                    _ => {}
+                }
+            }
+        }
+
         match self.inner.handle_ui_event(event.clone()) {
+            UiEventResult::PadSelected(pad_id) if event.modifiers().ctrl => {
+                self.toggle_mark(pad_id, event.modifiers())
+            },
             UiEventResult::PadSelected(pad_id) => {
                 self.request_delete_board(pad_id, event.modifiers())
             },
@@ -628,6 +817,13 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler
                         }
                     }
                 },
+                DeleteBoardListContext::BatchConfirmation => {
+                    if let Some(confirmed) = result.downcast_ref::<bool>() {
+                        if *confirmed {
+                            return self.delete_marked();
+                        }
+                    }
+                },
                 DeleteBoardListContext::Success => {
                     self.inner.clamp_current_page();
                     return UiEventResult::RequiresRedraw;
@@ -652,6 +848,7 @@ enum CreateBoardListContext {
     NewSubBoard(String),
     NewCollection(String),
     CollectionCreated,
+    Duplicate(String),
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut + 'static> CreateBoardList<R> {
@@ -676,6 +873,11 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> CreateBoardList<R>
                         )),
                         context: Box::new(CreateBoardListContext::NewCollection(board_name)),
                     }
+                } else if modifiers.shift {
+                    UiEventResult::PushState {
+                        board: Box::new(yes_no_question_board(format!("Duplicate board\n\"{}\"", board_name), self)),
+                        context: Box::new(CreateBoardListContext::Duplicate(board_name)),
+                    }
                 } else {
                     UiEventResult::PushState {
                         board: Box::new(string_editor_board("New board".to_string(), self, "Create Board".to_string())),
@@ -704,6 +906,20 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> CreateBoardList<R>
         };
     }
 
+    fn duplicate(&mut self, board_name: String) -> UiEventResult {
+        let board = match duplicate_board(self.repository.as_ref(), &board_name) {
+            Ok(copy) => {
+                self.inner.move_to_end();
+                success_board(format!("Duplicated as\n\"{}\"", copy.name), self)
+            }
+            Err(err) => error_board(format!("{}", err), self),
+        };
+        UiEventResult::PushState {
+            board: Box::new(board),
+            context: Box::new(()),
+        }
+    }
+
     fn create_collection(&mut self, board_name: String) -> UiEventResult {
         match create_new_chain_with_board(self.repository.as_ref(), &board_name) {
             Ok(collection) => {
@@ -741,7 +957,11 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard fo
     fn delegate_tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
         self.inner.tags(modifier).into_iter().chain(
             vec![ Tag {
-                text: format!("New {}", if modifier.unwrap_or_default().ctrl { "Collection" } else { "Board" }),
+                text: match modifier.unwrap_or_default() {
+                    m if m.ctrl => "New Collection",
+                    m if m.shift => "Duplicate Board",
+                    _ => "New Board",
+                }.to_string(),
                 anchor: Anchor::SW, font_idx: Some(0), ..Default::default() }, ]
         ).collect()
     }
@@ -773,6 +993,13 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler
                         }
                     }
                 }
+                CreateBoardListContext::Duplicate(board_name) => {
+                    if let Some(confirmed) = result.downcast_ref::<bool>() {
+                        if *confirmed {
+                            return self.duplicate(board_name.clone());
+                        }
+                    }
+                }
                 CreateBoardListContext::CollectionCreated => {
                     return UiEventResult::ReplaceState {
                         board: Box::new(ChainBoardList::new(self.inner.board.clone(), self.repository.clone()))
@@ -896,6 +1123,83 @@ impl_board_component_generic!(ConvertBoardList<R>);
 
 
 
+/// Export boards screen, writes the selected board with the boards it opens to a file
+
+struct ExportBoardList<R: SettingsRepository + SettingsRepositoryMut> {
+    inner: BoardListBase<R>,
+    repository: Rc<R>,
+    selected_board: Option<String>
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> ExportBoardList<R> {
+    pub fn new(board: core::Board, repository: Rc<R>) -> Self {
+        Self {
+            inner: BoardListBase::new(board, repository.clone(), |_| true),
+            repository,
+            selected_board: None
+        }
+    }
+
+    fn export_board(&mut self, board_name: String, parent_hwnd: windows::Win32::Foundation::HWND) -> UiEventResult {
+        let file_name = format!("board_{}.json", board_name.replace(['/', '\\'], "_"));
+        let Some(path) = pick_board_file(Some(parent_hwnd), Some(&file_name)) else {
+            return UiEventResult::NotHandled;
+        };
+        let board = match export_board_to_file(self.repository.as_ref(), &board_name, &path) {
+            Ok(export) => success_board(format!("Exported\n\"{}\"\nwith {} boards", board_name, export.boards.len()), self),
+            Err(err) => error_board(format!("{}", err), self),
+        };
+        UiEventResult::PushState { board: Box::new(board), context: Box::new(()) }
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> HasBoard for ExportBoardList<R> {
+    fn board(&self) -> &dyn Board {
+        &self.inner
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> HasHandler for ExportBoardList<R> {
+    fn handler(&mut self) -> Option<&mut dyn UiEventHandler> {
+        Some(&mut self.inner)
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingBoard for ExportBoardList<R> {
+    fn delegate_tags(&self, modifier: Option<ModifierState>) -> Vec<Tag> {
+        self.inner.tags(modifier).into_iter().chain(
+            vec![ Tag { text: "1-9: export to a file".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() }, ]
+        ).collect()
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler for ExportBoardList<R> {
+    fn delegate_handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        let result = self.inner.handle_ui_event(event.clone());
+        if let UiEventResult::PadSelected(pad_id) = result {
+            match self.padset(Some(event.modifiers())).pad(pad_id).board().clone() {
+                Some(board_name) => {
+                    self.selected_board = Some(board_name);
+                    return UiEventResult::RequestChildWindow(ChildWindowRequest::BoardExport)
+                },
+                None => return UiEventResult::NotHandled
+            }
+        }
+        result
+    }
+
+    fn delegate_create_child_window(&mut self, request: ChildWindowRequest, parent_hwnd: windows::Win32::Foundation::HWND) -> UiEventResult {
+        match (request, self.selected_board.take()) {
+            (ChildWindowRequest::BoardExport, Some(board_name)) => self.export_board(board_name, parent_hwnd),
+            _ => UiEventResult::NotHandled,
+        }
+    }
+}
+
+impl_board_component_generic!(ExportBoardList<R>);
+
+
+
 /// Chain boards screen
 struct ChainBoardList<R: SettingsRepository + SettingsRepositoryMut> {
     inner: BoardListBase<R>,
@@ -924,6 +1228,15 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> ChainBoardList<R>
             .collect()
     }
 
+    /// Collections that can be nested into the selected one
+    fn other_chain_boards(&self) -> Vec<String> {
+        self.repository.boards().iter()
+            .filter_map(|name| self.repository.get_board(name).ok())
+            .filter(|b| (self.inner.filter_function)(b) && Some(&b.name) != self.selected_board.as_ref())
+            .map(|b| b.name)
+            .collect()
+    }
+
     fn get_selected_board(&self) -> Result<core::Board, Box<dyn std::error::Error>> {
         if let Some(board_name) = &self.selected_board {
             if let Ok(board) = self.repository.get_board(board_name) {
@@ -982,11 +1295,21 @@ impl<R: SettingsRepository + SettingsRepositoryMut + 'static> DelegatingHandler
                     if let Some((new_boards, new_initial)) = open_chain_editor(
                         params.boards(), params.initial_board.clone(),
                         self.all_non_chain_boards(),
+                        self.other_chain_boards(),
                         Some(parent_hwnd)
                     ) {
+                        // Nesting a collection that already contains this one would loop forever
+                        if let Err(err) = chain_members(self.repository.as_ref(), self.selected_board.as_deref().unwrap_or_default(), &new_boards) {
+                            return UiEventResult::PushState {
+                                board: Box::new(error_board(err, self)),
+                                context: Box::new(()),
+                            }
+                        }
+
                         let mut new_params = params.clone();
                         new_params.boards = new_boards.join(",");
                         new_params.initial_board = Some(new_initial);
+                        new_params.correct_initial_board(self.selected_board.as_deref().unwrap_or_default());
 
                         if let Ok(mut board) = self.get_selected_board() {
                             board.board_type = BoardType::Chain(new_params);
@@ -0,0 +1,89 @@
+use crate::core;
+use crate::model::{Board, ModifierState, Pad, PadId, PadSet, Tag};
+
+use super::{DelegatingBoard, HasBoard};
+
+/// StackOverlay - the displayed board with its pads replaced by the state stack, for diagnosing navigation:
+/// one label pad per board in reading order, the root first and the board shown last
+pub struct StackOverlay<'a> {
+    inner: &'a dyn Board,
+    stack: Vec<String>,
+}
+
+impl<'a> StackOverlay<'a> {
+    pub fn new(inner: &'a dyn Board, stack: Vec<String>) -> Self {
+        Self { inner, stack }
+    }
+}
+
+impl HasBoard for StackOverlay<'_> {
+    fn board(&self) -> &dyn Board {
+        self.inner
+    }
+}
+
+impl DelegatingBoard for StackOverlay<'_> {
+    fn delegate_title(&self) -> String {
+        format!("Stack depth {}", self.stack.len())
+    }
+
+    fn delegate_padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        Box::new(stack_pads(&self.stack))
+    }
+
+    fn delegate_tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![]
+    }
+}
+
+/// Pads top-left to bottom-right, as the stack is read. Deeper stacks show their top, the first pad tells how many are left out.
+fn stack_pads(stack: &[String]) -> Vec<Pad> {
+    let reading_order = PadId::reading_order();
+    let hidden = stack.len().saturating_sub(reading_order.len());
+
+    stack.iter().enumerate().skip(hidden)
+        .zip(reading_order)
+        .map(|((depth, name), pad_id)| {
+            let header = match depth {
+                _ if depth == stack.len() - 1 => "current".to_string(),
+                _ if depth == hidden && hidden > 0 => format!("{} below", hidden),
+                0 => "root".to_string(),
+                _ => (depth + 1).to_string(),
+            };
+            pad_id.with_data(core::Pad {
+                header: Some(header),
+                text: Some(name.clone()),
+                label: true,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(count: usize) -> Vec<String> {
+        (1..=count).map(|i| format!("board{}", i)).collect()
+    }
+
+    #[test]
+    fn test_stack_pads() {
+        let pads = stack_pads(&names(3));
+        assert_eq!(pads.iter().map(|p| p.pad_id()).collect::<Vec<_>>(), vec![PadId::Seven, PadId::Eight, PadId::Nine]);
+        assert_eq!(pads.iter().map(|p| p.header()).collect::<Vec<_>>(), vec!["root", "2", "current"]);
+        assert_eq!(pads[2].text(), "board3");
+        assert!(pads.iter().all(|p| !p.data.is_interactive()));
+
+        let pads = stack_pads(&names(1));
+        assert_eq!(pads[0].header(), "current");
+
+        let pads = stack_pads(&names(11));
+        assert_eq!(pads.len(), 9);
+        assert_eq!(pads[0].header(), "2 below");
+        assert_eq!(pads[0].text(), "board3");
+        assert_eq!(pads[8].header(), "current");
+        assert_eq!(pads[8].text(), "board11");
+    }
+}
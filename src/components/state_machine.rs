@@ -108,8 +108,27 @@ impl BoardStateMachine {
         }
     }
 
+    /// Drops every state stacked on the root one, and those of a state machine the root holds.
+    /// Returns whether anything was dropped.
+    pub fn pop_to_root(&mut self) -> bool {
+        let popped = self.stack_depth() > 1;
+        if popped {
+            self.stack.truncate(1);
+            log::info!("Popped to root state, stack depth now {}", self.stack_depth());
+        }
+        let root_reset = self.current_board().reset_state();
+        popped || root_reset
+    }
+
     pub fn stack_depth(&self) -> usize {
         self.stack.len()
     }
 
+    /// Board names of all frames from the root up, frames holding a state machine of their own list its stack
+    pub fn stack(&self) -> Vec<String> {
+        self.stack.iter()
+            .flat_map(|frame| frame.board.state_stack())
+            .collect()
+    }
+
 }
@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::core::{self, integration::TemplateParams, Param, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::model::{Anchor, Board, BoardHandle, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+
+use super::{BoardComponent, UiEvent, UiEventHandler, UiEventResult, Tags};
+
+/// TemplateBoard - one pad per item of a template board, running the template's actions for that item.
+/// Selecting a pad goes through the regular pad command, so the board only generates and pages the pads.
+pub struct TemplateBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    handle: BoardHandle<R>,
+    template: TemplateParams,
+    params: Vec<Param>,
+    items: Vec<String>,
+    current_page: usize,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> TemplateBoard<R> {
+    pub fn new(board_name: String, template: TemplateParams, params: Vec<Param>, repository: Rc<R>) -> Self {
+        Self {
+            handle: BoardHandle::new(repository, board_name),
+            items: template.items(&params),
+            template,
+            params,
+            current_page: 0,
+        }
+    }
+
+    fn max_page(&self) -> usize {
+        self.items.len().saturating_sub(1) / PadId::reading_order().len()
+    }
+
+    fn get_pads(&self) -> Vec<Pad> {
+        self.items.iter()
+            .skip(self.current_page * PadId::reading_order().len())
+            .zip(PadId::reading_order().iter())
+            .map(|(item, pad_id)| pad_id.with_data(core::Pad {
+                text: Some(item.clone()),
+                actions: self.template.actions_for(item, &self.params),
+                ..Default::default()
+            }))
+            .collect()
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for TemplateBoard<R> {
+    fn name(&self) -> String {
+        self.handle.name().to_string()
+    }
+
+    fn title(&self) -> String {
+        self.handle.title().unwrap_or_else(|_| self.name())
+    }
+
+    fn icon(&self) -> Option<String> {
+        self.handle.icon().ok().flatten()
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.handle.color_scheme().unwrap_or_default()
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.handle.text_style().unwrap_or_default()
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        Box::new(self.get_pads())
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        let mut tags = vec![];
+        if self.max_page() > 0 {
+            tags.push(Tag { text: format!("{}/{}", self.current_page + 1, self.max_page() + 1), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() });
+        }
+        if self.current_page > 0 {
+            tags.push(Tags::UpWhite.tag(Anchor::NE));
+        }
+        if self.current_page < self.max_page() {
+            tags.push(Tags::DownWhite.tag(Anchor::SE));
+        }
+        tags
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for TemplateBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match event {
+            UiEvent::KeyDown(ke) => {
+                match VIRTUAL_KEY(ke.key as u16) {
+                    VK_UP if self.current_page > 0 => {
+                        self.current_page -= 1;
+                        UiEventResult::RequiresRedraw
+                    },
+                    VK_DOWN if self.current_page < self.max_page() => {
+                        self.current_page += 1;
+                        UiEventResult::RequiresRedraw
+                    },
+                    _ => UiEventResult::NotHandled,
+                }
+            },
+            _ => UiEventResult::NotHandled,
+        }
+    }
+}
+
+impl_board_component_generic!(TemplateBoard<R>);
@@ -9,6 +9,14 @@ pub trait BoardComponent {
     fn handler(&mut self) -> Option<&mut dyn UiEventHandler> {
         None
     }
+    /// Names of the boards stacked up in this component, the bottom one first
+    fn state_stack(&self) -> Vec<String> {
+        vec![self.data().name()]
+    }
+    /// Drops the states stacked on the first one, returns whether there were any
+    fn reset_state(&mut self) -> bool {
+        false
+    }
 }
 
 pub trait UiEventHandler {
@@ -107,6 +115,9 @@ pub enum ChildWindowRequest {
     ColorEditor,
     FontSelector,
     ChainEditor,
+    ShortcutImport { folder: bool },
+    BoardExport,
+    BoardImport,
 }
 
 #[derive(Copy, Debug, Clone)]
@@ -0,0 +1,182 @@
+use std::rc::Rc;
+
+use serde::Deserialize;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::core::{self, SettingsRepository, SettingsRepositoryMut};
+use crate::impl_board_component_generic;
+use crate::model::{Anchor, Board, ColorScheme, ModifierState, Pad, PadId, PadSet, Tag, TextStyle};
+
+use super::{BoardComponent, UiEvent, UiEventHandler, UiEventResult};
+
+/// Version of the running application
+pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Release notes, newest version first
+const WHATS_NEW_JSON: &str = include_str!("../../resources/whatsnew.json");
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WhatsNewEntry {
+    pub version: String,
+    pub notes: Vec<String>,
+}
+
+/// WhatsNewBoard - lists the release notes of versions the user has not seen yet
+pub struct WhatsNewBoard<R: SettingsRepository + SettingsRepositoryMut> {
+    color_scheme: ColorScheme,
+    text_style: TextStyle,
+    repository: Rc<R>,
+    entries: Vec<WhatsNewEntry>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> WhatsNewBoard<R> {
+    pub fn new(repository: Rc<R>, entries: Vec<WhatsNewEntry>) -> Self {
+        Self {
+            color_scheme: repository.resolve_color_scheme(&None),
+            text_style: repository.resolve_text_style(&None),
+            repository,
+            entries,
+        }
+    }
+
+    fn get_pads(&self) -> Vec<Pad> {
+        self.entries.iter()
+            .flat_map(|entry| entry.notes.iter().map(move |note| (entry.version.clone(), note.clone())))
+            .zip(PadId::reading_order().iter())
+            .map(|((version, note), pad_id)| pad_id.with_data(core::Pad {
+                header: Some(format!("v{}", version)),
+                text: Some(note),
+                ..Default::default()
+            }))
+            .collect()
+    }
+
+    fn dont_show_again(&self) -> UiEventResult {
+        self.repository.set_last_seen_version(APP_VERSION);
+        if let Err(e) = self.repository.flush() {
+            log::error!("Failed to save last seen version: {}", e);
+        }
+        UiEventResult::CloseWindow
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> Board for WhatsNewBoard<R> {
+    fn name(&self) -> String {
+        "whats_new".to_string()
+    }
+
+    fn title(&self) -> String {
+        format!("What's new in v{}", APP_VERSION)
+    }
+
+    fn icon(&self) -> Option<String> {
+        None
+    }
+
+    fn color_scheme(&self) -> ColorScheme {
+        self.color_scheme.clone()
+    }
+
+    fn text_style(&self) -> TextStyle {
+        self.text_style.clone()
+    }
+
+    fn padset(&self, _modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+        Box::new(self.get_pads())
+    }
+
+    fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
+        vec![
+            Tag { text: "esc".to_string(), anchor: Anchor::NW, font_idx: Some(0), ..Default::default() },
+            Tag { text: "d: don't show again".to_string(), anchor: Anchor::SW, font_idx: Some(0), ..Default::default() },
+        ]
+    }
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut + 'static> UiEventHandler for WhatsNewBoard<R> {
+    fn handle_ui_event(&mut self, event: UiEvent) -> UiEventResult {
+        match event {
+            UiEvent::KeyDown(ke) => match VIRTUAL_KEY(ke.key as u16) {
+                VK_D => self.dont_show_again(),
+                _ => UiEventResult::NotHandled,
+            },
+            _ => UiEventResult::NotHandled,
+        }
+    }
+}
+
+impl_board_component_generic!(WhatsNewBoard<R>);
+
+
+/// Release notes of versions newer than `last_seen` up to the running version, `None` means nothing was seen yet
+pub fn unseen_entries(last_seen: Option<&str>) -> Vec<WhatsNewEntry> {
+    let entries: Vec<WhatsNewEntry> = serde_json::from_str(WHATS_NEW_JSON).unwrap_or_else(|e| {
+        log::error!("Failed to parse release notes: {}", e);
+        vec![]
+    });
+    filter_entries(entries, APP_VERSION, last_seen)
+}
+
+fn filter_entries(entries: Vec<WhatsNewEntry>, running: &str, last_seen: Option<&str>) -> Vec<WhatsNewEntry> {
+    if !last_seen.map_or(true, |seen| is_newer_version(running, seen)) {
+        return vec![];
+    }
+    entries.into_iter()
+        .filter(|e| !is_newer_version(&e.version, running))
+        .filter(|e| last_seen.map_or(true, |seen| is_newer_version(&e.version, seen)))
+        .collect()
+}
+
+/// Compares dot separated numeric versions, missing or non-numeric parts count as 0
+pub fn is_newer_version(version: &str, other: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version.trim().trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+    let (a, b) = (parts(version), parts(other));
+    let len = a.len().max(b.len());
+    let pad = |v: &Vec<u64>| (0..len).map(|i| v.get(i).copied().unwrap_or(0)).collect::<Vec<_>>();
+    pad(&a) > pad(&b)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("0.2.0", "0.1.9"));
+        assert!(is_newer_version("0.10.0", "0.9.0"));
+        assert!(is_newer_version("1.0", "0.9.9"));
+        assert!(is_newer_version("v1.2.1", "1.2"));
+        assert!(!is_newer_version("0.2.0", "0.2.0"));
+        assert!(!is_newer_version("0.2", "0.2.0"));
+        assert!(!is_newer_version("0.1.9", "0.2.0"));
+    }
+
+    #[test]
+    fn test_filter_entries() {
+        let entries = vec![
+            WhatsNewEntry { version: "0.3.0".to_string(), notes: vec!["future".to_string()] },
+            WhatsNewEntry { version: "0.2.0".to_string(), notes: vec!["current".to_string()] },
+            WhatsNewEntry { version: "0.1.0".to_string(), notes: vec!["old".to_string()] },
+        ];
+
+        let versions = |seen: Option<&str>| filter_entries(entries.clone(), "0.2.0", seen)
+            .into_iter().map(|e| e.version).collect::<Vec<_>>();
+
+        assert_eq!(versions(None), vec!["0.2.0", "0.1.0"]);
+        assert_eq!(versions(Some("0.1.0")), vec!["0.2.0"]);
+        assert!(versions(Some("0.2.0")).is_empty());
+        assert!(versions(Some("0.3.0")).is_empty());
+    }
+
+    #[test]
+    fn test_release_notes_parse() {
+        let entries: Vec<WhatsNewEntry> = serde_json::from_str(WHATS_NEW_JSON).unwrap();
+        assert!(entries.iter().any(|e| e.version == APP_VERSION));
+    }
+}
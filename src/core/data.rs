@@ -1,7 +1,10 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize, Serializer};
 
-use super::integration::{ActionType, BoardType, Param};
+use super::integration::{all_actions, ActionType, BoardType, Param};
 
 const DEFAULT_SCHEME: &str = "default";
 const DEFAULT_TEXT_STYLE: &str = "default";
@@ -20,7 +23,94 @@ const DEFAULT_TAG_FONT: &str = "Consolas Bold 18";
 pub const DEFAULT_TIMEOUT : u64 = 4;
 pub const DEFAULT_FEEDBACK : u64 = 0;
 pub const HOME_BOARD_NAME: &str = "home";
+pub const SETTINGS_BOARD_NAME: &str = "settings";
+/// Built-in diagnostic boards, never part of the settings
+pub const SELFTEST_BOARD_NAME: &str = "__selftest";
+pub const KEYTEST_BOARD_NAME: &str = "__keytest";
 pub const DEFAULT_EDITOR: &str = "notepad.exe";
+pub const DEFAULT_SAVE_AND_CLOSE_KEY: &str = "Ctrl+Enter";
+pub const DEFAULT_COPY_BOARD_KEY: &str = "Ctrl+C";
+pub const DEFAULT_HELP_MODIFIER: &str = "Shift";
+pub const DEFAULT_HELP_KEY: &str = "F1";
+pub const DEFAULT_FOCUS_KEY: &str = "F6";
+pub const DEFAULT_HOME_KEY: &str = "Ctrl+Home";
+pub const DEFAULT_ZOOM: f64 = 1.0;
+pub const MIN_ZOOM: f64 = 0.5;
+pub const MAX_ZOOM: f64 = 3.0;
+pub const ZOOM_STEP: f64 = 0.1;
+pub const MIN_BOARD_OPACITY: f64 = 0.1;
+pub const DEFAULT_AUTO_SWITCH_DEBOUNCE: u64 = 500;
+pub const MAX_MODIFIER_GRACE: u64 = 200;
+
+/// Whether `name` is one of the built-in diagnostic boards, which open without being in the settings
+pub fn is_diagnostic_board(name: &str) -> bool {
+    name == SELFTEST_BOARD_NAME || name == KEYTEST_BOARD_NAME
+}
+
+/// Rounds to whole zoom steps, so repeated zooming does not accumulate float errors
+pub fn clamp_zoom(zoom: f64) -> f64 {
+    let zoom = if zoom.is_finite() { zoom } else { DEFAULT_ZOOM };
+    let steps_per_unit = 1.0 / ZOOM_STEP;
+    ((zoom * steps_per_unit).round() / steps_per_unit).clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+/// Keeps a board's own opacity visible, non-finite values make it opaque
+pub fn clamp_opacity(opacity: f64) -> f64 {
+    if opacity.is_finite() { opacity.clamp(MIN_BOARD_OPACITY, 1.0) } else { 1.0 }
+}
+
+/// Bounds of the lists kept in memory, clamped to a safe range when the settings are loaded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Limits {
+    pub palette_results: usize, // matches kept by the command palette
+    pub undo_steps: usize,      // edits that can be undone
+    pub recent_boards: usize,   // most recently used boards remembered
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self { palette_results: 27, undo_steps: 50, recent_boards: 9 }
+    }
+}
+
+impl Limits {
+    /// Copy with every limit within its range, adjusted values are logged
+    pub fn clamped(&self) -> Self {
+        fn clamp(name: &str, value: usize, min: usize, max: usize) -> usize {
+            let clamped = value.clamp(min, max);
+            if clamped != value {
+                log::warn!("Limit '{}' of {} is out of range {}-{}, using {}", name, value, min, max, clamped);
+            }
+            clamped
+        }
+
+        Self {
+            palette_results: clamp("palette_results", self.palette_results, 9, 99),
+            undo_steps: clamp("undo_steps", self.undo_steps, 1, 500),
+            recent_boards: clamp("recent_boards", self.recent_boards, 1, 27),
+        }
+    }
+}
+
+/// Which number keys select pads, keys left out are handled by the board like any other key
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PadKeys {
+    #[default]
+    Both,   // numpad and top-row digits
+    Numpad, // numpad only
+    Digits, // top-row digits only
+}
+
+/// How board text is drawn. Only the text: lines, icons and backgrounds are painted the same either way
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Renderer {
+    #[default]
+    Gdi,
+    Direct2D, // DirectWrite text, falls back to GDI where Direct2D is unavailable
+}
 
 /// For use with serde's [serialize_with] attribute
 fn ordered_map<S, K: Ord + Serialize, V: Serialize>(
@@ -39,9 +129,13 @@ pub struct ColorScheme {
     pub name: String,
     pub opacity: f64,
     pub background: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background2: Option<String>, // bottom of a vertical gradient from background, flat when missing
     pub foreground1: String, // lines
     pub foreground2: String, // text
     pub tag_foreground: String, // tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>, // active markers, palette[0] when missing
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub palette: Vec<String>
 }
@@ -62,10 +156,32 @@ pub struct TextStyle {
 #[serde(rename_all = "lowercase")]
 pub enum Detection {
     Win32(String),
+    /// Regular expression matched against the process name, case-insensitive
+    Regex(String),
     None,
 }
 
 
+/// How a pad's Shortcut actions treat their keys. Without a behavior they are pressed and released at once.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PadBehavior {
+    /// Keys stay down while the key that selected the pad is held
+    Momentary,
+    /// Keys stay down until the pad is selected again
+    Toggle,
+}
+
+/// Where a pad's icon and text sit within the tile
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VerticalAlign {
+    Top,
+    #[default]
+    Center,
+    Bottom,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "lowercase")]
 enum PadSetType {
@@ -95,6 +211,285 @@ pub struct Board {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     #[serde(serialize_with = "ordered_map")]
     pub modifier_pads: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stack_modifier_pads: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires_process: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_pad: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub letter_keys: Option<bool>, // overrides the letter_keys setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<MonitorSelector>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub key_caps: bool, // pads show their shortcut as key caps
+    #[serde(default, skip_serializing_if = "WindowSizing::is_fixed")]
+    pub sizing: WindowSizing,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rect: Option<WindowRect>, // where the board's window was last moved, the shared layout when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>, // global hotkey opening the board, e.g. "Ctrl Alt H"
+}
+
+/// How a board's window is sized when it opens
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowSizing {
+    /// The board's own window rect, or the window layout shared by the boards
+    #[default]
+    Fixed,
+    /// Fitted to the longest pad text and the title, measured as the board opens
+    Fit,
+}
+
+impl WindowSizing {
+    pub fn is_fixed(&self) -> bool {
+        *self == WindowSizing::Fixed
+    }
+}
+
+/// Window rect of a board, unzoomed at 100% scaling like the shared layout
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WindowRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Monitor a board always opens on: its position in the system's monitor order (0 is the first)
+/// or its device name, e.g. `\\.\DISPLAY2`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum MonitorSelector {
+    Index(usize),
+    Device(String),
+}
+
+impl MonitorSelector {
+    pub fn matches(&self, index: usize, device: &str) -> bool {
+        match self {
+            MonitorSelector::Index(selected) => *selected == index,
+            MonitorSelector::Device(name) => name.eq_ignore_ascii_case(device),
+        }
+    }
+}
+
+/// A board together with the padsets it references, and for a board exported to a file the boards it opens
+/// and the color schemes and text styles they use.
+/// Serialized in the shape of an include file, so a copied board can be added back through `includes`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BoardExport {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub color_schemes: Vec<ColorScheme>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub text_styles: Vec<TextStyle>,
+    pub boards: Vec<Board>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub padsets: Vec<PadSet>,
+}
+
+impl BoardExport {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Checks the export holds together on its own: at least one board, no duplicate names,
+    /// and every padset a board uses is part of it
+    pub fn validate(&self) -> Result<(), String> {
+        if self.boards.is_empty() {
+            return Err("No boards to import".to_string());
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(board) = self.boards.iter().find(|b| !seen.insert(b.name.as_str())) {
+            return Err(format!("Duplicate board name '{}'", board.name));
+        }
+        let mut seen = HashSet::new();
+        if let Some(padset) = self.padsets.iter().find(|p| !seen.insert(p.name.as_str())) {
+            return Err(format!("Duplicate pad set name '{}'", padset.name));
+        }
+
+        for board in &self.boards {
+            for padset_name in board.base_pads.iter().chain(board.modifier_pads.values()) {
+                if !seen.contains(padset_name.as_str()) {
+                    return Err(format!("Pad set '{}' of board '{}' is not part of the import", padset_name, board.name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Renames the boards and padsets whose names are taken by appending `_2`, `_3`..., the references
+    /// between them are updated to match. Returns the renamed export and the new names of the boards.
+    pub fn renamed(mut self, board_taken: impl Fn(&str) -> bool, padset_taken: impl Fn(&str) -> bool) -> (Self, HashMap<String, String>) {
+        let board_names = unique_names(self.boards.iter().map(|b| b.name.clone()).collect(), board_taken);
+        let padset_names = unique_names(self.padsets.iter().map(|p| p.name.clone()).collect(), padset_taken);
+        let rename = |names: &HashMap<String, String>, name: &mut String| {
+            if let Some(new_name) = names.get(name) {
+                *name = new_name.clone();
+            }
+        };
+
+        for board in &mut self.boards {
+            rename(&board_names, &mut board.name);
+            if let Some(base_pads) = &mut board.base_pads {
+                rename(&padset_names, base_pads);
+            }
+            for padset_name in board.modifier_pads.values_mut() {
+                rename(&padset_names, padset_name);
+            }
+            if let BoardType::Chain(params) = &mut board.board_type {
+                let mut members = params.boards();
+                members.iter_mut().for_each(|member| rename(&board_names, member));
+                params.boards = members.join(",");
+                if let Some(initial_board) = &mut params.initial_board {
+                    rename(&board_names, initial_board);
+                }
+            }
+        }
+        for padset in &mut self.padsets {
+            rename(&padset_names, &mut padset.name);
+            for pad in &mut padset.items {
+                if let Some(board_name) = &mut pad.board {
+                    rename(&board_names, board_name);
+                }
+            }
+        }
+        (self, board_names)
+    }
+
+    /// Leaves out the boards whose names are taken, with the padsets no other board of the export uses.
+    /// Returns the rest of the export and the names of the boards left out.
+    pub fn skipping(mut self, board_taken: impl Fn(&str) -> bool) -> (Self, Vec<String>) {
+        let (skipped, kept): (Vec<Board>, Vec<Board>) = self.boards.into_iter().partition(|b| board_taken(&b.name));
+        let used: HashSet<&String> = kept.iter().flat_map(|b| b.base_pads.iter().chain(b.modifier_pads.values())).collect();
+        let dropped: HashSet<&String> = skipped.iter().flat_map(|b| b.base_pads.iter().chain(b.modifier_pads.values())).collect();
+
+        self.padsets.retain(|p| used.contains(&p.name) || !dropped.contains(&p.name));
+        let skipped = skipped.iter().map(|b| b.name.clone()).collect();
+        self.boards = kept;
+        (self, skipped)
+    }
+
+    /// Boards the exported boards open, as collection members or from pads, that aren't part of the export
+    pub fn referenced_boards(&self) -> Vec<String> {
+        let members = self.boards.iter().flat_map(|board| match &board.board_type {
+            BoardType::Chain(params) => params.boards().into_iter().chain(params.initial_board.clone()).collect(),
+            _ => vec![],
+        });
+        let pad_boards = self.padsets.iter().flat_map(|padset| padset.items.iter().filter_map(|pad| pad.board.clone()));
+
+        let mut names: Vec<String> = members.chain(pad_boards)
+            .filter(|name| !self.boards.iter().any(|board| board.name == *name))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Names of the color schemes and of the text styles the boards and their pads use, including the styles pads switch to
+    pub fn referenced_styles(&self) -> (Vec<String>, Vec<String>) {
+        let pads = || self.padsets.iter().flat_map(|padset| padset.items.iter());
+        let switched_styles = pads().flat_map(|pad| all_actions(&pad.actions)).filter_map(|action| match action {
+            ActionType::SwitchTextStyle(name) => Some(name.clone()),
+            _ => None,
+        });
+
+        let mut color_schemes: Vec<String> = self.boards.iter().filter_map(|board| board.color_scheme.clone())
+            .chain(pads().filter_map(|pad| pad.color_scheme.clone()))
+            .collect();
+        let mut text_styles: Vec<String> = self.boards.iter().filter_map(|board| board.text_style.clone())
+            .chain(pads().filter_map(|pad| pad.text_style.clone()))
+            .chain(switched_styles)
+            .collect();
+        for names in [&mut color_schemes, &mut text_styles] {
+            names.sort();
+            names.dedup();
+        }
+        (color_schemes, text_styles)
+    }
+}
+
+/// What an import does with a board or icon whose name is already taken
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ImportPolicy {
+    /// The imported one is added under a new name, `_2`, `_3`... appended
+    #[default]
+    Rename,
+    /// The imported one replaces the existing one, which is backed up first
+    Overwrite,
+    /// The existing one is kept and the imported one left out
+    Skip,
+}
+
+impl ImportPolicy {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            name if name.eq_ignore_ascii_case("Rename") => Some(ImportPolicy::Rename),
+            name if name.eq_ignore_ascii_case("Overwrite") => Some(ImportPolicy::Overwrite),
+            name if name.eq_ignore_ascii_case("Skip") => Some(ImportPolicy::Skip),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ImportPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportPolicy::Rename => write!(f, "Rename"),
+            ImportPolicy::Overwrite => write!(f, "Overwrite"),
+            ImportPolicy::Skip => write!(f, "Skip"),
+        }
+    }
+}
+
+/// What an import did with one board or icon
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportOutcome {
+    Added(String),
+    Renamed { name: String, new_name: String },
+    Overwritten(String),
+    Skipped(String),
+}
+
+impl std::fmt::Display for ImportOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportOutcome::Added(name) => write!(f, "\"{}\" added", name),
+            ImportOutcome::Renamed { name, new_name } => write!(f, "\"{}\" added as \"{}\"", name, new_name),
+            ImportOutcome::Overwritten(name) => write!(f, "\"{}\" overwritten", name),
+            ImportOutcome::Skipped(name) => write!(f, "\"{}\" skipped", name),
+        }
+    }
+}
+
+/// Maps the names taken elsewhere to free ones, none of them clashing with the other names in `names`
+fn unique_names(names: Vec<String>, taken: impl Fn(&str) -> bool) -> HashMap<String, String> {
+    let mut used: HashSet<String> = names.iter().cloned().collect();
+    let mut renamed = HashMap::new();
+    for name in names {
+        if !taken(&name) {
+            continue;
+        }
+        let new_name = (2..)
+            .map(|counter| format!("{}_{}", name, counter))
+            .find(|candidate| !taken(candidate) && !used.contains(candidate))
+            .unwrap();
+        used.insert(new_name.clone());
+        renamed.insert(name, new_name);
+    }
+    renamed
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -134,9 +529,49 @@ pub struct Pad {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub text_style: Option<String>,
+
+    /// Background color of this pad only (e.g. "#204060"), the rest of the colors stay those of its scheme
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<PadBehavior>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub on_release: bool,
+
+    /// Feedback delay of the pad in milliseconds, in place of the global `feedback`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<u64>,
+
+    /// Times the actions run in a row, once when not set and not at all with 0
+    #[serde(default, skip_serializing_if = "runs_once")]
+    pub repeat: Option<u32>,
+
+    /// Milliseconds after its actions ran during which the pad can't be run again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertical_align: Option<VerticalAlign>,
+
+    /// Long text is drawn in a smaller font until it fits the pad, instead of being cut off
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shrink_text: bool,
+
+    /// Separator pad only showing its text, it has no number and can't be selected
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub label: bool,
 }
 
 
+fn runs_once(repeat: &Option<u32>) -> bool {
+    matches!(repeat, None | Some(1))
+}
+
 impl Default for BoardType {
     fn default() -> Self {
         BoardType::Static
@@ -149,15 +584,125 @@ impl Default for PadSetType {
     }
 }
 
+impl ColorScheme {
+    /// Equal in every field but the name
+    pub fn same_colors(&self, other: &ColorScheme) -> bool {
+        *self == ColorScheme { name: self.name.clone(), ..other.clone() }
+    }
+}
+
+/// Groups of color schemes with the same colors as (kept scheme, its duplicates), in settings order.
+/// The default scheme is kept when it is part of a group, otherwise the first one.
+pub fn duplicate_color_schemes(schemes: &[ColorScheme]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<Vec<&ColorScheme>> = vec![];
+    for scheme in schemes {
+        match groups.iter_mut().find(|group| group[0].same_colors(scheme)) {
+            Some(group) => group.push(scheme),
+            None => groups.push(vec![scheme]),
+        }
+    }
+
+    groups.into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            let kept = group.iter().find(|cs| cs.name == DEFAULT_SCHEME).unwrap_or(&group[0]).name.clone();
+            let duplicates = group.iter().map(|cs| cs.name.clone()).filter(|name| *name != kept).collect();
+            (kept, duplicates)
+        })
+        .collect()
+}
+
+/// Kind of component a name in the settings refers to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReferenceKind {
+    ColorScheme,
+    TextStyle,
+    PadSet,
+    Board,
+}
+
+impl std::fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceKind::ColorScheme => write!(f, "color scheme"),
+            ReferenceKind::TextStyle => write!(f, "text style"),
+            ReferenceKind::PadSet => write!(f, "padset"),
+            ReferenceKind::Board => write!(f, "board"),
+        }
+    }
+}
+
+/// A name in the settings referring to a component they don't define
+#[derive(Clone, Debug, PartialEq)]
+pub struct DanglingReference {
+    /// Where the name is used, e.g. "board 'code'" or "item 3 of padset 'code'"
+    pub location: String,
+    pub kind: ReferenceKind,
+    pub name: String,
+}
+
+impl std::fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} '{}' not found", self.location, self.kind, self.name)
+    }
+}
+
+/// The color schemes, text styles, padsets, collection members and pad boards the boards and padsets
+/// refer to that aren't defined, in settings order
+pub fn dangling_references(boards: &[Board], padsets: &[PadSet], color_schemes: &[ColorScheme], text_styles: &[TextStyle]) -> Vec<DanglingReference> {
+    let exists = |kind: ReferenceKind, name: &str| match kind {
+        ReferenceKind::ColorScheme => color_schemes.iter().any(|cs| cs.name == name),
+        ReferenceKind::TextStyle => text_styles.iter().any(|ts| ts.name == name),
+        ReferenceKind::PadSet => padsets.iter().any(|ps| ps.name == name),
+        ReferenceKind::Board => boards.iter().any(|b| b.name == name),
+    };
+    let mut problems = vec![];
+    let mut check = |location: &str, kind: ReferenceKind, name: &str| {
+        if !exists(kind, name) {
+            problems.push(DanglingReference { location: location.to_string(), kind, name: name.to_string() });
+        }
+    };
+
+    for board in boards {
+        let location = format!("board '{}'", board.name);
+        board.color_scheme.iter().for_each(|name| check(&location, ReferenceKind::ColorScheme, name));
+        board.text_style.iter().for_each(|name| check(&location, ReferenceKind::TextStyle, name));
+        board.base_pads.iter().for_each(|name| check(&location, ReferenceKind::PadSet, name));
+        let mut modifier_pads: Vec<&String> = board.modifier_pads.values().collect();
+        modifier_pads.sort();
+        modifier_pads.into_iter().for_each(|name| check(&location, ReferenceKind::PadSet, name));
+        if let BoardType::Chain(params) = &board.board_type {
+            params.boards().iter()
+                .filter(|name| !name.is_empty())
+                .chain(params.initial_board.iter())
+                .for_each(|name| check(&location, ReferenceKind::Board, name));
+        }
+    }
+
+    for padset in padsets {
+        for (index, pad) in padset.items.iter().enumerate() {
+            let location = format!("item {} of padset '{}'", index + 1, padset.name);
+            pad.color_scheme.iter().for_each(|name| check(&location, ReferenceKind::ColorScheme, name));
+            pad.text_style.iter().for_each(|name| check(&location, ReferenceKind::TextStyle, name));
+            pad.board.iter()
+                .filter(|name| !name.is_empty())
+                .for_each(|name| check(&location, ReferenceKind::Board, name));
+        }
+    }
+    problems
+}
+
 impl Default for ColorScheme {
     fn default() -> Self {
         Self {
             name: DEFAULT_SCHEME.to_owned(),
             opacity: DEFAULT_OPACITY,
             background: DEFAULT_BACKGROUND.to_owned(),
+            background2: None,
             foreground1: DEFAULT_FOREGROUND1.to_owned(),
             foreground2: DEFAULT_FOREGROUND2.to_owned(),
             tag_foreground: DEFAULT_TAG_COLOR.to_owned(),
+            accent: None,
             palette: vec![]
         }
     }
@@ -199,11 +744,37 @@ impl Detection {
     pub fn is_match(&self, process_name: &str) -> bool {
         match self {
             Detection::Win32(keyword) => process_name.to_lowercase().contains(&keyword.to_lowercase()),
+            Detection::Regex(pattern) => detection_regex(pattern).is_some_and(|regex| regex.is_match(process_name)),
             Detection::None => false,
         }
     }
 }
 
+/// Compiled detection patterns, shared by every copy of a detection. An invalid pattern is kept as None,
+/// so its error is logged once and it never matches.
+static DETECTION_PATTERNS: Lazy<Mutex<HashMap<String, Option<Regex>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn detection_regex(pattern: &str) -> Option<Regex> {
+    DETECTION_PATTERNS.lock().unwrap()
+        .entry(pattern.to_string())
+        .or_insert_with(|| match RegexBuilder::new(pattern).case_insensitive(true).build() {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                log::error!("Invalid detection pattern '{}': {}", pattern, e);
+                None
+            }
+        })
+        .clone()
+}
+
+/// The board detected for the process. A board with a matching Win32 detection wins over one with a matching
+/// Regex, so a board for one application can be kept next to a pattern covering several. Otherwise the first in order.
+pub fn detect_board<'a>(boards: &'a [Board], process_name: &str) -> Option<&'a Board> {
+    let matching = |board: &&Board| board.detection.is_match(process_name);
+    boards.iter().filter(matching).find(|board| !matches!(board.detection, Detection::Regex(_)))
+        .or_else(|| boards.iter().find(matching))
+}
+
 impl PadSet {
     pub fn new(name: &str, items: Vec<Pad>) -> Self {
         Self {
@@ -231,6 +802,19 @@ impl Board {
         self.icon.as_deref().unwrap_or("")
     }
 
+    /// Zoom factor the board is rendered with, clamped to the supported range
+    pub fn zoom(&self) -> f64 {
+        clamp_zoom(self.zoom.unwrap_or(DEFAULT_ZOOM))
+    }
+
+    /// The board's color scheme with the board's own opacity, when it has one, in place of the scheme's
+    pub fn with_opacity(&self, mut color_scheme: ColorScheme) -> ColorScheme {
+        if let Some(opacity) = self.opacity {
+            color_scheme.opacity = opacity;
+        }
+        color_scheme
+    }
+
 
     pub fn padset_name(&self, modifier: Option<&str>) -> Option<&str> {
         if let Some(mod_key) = modifier {
@@ -258,6 +842,331 @@ impl Pad {
     }
 
     pub fn is_interactive(&self) -> bool {
-        self.has_actions() || self.has_board()
+        !self.label && (self.has_actions() || self.has_board())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        [&self.header, &self.text, &self.icon].iter().all(|s| s.as_deref().unwrap_or("").is_empty())
+            && !self.is_interactive()
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_zoom() {
+        assert_eq!(clamp_zoom(1.0), 1.0);
+        assert_eq!(clamp_zoom(1.0 + ZOOM_STEP), 1.1);
+        assert_eq!(clamp_zoom(1.14), 1.1);
+        assert_eq!(clamp_zoom(0.1), MIN_ZOOM);
+        assert_eq!(clamp_zoom(10.0), MAX_ZOOM);
+        assert_eq!(clamp_zoom(f64::NAN), DEFAULT_ZOOM);
+    }
+
+    #[test]
+    fn test_board_opacity() {
+        assert_eq!(clamp_opacity(0.5), 0.5);
+        assert_eq!(clamp_opacity(0.0), MIN_BOARD_OPACITY);
+        assert_eq!(clamp_opacity(2.0), 1.0);
+        assert_eq!(clamp_opacity(f64::NAN), 1.0);
+
+        let scheme = ColorScheme { opacity: 0.8, ..Default::default() };
+        let mut board = Board::default();
+        assert_eq!(board.with_opacity(scheme.clone()).opacity, 0.8);
+        board.opacity = Some(0.5);
+        assert_eq!(board.with_opacity(scheme).opacity, 0.5);
+    }
+
+    #[test]
+    fn test_monitor_selector() {
+        let index: MonitorSelector = serde_json::from_str("1").unwrap();
+        assert_eq!(index, MonitorSelector::Index(1));
+        assert!(index.matches(1, r"\\.\DISPLAY1"));
+        assert!(!index.matches(0, r"\\.\DISPLAY2"));
+
+        let device: MonitorSelector = serde_json::from_str(r#""\\\\.\\display2""#).unwrap();
+        assert_eq!(device, MonitorSelector::Device(r"\\.\display2".to_string()));
+        assert!(device.matches(0, r"\\.\DISPLAY2"));
+        assert!(!device.matches(1, r"\\.\DISPLAY1"));
+    }
+
+    #[test]
+    fn test_duplicate_color_schemes() {
+        let scheme = |name: &str, background: &str| ColorScheme { name: name.to_string(), background: background.to_string(), ..Default::default() };
+        let schemes = vec![
+            scheme("dark", "#000000"),
+            scheme("light", "#ffffff"),
+            scheme("dark copy", "#000000"),
+            scheme(DEFAULT_SCHEME, "#ffffff"),
+            scheme("dark 2", "#000000"),
+            scheme("almost dark", "#000001"),
+        ];
+
+        assert_eq!(duplicate_color_schemes(&schemes), vec![
+            ("dark".to_string(), vec!["dark copy".to_string(), "dark 2".to_string()]),
+            (DEFAULT_SCHEME.to_string(), vec!["light".to_string()]),
+        ]);
+
+        let translucent = ColorScheme { opacity: 0.5, ..scheme("dark translucent", "#000000") };
+        assert!(duplicate_color_schemes(&[scheme("dark", "#000000"), translucent]).is_empty());
+        assert!(duplicate_color_schemes(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_board_export_round_trip() {
+        let mut board = Board { name: "code".to_string(), base_pads: Some("code".to_string()), ..Default::default() };
+        board.modifier_pads.insert("Ctrl".to_string(), "code_ctrl".to_string());
+        let export = BoardExport {
+            boards: vec![board],
+            padsets: vec![
+                PadSet { name: "code".to_string(), items: vec![Pad { header: Some("Save".to_string()), ..Default::default() }], ..Default::default() },
+                PadSet { name: "code_ctrl".to_string(), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+
+        let json = export.to_json().unwrap();
+        let imported = BoardExport::from_json(&json).unwrap();
+
+        assert_eq!(imported.boards.len(), 1);
+        assert_eq!(imported.boards[0].name, "code");
+        assert_eq!(imported.boards[0].modifier_pads.get("Ctrl"), Some(&"code_ctrl".to_string()));
+        assert_eq!(imported.padsets.len(), 2);
+        assert_eq!(imported.padsets[0].items[0].header.as_deref(), Some("Save"));
+    }
+
+    #[test]
+    fn test_board_export_renamed() {
+        let export = BoardExport {
+            boards: vec![
+                Board { name: "dev".to_string(), base_pads: Some("dev".to_string()), ..Default::default() },
+                Board { name: "dev/git".to_string(), base_pads: Some("dev/git".to_string()), ..Default::default() },
+            ],
+            padsets: vec![
+                PadSet { name: "dev".to_string(), items: vec![Pad { board: Some("dev/git".to_string()), ..Default::default() }], ..Default::default() },
+                PadSet { name: "dev/git".to_string(), items: vec![Pad { board: Some("home".to_string()), ..Default::default() }], ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        assert!(export.validate().is_ok());
+
+        let taken = ["dev", "dev_2", "dev/git"];
+        let (renamed, board_names) = export.clone().renamed(|name| taken.contains(&name), |name| name == "dev");
+        assert_eq!(board_names.get("dev").map(String::as_str), Some("dev_3"));
+        assert_eq!(board_names.get("dev/git").map(String::as_str), Some("dev/git_2"));
+        assert_eq!(renamed.boards[0].base_pads.as_deref(), Some("dev_2"));
+        assert_eq!(renamed.boards[1].base_pads.as_deref(), Some("dev/git"));
+        assert_eq!(renamed.padsets[0].name, "dev_2");
+        assert_eq!(renamed.padsets[0].items[0].board.as_deref(), Some("dev/git_2"));
+        assert_eq!(renamed.padsets[1].items[0].board.as_deref(), Some("home"));
+
+        let (unchanged, board_names) = export.clone().renamed(|_| false, |_| false);
+        assert!(board_names.is_empty());
+        assert_eq!(unchanged.boards[0].name, "dev");
+
+        let mut missing = export.clone();
+        missing.padsets.pop();
+        assert!(missing.validate().is_err());
+        let mut duplicate = export;
+        duplicate.boards[1].name = "dev".to_string();
+        assert!(duplicate.validate().is_err());
+        assert!(BoardExport::default().validate().is_err());
+    }
+
+    #[test]
+    fn test_board_export_skipping() {
+        let export = BoardExport {
+            boards: vec![
+                Board { name: "dev".to_string(), base_pads: Some("dev".to_string()), ..Default::default() },
+                Board { name: "dev/git".to_string(), base_pads: Some("dev/git".to_string()), ..Default::default() },
+                Board { name: "dev/rust".to_string(), base_pads: Some("dev/git".to_string()), ..Default::default() },
+            ],
+            padsets: vec![
+                PadSet { name: "dev".to_string(), ..Default::default() },
+                PadSet { name: "dev/git".to_string(), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+
+        // A padset shared with a kept board stays
+        let (kept, skipped) = export.clone().skipping(|name| name == "dev" || name == "dev/git");
+        assert_eq!(skipped, vec!["dev".to_string(), "dev/git".to_string()]);
+        assert_eq!(kept.boards.len(), 1);
+        assert_eq!(kept.padsets.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), vec!["dev/git"]);
+        assert!(kept.validate().is_ok());
+
+        let (kept, skipped) = export.skipping(|_| true);
+        assert_eq!(skipped.len(), 3);
+        assert!(kept.boards.is_empty() && kept.padsets.is_empty());
+    }
+
+    #[test]
+    fn test_dangling_references() {
+        let chain = crate::core::integration::ChainParams { boards: "dev/git, dev/go".to_string(), initial_board: Some("dev/rust".to_string()), params: vec![] };
+        let boards = vec![
+            Board { name: "dev".to_string(), board_type: BoardType::Chain(chain), color_scheme: Some("dark".to_string()), ..Default::default() },
+            Board { name: "dev/git".to_string(), base_pads: Some("dev/git".to_string()), text_style: Some("default".to_string()),
+                modifier_pads: HashMap::from([("Ctrl".to_string(), "dev/git/ctrl".to_string())]), ..Default::default() },
+        ];
+        let padsets = vec![
+            PadSet { name: "dev/git".to_string(), items: vec![
+                Pad { board: Some("dev".to_string()), ..Default::default() },
+                Pad { board: Some("home".to_string()), text_style: Some("small".to_string()), ..Default::default() },
+            ], ..Default::default() },
+        ];
+
+        let problems = dangling_references(&boards, &padsets, &[ColorScheme::default()], &[TextStyle::default()]);
+        let found: Vec<(&str, ReferenceKind, &str)> = problems.iter().map(|p| (p.location.as_str(), p.kind, p.name.as_str())).collect();
+        assert_eq!(found, vec![
+            ("board 'dev'", ReferenceKind::ColorScheme, "dark"),
+            ("board 'dev'", ReferenceKind::Board, "dev/go"),
+            ("board 'dev'", ReferenceKind::Board, "dev/rust"),
+            ("board 'dev/git'", ReferenceKind::PadSet, "dev/git/ctrl"),
+            ("item 2 of padset 'dev/git'", ReferenceKind::TextStyle, "small"),
+            ("item 2 of padset 'dev/git'", ReferenceKind::Board, "home"),
+        ]);
+        assert_eq!(problems[0].to_string(), "board 'dev': color scheme 'dark' not found");
+
+        assert_eq!(dangling_references(&boards[1..], &[], &[], &[TextStyle::default()]).len(), 2);
+    }
+
+    #[test]
+    fn test_board_export_references() {
+        let chain = crate::core::integration::ChainParams { boards: "dev/git,dev/rust".to_string(), initial_board: Some("dev/git".to_string()), params: vec![] };
+        let export = BoardExport {
+            boards: vec![
+                Board { name: "dev".to_string(), board_type: BoardType::Chain(chain), color_scheme: Some("dark".to_string()), ..Default::default() },
+                Board { name: "dev/git".to_string(), base_pads: Some("dev/git".to_string()), text_style: Some("small".to_string()), ..Default::default() },
+            ],
+            padsets: vec![
+                PadSet { name: "dev/git".to_string(), items: vec![
+                    Pad { board: Some("home".to_string()), color_scheme: Some("dark".to_string()), ..Default::default() },
+                    Pad { board: Some("dev".to_string()), actions: vec![ActionType::SwitchTextStyle("large".to_string())], ..Default::default() },
+                ], ..Default::default() },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(export.referenced_boards(), vec!["dev/rust".to_string(), "home".to_string()]);
+        let (color_schemes, text_styles) = export.referenced_styles();
+        assert_eq!(color_schemes, vec!["dark".to_string()]);
+        assert_eq!(text_styles, vec!["large".to_string(), "small".to_string()]);
+
+        // Color schemes and text styles are left out of the JSON when there are none
+        let json = export.to_json().unwrap();
+        assert!(!json.contains("color_schemes") && !json.contains("text_styles"));
+        let with_scheme = BoardExport { color_schemes: vec![ColorScheme { name: "dark".to_string(), ..Default::default() }], ..export };
+        let imported = BoardExport::from_json(&with_scheme.to_json().unwrap()).unwrap();
+        assert_eq!(imported.color_schemes[0].name, "dark");
+    }
+
+    #[test]
+    fn test_detection_precedence() {
+        let board = |name: &str, detection: Detection| Board { name: name.to_string(), detection, ..Default::default() };
+        let boards = vec![
+            board("browsers", Detection::Regex(r"^(chrome|msedge)\.exe$".to_string())),
+            board("edge", Detection::Win32("msedge".to_string())),
+            board("broken", Detection::Regex("(chrome".to_string())),
+            board("home", Detection::None),
+        ];
+        let detected = |process_name: &str| detect_board(&boards, process_name).map(|b| b.name.as_str());
+
+        // The exact board wins over the earlier pattern covering it
+        assert_eq!(detected("msedge.exe"), Some("edge"));
+        assert_eq!(detected("Chrome.exe"), Some("browsers"));
+        assert_eq!(detected("chromedriver.exe"), None);
+        assert_eq!(detected("none"), None);
+
+        let detection: Detection = serde_json::from_str(r#"{"regex": "^code\\.exe$"}"#).unwrap();
+        assert_eq!(detection, Detection::Regex(r"^code\.exe$".to_string()));
+        assert!(detection.is_match("code.exe") && !detection.is_match("vscode.exe"));
+        assert!(!Detection::Regex("[".to_string()).is_match("["));
+    }
+
+    #[test]
+    fn test_import_policy() {
+        assert_eq!(ImportPolicy::default(), ImportPolicy::Rename);
+        for policy in [ImportPolicy::Rename, ImportPolicy::Overwrite, ImportPolicy::Skip] {
+            assert_eq!(ImportPolicy::parse(&policy.to_string()), Some(policy));
+        }
+        assert_eq!(ImportPolicy::parse(" overwrite "), Some(ImportPolicy::Overwrite));
+        assert_eq!(ImportPolicy::parse("replace"), None);
+    }
+
+    #[test]
+    fn test_pad_optional_fields() {
+        // Each field set reads back and is written the same, left out it isn't written at all
+        for json in [
+            r#"{"text":"Shift","behavior":"momentary"}"#,
+            r#"{"text":"Preview","on_release":true}"#,
+            r#"{"board":"git","feedback":0}"#,
+            r#"{"actions":[{"Shortcut":"Ctrl S"}],"cooldown":2000}"#,
+            r#"{"vertical_align":"top"}"#,
+            r#"{"text":"Restart Windows Explorer","shrink_text":true}"#,
+            r#"{"text":"Git","board":"git","label":true}"#,
+            r#"{"text":"Plain"}"#,
+        ] {
+            let pad: Pad = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_string(&pad).unwrap(), json);
+        }
+
+        let pad: Pad = serde_json::from_str(r#"{"text":"Plain"}"#).unwrap();
+        assert_eq!((pad.behavior, pad.on_release, pad.feedback, pad.cooldown), (None, false, None, None));
+        assert_eq!((pad.vertical_align.unwrap_or_default(), pad.shrink_text, pad.label), (VerticalAlign::Center, false, false));
+
+        let pad: Pad = serde_json::from_str(r#"{"text":"Git","board":"git"}"#).unwrap();
+        assert!(pad.is_interactive());
+        let label = Pad { label: true, ..pad };
+        assert!(!label.is_interactive());
+        assert!(!label.is_empty());
+
+        for json in [r#"{"behavior": "sticky"}"#, r#"{"vertical_align": "middle"}"#] {
+            assert!(serde_json::from_str::<Pad>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_pad_repeat() {
+        let pad: Pad = serde_json::from_str(r#"{"actions": [{"Text": "ok"}, {"Pause": 500}], "repeat": 3}"#).unwrap();
+        assert_eq!(pad.repeat, Some(3));
+
+        let pad: Pad = serde_json::from_str(r#"{"text": "Once", "repeat": 1}"#).unwrap();
+        assert_eq!(serde_json::to_string(&pad).unwrap(), r#"{"text":"Once"}"#);
+
+        let pad = Pad { text: Some("Never".to_string()), repeat: Some(0), ..Default::default() };
+        assert_eq!(serde_json::to_string(&pad).unwrap(), r#"{"text":"Never","repeat":0}"#);
+    }
+
+    #[test]
+    fn test_limits_clamped() {
+        let limits: Limits = serde_json::from_str(r#"{"undo_steps": 0, "recent_boards": 1000}"#).unwrap();
+        let clamped = limits.clamped();
+
+        assert_eq!(clamped.palette_results, Limits::default().palette_results);
+        assert_eq!(clamped.undo_steps, 1);
+        assert_eq!(clamped.recent_boards, 27);
+    }
+
+    #[test]
+    fn test_board_help() {
+        let board: Board = serde_json::from_str(r#"{"name": "git", "help": "7: pull\n9: push"}"#).unwrap();
+        assert_eq!(board.help.as_deref(), Some("7: pull\n9: push"));
+
+        let board: Board = serde_json::from_str(r#"{"name": "git"}"#).unwrap();
+        assert!(board.help.is_none());
+        assert!(!serde_json::to_string(&board).unwrap().contains("help"));
+    }
+
+    #[test]
+    fn test_board_rect() {
+        let board: Board = serde_json::from_str(r#"{"name": "numpad", "rect": {"x": 1500, "y": 700, "width": 400, "height": 300}}"#).unwrap();
+        assert_eq!(board.rect, Some(WindowRect { x: 1500, y: 700, width: 400, height: 300 }));
+
+        let board: Board = serde_json::from_str(r#"{"name": "home"}"#).unwrap();
+        assert!(board.rect.is_none());
+        assert!(!serde_json::to_string(&board).unwrap().contains("rect"));
+    }
+}
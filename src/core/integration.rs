@@ -104,25 +104,298 @@ impl PathString for String {
 }
 
 
+/// One combination of a structured shortcut: a virtual key code and the modifiers held while it's pressed.
+/// Unlike the text form it doesn't depend on the keyboard layout it was captured with.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct KeyChord {
+    pub key: u16,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ctrl: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shift: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub alt: bool,
+    #[serde(default, rename = "super", skip_serializing_if = "std::ops::Not::not")]
+    pub super_key: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ActionType {
     Shortcut(String),
+    /// Shortcut played from its key codes, `text` is only the display form it was captured or migrated with
+    ShortcutKeys {
+        text: String,
+        keys: Vec<KeyChord>,
+    },
     Text(String),
     Line(String),
+    /// Text typed with the caret moved back to its [`SNIPPET_CURSOR`] marker
+    Snippet(String),
     Paste(String),
     PasteEnter(String),
+    /// Pastes like Paste, leaving the text on the clipboard instead of putting back what the clipboard held
+    PasteKeep(String),
+    /// PasteEnter leaving the text on the clipboard, like PasteKeep
+    PasteEnterKeep(String),
+    TypeClipboard(u64),
+    /// Types `text` changed to another case, the clipboard's text when `text` is empty
+    TypeTransformed {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        text: String,
+        transform: TextTransform,
+    },
+    WaitForClipboard {
+        timeout_ms: u64,
+    },
+    /// Copies the selected text and types it back between `prefix` and `suffix`, restoring the clipboard after
+    WrapSelection {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        prefix: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        suffix: String,
+    },
     Pause(u64),
     OpenUrl(String),
+    OpenQuery {
+        base: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        params: Vec<Param>,
+    },
+    /// Starts the executable at `path` with `args`, in `working_dir` when given
+    LaunchApp {
+        path: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        working_dir: Option<String>,
+    },
+    AppendFile {
+        path: String,
+        text: String,
+        #[serde(default)]
+        add_newline: bool,
+    },
+    SwitchTextStyle(String),
+    FocusPrevious,
+    Audio {
+        command: AudioCommand,
+    },
+    /// Presses a media key, handled by whichever player or the system takes them
+    MediaKey(MediaKey),
+    /// Minimizes, maximizes, closes or snaps the window the board was opened over
+    WindowCommand(WindowCommand),
+    /// Runs the `then` actions when the local time is between `from` and `to` ("HH:MM"), the `else` actions otherwise
+    IfTime {
+        from: String,
+        to: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        then: Vec<ActionType>,
+        #[serde(default, rename = "else", skip_serializing_if = "Vec::is_empty")]
+        else_: Vec<ActionType>,
+    },
     Custom(ActionParams),
 }
 
+/// Change to the volume of the default playback device
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AudioCommand {
+    VolumeUp,
+    VolumeDown,
+    /// Toggles the mute
+    Mute,
+    /// Volume in percent
+    SetVolume(u8),
+}
+
+impl AudioCommand {
+    /// Parses the single value form of the pad editor: a command name or a volume from 0 to 100
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            name if name.eq_ignore_ascii_case("VolumeUp") => Some(AudioCommand::VolumeUp),
+            name if name.eq_ignore_ascii_case("VolumeDown") => Some(AudioCommand::VolumeDown),
+            name if name.eq_ignore_ascii_case("Mute") => Some(AudioCommand::Mute),
+            volume => volume.trim_end_matches('%').parse().ok()
+                .filter(|volume| *volume <= 100)
+                .map(AudioCommand::SetVolume),
+        }
+    }
+}
+
+impl std::fmt::Display for AudioCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioCommand::VolumeUp => write!(f, "VolumeUp"),
+            AudioCommand::VolumeDown => write!(f, "VolumeDown"),
+            AudioCommand::Mute => write!(f, "Mute"),
+            AudioCommand::SetVolume(volume) => write!(f, "{}", volume),
+        }
+    }
+}
+
+/// Media keys of multimedia keyboards
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MediaKey {
+    VolumeUp,
+    VolumeDown,
+    Mute,
+    PlayPause,
+    NextTrack,
+    PrevTrack,
+    Stop,
+}
+
+impl MediaKey {
+    pub fn all() -> [MediaKey; 7] {
+        [MediaKey::VolumeUp, MediaKey::VolumeDown, MediaKey::Mute, MediaKey::PlayPause, MediaKey::NextTrack, MediaKey::PrevTrack, MediaKey::Stop]
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::all().into_iter().find(|key| key.to_string().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl std::fmt::Display for MediaKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MediaKey::VolumeUp => write!(f, "VolumeUp"),
+            MediaKey::VolumeDown => write!(f, "VolumeDown"),
+            MediaKey::Mute => write!(f, "Mute"),
+            MediaKey::PlayPause => write!(f, "PlayPause"),
+            MediaKey::NextTrack => write!(f, "NextTrack"),
+            MediaKey::PrevTrack => write!(f, "PrevTrack"),
+            MediaKey::Stop => write!(f, "Stop"),
+        }
+    }
+}
+
+/// What a WindowCommand does with the window the board was opened over
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum WindowCommand {
+    Minimize,
+    Maximize,
+    Restore,
+    /// Asks the window to close, the application can still ask to save changes
+    Close,
+    /// Left half of the work area of the window's monitor
+    SnapLeft,
+    /// Right half of the work area of the window's monitor
+    SnapRight,
+}
+
+impl WindowCommand {
+    pub fn all() -> [WindowCommand; 6] {
+        [WindowCommand::Minimize, WindowCommand::Maximize, WindowCommand::Restore, WindowCommand::Close, WindowCommand::SnapLeft, WindowCommand::SnapRight]
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::all().into_iter().find(|command| command.to_string().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl std::fmt::Display for WindowCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowCommand::Minimize => write!(f, "Minimize"),
+            WindowCommand::Maximize => write!(f, "Maximize"),
+            WindowCommand::Restore => write!(f, "Restore"),
+            WindowCommand::Close => write!(f, "Close"),
+            WindowCommand::SnapLeft => write!(f, "SnapLeft"),
+            WindowCommand::SnapRight => write!(f, "SnapRight"),
+        }
+    }
+}
+
+/// Case change of typed text, by the Unicode rules without locale specifics (e.g. Turkish i is not dotted)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TextTransform {
+    Upper,
+    Lower,
+    /// First letter of every word upper case, the rest lower case
+    Title,
+}
+
+impl TextTransform {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            name if name.eq_ignore_ascii_case("Upper") => Some(TextTransform::Upper),
+            name if name.eq_ignore_ascii_case("Lower") => Some(TextTransform::Lower),
+            name if name.eq_ignore_ascii_case("Title") => Some(TextTransform::Title),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            TextTransform::Upper => text.to_uppercase(),
+            TextTransform::Lower => text.to_lowercase(),
+            TextTransform::Title => title_case(text),
+        }
+    }
+}
+
+impl std::fmt::Display for TextTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextTransform::Upper => write!(f, "Upper"),
+            TextTransform::Lower => write!(f, "Lower"),
+            TextTransform::Title => write!(f, "Title"),
+        }
+    }
+}
+
+/// Words are runs of letters and digits, an apostrophe inside one doesn't start a new word ("don't")
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    for ch in text.chars() {
+        if ch.is_alphanumeric() || (!word.is_empty() && matches!(ch, '\'' | '’')) {
+            word.push(ch);
+        } else {
+            push_title_word(&mut result, &word);
+            word.clear();
+            result.push(ch);
+        }
+    }
+    push_title_word(&mut result, &word);
+    result
+}
+
+fn push_title_word(result: &mut String, word: &str) {
+    let Some(first) = word.chars().next() else { return };
+    match title_digraph(first) {
+        Some(title) => result.push(title),
+        None => {
+            // Letters upper cased to several (ß to SS) keep only the first one upper case
+            let mut upper = first.to_uppercase();
+            result.extend(upper.next());
+            result.push_str(&upper.collect::<String>().to_lowercase());
+        }
+    }
+    // The whole word is lower cased for context rules like the final sigma, the first letter is skipped afterwards
+    result.extend(word.to_lowercase().chars().skip(first.to_lowercase().count()));
+}
+
+/// Title case forms of the digraph letters (Dž, Lj, Nj, Dz), their upper case form is all capitals
+fn title_digraph(ch: char) -> Option<char> {
+    match ch {
+        'Ǆ' | 'ǅ' | 'ǆ' => Some('ǅ'),
+        'Ǉ' | 'ǈ' | 'ǉ' => Some('ǈ'),
+        'Ǌ' | 'ǋ' | 'ǌ' => Some('ǋ'),
+        'Ǳ' | 'ǲ' | 'ǳ' => Some('ǲ'),
+        _ => None,
+    }
+}
+
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum BoardType {
     Static,
     Home,
+    /// Lists the most recently used boards
+    Recent,
     Chain(ChainParams),
+    Template(TemplateParams),
     Custom(BoardParams),
 }
 
@@ -167,6 +440,133 @@ impl ChainParams {
     pub fn boards(&self) -> Vec<String> {
         self.boards.split(',').map(|s| s.trim().to_string()).collect()
     }
+
+    /// Points the members and the initial board named `old_name` to `new_name`, returns how many were changed
+    pub fn rename_board(&mut self, old_name: &str, new_name: &str) -> usize {
+        let mut members = self.boards();
+        let mut renamed = 0;
+        for member in members.iter_mut().filter(|member| *member == old_name) {
+            *member = new_name.to_string();
+            renamed += 1;
+        }
+        if renamed > 0 {
+            self.boards = members.join(",");
+        }
+        if let Some(initial_board) = self.initial_board.as_mut().filter(|initial| *initial == old_name) {
+            *initial_board = new_name.to_string();
+            renamed += 1;
+        }
+        renamed
+    }
+
+    /// Resets an initial board that isn't a member of the collection to its first member,
+    /// a collection can't be activated on a board it doesn't contain. Returns whether it was changed.
+    pub fn correct_initial_board(&mut self, chain: &str) -> bool {
+        let Some(initial) = &self.initial_board else {
+            return false;
+        };
+        let boards = self.boards();
+        if boards.contains(initial) {
+            return false;
+        }
+
+        let first = boards.into_iter().find(|b| !b.is_empty());
+        log::warn!("Initial board '{}' of collection '{}' is not one of its boards, using {:?}", initial, chain, first);
+        self.initial_board = first;
+        true
+    }
+}
+
+/// Generates one pad per item, `{item}` in the action template is replaced with the pad's item
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TemplateParams {
+    pub items: String,
+    pub actions: Vec<ActionType>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Param>,
+}
+
+impl TemplateParams {
+    /// An `items` param (e.g. from the command line) replaces the configured items
+    pub fn items(&self, dynamic_params: &[Param]) -> Vec<String> {
+        let items = dynamic_params.iter()
+            .find(|p| p.name == "items")
+            .map(|p| p.value.as_str())
+            .unwrap_or(&self.items);
+        items.split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    /// The item is available as `{item}`, the other params by their names
+    pub fn actions_for(&self, item: &str, dynamic_params: &[Param]) -> Vec<ActionType> {
+        let params = self.merge_params(dynamic_params.to_vec());
+        let vars = std::iter::once(("item", item))
+            .chain(params.iter().filter(|p| p.name != "items").map(|p| (p.name.as_str(), p.value.as_str())))
+            .collect::<Vec<_>>();
+        self.actions.iter().map(|action| expand_action(action, &vars)).collect()
+    }
+}
+
+impl Params for TemplateParams {
+    fn get_params(&self) -> Vec<Param> {
+        self.params.clone()
+    }
+    fn get_param(&self, name: &str) -> Option<Param> {
+        self.params.iter().find(|p| p.name == name).cloned()
+    }
+}
+
+/// Expands the placeholders in every string of an action, whatever its type
+pub fn expand_action(action: &ActionType, vars: &[(&str, &str)]) -> ActionType {
+    fn expand_value(value: serde_json::Value, vars: &[(&str, &str)]) -> serde_json::Value {
+        match value {
+            serde_json::Value::String(text) => serde_json::Value::String(expand_template(&text, vars)),
+            serde_json::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(|v| expand_value(v, vars)).collect()),
+            serde_json::Value::Object(fields) => serde_json::Value::Object(fields.into_iter().map(|(k, v)| (k, expand_value(v, vars))).collect()),
+            other => other,
+        }
+    }
+
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|value| serde_json::from_value(expand_value(value, vars)).ok())
+        .unwrap_or_else(|| action.clone())
+}
+
+/// Expands collection members into the boards to page through, nested collections are replaced by
+/// their own members. `sub_members` returns the members of a nested collection and None for other boards.
+/// Boards reachable through several nested collections are listed once.
+pub fn expand_chain_members<F>(chain: &str, members: &[String], sub_members: &F) -> Result<Vec<String>, String>
+where
+    F: Fn(&str) -> Option<Vec<String>>,
+{
+    let mut path = vec![chain.to_string()];
+    let mut expanded = vec![];
+    expand_into(members, sub_members, &mut path, &mut expanded)?;
+    Ok(expanded)
+}
+
+fn expand_into<F>(members: &[String], sub_members: &F, path: &mut Vec<String>, expanded: &mut Vec<String>) -> Result<(), String>
+where
+    F: Fn(&str) -> Option<Vec<String>>,
+{
+    for member in members {
+        if path.contains(member) {
+            return Err(format!("Collection cycle: {} -> {}", path.join(" -> "), member));
+        }
+        match sub_members(member) {
+            Some(nested) => {
+                path.push(member.clone());
+                expand_into(&nested, sub_members, path, expanded)?;
+                path.pop();
+            }
+            None if !expanded.contains(member) => expanded.push(member.clone()),
+            None => {}
+        }
+    }
+    Ok(())
 }
 
 impl Params for ChainParams {
@@ -199,6 +599,202 @@ impl From<Vec<Param>> for ChainParams {
     }
 }
 
+/// Percent-encodes a URL query component, only RFC 3986 unreserved characters are kept as they are
+pub fn percent_encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Replaces `{name}` placeholders with variable values, unknown placeholders are left untouched
+pub fn expand_template(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter().fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// Checks that the base of a query URL is an absolute http(s) URL with a host
+pub fn validate_base_url(base: &str) -> Result<(), String> {
+    let rest = base.strip_prefix("https://")
+        .or_else(|| base.strip_prefix("http://"))
+        .ok_or_else(|| format!("URL '{}' must start with http:// or https://", base))?;
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    if host.is_empty() || base.contains(char::is_whitespace) {
+        return Err(format!("URL '{}' is not well-formed", base));
+    }
+    Ok(())
+}
+
+/// Marks where the caret lands after a snippet is typed, `\$|$` types the marker itself
+pub const SNIPPET_CURSOR: &str = "$|$";
+
+/// Splits a snippet at its first cursor marker into the text before and after the caret.
+/// Escaped markers become literal ones, later markers are dropped. Without one the caret ends up after the text.
+pub fn split_snippet(snippet: &str) -> (String, String) {
+    let escaped = format!("\\{}", SNIPPET_CURSOR);
+    let mut parts = (String::new(), String::new());
+    let mut caret_placed = false;
+    let mut rest = snippet;
+
+    while !rest.is_empty() {
+        let text = if caret_placed { &mut parts.1 } else { &mut parts.0 };
+        if let Some(after) = rest.strip_prefix(escaped.as_str()) {
+            text.push_str(SNIPPET_CURSOR);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix(SNIPPET_CURSOR) {
+            caret_placed = true;
+            rest = after;
+        } else {
+            let ch = rest.chars().next().unwrap();
+            text.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+    parts
+}
+
+/// Splits a command line at the spaces outside double quotes. The quotes are removed, `""` is an empty argument
+/// and a doubled quote inside quotes is a literal one.
+pub fn split_command_line(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                part.get_or_insert_with(String::new).push('"');
+            },
+            '"' => {
+                quoted = !quoted;
+                part.get_or_insert_with(String::new);
+            },
+            ch if ch.is_whitespace() && !quoted => parts.extend(part.take()),
+            ch => part.get_or_insert_with(String::new).push(ch),
+        }
+    }
+    parts.extend(part);
+    parts
+}
+
+/// The command line [`split_command_line`] splits into `parts`. Empty parts and ones with spaces, quotes or `|`
+/// are quoted, their quotes doubled.
+pub fn join_command_line<S: AsRef<str>>(parts: &[S]) -> String {
+    parts.iter()
+        .map(|part| match part.as_ref() {
+            part if part.is_empty() || part.contains(|ch: char| ch.is_whitespace() || ch == '"' || ch == '|') =>
+                format!("\"{}\"", part.replace('"', "\"\"")),
+            part => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses "HH:MM" (24-hour, the hour may have a single digit) into minutes since midnight
+pub fn parse_time_of_day(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let valid = |part: &str, max_len: usize| !part.is_empty() && part.len() <= max_len && part.chars().all(|c| c.is_ascii_digit());
+    if !valid(hours, 2) || !valid(minutes, 2) || minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Whether `minute` falls in the range from `from` up to, not including, `to`.
+/// A range ending earlier than it starts wraps past midnight, "22:00" to "06:00" is the night.
+pub fn time_in_range(from: u32, to: u32, minute: u32) -> bool {
+    if from <= to {
+        from <= minute && minute < to
+    } else {
+        minute >= from || minute < to
+    }
+}
+
+/// How deep conditional actions can be nested in one another
+pub const MAX_ACTION_DEPTH: usize = 8;
+
+/// The actions to run at `minute` of the day, conditional ones replaced by the branch they take.
+/// Conditions with an invalid time and the ones nested too deep run neither branch.
+pub fn resolve_conditions(actions: &[ActionType], minute: u32) -> Vec<ActionType> {
+    fn resolve_into(actions: &[ActionType], minute: u32, depth: usize, resolved: &mut Vec<ActionType>) {
+        for action in actions {
+            let ActionType::IfTime { from, to, then, else_ } = action else {
+                resolved.push(action.clone());
+                continue;
+            };
+            if depth >= MAX_ACTION_DEPTH {
+                log::warn!("Condition {}-{} nested deeper than {} levels, skipped", from, to, MAX_ACTION_DEPTH);
+                continue;
+            }
+            match (parse_time_of_day(from), parse_time_of_day(to)) {
+                (Some(from), Some(to)) => {
+                    let branch = if time_in_range(from, to, minute) { then } else { else_ };
+                    resolve_into(branch, minute, depth + 1, resolved);
+                },
+                _ => log::warn!("Condition {}-{} skipped, expected HH:MM times", from, to),
+            }
+        }
+    }
+
+    let mut resolved = vec![];
+    resolve_into(actions, minute, 0, &mut resolved);
+    resolved
+}
+
+/// Checks the times of the conditional actions and how deep they are nested
+pub fn validate_conditions(actions: &[ActionType]) -> Result<(), String> {
+    fn validate_at(actions: &[ActionType], depth: usize) -> Result<(), String> {
+        for action in actions {
+            if let ActionType::IfTime { from, to, then, else_ } = action {
+                if depth >= MAX_ACTION_DEPTH {
+                    return Err(format!("Conditions nested deeper than {} levels", MAX_ACTION_DEPTH));
+                }
+                for time in [from, to] {
+                    if parse_time_of_day(time).is_none() {
+                        return Err(format!("Invalid time '{}', expected HH:MM", time));
+                    }
+                }
+                validate_at(then, depth + 1)?;
+                validate_at(else_, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+    validate_at(actions, 0)
+}
+
+/// The actions and the ones nested in their conditions, depth first
+pub fn all_actions(actions: &[ActionType]) -> Vec<&ActionType> {
+    actions.iter()
+        .flat_map(|action| {
+            let nested = match action {
+                ActionType::IfTime { then, else_, .. } => [all_actions(then), all_actions(else_)].concat(),
+                _ => vec![],
+            };
+            std::iter::once(action).chain(nested)
+        })
+        .collect()
+}
+
+/// Appends the query params to the base URL, placeholders in the values are expanded before encoding
+pub fn build_query_url(base: &str, params: &[Param], vars: &[(&str, &str)]) -> String {
+    if params.is_empty() {
+        return base.to_string();
+    }
+
+    let query = params.iter()
+        .map(|p| format!("{}={}", percent_encode(&p.name), percent_encode(&expand_template(&p.value, vars))))
+        .collect::<Vec<_>>()
+        .join("&");
+    let separator = if base.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", base, separator, query)
+}
+
  #[cfg(test)]
 mod tests {
 
@@ -248,5 +844,339 @@ mod tests {
         assert_eq!(v, None);
     }
 
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("abc-XYZ_0.9~"), "abc-XYZ_0.9~");
+        assert_eq!(percent_encode("a b&c=d"), "a%20b%26c%3Dd");
+        assert_eq!(percent_encode("čaj"), "%C4%8Daj");
+    }
+
+    #[test]
+    fn test_validate_base_url() {
+        assert!(validate_base_url("https://example.com/search").is_ok());
+        assert!(validate_base_url("http://localhost:8080").is_ok());
+        assert!(validate_base_url("example.com/search").is_err());
+        assert!(validate_base_url("https:///search").is_err());
+        assert!(validate_base_url("https://exa mple.com").is_err());
+    }
+
+    #[test]
+    fn test_build_query_url() {
+        let params = vec![
+            Param::new("q".to_string(), "{clipboard}".to_string()),
+            Param::new("lang".to_string(), "en".to_string()),
+        ];
+        let vars = [("clipboard", "rust & go")];
+
+        assert_eq!(build_query_url("https://example.com/search", &params, &vars), "https://example.com/search?q=rust%20%26%20go&lang=en");
+        assert_eq!(build_query_url("https://example.com/?src=hk", &params, &vars), "https://example.com/?src=hk&q=rust%20%26%20go&lang=en");
+        assert_eq!(build_query_url("https://example.com", &[], &vars), "https://example.com");
+    }
+
+    #[test]
+    fn test_expand_chain_members() {
+        let chains = |name: &str| -> Option<Vec<String>> {
+            match name {
+                "dev" => Some(vec!["code".to_string(), "git".to_string()]),
+                "all" => Some(vec!["dev".to_string(), "browser".to_string(), "code".to_string()]),
+                "loop_a" => Some(vec!["loop_b".to_string()]),
+                "loop_b" => Some(vec!["browser".to_string(), "loop_a".to_string()]),
+                _ => None,
+            }
+        };
+        let members = |name: &str| chains(name).unwrap();
+
+        assert_eq!(expand_chain_members("dev", &members("dev"), &chains).unwrap(), vec!["code", "git"]);
+        assert_eq!(expand_chain_members("all", &members("all"), &chains).unwrap(), vec!["code", "git", "browser"]);
+
+        let err = expand_chain_members("loop_a", &members("loop_a"), &chains).unwrap_err();
+        assert_eq!(err, "Collection cycle: loop_a -> loop_b -> loop_a");
+        assert!(expand_chain_members("dev", &["dev".to_string()], &chains).is_err());
+    }
+
+    #[test]
+    fn test_correct_initial_board() {
+        let chain = |initial: Option<&str>| ChainParams {
+            boards: "code, git".to_string(),
+            initial_board: initial.map(|s| s.to_string()),
+            params: vec![],
+        };
+
+        let mut params = chain(Some("git"));
+        assert!(!params.correct_initial_board("dev"));
+        assert_eq!(params.initial_board.as_deref(), Some("git"));
+
+        let mut params = chain(None);
+        assert!(!params.correct_initial_board("dev"));
+        assert_eq!(params.initial_board, None);
+
+        let mut params = chain(Some("browser"));
+        assert!(params.correct_initial_board("dev"));
+        assert_eq!(params.initial_board.as_deref(), Some("code"));
+
+        let mut params = ChainParams { boards: String::new(), initial_board: Some("code".to_string()), params: vec![] };
+        assert!(params.correct_initial_board("empty"));
+        assert_eq!(params.initial_board, None);
+    }
+
+    #[test]
+    fn test_split_snippet() {
+        assert_eq!(split_snippet("if ($|$) {}"), ("if (".to_string(), ") {}".to_string()));
+        assert_eq!(split_snippet("no marker"), ("no marker".to_string(), String::new()));
+        assert_eq!(split_snippet("$|$end"), (String::new(), "end".to_string()));
+        assert_eq!(split_snippet(r"a \$|$ b $|$ c"), ("a $|$ b ".to_string(), " c".to_string()));
+        assert_eq!(split_snippet("<b>$|$</b>$|$"), ("<b>".to_string(), "</b>".to_string()));
+        assert_eq!(split_snippet("ä$|$ö"), ("ä".to_string(), "ö".to_string()));
+
+        let action: ActionType = serde_json::from_str(r#"{"Snippet": "<b>$|$</b>"}"#).unwrap();
+        assert!(matches!(&action, ActionType::Snippet(text) if text == "<b>$|$</b>"));
+    }
+
+    #[test]
+    fn test_command_line() {
+        assert_eq!(split_command_line(r#"code.exe --new-window  "C:\My Projects" """#),
+            vec!["code.exe", "--new-window", r"C:\My Projects", ""]);
+        assert_eq!(split_command_line(r#"C:\Tools\"my app".exe"#), vec![r"C:\Tools\my app.exe"]);
+        assert!(split_command_line("  ").is_empty());
+
+        let parts = [r"C:\Program Files\App\app.exe", "-v", "two words", ""];
+        assert_eq!(join_command_line(&parts), r#""C:\Program Files\App\app.exe" -v "two words" """#);
+        assert_eq!(split_command_line(&join_command_line(&parts)), parts);
+
+        assert_eq!(split_command_line(r#"echo "say ""hi""" a""b"#), vec!["echo", r#"say "hi""#, "ab"]);
+        let parts = ["findstr", r#"a "quoted" word"#, "x|y", r#"""#];
+        assert_eq!(join_command_line(&parts), r#"findstr "a ""quoted"" word" "x|y" """""#);
+        assert_eq!(split_command_line(&join_command_line(&parts)), parts);
+    }
+
+    #[test]
+    fn test_launch_app_format() {
+        let action: ActionType = serde_json::from_str(
+            r#"{"LaunchApp": {"path": "code.exe", "args": ["--new-window", "~/notes"], "working_dir": "%USERPROFILE%"}}"#).unwrap();
+        assert!(matches!(&action, ActionType::LaunchApp { path, args, working_dir: Some(dir) }
+            if path == "code.exe" && args.len() == 2 && dir == "%USERPROFILE%"));
+
+        let action: ActionType = serde_json::from_str(r#"{"LaunchApp": {"path": "calc.exe"}}"#).unwrap();
+        assert!(matches!(&action, ActionType::LaunchApp { args, working_dir: None, .. } if args.is_empty()));
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"LaunchApp":{"path":"calc.exe"}}"#);
+    }
+
+    #[test]
+    fn test_wait_for_clipboard_format() {
+        let action: ActionType = serde_json::from_str(r#"{"WaitForClipboard": {"timeout_ms": 5000}}"#).unwrap();
+        assert!(matches!(action, ActionType::WaitForClipboard { timeout_ms: 5000 }));
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"WaitForClipboard":{"timeout_ms":5000}}"#);
+        assert!(serde_json::from_str::<ActionType>(r#"{"WaitForClipboard": 5000}"#).is_err());
+    }
+
+    #[test]
+    fn test_paste_keep_format() {
+        let action: ActionType = serde_json::from_str(r#"{"PasteKeep": "https://example.com"}"#).unwrap();
+        assert!(matches!(&action, ActionType::PasteKeep(text) if text == "https://example.com"));
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"PasteKeep":"https://example.com"}"#);
+        let action: ActionType = serde_json::from_str(r#"{"PasteEnterKeep": "ssh user@server"}"#).unwrap();
+        assert!(matches!(&action, ActionType::PasteEnterKeep(text) if text == "ssh user@server"));
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"PasteEnterKeep":"ssh user@server"}"#);
+    }
+
+    #[test]
+    fn test_switch_text_style_format() {
+        let action: ActionType = serde_json::from_str(r#"{"SwitchTextStyle": "large"}"#).unwrap();
+        assert!(matches!(&action, ActionType::SwitchTextStyle(name) if name == "large"));
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"SwitchTextStyle":"large"}"#);
+    }
+
+    #[test]
+    fn test_audio_format() {
+        let action: ActionType = serde_json::from_str(r#"{"Audio": {"command": {"SetVolume": 40}}}"#).unwrap();
+        assert!(matches!(action, ActionType::Audio { command: AudioCommand::SetVolume(40) }));
+        let action = ActionType::Audio { command: AudioCommand::Mute };
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"Audio":{"command":"Mute"}}"#);
+
+        for command in [AudioCommand::VolumeUp, AudioCommand::VolumeDown, AudioCommand::Mute, AudioCommand::SetVolume(75)] {
+            assert_eq!(AudioCommand::parse(&command.to_string()), Some(command));
+        }
+        assert_eq!(AudioCommand::parse(" mute "), Some(AudioCommand::Mute));
+        assert_eq!(AudioCommand::parse("30%"), Some(AudioCommand::SetVolume(30)));
+        assert_eq!(AudioCommand::parse("101"), None);
+        assert_eq!(AudioCommand::parse("louder"), None);
+    }
+
+    #[test]
+    fn test_media_key_format() {
+        let action: ActionType = serde_json::from_str(r#"{"MediaKey": "PlayPause"}"#).unwrap();
+        assert!(matches!(action, ActionType::MediaKey(MediaKey::PlayPause)));
+        let action = ActionType::MediaKey(MediaKey::PrevTrack);
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"MediaKey":"PrevTrack"}"#);
+
+        for key in MediaKey::all() {
+            assert_eq!(MediaKey::parse(&key.to_string()), Some(key));
+        }
+        assert_eq!(MediaKey::parse(" nexttrack "), Some(MediaKey::NextTrack));
+        assert_eq!(MediaKey::parse("Rewind"), None);
+    }
+
+    #[test]
+    fn test_window_command_format() {
+        let action: ActionType = serde_json::from_str(r#"{"WindowCommand": "SnapLeft"}"#).unwrap();
+        assert!(matches!(action, ActionType::WindowCommand(WindowCommand::SnapLeft)));
+        let action = ActionType::WindowCommand(WindowCommand::Minimize);
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"WindowCommand":"Minimize"}"#);
+
+        for command in WindowCommand::all() {
+            assert_eq!(WindowCommand::parse(&command.to_string()), Some(command));
+        }
+        assert_eq!(WindowCommand::parse(" snapright "), Some(WindowCommand::SnapRight));
+        assert_eq!(WindowCommand::parse("Fullscreen"), None);
+    }
+
+    #[test]
+    fn test_type_transformed_format() {
+        let action: ActionType = serde_json::from_str(r#"{"TypeTransformed": {"text": "hello", "transform": "Title"}}"#).unwrap();
+        assert!(matches!(&action, ActionType::TypeTransformed { text, transform: TextTransform::Title } if text == "hello"));
+
+        let action = ActionType::TypeTransformed { text: String::new(), transform: TextTransform::Upper };
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"TypeTransformed":{"transform":"Upper"}}"#);
+
+        for transform in [TextTransform::Upper, TextTransform::Lower, TextTransform::Title] {
+            assert_eq!(TextTransform::parse(&transform.to_string()), Some(transform));
+        }
+        assert_eq!(TextTransform::parse(" lower "), Some(TextTransform::Lower));
+        assert_eq!(TextTransform::parse("camel"), None);
+    }
+
+    #[test]
+    fn test_wrap_selection_format() {
+        let action: ActionType = serde_json::from_str(r#"{"WrapSelection": {"prefix": "**", "suffix": "**"}}"#).unwrap();
+        assert!(matches!(&action, ActionType::WrapSelection { prefix, suffix } if prefix == "**" && suffix == "**"));
+
+        let action: ActionType = serde_json::from_str(r#"{"WrapSelection": {"prefix": "("}}"#).unwrap();
+        assert!(matches!(&action, ActionType::WrapSelection { suffix, .. } if suffix.is_empty()));
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"WrapSelection":{"prefix":"("}}"#);
+    }
+
+    #[test]
+    fn test_chain_rename_board() {
+        let mut params = ChainParams { boards: "a, b,c".to_string(), initial_board: Some("b".to_string()), params: vec![] };
+        assert_eq!(params.rename_board("b", "bb"), 2);
+        assert_eq!(params.boards(), vec!["a", "bb", "c"]);
+        assert_eq!(params.initial_board.as_deref(), Some("bb"));
+
+        assert_eq!(params.rename_board("c", "cc"), 1);
+        assert_eq!(params.initial_board.as_deref(), Some("bb"));
+
+        let before = params.boards.clone();
+        assert_eq!(params.rename_board("x", "y"), 0);
+        assert_eq!(params.boards, before);
+    }
+
+    #[test]
+    fn test_if_time() {
+        let action: ActionType = serde_json::from_str(r#"{"IfTime": {"from": "22:00", "to": "6:00", "then": [{"Text": "night"}], "else": [{"Text": "day"}]}}"#).unwrap();
+        assert!(matches!(&action, ActionType::IfTime { else_, .. } if else_.len() == 1));
+        assert_eq!(serde_json::to_string(&action).unwrap(),
+            r#"{"IfTime":{"from":"22:00","to":"6:00","then":[{"Text":"night"}],"else":[{"Text":"day"}]}}"#);
+
+        let texts = |minute: u32| resolve_conditions(&[action.clone(), ActionType::Text("!".to_string())], minute).iter()
+            .map(|action| match action {
+                ActionType::Text(text) => text.clone(),
+                other => panic!("Unexpected action {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts(23 * 60), vec!["night", "!"]);
+        assert_eq!(texts(0), vec!["night", "!"]);
+        assert_eq!(texts(6 * 60), vec!["day", "!"]);
+        assert_eq!(texts(12 * 60), vec!["day", "!"]);
+
+        assert!(time_in_range(8 * 60, 12 * 60, 8 * 60));
+        assert!(!time_in_range(8 * 60, 12 * 60, 12 * 60));
+        assert!(!time_in_range(8 * 60, 8 * 60, 8 * 60));
+
+        assert!(validate_conditions(&[action.clone()]).is_ok());
+        let invalid: ActionType = serde_json::from_str(r#"{"IfTime": {"from": "8", "to": "12:00"}}"#).unwrap();
+        assert!(validate_conditions(&[invalid.clone()]).is_err());
+        assert!(resolve_conditions(&[invalid], 9 * 60).is_empty());
+
+        let nested = (0..MAX_ACTION_DEPTH).fold(ActionType::Text("deep".to_string()), |inner, _| ActionType::IfTime {
+            from: "0:00".to_string(), to: "0:00".to_string(), then: vec![], else_: vec![inner],
+        });
+        assert!(validate_conditions(&[nested.clone()]).is_ok());
+        assert_eq!(all_actions(&[nested.clone()]).len(), MAX_ACTION_DEPTH + 1);
+        let too_deep = ActionType::IfTime { from: "0:00".to_string(), to: "0:00".to_string(), then: vec![], else_: vec![nested] };
+        assert!(validate_conditions(&[too_deep.clone()]).is_err());
+        assert!(resolve_conditions(&[too_deep], 0).is_empty());
+    }
+
+    #[test]
+    fn test_text_transform() {
+        assert_eq!(TextTransform::Upper.apply("straße"), "STRASSE");
+        assert_eq!(TextTransform::Lower.apply("STRASSE"), "strasse");
+        assert_eq!(TextTransform::Title.apply("ßig"), "Ssig");
+        assert_eq!(TextTransform::Title.apply("große straße"), "Große Straße");
+
+        // Turkish dotless and dotted i follow the locale independent mappings
+        assert_eq!(TextTransform::Upper.apply("ırmak"), "IRMAK");
+        assert_eq!(TextTransform::Lower.apply("İstanbul"), "i\u{307}stanbul");
+        assert_eq!(TextTransform::Title.apply("istanbul"), "Istanbul");
+
+        // Final sigma, also within a title cased word
+        assert_eq!(TextTransform::Lower.apply("ΟΔΟΣ"), "οδος");
+        assert_eq!(TextTransform::Title.apply("ΟΔΟΣ ΣΟΦΙΑΣ"), "Οδος Σοφιας");
+
+        // Digraph letters have a title case of their own, two separate letters are two letters
+        assert_eq!(TextTransform::Title.apply("ǆep ǇUBAV ǌuška"), "ǅep ǈubav ǋuška");
+        assert_eq!(TextTransform::Title.apply("LJUBAV"), "Ljubav");
+        assert_eq!(TextTransform::Upper.apply("ǆep"), "ǄEP");
+
+        assert_eq!(TextTransform::Title.apply("don't STOP-me now, 3rd time"), "Don't Stop-Me Now, 3rd Time");
+        assert_eq!(TextTransform::Title.apply("  'quoted'\n"), "  'Quoted'\n");
+        assert_eq!(TextTransform::Title.apply(""), "");
+    }
+
+    #[test]
+    fn test_focus_previous_format() {
+        let actions: Vec<ActionType> = serde_json::from_str(r#"[{"OpenUrl": "https://example.com"}, "FocusPrevious"]"#).unwrap();
+        assert!(matches!(actions[1], ActionType::FocusPrevious));
+        assert_eq!(serde_json::to_string(&actions[1]).unwrap(), r#""FocusPrevious""#);
+    }
+
+    #[test]
+    fn test_shortcut_keys_format() {
+        let action: ActionType = serde_json::from_str(r#"{"ShortcutKeys": {"text": "Ctrl K + B", "keys": [{"key": 75, "ctrl": true}, {"key": 66}]}}"#).unwrap();
+        let ActionType::ShortcutKeys { text, keys } = &action else { panic!("Expected ShortcutKeys") };
+        assert_eq!(text, "Ctrl K + B");
+        assert_eq!(keys, &vec![
+            KeyChord { key: 75, ctrl: true, shift: false, alt: false, super_key: false },
+            KeyChord { key: 66, ctrl: false, shift: false, alt: false, super_key: false },
+        ]);
+        assert_eq!(serde_json::to_string(&action).unwrap(), r#"{"ShortcutKeys":{"text":"Ctrl K + B","keys":[{"key":75,"ctrl":true},{"key":66}]}}"#);
+    }
+
+    #[test]
+    fn test_template_params() {
+        let template: BoardType = serde_json::from_str(r#"{"template": {
+            "items": "alpha, beta,,gamma",
+            "actions": [{"Line": "git checkout {item}"}, {"OpenQuery": {"base": "https://{host}/q", "params": [{"name": "q", "value": "{item}"}]}}],
+            "params": [{"name": "host", "value": "example.com"}]
+        }}"#).unwrap();
+        let BoardType::Template(template) = template else { panic!("Expected a template board") };
+
+        assert_eq!(template.items(&[]), vec!["alpha", "beta", "gamma"]);
+        assert_eq!(template.items(&[Param::new("items".to_string(), "x,y".to_string())]), vec!["x", "y"]);
+
+        let actions = template.actions_for("beta", &[]);
+        assert!(matches!(&actions[0], ActionType::Line(text) if text == "git checkout beta"));
+        match &actions[1] {
+            ActionType::OpenQuery { base, params } => {
+                assert_eq!(base, "https://example.com/q");
+                assert_eq!(params[0].value, "beta");
+            },
+            other => panic!("Unexpected action {:?}", other),
+        }
+
+        let actions = template.actions_for("beta", &[Param::new("host".to_string(), "local".to_string())]);
+        assert!(matches!(&actions[1], ActionType::OpenQuery { base, .. } if base == "https://local/q"));
+    }
+
 }
 
@@ -6,9 +6,9 @@ pub mod integration;
 
 // #[cfg(test)]
 
-pub use data::{TextStyle, ColorScheme, Board, PadSet, Pad, Detection};
+pub use data::{TextStyle, ColorScheme, Board, BoardExport, DanglingReference, dangling_references, detect_board, ImportOutcome, ImportPolicy, PadSet, Pad, PadBehavior, Detection, Limits, MonitorSelector, PadKeys, Renderer, VerticalAlign, WindowRect, WindowSizing, duplicate_color_schemes};
 pub use repository::{SettingsRepository, SettingsRepositoryMut};
-pub use integration::{ActionType, ActionParams, BoardType, Param, Params, PathString};
+pub use integration::{ActionType, ActionParams, AudioCommand, KeyChord, MediaKey, BoardType, Param, Params, PathString, TextTransform, WindowCommand, all_actions, build_query_url, expand_chain_members, join_command_line, parse_time_of_day, resolve_conditions, split_command_line, split_snippet, validate_base_url, validate_conditions};
 // pub use integration::*;
 
-pub use resources::{Resources, DetectedIcon, slugify_process_name};
\ No newline at end of file
+pub use resources::{Resources, DetectedIcon, slugify_process_name, expand_path};
\ No newline at end of file
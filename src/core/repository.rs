@@ -1,4 +1,4 @@
-use super::data::{Board, PadSet, TextStyle, ColorScheme};
+use super::data::{Board, DanglingReference, PadSet, TextStyle, ColorScheme, Limits, PadKeys};
 
 /// Core repository interface for read operations
 pub trait SettingsRepository {
@@ -6,6 +6,13 @@ pub trait SettingsRepository {
     fn feedback(&self) -> u64;
     fn editor(&self) -> String;
     fn natural_key_order(&self) -> bool;
+    fn pad_keys(&self) -> PadKeys;
+    /// Pads of regular boards are labeled and selected with the letters a-i, next to the number keys
+    fn letter_keys(&self) -> bool;
+    /// Regular boards open in edit mode, for authors iterating on them
+    fn open_in_edit_mode(&self) -> bool;
+    /// Captured shortcuts are stored as key codes instead of layout-dependent text
+    fn structured_shortcuts(&self) -> bool;
     fn get_text_style(&self, name: &str) -> Option<TextStyle>;
     fn get_color_scheme(&self, name: &str) -> Option<ColorScheme>;
     fn get_board(&self, name: &str) -> Result<Board, Box<dyn std::error::Error>>;
@@ -18,6 +25,18 @@ pub trait SettingsRepository {
     fn text_styles(&self) -> Vec<String>;
     fn boards(&self) -> Vec<String>;
     fn padsets(&self) -> Vec<String>;
+
+    fn last_seen_version(&self) -> Option<String>;
+    /// Key combination copying the edited board to the clipboard, an empty string disables it
+    fn copy_board_key(&self) -> String;
+    /// Modifier (e.g. "Shift", "Ctrl+Alt") showing a board's own help instead of the edit commands
+    fn help_modifier(&self) -> String;
+    /// Bounds of the in-memory lists, already clamped to their ranges
+    fn limits(&self) -> Limits;
+    /// Boards opened most recently, the latest first
+    fn recent_boards(&self) -> Vec<String>;
+    /// Color schemes, text styles, padsets and boards referred to by name but not defined
+    fn dangling_references(&self) -> Vec<DanglingReference>;
 }
 
 
@@ -34,11 +53,33 @@ pub trait SettingsRepositoryMut {
     fn add_text_style(&self, text_style: TextStyle) -> Result<(), Box<dyn std::error::Error>>;
     fn rename_color_scheme(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>>;
     fn rename_text_style(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Renames a board, the pads and the settings opening it follow. Collections listing it are left as they are.
     fn rename_board(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>>;
     fn delete_color_scheme(&self, name: &str) -> Result<(), Box<dyn std::error::Error>>;
     fn delete_text_style(&self, name: &str) -> Result<(), Box<dyn std::error::Error>>;
     fn delete_board(&self, name: &str) -> Result<(), Box<dyn std::error::Error>>;
     fn delete_padset(&self, name: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Takes back the last edit of the boards, padsets, color schemes or text styles, returns whether there was one
+    fn undo(&self) -> bool;
+    /// Makes the last undone edit again, returns whether there was one
+    fn redo(&self) -> bool;
+    /// Starts edits undone together, until the matching `end_edit_group`. Groups can be nested.
+    fn begin_edit_group(&self);
+    fn end_edit_group(&self);
+    /// Runs edits that are undone as one step, the ones made before an error included
+    fn edit_group<T>(&self, edits: impl FnOnce() -> Result<T, Box<dyn std::error::Error>>) -> Result<T, Box<dyn std::error::Error>>
+    where
+        Self: Sized,
+    {
+        self.begin_edit_group();
+        let result = edits();
+        self.end_edit_group();
+        result
+    }
+    fn set_last_seen_version(&self, version: &str);
+    fn set_open_in_edit_mode(&self, enabled: bool);
+    /// Records a board as opened, the settings are not marked modified. Returns whether the recent boards changed.
+    fn add_recent_board(&self, name: &str) -> bool;
     fn mark_dirty(&self);
     fn is_dirty(&self) -> bool;
     fn flush(&self) -> Result<(), Box<dyn std::error::Error>>;
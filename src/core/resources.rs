@@ -13,6 +13,7 @@ pub struct ResourceNames {
     settings_json: String,
     data_json: String,
     icons_dir: String,
+    templates_dir: String,
 }
 
 impl Default for ResourceNames {
@@ -22,6 +23,7 @@ impl Default for ResourceNames {
             settings_json: env!("RESOURCE_SETTINGS_FILE").to_string(),
             data_json: env!("RESOURCE_DATA_FILE").to_string(),
             icons_dir: "icons".to_string(),
+            templates_dir: "templates".to_string(),
         }
     }
 }
@@ -43,6 +45,10 @@ impl ResourceNames {
     pub fn icons_dir(&self) -> String {
         self.icons_dir.clone()
     }
+
+    pub fn templates_dir(&self) -> String {
+        self.templates_dir.clone()
+    }
 }
 
 impl Resources {
@@ -70,6 +76,28 @@ impl Resources {
         self.file(&icon_file)
     }
 
+    /// The board templates (`.json` files) of the first config folder having a templates folder, sorted by file name
+    pub fn templates(&self) -> Vec<PathBuf> {
+        let Some(dir) = self.file(&self.resource_names.templates_dir) else { return vec![] };
+        let mut templates: Vec<PathBuf> = std::fs::read_dir(dir).into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+            .collect();
+        templates.sort();
+        templates
+    }
+
+    /// Path of the icon in the primary config folder, whether it exists or not
+    pub fn local_icon(&self, icon_file: &str) -> PathBuf {
+        self.config_paths[0].join(&self.resource_names.icons_dir).join(icon_file)
+    }
+
+    /// Where a board replaced by an import is kept, in the shape of an include file, next to the settings
+    pub fn board_backup(&self, board_name: &str) -> PathBuf {
+        self.config_paths[0].join(format!("board_{}.json.bak", board_name.replace(['/', '\\'], "_")))
+    }
+
     pub fn log_toml(&self) -> Option<PathBuf> {
         self.file(&self.resource_names.log_toml)
     }
@@ -172,3 +200,50 @@ impl DetectedIcon {
 pub fn slugify_process_name(process_name: &str) -> String {
     process_name.to_lowercase().replace(".exe", "").replace(" ", "_").replace("-", "_").replace("+", "_")
 }
+
+/// Expands `%VAR%` environment variables and a leading `~` (user profile directory) in a path.
+/// Unknown variables are left as they are.
+pub fn expand_path(path: &str) -> PathBuf {
+    let mut expanded = String::new();
+    let mut rest = path;
+
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else { break };
+        let name = &after[..end];
+
+        expanded.push_str(&rest[..start]);
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ => expanded.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    if expanded == "~" || expanded.starts_with("~/") || expanded.starts_with("~\\") {
+        if let Ok(home) = std::env::var("USERPROFILE") {
+            expanded = format!("{}{}", home, &expanded[1..]);
+        }
+    }
+
+    PathBuf::from(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_path() {
+        std::env::set_var("HOTKEYS_TEST_DIR", "C:\\data");
+
+        assert_eq!(expand_path("%HOTKEYS_TEST_DIR%\\log.txt"), PathBuf::from("C:\\data\\log.txt"));
+        if let Ok(home) = std::env::var("USERPROFILE") {
+            assert_eq!(expand_path("~\\notes.md"), PathBuf::from(format!("{}\\notes.md", home)));
+        }
+        assert_eq!(expand_path("%HOTKEYS_UNDEFINED%\\x"), PathBuf::from("%HOTKEYS_UNDEFINED%\\x"));
+        assert_eq!(expand_path("100% sure"), PathBuf::from("100% sure"));
+        assert_eq!(expand_path("a~b"), PathBuf::from("a~b"));
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use windows::Win32::UI::Input::KeyboardAndMouse::{SendInput, INPUT, KEYBDINPUT, KEYEVENTF_KEYUP, VIRTUAL_KEY, INPUT_KEYBOARD, INPUT_0, KEYBD_EVENT_FLAGS};
+use windows::Win32::UI::Input::KeyboardAndMouse::{SendInput, INPUT, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VIRTUAL_KEY, INPUT_KEYBOARD, INPUT_0, KEYBD_EVENT_FLAGS};
 
 pub struct KeyboardInput {
     pub vk_code: u16,
@@ -27,6 +27,38 @@ pub fn send_inputs (inputs: Vec<KeyboardInput>) {
     }
 }
 
+/// Types UTF-16 code units as they are, independent of the keyboard layout.
+/// Surrogate pairs have to be sent together, so the target can combine them.
+pub fn send_unicode (units: &[u16]) {
+    unsafe {
+        log::trace!(target:"input_api", "Unicode: {:x?}", units);
+        let pinputs = units.iter().flat_map(|unit| [
+            create_unicode_input(*unit, true),
+            create_unicode_input(*unit, false),
+        ]).collect::<Vec<INPUT>>();
+
+        SendInput(pinputs.as_slice(), std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+fn create_unicode_input(unit: u16, key_down: bool) -> INPUT {
+    unsafe {
+        let mut input_u: INPUT_0 = std::mem::zeroed();
+        *(& mut input_u.ki) = KEYBDINPUT {
+            wVk: VIRTUAL_KEY(0),
+            dwFlags: if key_down { KEYEVENTF_UNICODE } else { KEYEVENTF_UNICODE | KEYEVENTF_KEYUP },
+            dwExtraInfo: 1,
+            wScan: unit,
+            time: 0,
+        };
+
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: input_u
+        }
+    }
+}
+
 fn create_input(vk_code: u16, key_down: bool) -> INPUT {
     unsafe {
         let mut input_u: INPUT_0 = std::mem::zeroed();
@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use super::keys::{ModifierState, keyboard_api};
 use windows::Win32::Foundation::*;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
@@ -78,6 +80,51 @@ impl ModifierHandler {
 
 }
 
+/// Key press held back for a grace period, so a modifier pressed together with the key,
+/// but arriving a few milliseconds after it, is still part of the state the press is resolved with
+pub struct ModifierGrace<T> {
+    period: u64,
+    held: Option<(T, Instant)>,
+}
+
+impl<T> ModifierGrace<T> {
+    pub fn new(period: u64) -> Self {
+        Self { period, held: None }
+    }
+
+    /// Grace period in milliseconds, 0 when presses are resolved right away
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    /// Holds the press back, or hands it back to be resolved right away when there is no grace period
+    pub fn press(&mut self, press: T) -> Option<T> {
+        self.press_at(press, Instant::now())
+    }
+
+    fn press_at(&mut self, press: T, now: Instant) -> Option<T> {
+        if self.period == 0 {
+            return Some(press);
+        }
+        self.held = Some((press, now));
+        None
+    }
+
+    pub fn held(&self) -> Option<&T> {
+        self.held.as_ref().map(|(press, _)| press)
+    }
+
+    /// Whether the held press waited out its grace period by `now`, a modifier coming later isn't part of it
+    pub fn expired(&self, now: Instant) -> bool {
+        self.held.as_ref().is_some_and(|(_, pressed)| now.duration_since(*pressed) >= Duration::from_millis(self.period))
+    }
+
+    /// The held press, to be resolved with the modifier state as it is now
+    pub fn release(&mut self) -> Option<T> {
+        self.held.take().map(|(press, _)| press)
+    }
+}
+
 
 pub struct TextCapture {
     allow_newline: bool,
@@ -161,15 +208,18 @@ impl TextCapture {
 
 
 /// A keyboard key combination (e.g. "Ctrl+K")
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Combination {
     pub modifiers: ModifierState,
     pub key: Option<u16>
 }
 
+/// Records a shortcut as it is typed, a chord sequence such as "Ctrl K + Ctrl C" step by step.
+/// Every key pressed is a step with the modifiers held, a held key repeating is not another one.
 pub struct KeyCombinationCapture {
     records: Vec<Combination>,
-    has_active_record: bool,
+    /// Key of the last record until it is released
+    held_key: Option<u16>,
     modifiers: ModifierState,
 }
 
@@ -177,105 +227,40 @@ impl KeyCombinationCapture {
     pub fn new() -> Self {
         Self {
             records: Vec::new(),
-            has_active_record: false,
+            held_key: None,
             modifiers: ModifierState::default(),
         }
     }
 
-    fn activate_record(&mut self) {
-        if !self.has_active_record {
-            self.has_active_record = true;
-        }
-    }
-
-    fn finalize_record(&mut self, vk_code: VIRTUAL_KEY) {
-        self.has_active_record = false;
-        log::debug!(target:"input_api", "Captured: ({:#x}, {})", vk_code.0, self.modifiers);
-        self.records.push(Combination {
-            modifiers: self.modifiers.clone(),
-            key: Some(vk_code.0),
-        });
-    }
-
     pub fn on_keydown(&mut self, wparam: WPARAM, modifiers: ModifierState) -> LRESULT {
         let vk_code = VIRTUAL_KEY(wparam.0 as u16);
-
-        // Handle modifier keys first
-        let old_state = self.modifiers.clone();
-        let is_modifier = old_state != modifiers;
-
-        if is_modifier {
-            self.modifiers = modifiers.clone();
-            if !self.has_active_record {
-                self.activate_record();
-            }
-        } else {
-            self.finalize_record(vk_code);
+        // Modifiers, including their auto-repeat, only change the state the next key is pressed with.
+        // The state comes with every key, so one held before the capture started counts as well.
+        self.modifiers = modifiers;
+
+        if !ModifierHandler::is_modifier(vk_code) && self.held_key != Some(vk_code.0) {
+            log::debug!(target:"input_api", "Captured: ({:#x}, {})", vk_code.0, self.modifiers);
+            self.records.push(Combination { modifiers: self.modifiers, key: Some(vk_code.0) });
+            self.held_key = Some(vk_code.0);
         }
-
         LRESULT(0)
     }
 
-    pub fn on_keyup(&mut self, _wparam: WPARAM, modifiers: ModifierState) -> LRESULT {
-        self.modifiers = modifiers.clone();
+    pub fn on_keyup(&mut self, wparam: WPARAM, modifiers: ModifierState) -> LRESULT {
+        self.modifiers = modifiers;
+        if self.held_key == Some(wparam.0 as u16) {
+            self.held_key = None;
+        }
         LRESULT(0)
     }
 
-    fn has_captured_anything(&self) -> bool {
-        ! ( self.records.is_empty() && !self.has_active_record && self.modifiers.is_none() )
-    }
-
+    /// The steps recorded so far, followed by the modifiers held for the next one
     pub fn get_current_capture(&self) -> Vec<Combination> {
-
-        if ! self.has_captured_anything() {
-            return vec![]
+        let mut parts = self.records.clone();
+        if self.held_key.is_none() && !self.modifiers.is_none() {
+            parts.push(Combination { modifiers: self.modifiers, key: None });
         }
-
-        let mut parts = Vec::new();
-
-        // Determine how many records to show based on parity
-        let total_records = self.records.len() + if self.has_active_record { 1 } else { 0 };
-        if total_records > 0 {
-            let is_odd = total_records % 2 == 1;
-            // eprintln!("- act:{}, rec:{}, tot:{}, odd:{}", self.has_active_record, self.records.len(), total_records, is_odd );
-            if is_odd {
-                if self.has_active_record {
-                    parts.push(Combination{
-                        modifiers: self.modifiers.clone(),
-                        key: None,
-                    });
-                } else {
-                    if let Some(last) = self.records.last() {
-                        parts.push(last.clone());
-                    }
-                }
-            } else {
-                if ! self.has_active_record {
-                    // add last two terms
-                    if self.records.len() as i32 - 2 >= 0 {
-                        if let Some(second_last) = self.records.get(self.records.len() - 2) {
-                            parts.push(second_last.clone());
-                        }
-                    } else {
-                        log::error!("No second last record, but expected one");
-                    }
-                    if let Some(last) = self.records.last() {
-                        parts.push(last.clone());
-                    }
-                } else {
-                    if let Some(last) = self.records.last() {
-                        parts.push(last.clone());
-                    }
-                    parts.push(Combination{
-                        modifiers: self.modifiers.clone(),
-                        key: None,
-                    });
-                }
-            }
-        }
-
         parts
-
     }
 
     pub fn last_record(&self) -> Option<&Combination> {
@@ -286,8 +271,10 @@ impl KeyCombinationCapture {
         self.records.pop();
     }
 
+    /// Ends the capture, the modifiers still held are not a step
     pub fn deactivate_record(&mut self) {
-        self.has_active_record = false;
+        self.held_key = None;
+        self.modifiers = ModifierState::default();
     }
 
 }
@@ -392,4 +379,105 @@ impl DisplayFormats {
             DisplayFormats::InverseSpaced => &INVERSE_FORMAT_SPACED,
         }
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(capture: &mut KeyCombinationCapture, vk_code: VIRTUAL_KEY, modifiers: ModifierState) {
+        capture.on_keydown(WPARAM(vk_code.0 as usize), modifiers);
+    }
+
+    fn release(capture: &mut KeyCombinationCapture, vk_code: VIRTUAL_KEY, modifiers: ModifierState) {
+        capture.on_keyup(WPARAM(vk_code.0 as usize), modifiers);
+    }
+
+    #[test]
+    fn test_key_combination_capture() {
+        let ctrl = ModifierState { ctrl: true, ..Default::default() };
+        let none = ModifierState::default();
+        let ctrl_k = Combination { modifiers: ctrl, key: Some(VK_K.0) };
+        let mut capture = KeyCombinationCapture::new();
+        assert!(capture.get_current_capture().is_empty());
+
+        // The held modifiers show as the step being typed, a repeating key is recorded once
+        press(&mut capture, VK_CONTROL, ctrl);
+        assert_eq!(capture.get_current_capture(), vec![Combination { modifiers: ctrl, key: None }]);
+        press(&mut capture, VK_K, ctrl);
+        press(&mut capture, VK_CONTROL, ctrl);
+        press(&mut capture, VK_K, ctrl);
+        assert_eq!(capture.get_current_capture(), vec![ctrl_k.clone()]);
+        release(&mut capture, VK_K, ctrl);
+        release(&mut capture, VK_CONTROL, none);
+        assert_eq!(capture.get_current_capture(), vec![ctrl_k.clone()]);
+
+        // The steps accumulate, with the modifier held or pressed again
+        press(&mut capture, VK_CONTROL, ctrl);
+        press(&mut capture, VK_C, ctrl);
+        release(&mut capture, VK_C, ctrl);
+        press(&mut capture, VK_K, ctrl);
+        release(&mut capture, VK_K, ctrl);
+        release(&mut capture, VK_CONTROL, none);
+        press(&mut capture, VK_S, none);
+        assert_eq!(capture.get_current_capture(), vec![
+            ctrl_k.clone(),
+            Combination { modifiers: ctrl, key: Some(VK_C.0) },
+            ctrl_k.clone(),
+            Combination { modifiers: none, key: Some(VK_S.0) },
+        ]);
+
+        capture.remove_last_record();
+        press(&mut capture, VK_SHIFT, ModifierState { shift: true, ..Default::default() });
+        capture.deactivate_record();
+        assert_eq!(capture.get_current_capture().len(), 3);
+    }
+
+    #[test]
+    fn test_capture_with_modifier_held_before() {
+        let ctrl = ModifierState { ctrl: true, ..Default::default() };
+        let mut capture = KeyCombinationCapture::new();
+
+        // Ctrl went down before the capture started, the first key still counts
+        press(&mut capture, VK_K, ctrl);
+        assert_eq!(capture.get_current_capture(), vec![Combination { modifiers: ctrl, key: Some(VK_K.0) }]);
+
+        // As does the one right after the capture is ended with Ctrl still held
+        release(&mut capture, VK_K, ctrl);
+        capture.deactivate_record();
+        press(&mut capture, VK_C, ctrl);
+        assert_eq!(capture.last_record(), Some(&Combination { modifiers: ctrl, key: Some(VK_C.0) }));
+        assert_eq!(capture.get_current_capture().len(), 2);
+    }
+
+    #[test]
+    fn test_modifier_grace() {
+        // Without a grace period the press is resolved right away
+        let mut grace = ModifierGrace::new(0);
+        assert_eq!(grace.press(VK_NUMPAD7), Some(VK_NUMPAD7));
+        assert_eq!(grace.held(), None);
+
+        // Pad key first, the modifier within the grace period: the press waits for the modifier
+        let pressed = Instant::now();
+        let mut grace = ModifierGrace::new(50);
+        let mut modifiers = ModifierHandler::new(ModifierState::default());
+        assert_eq!(grace.press_at(VK_NUMPAD7, pressed), None);
+        assert_eq!(grace.held(), Some(&VK_NUMPAD7));
+        assert!(!grace.expired(pressed + Duration::from_millis(30)));
+
+        assert!(modifiers.handle_key_press(VK_LSHIFT));
+        assert_eq!(grace.release(), Some(VK_NUMPAD7));
+        assert!(modifiers.state().shift);
+        assert_eq!(grace.release(), None);
+        assert!(!grace.expired(pressed + Duration::from_millis(80)));
+
+        // The modifier after the grace period: the press is resolved before it, without the modifier
+        let mut modifiers = ModifierHandler::new(ModifierState::default());
+        assert_eq!(grace.press_at(VK_NUMPAD7, pressed), None);
+        assert!(grace.expired(pressed + Duration::from_millis(80)));
+        assert_eq!(grace.release(), Some(VK_NUMPAD7));
+        assert!(!modifiers.state().shift);
+        assert!(modifiers.handle_key_press(VK_LSHIFT));
+    }
+}
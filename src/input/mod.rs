@@ -8,4 +8,5 @@ pub mod capture;
 pub use keys::ModifierState;
 pub use capture::TextCapture;
 pub use capture::ModifierHandler;
+pub use capture::ModifierGrace;
 pub use capture::KeyCombinationCapture;
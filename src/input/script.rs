@@ -1,7 +1,7 @@
 use super::{
     steps::*,
     keys::{
-        vkey::{VK_SHIFT, VK_ENTER, VK_ALT, VK_CTRL, find_vkey_by_text},
+        vkey::{VK_SHIFT, VK_ENTER, VK_TAB, VK_ALT, VK_CTRL, VK_LWIN, VK_RWIN, VK_LARROW, find_vkey_by_text},
         ModifierState
     }
 };
@@ -23,6 +23,7 @@ enum Token {
     WORD(String),
 }
 
+#[derive(Clone, Debug, PartialEq)]
 struct KeyCombination {
     keys: Vec<u16>,
 }
@@ -35,6 +36,34 @@ impl Default for KeyCombination {
 
 use Token::*;
 
+/// Keys of a shortcut, one combination after another, parsed from its text or built from stored key codes
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct KeySequence {
+    combinations: Vec<KeyCombination>,
+}
+
+impl KeySequence {
+    pub fn parse(text: &str) -> Self {
+        Self { combinations: parse_shortcut_expression(text) }
+    }
+
+    /// Each combination presses its modifiers (Ctrl, Shift, Alt, Win) before the key
+    pub fn from_combinations<I: IntoIterator<Item = (ModifierState, u16)>>(combinations: I) -> Self {
+        let combinations = combinations.into_iter()
+            .map(|(modifiers, key)| {
+                let mut keys = vec![];
+                if modifiers.ctrl { keys.push(VK_CTRL.vkey); }
+                if modifiers.shift { keys.push(VK_SHIFT.vkey); }
+                if modifiers.alt { keys.push(VK_ALT.vkey); }
+                if modifiers.super_key { keys.push(VK_LWIN.vkey); }
+                keys.push(key);
+                KeyCombination { keys }
+            })
+            .collect();
+        Self { combinations }
+    }
+}
+
 fn scan_shortcut_expression(text: &str) -> Vec<Token> {
     let txt = text.to_owned()
                 .replace("'+'", "_PLUS_")
@@ -104,16 +133,123 @@ fn parse_shortcut_expression(text: &str) -> Vec<KeyCombination> {
     })
 }
 
+/// Parses a single key combination such as "Ctrl Alt NumPad1" (or "Ctrl+Alt+NumPad1") into its
+/// modifier state and main key. Unknown keys and combinations without exactly one non-modifier key are rejected.
+pub fn parse_hotkey(text: &str) -> Option<(ModifierState, u16)> {
+    parse_combination(&scan_shortcut_expression(text.to_lowercase().as_str()))
+}
+
+/// Parses a shortcut such as "Ctrl K + Ctrl B" into the modifier state and main key of each combination,
+/// None unless every combination has exactly one non-modifier key
+pub fn parse_shortcut_keys(text: &str) -> Option<Vec<(ModifierState, u16)>> {
+    scan_shortcut_expression(text.to_lowercase().as_str())
+        .split(|token| matches!(token, PLUS))
+        .map(parse_combination)
+        .collect()
+}
+
+fn parse_combination(tokens: &[Token]) -> Option<(ModifierState, u16)> {
+    let mut modifiers = ModifierState::default();
+    let mut key = None;
+
+    for token in tokens {
+        let vk_code = match token {
+            WORD(text) => find_vkey_by_text(text.clone())?.vkey,
+            CHAR(text) | QUOTED(text) => {
+                let char = text.chars().next()?;
+                match super::keys::keyboard_api::char_to_vkey(char)? {
+                    (vk_code, char_modifiers) if char_modifiers.is_none() => vk_code,
+                    _ => return None,
+                }
+            },
+            PLUS => continue,
+        };
+
+        match vk_code {
+            vk if vk == VK_CTRL.vkey => modifiers.ctrl = true,
+            vk if vk == VK_SHIFT.vkey => modifiers.shift = true,
+            vk if vk == VK_ALT.vkey => modifiers.alt = true,
+            vk if vk == VK_LWIN.vkey || vk == VK_RWIN.vkey => modifiers.super_key = true,
+            vk => {
+                if key.replace(vk).is_some() {
+                    return None;
+                }
+            }
+        }
+    }
+
+    key.map(|key| (modifiers, key))
+}
+
+/// Parses a modifier-only combination such as "Ctrl+Alt", None when it has no modifier or any other key
+pub fn parse_modifiers(text: &str) -> Option<ModifierState> {
+    let mut modifiers = ModifierState::default();
+
+    for token in scan_shortcut_expression(text.to_lowercase().as_str()) {
+        let vk_code = match token {
+            WORD(text) => find_vkey_by_text(text)?.vkey,
+            PLUS => continue,
+            _ => return None,
+        };
+
+        match vk_code {
+            vk if vk == VK_CTRL.vkey => modifiers.ctrl = true,
+            vk if vk == VK_SHIFT.vkey => modifiers.shift = true,
+            vk if vk == VK_ALT.vkey => modifiers.alt = true,
+            vk if vk == VK_LWIN.vkey || vk == VK_RWIN.vkey => modifiers.super_key = true,
+            _ => return None,
+        }
+    }
+
+    modifiers.is_any().then_some(modifiers)
+}
+
 pub fn for_shortcut(text: String) -> InputScript {
     log::debug!(target:"input_api", "Shortcut: {}",  text);
+    for_keys(&KeySequence::parse(&text))
+}
+
+pub fn for_keys(keys: &KeySequence) -> InputScript {
+    let mut steps = vec![];
+    for cmb in &keys.combinations {
+        steps.append(&mut cmb.keys.iter().map(
+            |key| Box::new(map_vk_code(*key, true)) as Box<dyn InputStep>).collect());
+        steps.append(&mut cmb.keys.iter().rev().map(
+            |key| Box::new(map_vk_code(*key, false)) as Box<dyn InputStep>).collect());
+    }
+
+    InputScript { steps }
+}
+
+/// Presses a shortcut without releasing it. Leading chord parts are typed as usual,
+/// the keys of the last combination stay down until [`for_keys_release`] is played.
+pub fn for_keys_press(keys: &KeySequence) -> InputScript {
+    log::debug!(target:"input_api", "Shortcut press: {:?}",  keys);
+
+    let Some((last, combinations)) = keys.combinations.split_last() else {
+        return InputScript { steps: vec![] };
+    };
 
     let mut steps = vec![];
-    for cmb in parse_shortcut_expression(text.as_str()) {
+    for cmb in combinations {
         steps.append(&mut cmb.keys.iter().map(
             |key| Box::new(map_vk_code(*key, true)) as Box<dyn InputStep>).collect());
         steps.append(&mut cmb.keys.iter().rev().map(
             |key| Box::new(map_vk_code(*key, false)) as Box<dyn InputStep>).collect());
     }
+    steps.append(&mut last.keys.iter().map(
+        |key| Box::new(map_vk_code(*key, true)) as Box<dyn InputStep>).collect());
+
+    InputScript { steps }
+}
+
+/// Releases the keys left down by [`for_keys_press`], in reverse order
+pub fn for_keys_release(keys: &KeySequence) -> InputScript {
+    log::debug!(target:"input_api", "Shortcut release: {:?}",  keys);
+
+    let last = keys.combinations.last().cloned().unwrap_or_default();
+    let steps = last.keys.iter().rev().map(
+        |key| Box::new(map_vk_code(*key, false)) as Box<dyn InputStep>).collect();
 
     InputScript { steps }
 }
@@ -135,6 +271,44 @@ pub fn for_line(text: String) -> InputScript {
     for_text_or_line(text, true)
 }
 
+/// Types `before` and `after` like [`for_text`], then presses Left once per character typed after the caret
+pub fn for_snippet(before: &str, after: &str) -> InputScript {
+    log::debug!(target:"input_api", "Snippet: {}|{}",  before, after);
+
+    let mut script = for_text_or_line(format!("{}{}", before, after), false);
+    // A line break is a single caret position, also when typed as \r\n
+    let moves = after.replace("\r\n", "\n").chars()
+        .filter(|ch| super::keys::keyboard_api::char_to_vkey(*ch).is_some())
+        .count();
+    for _ in 0..moves {
+        let inputs = map_character_key(VK_LARROW.vkey, &ModifierState::default());
+        script.steps.push(Box::new(KeyInputs { inputs }) as Box<dyn InputStep>);
+    }
+    script
+}
+
+/// Types any text, including characters missing from the keyboard layout, as Unicode input.
+/// Line breaks and tabs are sent as Enter and Tab keys, `delay` pauses between characters (ms).
+pub fn for_unicode_text(text: String, delay: u64) -> InputScript {
+    log::debug!(target:"input_api", "Unicode text: {} chars", text.chars().count());
+
+    let mut steps = vec![];
+    for ch in text.replace("\r\n", "\n").chars() {
+        if delay > 0 && !steps.is_empty() {
+            steps.push(Box::new(NoInput { pause: delay }) as Box<dyn InputStep>);
+        }
+
+        let step = match ch {
+            '\n' | '\r' => Box::new(KeyInputs { inputs: map_character_key(VK_ENTER.vkey, &ModifierState::default()) }) as Box<dyn InputStep>,
+            '\t' => Box::new(KeyInputs { inputs: map_character_key(VK_TAB.vkey, &ModifierState::default()) }) as Box<dyn InputStep>,
+            ch => Box::new(UnicodeInput { units: ch.encode_utf16(&mut [0; 2]).to_vec() }) as Box<dyn InputStep>,
+        };
+        steps.push(step);
+    }
+
+    InputScript { steps }
+}
+
 fn for_text_or_line(text: String, new_line: bool) -> InputScript {
     let mut steps = vec![];
 
@@ -209,6 +383,18 @@ mod tests {
         assert_eq!(script.steps[5].as_any().downcast_ref::<KeyInput>().unwrap(), &KeyInput { vk_code: VK_CTRL.vkey, key_down: false });
     }
 
+    #[test]
+    fn test_snippet() {
+        let script = for_snippet("f(", ");");
+
+        // 4 typed characters, then the caret moved back over ");"
+        assert_eq!(script.steps.len(), 4 + 2);
+        let left = KeyInputs { inputs: map_character_key(VK_LARROW.vkey, &ModifierState::default()) };
+        for step in &script.steps[4..] {
+            assert_eq!(step.as_any().downcast_ref::<KeyInputs>().unwrap().inputs, left.inputs);
+        }
+    }
+
     #[test]
     fn test_shortcurt_chord() {
         let script = for_shortcut("Ctrl K + Ctrl B".to_string());
@@ -226,6 +412,40 @@ mod tests {
         assert_eq!(script.steps[7].as_any().downcast_ref::<KeyInput>().unwrap(), &KeyInput { vk_code: VK_CTRL.vkey, key_down: false });
     }
 
+    #[test]
+    fn test_shortcut_press_release() {
+        let keys = KeySequence::parse("Ctrl K + Ctrl Shift");
+        let press = for_keys_press(&keys);
+        assert_eq!(press.steps.len(), 6);
+        assert_eq!(press.steps[3].as_any().downcast_ref::<KeyInput>().unwrap(), &KeyInput { vk_code: VK_CTRL.vkey, key_down: false });
+        assert_eq!(press.steps[4].as_any().downcast_ref::<KeyInput>().unwrap(), &KeyInput { vk_code: VK_CTRL.vkey, key_down: true });
+        assert_eq!(press.steps[5].as_any().downcast_ref::<KeyInput>().unwrap(), &KeyInput { vk_code: VK_SHIFT.vkey, key_down: true });
+
+        let release = for_keys_release(&keys);
+        assert_eq!(release.steps.len(), 2);
+        assert_eq!(release.steps[0].as_any().downcast_ref::<KeyInput>().unwrap(), &KeyInput { vk_code: VK_SHIFT.vkey, key_down: false });
+        assert_eq!(release.steps[1].as_any().downcast_ref::<KeyInput>().unwrap(), &KeyInput { vk_code: VK_CTRL.vkey, key_down: false });
+
+        assert!(for_keys_press(&KeySequence::parse("")).steps.is_empty());
+        assert!(for_keys_release(&KeySequence::parse("")).steps.is_empty());
+    }
+
+    #[test]
+    fn test_shortcut_keys() {
+        let keys = parse_shortcut_keys("Ctrl K + Shift Alt B").unwrap();
+        assert_eq!(keys, vec![
+            (ModifierState { ctrl: true, ..Default::default() }, VK_K.0),
+            (ModifierState { shift: true, alt: true, ..Default::default() }, VK_B.0),
+        ]);
+        assert_eq!(KeySequence::from_combinations(keys), KeySequence::parse("Ctrl K + Shift Alt B"));
+
+        assert!(parse_shortcut_keys("").is_none());
+        assert!(parse_shortcut_keys("Shift").is_none());
+        assert!(parse_shortcut_keys("Ctrl K B").is_none());
+        assert!(parse_shortcut_keys("Ctrl K +").is_none());
+        assert!(parse_shortcut_keys("Ctrl Foo").is_none());
+    }
+
     #[test]
     fn test_text() {
         let script = for_text("abK".to_string());
@@ -246,6 +466,28 @@ mod tests {
         assert_eq!(script.steps[2].as_any().downcast_ref::<KeyInputs>().unwrap().inputs[3], KeyInput { vk_code: VK_SHIFT.vkey, key_down: false });
     }
 
+    #[test]
+    fn test_unicode_text() {
+        let script = for_unicode_text("é😀\r\nx\ty".to_string(), 0);
+
+        assert_eq!(script.steps.len(), 6);
+        assert_eq!(script.steps[0].as_any().downcast_ref::<UnicodeInput>().unwrap().units, vec![0xE9]);
+        assert_eq!(script.steps[1].as_any().downcast_ref::<UnicodeInput>().unwrap().units, vec![0xD83D, 0xDE00]);
+        assert_eq!(script.steps[2].as_any().downcast_ref::<KeyInputs>().unwrap().inputs[0], KeyInput { vk_code: VK_ENTER.vkey, key_down: true });
+        assert_eq!(script.steps[3].as_any().downcast_ref::<UnicodeInput>().unwrap().units, vec!['x' as u16]);
+        assert_eq!(script.steps[4].as_any().downcast_ref::<KeyInputs>().unwrap().inputs[0], KeyInput { vk_code: VK_TAB.vkey, key_down: true });
+    }
+
+    #[test]
+    fn test_unicode_text_delay() {
+        let script = for_unicode_text("abc".to_string(), 20);
+
+        assert_eq!(script.steps.len(), 5);
+        assert_eq!(script.steps[1].as_any().downcast_ref::<NoInput>().unwrap(), &NoInput { pause: 20 });
+        assert_eq!(script.steps[3].as_any().downcast_ref::<NoInput>().unwrap(), &NoInput { pause: 20 });
+        assert!(for_unicode_text(String::new(), 20).steps.is_empty());
+    }
+
     #[test]
     fn test_scan_basic_tokens() {
         let tokens = scan_shortcut_expression("ctrl a");
@@ -399,6 +641,25 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_parse_hotkey() {
+        assert_eq!(parse_hotkey("Ctrl Alt NumPad1"), Some((ModifierState { ctrl: true, alt: true, ..Default::default() }, VK_NUMPAD1.0)));
+        assert_eq!(parse_hotkey("Shift+F5"), Some((ModifierState { shift: true, ..Default::default() }, VK_F5.0)));
+        assert_eq!(parse_hotkey("Ctrl K + Ctrl B"), None);
+        assert_eq!(parse_hotkey("Ctrl Shift"), None);
+        assert_eq!(parse_hotkey("Ctrl A B"), None);
+        assert_eq!(parse_hotkey("Ctrl Unknown"), None);
+        assert_eq!(parse_hotkey(""), None);
+    }
+
+    #[test]
+    fn test_parse_modifiers() {
+        assert_eq!(parse_modifiers("Shift"), Some(ModifierState { shift: true, ..Default::default() }));
+        assert_eq!(parse_modifiers("Ctrl+Alt"), Some(ModifierState { ctrl: true, alt: true, ..Default::default() }));
+        assert_eq!(parse_modifiers("Ctrl A"), None);
+        assert_eq!(parse_modifiers(""), None);
+    }
+
     #[test]
     fn test_map_character_key_with_shift() {
         let inputs = map_character_key(VK_A.0, &ModifierState { shift: true, ..Default::default() });
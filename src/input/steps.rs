@@ -21,6 +21,12 @@ pub struct NoInput {
     pub pause: u64
 }
 
+/// A character typed as UTF-16 code units rather than through a virtual key
+#[derive(Debug, PartialEq)]
+pub struct UnicodeInput {
+    pub units: Vec<u16>
+}
+
 pub trait InputStep {
     fn play(&self);
     #[allow(dead_code)]
@@ -63,6 +69,15 @@ impl InputStep for KeyInputs {
     }
 }
 
+impl InputStep for UnicodeInput {
+    fn play(&self) {
+        api::send_unicode(&self.units);
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
 fn map_api_input(input: &KeyInput) -> api::KeyboardInput {
     api::KeyboardInput {
         vk_code: input.vk_code,
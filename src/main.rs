@@ -10,20 +10,27 @@ mod model;
 mod components;
 mod settings;
 
-use crate::app::{Application, ActionFactoryRegistry, BoardFactoryRegistry};
-use crate::settings::Settings;
+use crate::app::{init_logging, Application, ActionFactoryRegistry, BoardFactoryRegistry};
+use crate::settings::{export_all, import_all, BundleSummary, Settings};
 use crate::framework::{set_app_handler};
-use crate::ui::components::{svg::ICON_CACHE, png::PNG_CACHE};
-use crate::core::{Param, Resources};
+use crate::ui::components::{initialize_icon_caches, svg::ICON_CACHE, png::PNG_CACHE};
+use crate::core::{ImportPolicy, Param, Resources, SettingsRepository};
 
 use windows::core::{Result, Error};
-use std::{env, path::PathBuf, process::Command};
+use windows::Win32::UI::HiDpi::{SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2};
+use std::{env, path::{Path, PathBuf}, process::Command};
 
 #[derive(Debug)]
 struct Args {
     config_dir: Option<String>,
     board: Option<String>,
     params: Vec<Param>,
+    safe_mode: bool,
+    quiet: bool,
+    export_all: Option<String>,
+    import_all: Option<String>,
+    import_policy: ImportPolicy,
+    validate: bool,
 }
 
 fn parse_args() -> Args {
@@ -32,6 +39,12 @@ fn parse_args() -> Args {
     let mut config_dir: Option<String> = None;
     let mut board: Option<String> = None;
     let mut params: Vec<Param> = Vec::new();
+    let mut safe_mode = true;
+    let mut quiet = false;
+    let mut export_all: Option<String> = None;
+    let mut import_all: Option<String> = None;
+    let mut import_policy = ImportPolicy::default();
+    let mut validate = false;
     let mut i = 1;
     let mut parsing_params = false;
 
@@ -57,6 +70,48 @@ fn parse_args() -> Args {
                     std::process::exit(1);
                 }
             },
+            "--export-all" => {
+                if i + 1 < args.len() {
+                    export_all = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("ERROR: --export-all requires a value");
+                    std::process::exit(1);
+                }
+            },
+            "--import-all" => {
+                if i + 1 < args.len() {
+                    import_all = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("ERROR: --import-all requires a value");
+                    std::process::exit(1);
+                }
+            },
+            "--import-policy" => {
+                match args.get(i + 1).and_then(|value| ImportPolicy::parse(value)) {
+                    Some(policy) => {
+                        import_policy = policy;
+                        i += 2;
+                    },
+                    None => {
+                        eprintln!("ERROR: --import-policy requires one of rename, overwrite, skip");
+                        std::process::exit(1);
+                    }
+                }
+            },
+            "--no-safe-mode" => {
+                safe_mode = false;
+                i += 1;
+            },
+            "--quiet" => {
+                quiet = true;
+                i += 1;
+            },
+            "--validate" => {
+                validate = true;
+                i += 1;
+            },
             "--" => {
                 parsing_params = true;
                 i += 1;
@@ -84,7 +139,7 @@ fn parse_args() -> Args {
             }
         }
     }
-    Args { config_dir, board, params }
+    Args { config_dir, board, params, safe_mode, quiet, export_all, import_all, import_policy, validate }
 }
 
 
@@ -135,32 +190,104 @@ pub fn get_resource_path(config_dir: Option<PathBuf>) -> PathBuf {
     })
 }
 
-fn initialize_icon_caches(resources: &Resources) {
-    ICON_CACHE.with(|cache| {
-        cache.borrow_mut().initialize(resources.clone());
-    });
-    PNG_CACHE.with(|cache| {
-        cache.borrow_mut().initialize(resources.clone());
-    });
+/// Prints the outcome of `--export-all` / `--import-all` and exits, the application is not started
+fn exit_after_bundle(operation: &str, result: std::result::Result<BundleSummary, Box<dyn std::error::Error>>) -> ! {
+    match result {
+        Ok(summary) => {
+            println!("{} {} boards and {} icons ({} renamed)", operation, summary.boards, summary.icons, summary.renamed_icons);
+            for outcome in &summary.outcomes {
+                println!("  icon {}", outcome);
+            }
+            std::process::exit(0);
+        },
+        Err(e) => {
+            eprintln!("ERROR: {} failed: {}", operation, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the problems `--validate` finds in the settings and exits, nonzero when there are any.
+/// All dangling references are listed, the remaining checks stop at the first problem as they do on startup.
+fn exit_after_validation(resources: &Resources) -> ! {
+    let settings = match Settings::load_unchecked(resources.clone()) {
+        Ok(settings) => settings,
+        Err(e) => {
+            println!("Settings not loaded: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let problems = settings.dangling_references();
+    if !problems.is_empty() {
+        println!("{} dangling references:", problems.len());
+        for problem in &problems {
+            println!("  {}", problem);
+        }
+        std::process::exit(1);
+    }
+
+    match Settings::load(resources.clone()) {
+        Ok(settings) => {
+            println!("Settings are valid: {} boards, {} padsets", settings.boards().len(), settings.padsets().len());
+            std::process::exit(0);
+        },
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn run() -> Result<()> {
     let args = parse_args();
+
+    // The import target doesn't have to exist yet
+    if let Some(zip_path) = &args.import_all {
+        let config_dir = args.config_dir.clone().map(PathBuf::from).unwrap_or_else(|| get_resource_path(None));
+        exit_after_bundle("Imported", import_all(Path::new(zip_path), &config_dir, args.import_policy));
+    }
+
     let resources = Resources::new(vec![get_resource_path(args.config_dir.clone().map(PathBuf::from))]);
 
+    if let Some(zip_path) = &args.export_all {
+        exit_after_bundle("Exported", export_all(&resources, Path::new(zip_path)));
+    }
+
+    // Checked before anything is set up, no window is created and no hooks registered
+    if args.validate {
+        exit_after_validation(&resources);
+    }
+
     // Initialize icon caches with resources
     initialize_icon_caches(&resources);
 
-    log4rs::init_file(resources.log_toml().unwrap(), Default::default()).expect("Log init error");
-    log::warn!("Starting HotKeys");
+    // Runs without a log when even the built-in configuration can't be set up
+    if let Err(e) = init_logging(resources.log_toml().as_deref(), None) {
+        eprintln!("Logging not initialized: {}", e);
+    }
+    // Launchers starting the application with the session don't need it in the log every time
+    let lifecycle_level = if args.quiet { log::Level::Debug } else { log::Level::Warn };
+    log::log!(lifecycle_level, "Starting HotKeys");
     log::info!("Args: {:?}", args);
 
-    let settings = Settings::load(resources.clone())
-        .map_err(|e| {
+    // Boards scale themselves to the DPI of their monitor, the dialogs are scaled by Windows (see DpiUnawareScope)
+    if let Err(e) = unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) } {
+        log::warn!("Per-monitor DPI awareness not set, boards are scaled by Windows: {}", e);
+    }
+
+    let settings = match Settings::load(resources.clone()) {
+        Ok(settings) => settings,
+        Err(e) if args.safe_mode => {
+            log::error!("Failed to load settings, starting in safe mode: {}", e);
+            Settings::safe_mode(resources.clone(), e.to_string())
+        },
+        Err(e) => {
             log::error!("Failed to load settings: {}", e);
             eprintln!("Error: Failed to load settings: {}", e);
-            Error::from_hresult(windows::Win32::Foundation::E_FAIL)
-        })?;
+            return Err(Error::from_hresult(windows::Win32::Foundation::E_FAIL));
+        }
+    };
 
     #[allow(unused_mut)]
     let mut action_factory_registry = ActionFactoryRegistry::<Settings>::new();
@@ -173,7 +300,7 @@ fn run() -> Result<()> {
 
     let mut app = Application::create(settings, action_factory_registry, board_factory_registry);
     set_app_handler::<Application>(&mut app);
-    app.run(args.board.clone(), args.params.clone())?;
+    app.run(args.board.clone(), args.params.clone(), args.quiet)?;
 
     // Check if restart was requested
     if let Some(restart_board) = app.restart_info().clone() {
@@ -184,7 +311,7 @@ fn run() -> Result<()> {
     ICON_CACHE.with(|cache| cache.borrow_mut().clear());
     PNG_CACHE.with(|cache| cache.borrow().clear());
 
-    log::warn!("Exiting HotKeys");
+    log::log!(lifecycle_level, "Exiting HotKeys");
     Ok(())
 }
 
@@ -199,6 +326,10 @@ fn restart_with_board(restart_board: Option<String>, original_args: &Args) {
         new_args.push(config_dir.clone());
     }
 
+    if !original_args.safe_mode {
+        new_args.push("--no-safe-mode".to_string());
+    }
+
     // Add new --board argument if specified
     if let Some(board_name) = restart_board {
         new_args.push("--board".to_string());
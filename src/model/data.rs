@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
 use windows::Win32::Foundation::{COLORREF, RECT};
-use windows::Win32::Graphics::Gdi::{CreateFontW, CLEARTYPE_QUALITY, CLIP_DEFAULT_PRECIS, DEFAULT_CHARSET, DRAW_TEXT_FORMAT, DT_CENTER, DT_LEFT, DT_RIGHT, FW_BOLD, FW_NORMAL, HFONT, OUT_DEVICE_PRECIS};
+use windows::Win32::Graphics::Gdi::{CreateFontW, CLEARTYPE_QUALITY, CLIP_DEFAULT_PRECIS, DEFAULT_CHARSET, DRAW_TEXT_FORMAT, DT_CENTER, DT_LEFT, DT_RIGHT, FW_BOLD, FW_NORMAL, HBRUSH, HFONT, OUT_DEVICE_PRECIS};
 
-pub use crate::core::data::{ColorScheme, TextStyle};
+pub use crate::core::data::{ColorScheme, TextStyle, WindowSizing};
 pub use crate::input::ModifierState;
 
 use crate::core;
+use crate::input::capture::{Combination, DisplayFormatable, DisplayFormats};
 use crate::ui::components::assets::Assets;
 
 
@@ -42,6 +43,11 @@ impl PadId {
         *self as i32
     }
 
+    /// Letter of the pad when pads are labeled a-i, read top-down like the grid
+    pub fn as_letter(&self) -> char {
+        (b'a' + ((2 - self.row()) * 3 + self.col()) as u8) as char
+    }
+
     pub fn row(&self) -> i32 {
         (self.as_keypad_int() - 1) / 3
     }
@@ -65,6 +71,16 @@ impl PadId {
         }
     }
 
+    /// Pads top-left to bottom-right, the order lists of items are laid out on a board in.
+    /// Every board paging through items uses it, so they all read like the board list.
+    pub const fn reading_order() -> [PadId; 9] {
+        [
+            PadId::Seven, PadId::Eight, PadId::Nine,
+            PadId::Four, PadId::Five, PadId::Six,
+            PadId::One, PadId::Two, PadId::Three,
+        ]
+    }
+
     pub fn all() -> Vec<PadId> {
         vec![
             PadId::One, PadId::Two, PadId::Three,
@@ -197,6 +213,9 @@ pub struct Tag {
     pub pin: Option<AnchorPin>,
     pub color_idx: Option<usize>,
     pub font_idx: Option<usize>,
+    pub accent: bool,
+    pub background: Option<usize>, // palette color of a chip drawn behind the text, transparent when None
+    pub key_caps: bool, // text is a shortcut, drawn as a row of key caps
 }
 
 impl Tag {
@@ -210,6 +229,9 @@ impl Tag {
     }
 
     pub fn get_color(&self, assets: &Assets) -> COLORREF {
+        if self.accent {
+            return assets.accent_color();
+        }
         if let Some(index) = self.color_idx {
             if let Some(color) = assets.palette_color(index) {
                 return color;
@@ -218,6 +240,11 @@ impl Tag {
         assets.tag_color()
     }
 
+    /// Brush filling the chip behind the text, None for a transparent tag or a palette index out of range
+    pub fn get_background(&self, assets: &Assets) -> Option<HBRUSH> {
+        self.background.and_then(|index| assets.palette_brush(index))
+    }
+
     pub fn get_effective_handle(&self) -> AnchorPin {
         self.pin.unwrap_or_else(|| {
             AnchorPin::default_for_anchor_point(&self.anchor)
@@ -225,6 +252,26 @@ impl Tag {
     }
 }
 
+/// Keys of a shortcut such as "Ctrl K + B" as labeled on key caps, one list per combination
+pub fn shortcut_key_caps(shortcut: &str) -> Vec<Vec<String>> {
+    shortcut.replace("'+'", "\u{0}")
+        .split('+')
+        .map(|combination| combination.split_whitespace().map(key_cap_label).collect::<Vec<_>>())
+        .filter(|keys| !keys.is_empty())
+        .collect()
+}
+
+/// Quoted characters lose their quotes, key names start uppercase
+fn key_cap_label(key: &str) -> String {
+    let key = key.replace('\u{0}', "+");
+    let key = match key.chars().collect::<Vec<_>>().as_slice() {
+        ['\'', c, '\''] => c.to_string(),
+        _ => key,
+    };
+    let mut chars = key.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
 impl Default for Tag {
     fn default() -> Self {
         Tag {
@@ -233,6 +280,9 @@ impl Default for Tag {
             pin: None,
             color_idx: None,
             font_idx: None,
+            accent: false,
+            background: None,
+            key_caps: false,
         }
     }
 }
@@ -309,6 +359,46 @@ impl Pad {
         &self.data.board_params
     }
 
+    /// Prompt to confirm before the pad's actions run, empty prompts are ignored
+    pub fn confirm(&self) -> Option<String> {
+        self.data.confirm.clone().filter(|prompt| !prompt.trim().is_empty())
+    }
+
+    pub fn behavior(&self) -> Option<core::PadBehavior> {
+        self.data.behavior
+    }
+
+    pub fn on_release(&self) -> bool {
+        self.data.on_release
+    }
+
+    /// Feedback delay in milliseconds set on the pad, None for the global one
+    pub fn feedback(&self) -> Option<u64> {
+        self.data.feedback
+    }
+
+    /// Time after the pad ran before it can run again, None for pads without a cooldown
+    pub fn cooldown(&self) -> Option<std::time::Duration> {
+        self.data.cooldown.filter(|ms| *ms > 0).map(std::time::Duration::from_millis)
+    }
+
+    /// Times the actions run in a row, 1 when not set
+    pub fn repeat(&self) -> u32 {
+        self.data.repeat.unwrap_or(1)
+    }
+
+    pub fn vertical_align(&self) -> core::VerticalAlign {
+        self.data.vertical_align.unwrap_or_default()
+    }
+
+    pub fn shrink_text(&self) -> bool {
+        self.data.shrink_text
+    }
+
+    pub fn is_label(&self) -> bool {
+        self.data.label
+    }
+
     pub fn tags(&self) -> &Vec<Tag> {
         &self.tags
     }
@@ -352,6 +442,14 @@ impl Pad {
     }
 }
 
+/// A pad of a stored board, in the padset of the given modifiers
+#[derive(Debug, Clone, PartialEq)]
+pub struct PadSource {
+    pub board: String,
+    pub modifiers: ModifierState,
+    pub pad_id: PadId,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Color {
     pub r: u8,
@@ -387,6 +485,22 @@ impl Color {
         (self.r, self.g, self.b)
     }
 
+    /// Relative luminance as defined by WCAG 2, 0 for black and 1 for white
+    pub fn luminance(&self) -> f64 {
+        let linear = |channel: u8| {
+            let c = channel as f64 / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        let (r, g, b) = self.to_rgb();
+        0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+    }
+
+    /// WCAG contrast ratio, from 1 for colors of the same luminance to 21 for black on white
+    pub fn contrast_ratio(&self, other: &Self) -> f64 {
+        let (l1, l2) = (self.luminance(), other.luminance());
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+
     pub fn to_colorref(self) -> COLORREF {
         COLORREF(
             ((self.b as u32) << 16) + ((self.g as u32) << 8) + (self.r as u32)
@@ -427,6 +541,22 @@ impl ColorScheme {
         self.to_color(&self.background, "#00007f")
     }
 
+    /// The bottom color of a gradient background, None for a flat one
+    pub fn background2(&self) -> Option<Color> {
+        self.background2.as_deref().and_then(Color::from_hex)
+    }
+
+    /// Background color of pixel row `y` out of `height`, blended from background to background2 top to bottom
+    pub fn background_at(&self, y: i32, height: i32) -> Color {
+        let top = self.background();
+        let Some(bottom) = self.background2() else {
+            return top;
+        };
+        let t = if height > 1 { y.clamp(0, height - 1) as f64 / (height - 1) as f64 } else { 0.0 };
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color { r: mix(top.r, bottom.r), g: mix(top.g, bottom.g), b: mix(top.b, bottom.b) }
+    }
+
     pub fn foreground1(&self) -> Color {
         self.to_color(&self.foreground1, "#5454a9")
     }
@@ -439,14 +569,24 @@ impl ColorScheme {
         self.to_color(&self.tag_foreground, "#ff0000")
     }
 
+    /// Schemes without an accent keep highlighting with palette 0, or the tag color
+    pub fn accent(&self) -> Color {
+        self.accent.as_deref()
+            .and_then(Color::from_hex)
+            .or_else(|| self.palette_color(0))
+            .unwrap_or_else(|| self.tag_foreground())
+    }
+
     pub fn inverted(&self) -> ColorScheme {
         ColorScheme {
             name: format!("{} (inverted)", self.name),
             opacity: self.opacity,
             background: self.background().inverted().to_hex(),
+            background2: self.background2().map(|color| color.inverted().to_hex()),
             foreground1: self.foreground1().inverted().to_hex(),
             foreground2: self.foreground2().inverted().to_hex(),
             tag_foreground: self.tag_foreground().inverted().to_hex(),
+            accent: self.accent.as_ref().map(|_| self.accent().inverted().to_hex()),
             palette: self.palette.clone().into_iter().map(|c| {
                 let color = self.to_color(&c, "#ff0000");
                 color.inverted().to_hex()
@@ -454,6 +594,16 @@ impl ColorScheme {
         }
     }
 
+    /// Unnamed copy of the scheme with a flat `background`, for a pad colored on its own
+    pub fn with_background(&self, background: &str) -> ColorScheme {
+        ColorScheme {
+            name: String::new(),
+            background: background.to_string(),
+            background2: None,
+            ..self.clone()
+        }
+    }
+
     pub fn to_color(&self, value: &String, default: &str) -> Color {
         Color::from_hex_or(value.as_str(), default).unwrap()
     }
@@ -485,8 +635,9 @@ impl ColorScheme {
 
 
 impl TextStyle {
+    /// Splits on ASCII spaces only, so face names with other whitespace (e.g. the ideographic space) survive intact
     pub fn parse_font(font_str: &str) -> (String, bool, bool, i32) {
-        let parts: Vec<&str> = font_str.split_whitespace().collect();
+        let parts: Vec<&str> = font_str.split_ascii_whitespace().collect();
         if parts.is_empty() {
             return ("Arial".to_string(), false, false, 12);
         }
@@ -516,8 +667,27 @@ impl TextStyle {
         (face, bold, italic, size)
     }
 
+    /// Inverse of [`TextStyle::parse_font`], e.g. "Consolas Bold 14"
+    pub fn format_font(face: &str, bold: bool, italic: bool, size: i32) -> String {
+        let mut parts = vec![face.to_string()];
+        if bold {
+            parts.push("Bold".to_string());
+        }
+        if italic {
+            parts.push("Italic".to_string());
+        }
+        parts.push(size.to_string());
+        parts.join(" ")
+    }
+
     pub fn create_font(&self, font_str: &str) -> HFONT {
+        self.create_scaled_font(font_str, 1.0)
+    }
+
+    /// Creates the font with its size multiplied by the board zoom factor
+    pub fn create_scaled_font(&self, font_str: &str, zoom: f64) -> HFONT {
         let (face, bold, italic, size) = Self::parse_font(font_str);
+        let size = ((size as f64) * zoom).round() as i32;
         let weight = if bold { FW_BOLD.0 } else { FW_NORMAL.0 };
         let italic = if italic { 1 } else { 0 };
 
@@ -582,7 +752,26 @@ impl TextStyle {
     }
 }
 
+impl core::KeyChord {
+    pub fn new(modifiers: ModifierState, key: u16) -> Self {
+        Self { key, ctrl: modifiers.ctrl, shift: modifiers.shift, alt: modifiers.alt, super_key: modifiers.super_key }
+    }
 
+    pub fn combination(&self) -> (ModifierState, u16) {
+        (ModifierState { ctrl: self.ctrl, shift: self.shift, alt: self.alt, super_key: self.super_key }, self.key)
+    }
+}
+
+/// Display form of a structured shortcut, named with the current keyboard layout
+pub fn format_key_chords(keys: &[core::KeyChord]) -> String {
+    keys.iter()
+        .map(|chord| {
+            let (modifiers, key) = chord.combination();
+            Combination { modifiers, key: Some(key) }
+        })
+        .collect::<Vec<_>>()
+        .display_format(DisplayFormats::InverseSpaced.get_format())
+}
 
 impl From<(PadId, core::data::Pad)> for Pad {
     fn from((pad_id, pad): (PadId, core::data::Pad)) -> Self {
@@ -595,4 +784,112 @@ impl From<(PadId, core::data::Pad)> for Pad {
             disabled: false,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_font_round_trip() {
+        for face in ["Consolas", "Comic Sans MS", "ＭＳ　ゴシック", "微软雅黑", "Noto Sans Ελληνικά", "Ёлочка"] {
+            let font = TextStyle::format_font(face, true, false, 14);
+            assert_eq!(TextStyle::parse_font(&font), (face.to_string(), true, false, 14));
+        }
+    }
+
+    #[test]
+    fn test_parse_font() {
+        assert_eq!(TextStyle::parse_font("Comic Sans MS Bold 36"), ("Comic Sans MS".to_string(), true, false, 36));
+        assert_eq!(TextStyle::parse_font("Consolas italic 20"), ("Consolas".to_string(), false, true, 20));
+        assert_eq!(TextStyle::parse_font("  Segoe UI   12 "), ("Segoe UI".to_string(), false, false, 12));
+        assert_eq!(TextStyle::parse_font(""), ("Arial".to_string(), false, false, 12));
+    }
+
+    #[test]
+    fn test_shortcut_key_caps() {
+        assert_eq!(shortcut_key_caps("Ctrl K + B"), vec![vec!["Ctrl", "K"], vec!["B"]]);
+        assert_eq!(shortcut_key_caps("ctrl shift '+'"), vec![vec!["Ctrl", "Shift", "+"]]);
+        assert_eq!(shortcut_key_caps("Alt 'a'  F4"), vec![vec!["Alt", "A", "F4"]]);
+        assert!(shortcut_key_caps(" ").is_empty());
+    }
+
+    #[test]
+    fn test_accent_fallback() {
+        let mut cs = ColorScheme::default();
+        assert_eq!(cs.accent(), cs.tag_foreground());
+
+        cs.palette = vec!["#00ff00".to_string()];
+        assert_eq!(cs.accent(), Color { r: 0, g: 255, b: 0 });
+
+        cs.accent = Some("#ff8000".to_string());
+        assert_eq!(cs.accent(), Color { r: 255, g: 128, b: 0 });
+        assert_eq!(cs.inverted().accent(), Color { r: 0, g: 127, b: 255 });
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = Color { r: 0, g: 0, b: 0 };
+        let white = Color { r: 255, g: 255, b: 255 };
+        assert!((black.contrast_ratio(&white) - 21.0).abs() < 1e-9);
+        assert_eq!(white.contrast_ratio(&black), black.contrast_ratio(&white));
+        assert_eq!(white.contrast_ratio(&white), 1.0);
+
+        // Default scheme: text on the navy background passes, the lines only meet the graphics minimum
+        let cs = ColorScheme::default();
+        assert!(cs.foreground2().contrast_ratio(&cs.background()) > 4.5);
+        let lines = cs.foreground1().contrast_ratio(&cs.background());
+        assert!(lines > 3.0 && lines < 4.5);
+    }
+
+    #[test]
+    fn test_key_chord_combination() {
+        let modifiers = ModifierState { ctrl: true, super_key: true, ..Default::default() };
+        let chord = core::KeyChord::new(modifiers, 0x4B);
+        assert!(chord.ctrl && chord.super_key && !chord.shift && !chord.alt);
+        assert_eq!(chord.combination(), (modifiers, 0x4B));
+    }
+
+    #[test]
+    fn test_reading_order() {
+        // The pad letters follow the reading order
+        assert_eq!(PadId::reading_order().map(|pad_id| pad_id.as_letter()), ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i']);
+    }
+
+    #[test]
+    fn test_empty_pad_ids() {
+        use crate::model::PadSet;
+
+        let pads = vec![
+            PadId::Seven.with_data(core::Pad { text: Some("Code".to_string()), ..Default::default() }),
+            PadId::Two.with_data(core::Pad { board: Some("media".to_string()), ..Default::default() }),
+            PadId::Five.with_data(core::Pad { header: Some(String::new()), ..Default::default() }),
+        ];
+        assert_eq!(pads.empty_pad_ids(), vec![PadId::Eight, PadId::Nine, PadId::Four, PadId::Five, PadId::Six, PadId::One, PadId::Three]);
+        assert!(PadId::all().into_iter().map(|id| id.with_data(core::Pad { text: Some("x".to_string()), ..Default::default() }))
+            .collect::<Vec<_>>().empty_pad_ids().is_empty());
+    }
+
+    #[test]
+    fn test_background_gradient() {
+        let flat = ColorScheme { background: "#102030".to_string(), ..Default::default() };
+        assert_eq!(flat.background_at(0, 100), flat.background());
+        assert_eq!(flat.background_at(99, 100), flat.background());
+
+        let gradient = ColorScheme { background2: Some("#000000".to_string()), background: "#ff8000".to_string(), ..Default::default() };
+        assert_eq!(gradient.background_at(0, 101), Color { r: 255, g: 128, b: 0 });
+        assert_eq!(gradient.background_at(50, 101), Color { r: 128, g: 64, b: 0 });
+        assert_eq!(gradient.background_at(100, 101), Color { r: 0, g: 0, b: 0 });
+        assert_eq!(gradient.background_at(0, 1), gradient.background());
+
+        // An unreadable second color keeps the background flat
+        let invalid = ColorScheme { background2: Some("navy".to_string()), ..flat };
+        assert_eq!(invalid.background_at(99, 100), invalid.background());
+
+        // A pad's own background is flat and leaves the other colors alone
+        let pad_scheme = gradient.with_background("#204060");
+        assert_eq!(pad_scheme.background_at(100, 101), Color { r: 32, g: 64, b: 96 });
+        assert_eq!(pad_scheme.foreground2(), gradient.foreground2());
+        assert!(pad_scheme.name.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::core::integration::ChainParams;
-use crate::core::{self, slugify_process_name, BoardType, DetectedIcon, Detection, SettingsRepository, SettingsRepositoryMut};
+use crate::core::{self, all_actions, ImportOutcome, ImportPolicy, Resources, duplicate_color_schemes, expand_chain_members, slugify_process_name, validate_base_url, validate_conditions, ActionType, BoardType, DetectedIcon, Detection, SettingsRepository, SettingsRepositoryMut};
 use crate::model::{ColorScheme, ModifierState, Pad, PadId, PadSet, TextStyle};
+use std::path::Path;
 use std::rc::Rc;
 
 pub struct BoardHandle<R: SettingsRepository> {
@@ -37,7 +38,7 @@ impl<R: SettingsRepository> BoardHandle<R> {
 
     pub fn color_scheme(&self) -> Result<ColorScheme, Box<dyn std::error::Error>> {
         let board = self.repository.get_board(&self.board_name)?;
-        Ok(self.repository.resolve_color_scheme(&board.color_scheme))
+        Ok(board.with_opacity(self.repository.resolve_color_scheme(&board.color_scheme)))
     }
 
     pub fn text_style(&self) -> Result<TextStyle, Box<dyn std::error::Error>> {
@@ -56,6 +57,55 @@ impl<R: SettingsRepository> BoardHandle<R> {
         ))
     }
 
+    /// Board author's description of the pads, shown while the help modifier is held
+    pub fn help(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let board = self.repository.get_board(&self.board_name)?;
+        Ok(board.help.filter(|help| !help.trim().is_empty()))
+    }
+
+    /// Pad activated with Enter, boards without a valid `default_pad` have none
+    pub fn default_pad(&self) -> Result<Option<PadId>, Box<dyn std::error::Error>> {
+        let board = self.repository.get_board(&self.board_name)?;
+        Ok(board.default_pad
+            .filter(|pad| (1..=9).contains(pad))
+            .map(|pad| PadId::from_keypad_int(pad as i32)))
+    }
+
+    /// Whether pads are labeled and selected with the letters a-i, the board's own flag wins over the setting
+    pub fn letter_keys(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        let board = self.repository.get_board(&self.board_name)?;
+        Ok(board.letter_keys.unwrap_or_else(|| self.repository.letter_keys()))
+    }
+
+    /// Whether pads are tagged with their shortcut drawn as key caps
+    pub fn key_caps(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(self.repository.get_board(&self.board_name)?.key_caps)
+    }
+
+    pub fn sizing(&self) -> Result<core::WindowSizing, Box<dyn std::error::Error>> {
+        Ok(self.repository.get_board(&self.board_name)?.sizing)
+    }
+
+    /// Pads to display for the given modifier. With `stack_modifier_pads` set, the modifier
+    /// padset is overlaid onto the base padset, so empty modifier pads show the base pad instead.
+    pub fn pads(&self, modifier: Option<ModifierState>) -> Result<Vec<Pad>, Box<dyn std::error::Error>> {
+        let board = self.repository.get_board(&self.board_name)?;
+        let pads = self.padset(modifier)?.pads()?;
+
+        let modifier_name = modifier.map(|m| m.to_string()).unwrap_or_default();
+        if !board.stack_modifier_pads || !board.has_modifier(&modifier_name) {
+            return Ok(pads);
+        }
+
+        match board.base_pads {
+            Some(base_pads) => {
+                let base = PadSetHandle::new(self.repository.clone(), base_pads).pads()?;
+                Ok(base.overlay(pads.into_iter().filter(|p| !p.data.is_empty()).collect()))
+            }
+            None => Ok(pads),
+        }
+    }
+
 }
 
 impl<R: SettingsRepository + SettingsRepositoryMut> BoardHandle<R> {
@@ -78,6 +128,16 @@ impl<R: SettingsRepository + SettingsRepositoryMut> BoardHandle<R> {
         Ok(())
     }
 
+    pub fn set_zoom(&self, zoom: f64) -> Result<(), Box<dyn std::error::Error>> {
+        let mut board = self.repository.get_board(&self.board_name)?;
+        let zoom = core::data::clamp_zoom(zoom);
+
+        board.zoom = if zoom == core::data::DEFAULT_ZOOM { None } else { Some(zoom) };
+
+        self.repository.set_board(board)?;
+        Ok(())
+    }
+
     pub fn set_text_style(&self, text_style: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
         let mut board = self.repository.get_board(&self.board_name)?;
 
@@ -117,6 +177,21 @@ impl<R: SettingsRepository + SettingsRepositoryMut> PadSetHandle<R> {
         self.repository.set_padset(core::PadSet::new(&self.padset_name, pads))?;
         Ok(())
     }
+
+    /// Places the pads on the empty pads of the padset, returns how many of them found room
+    pub fn fill_empty_pads(&self, pads: Vec<core::Pad>) -> Result<usize, Box<dyn std::error::Error>> {
+        let existing = self.pads()?;
+        let placed: Vec<Pad> = existing.empty_pad_ids().into_iter()
+            .zip(pads)
+            .map(|(pad_id, data)| pad_id.with_data(data))
+            .collect();
+        let count = placed.len();
+        if count > 0 {
+            let pads = existing.overlay(placed).flatten().iter().map(|p| p.as_data()).collect();
+            self.repository.set_padset(core::PadSet::new(&self.padset_name, pads))?;
+        }
+        Ok(count)
+    }
 }
 
 pub struct ColorSchemeHandle<R: SettingsRepository> {
@@ -330,12 +405,26 @@ impl<R: SettingsRepository + SettingsRepositoryMut> CreateDetectableBoardUseCase
             detection: detection,
             base_pads: Some(name.clone()),
             modifier_pads: Default::default(),
+            stack_modifier_pads: false,
+            requires_process: None,
+            zoom: None,
+            opacity: None,
+            default_pad: None,
+            help: None,
+            letter_keys: None,
+            monitor: None,
+            key_caps: false,
+            sizing: Default::default(),
+            rect: None,
+            hotkey: None,
         };
 
         let padset = core::PadSet::new(name.as_str(), vec![]);
 
-        self.repository.add_board(board)?;
-        self.repository.add_padset(padset)?;
+        self.repository.edit_group(|| {
+            self.repository.add_board(board)?;
+            self.repository.add_padset(padset)
+        })?;
         self.repository.get_board(&name)
     }
 }
@@ -392,12 +481,50 @@ pub fn create_board<R: SettingsRepository + SettingsRepositoryMut>(
 
     let padset = core::PadSet::new(name.as_str(), vec![]);
 
-    repository.add_board(board)?;
-    repository.add_padset(padset)?;
+    repository.edit_group(|| {
+        repository.add_board(board)?;
+        repository.add_padset(padset)
+    })?;
     repository.get_board(&name)
 }
 
-
+/// Creates a copy of a board with copies of its padsets, named like the original with a `_2`, `_3`... suffix.
+/// Pads opening the board itself open the copy. The copy is not detected and has no hotkey, so it doesn't
+/// take over from the original until it is edited.
+pub fn duplicate_board<R: SettingsRepository + SettingsRepositoryMut>(
+    repository: &R, board_name: &str
+) -> Result<core::Board, Box<dyn std::error::Error>> {
+    let board = repository.get_board(board_name)?;
+
+    let mut padset_names: Vec<&String> = board.base_pads.iter().chain(board.modifier_pads.values()).collect();
+    padset_names.sort();
+    padset_names.dedup();
+    let padsets = padset_names.into_iter()
+        .filter_map(|name| repository.get_padset(name).ok())
+        .collect();
+
+    let copy = core::BoardExport {
+        boards: vec![core::Board { detection: Detection::None, hotkey: None, ..board }],
+        padsets,
+        ..Default::default()
+    };
+    // The original always counts as taken, so the copy gets a name of its own
+    let (copy, _) = copy.renamed(
+        |name| repository.get_board(name).is_ok(),
+        |name| repository.get_padset(name).is_ok(),
+    );
+
+    let new_board = copy.boards.into_iter().next().ok_or("Board not copied")?;
+    let new_name = new_board.name.clone();
+    repository.edit_group(|| {
+        for padset in copy.padsets {
+            repository.add_padset(padset)?;
+        }
+        repository.add_board(new_board)
+    })?;
+    log::info!("Board '{}' duplicated as '{}'", board_name, new_name);
+    repository.get_board(&new_name)
+}
 
 pub struct DeleteBoardUseCase<R: SettingsRepository + SettingsRepositoryMut> {
     repository: Rc<R>,
@@ -468,17 +595,19 @@ pub fn delete_board<R: SettingsRepository + SettingsRepositoryMut>(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let board = repository.get_board(&board_name)?;
 
-    repository.delete_board(&board.name)?;
+    repository.edit_group(|| {
+        repository.delete_board(&board.name)?;
 
-    if let Some(base_padset_name) = board.base_pads {
-        repository.delete_padset(&base_padset_name)?;
-    }
+        if let Some(base_padset_name) = &board.base_pads {
+            repository.delete_padset(base_padset_name)?;
+        }
 
-    for padset_name in board.modifier_pads.values() {
-        repository.delete_padset(padset_name)?;
-    }
+        for padset_name in board.modifier_pads.values() {
+            repository.delete_padset(padset_name)?;
+        }
 
-    Ok(())
+        Ok(())
+    })
 }
 
 pub fn create_modifier_pad_set<R: SettingsRepository + SettingsRepositoryMut>(
@@ -496,8 +625,10 @@ pub fn create_modifier_pad_set<R: SettingsRepository + SettingsRepositoryMut>(
 
     board.modifier_pads.insert(modifier.to_string(), padset_name.clone());
 
-    repository.add_padset(padset)?;
-    repository.set_board(board)?;
+    repository.edit_group(|| {
+        repository.add_padset(padset)?;
+        repository.set_board(board)
+    })?;
     repository.get_padset(&padset_name)
 }
 
@@ -507,14 +638,452 @@ pub fn delete_modifier_pad_set<R: SettingsRepository + SettingsRepositoryMut>(
     let mut board = repository.get_board(&board_name)?;
 
     if let Some(padset_name) = board.modifier_pads.remove(&modifier) {
-        repository.delete_padset(&padset_name)?;
-        repository.set_board(board)?;
-        Ok(())
+        repository.edit_group(|| {
+            repository.delete_padset(&padset_name)?;
+            repository.set_board(board)
+        })
     } else {
         Err("This modifier pad set does not exist for the board".into())
     }
 }
 
+/// Boards a collection pages through, members that are collections themselves are expanded recursively
+pub fn chain_members<R: SettingsRepository>(
+    repository: &R, chain_name: &str, members: &[String]
+) -> Result<Vec<String>, String> {
+    let sub_members = |name: &str| match repository.get_board(name) {
+        Ok(core::Board { board_type: BoardType::Chain(params), .. }) => Some(params.boards()),
+        _ => None,
+    };
+    expand_chain_members(chain_name, members, &sub_members)
+}
+
+/// Collects a board with its base and modifier padsets, missing padsets are skipped
+pub fn export_board<R: SettingsRepository>(
+    repository: &R, board_name: &str
+) -> Result<core::BoardExport, Box<dyn std::error::Error>> {
+    let board = repository.get_board(board_name)?;
+
+    let mut modifier_padsets: Vec<&String> = board.modifier_pads.values().collect();
+    modifier_padsets.sort();
+
+    let padsets = board.base_pads.iter()
+        .chain(modifier_padsets)
+        .filter_map(|name| repository.get_padset(name).ok())
+        .collect();
+
+    Ok(core::BoardExport { boards: vec![board], padsets, ..Default::default() })
+}
+
+/// Collects a board with the boards it opens, as collection members or from pads, and recursively the ones those open,
+/// together with the color schemes and text styles they use. The home, settings and diagnostic boards are left out,
+/// every configuration has its own. Missing boards, color schemes and text styles are skipped, the import reports them.
+pub fn export_board_with_references<R: SettingsRepository>(
+    repository: &R, board_name: &str
+) -> Result<core::BoardExport, Box<dyn std::error::Error>> {
+    let is_builtin = |name: &str| name == core::data::HOME_BOARD_NAME || name == core::data::SETTINGS_BOARD_NAME || core::data::is_diagnostic_board(name);
+    let mut export = export_board(repository, board_name)?;
+
+    loop {
+        let referenced: Vec<core::BoardExport> = export.referenced_boards().iter()
+            .filter(|name| !is_builtin(name))
+            .filter_map(|name| export_board(repository, name).ok())
+            .collect();
+        if referenced.is_empty() {
+            break;
+        }
+        for part in referenced {
+            export.boards.extend(part.boards);
+            for padset in part.padsets {
+                if !export.padsets.iter().any(|p| p.name == padset.name) {
+                    export.padsets.push(padset);
+                }
+            }
+        }
+    }
+
+    let (color_schemes, text_styles) = export.referenced_styles();
+    export.color_schemes = color_schemes.iter().filter_map(|name| repository.get_color_scheme(name)).collect();
+    export.text_styles = text_styles.iter().filter_map(|name| repository.get_text_style(name)).collect();
+    Ok(export)
+}
+
+/// Writes a board with everything it references to a standalone JSON file, returns the export written
+pub fn export_board_to_file<R: SettingsRepository>(
+    repository: &R, board_name: &str, path: &Path
+) -> Result<core::BoardExport, Box<dyn std::error::Error>> {
+    let export = export_board_with_references(repository, board_name)?;
+    std::fs::write(path, export.to_json()?)?;
+    log::info!("Board '{}' exported with {} boards to {:?}", board_name, export.boards.len(), path);
+    Ok(export)
+}
+
+/// Adds exported boards with their padsets, e.g. a template or a board copied from another configuration.
+/// A board whose name is in use is renamed, overwritten or skipped as the policy says. Padset names in use
+/// get a `_2`, `_3`... suffix, only the padsets of an overwritten board are replaced.
+pub struct ImportBoardUseCase<R: SettingsRepository + SettingsRepositoryMut> {
+    repository: Rc<R>,
+    resources: Resources,
+    export: core::BoardExport,
+    policy: ImportPolicy,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut> ImportBoardUseCase<R> {
+    pub fn new(repository: Rc<R>, resources: Resources, export: core::BoardExport, policy: ImportPolicy) -> Self {
+        Self { repository, resources, export, policy }
+    }
+
+    /// Imports a board file, e.g. one written by `export_board_to_file` on another machine
+    pub fn from_file(repository: Rc<R>, resources: Resources, path: &Path, policy: ImportPolicy) -> Result<Self, Box<dyn std::error::Error>> {
+        let export = core::BoardExport::from_json(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("Invalid board file\n{}", e))?;
+        Ok(Self::new(repository, resources, export, policy))
+    }
+
+    /// Names of the imported boards already in use
+    pub fn conflicts(&self) -> Vec<String> {
+        self.export.boards.iter()
+            .filter(|board| self.repository.get_board(&board.name).is_ok())
+            .map(|board| board.name.clone())
+            .collect()
+    }
+
+    /// Checks the export and what it references outside of itself: color schemes, text styles and boards
+    pub fn validate(&self) -> Result<(), String> {
+        self.export.validate()?;
+
+        let board_exists = |name: &str| self.export.boards.iter().any(|b| b.name == name) || self.repository.get_board(name).is_ok();
+        let scheme_exists = |name: &str| self.export.color_schemes.iter().any(|cs| cs.name == name) || self.repository.get_color_scheme(name).is_some();
+        let style_exists = |name: &str| self.export.text_styles.iter().any(|ts| ts.name == name) || self.repository.get_text_style(name).is_some();
+        let check_scheme = |name: &Option<String>, owner: &str| match name {
+            Some(name) if !scheme_exists(name) => Err(format!("Color scheme\n\"{}\"\nof {} does not exist", name, owner)),
+            _ => Ok(()),
+        };
+        let check_style = |name: &Option<String>, owner: &str| match name {
+            Some(name) if !style_exists(name) => Err(format!("Text style\n\"{}\"\nof {} does not exist", name, owner)),
+            _ => Ok(()),
+        };
+
+        for board in &self.export.boards {
+            let owner = format!("board \"{}\"", board.name);
+            check_scheme(&board.color_scheme, &owner)?;
+            check_style(&board.text_style, &owner)?;
+            if let BoardType::Chain(params) = &board.board_type {
+                if let Some(member) = params.boards().iter().find(|member| !board_exists(member)) {
+                    return Err(format!("Board\n\"{}\"\nin collection \"{}\" does not exist", member, board.name));
+                }
+            }
+        }
+
+        for padset in &self.export.padsets {
+            let owner = format!("a pad in \"{}\"", padset.name);
+            for pad in &padset.items {
+                check_scheme(&pad.color_scheme, &owner)?;
+                check_style(&pad.text_style, &owner)?;
+                if let Some(board_name) = pad.board.as_deref().filter(|name| !board_exists(name)) {
+                    return Err(format!("Board\n\"{}\"\nopened by {} does not exist", board_name, owner));
+                }
+                validate_conditions(&pad.actions).map_err(|e| format!("{} in {}", e, owner))?;
+                for action in all_actions(&pad.actions) {
+                    if let ActionType::OpenQuery { base, .. } = action {
+                        validate_base_url(base).map_err(|e| format!("{} in {}", e, owner))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates and adds the boards, returns what was done with each of them
+    pub fn import(&self) -> Result<Vec<ImportOutcome>, Box<dyn std::error::Error>> {
+        self.validate()?;
+
+        let board_taken = |name: &str| self.repository.get_board(name).is_ok();
+        let padset_taken = |name: &str| self.repository.get_padset(name).is_ok();
+        let mut outcomes = vec![];
+        let mut replaced_padsets: Vec<String> = vec![];
+
+        let export = match self.policy {
+            ImportPolicy::Rename => {
+                let (export, renamed) = self.export.clone().renamed(board_taken, padset_taken);
+                for board in &self.export.boards {
+                    outcomes.push(match renamed.get(&board.name) {
+                        Some(new_name) => ImportOutcome::Renamed { name: board.name.clone(), new_name: new_name.clone() },
+                        None => ImportOutcome::Added(board.name.clone()),
+                    });
+                }
+                export
+            },
+            ImportPolicy::Skip => {
+                let (export, skipped) = self.export.clone().skipping(board_taken);
+                outcomes.extend(skipped.into_iter().map(ImportOutcome::Skipped));
+                outcomes.extend(export.boards.iter().map(|board| ImportOutcome::Added(board.name.clone())));
+                export.renamed(board_taken, padset_taken).0
+            },
+            ImportPolicy::Overwrite => {
+                let replaced = self.conflicts();
+                for name in &replaced {
+                    self.back_up(name)?;
+                }
+                // The padsets of the replaced boards are replaced with them, other taken names are avoided
+                replaced_padsets = replaced.iter()
+                    .filter_map(|name| self.repository.get_board(name).ok())
+                    .flat_map(|board| board.base_pads.into_iter().chain(board.modifier_pads.into_values()))
+                    .collect();
+                for board in &self.export.boards {
+                    outcomes.push(match replaced.contains(&board.name) {
+                        true => ImportOutcome::Overwritten(board.name.clone()),
+                        false => ImportOutcome::Added(board.name.clone()),
+                    });
+                }
+                self.export.clone().renamed(|_| false, |name| padset_taken(name) && !replaced_padsets.iter().any(|p| p == name)).0
+            },
+        };
+
+        for outcome in &outcomes {
+            log::info!("Imported board {}", outcome);
+        }
+
+        self.repository.edit_group(|| self.add_all(export, replaced_padsets))?;
+        Ok(outcomes)
+    }
+
+    /// Adds the (renamed) export, deleting the padsets of the overwritten boards that the new boards don't use
+    fn add_all(&self, export: core::BoardExport, replaced_padsets: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+        let board_taken = |name: &str| self.repository.get_board(name).is_ok();
+        let padset_taken = |name: &str| self.repository.get_padset(name).is_ok();
+
+        // A color scheme or text style whose name is in use is not replaced, the boards get the configured one
+        for color_scheme in &self.export.color_schemes {
+            if self.repository.get_color_scheme(&color_scheme.name).is_none() {
+                self.repository.add_color_scheme(color_scheme.clone())?;
+            }
+        }
+        for text_style in &self.export.text_styles {
+            if self.repository.get_text_style(&text_style.name).is_none() {
+                self.repository.add_text_style(text_style.clone())?;
+            }
+        }
+
+        for padset in export.padsets {
+            match padset_taken(&padset.name) {
+                true => self.repository.set_padset(padset)?,
+                false => self.repository.add_padset(padset)?,
+            }
+        }
+        for board in export.boards {
+            match board_taken(&board.name) {
+                true => self.repository.set_board(board)?,
+                false => self.repository.add_board(board)?,
+            }
+        }
+
+        let used_padsets: Vec<String> = self.repository.boards().iter()
+            .filter_map(|name| self.repository.get_board(name).ok())
+            .flat_map(|board| board.base_pads.into_iter().chain(board.modifier_pads.into_values()))
+            .collect();
+        for padset_name in replaced_padsets.iter().filter(|name| !used_padsets.contains(name)) {
+            self.repository.delete_padset(padset_name)?;
+            log::info!("Padset '{}' of an overwritten board deleted", padset_name);
+        }
+        Ok(())
+    }
+
+    /// Writes the board about to be replaced with its padsets next to the settings, restorable as an include file
+    fn back_up(&self, board_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.resources.board_backup(board_name);
+        std::fs::write(&path, export_board(self.repository.as_ref(), board_name)?.to_json()?)?;
+        log::info!("Board '{}' backed up to {:?} before it is overwritten", board_name, path);
+        Ok(())
+    }
+}
+
+/// Renames a board and points the pads, collections and settings opening it to the new name.
+/// When a step fails the steps done so far are rolled back, no reference is left dangling.
+pub struct RenameBoardUseCase<R: SettingsRepository + SettingsRepositoryMut> {
+    repository: Rc<R>,
+    old_name: String,
+    new_name: String,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut> RenameBoardUseCase<R> {
+    pub fn new(repository: Rc<R>, old_name: String, new_name: String) -> Self {
+        Self { repository, old_name, new_name: new_name.trim().to_string() }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.new_name.is_empty() {
+            return Err("Board name can't be empty".to_string());
+        }
+        // Collections list their boards separated by commas
+        if self.new_name.contains(',') {
+            return Err(format!("Board name\n\"{}\"\ncan't contain a comma", self.new_name));
+        }
+        if self.repository.get_board(&self.old_name).is_err() {
+            return Err(format!("Board\n\"{}\"\ndoes not exist", self.old_name));
+        }
+        if self.repository.get_board(&self.new_name).is_ok() {
+            return Err(format!("Board\n\"{}\"\nalready exists", self.new_name));
+        }
+        Ok(())
+    }
+
+    /// Returns how many pads and collections referencing the board were updated
+    pub fn rename(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        self.validate()?;
+        let board = self.repository.get_board(&self.old_name)?;
+
+        let pad_references: usize = self.repository.padsets().iter()
+            .filter_map(|padset_name| self.repository.get_padset(padset_name).ok())
+            .map(|padset| padset.items.iter().filter(|pad| pad.board.as_deref() == Some(self.old_name.as_str())).count())
+            .sum();
+
+        let mut chains = vec![];
+        for board_name in self.repository.boards() {
+            let original = self.repository.get_board(&board_name)?;
+            let mut renamed = original.clone();
+            if let BoardType::Chain(params) = &mut renamed.board_type {
+                let references = params.rename_board(&self.old_name, &self.new_name);
+                if references > 0 {
+                    chains.push((original, renamed, references));
+                }
+            }
+        }
+
+        let chain_references = self.repository.edit_group(|| {
+            self.repository.rename_board(&self.old_name, &self.new_name)?;
+
+            let mut updated_chains = vec![];
+            let mut chain_references = 0;
+            for (original, renamed, references) in chains {
+                if let Err(e) = self.repository.set_board(renamed) {
+                    self.roll_back(board, updated_chains);
+                    return Err(e);
+                }
+                updated_chains.push(original);
+                chain_references += references;
+            }
+            Ok(chain_references)
+        })?;
+
+        log::info!("Board '{}' renamed to '{}', {} pad and {} collection references updated",
+            self.old_name, self.new_name, pad_references, chain_references);
+        Ok(pad_references + chain_references)
+    }
+
+    /// Restores the collections and the board as they were before the rename
+    fn roll_back(&self, board: core::Board, chains: Vec<core::Board>) {
+        let restored = chains.into_iter().try_for_each(|chain| self.repository.set_board(chain))
+            .and_then(|()| self.repository.rename_board(&self.new_name, &self.old_name))
+            .and_then(|()| self.repository.set_board(board));
+        match restored {
+            Ok(()) => log::warn!("Rename of board '{}' rolled back", self.old_name),
+            Err(e) => log::error!("Rolling back the rename of board '{}' failed: {}", self.old_name, e),
+        }
+    }
+}
+
+pub struct ReassignColorSchemeUseCase<R: SettingsRepository + SettingsRepositoryMut> {
+    repository: Rc<R>,
+    from: String,
+    to: String,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut> ReassignColorSchemeUseCase<R> {
+    pub fn new(repository: Rc<R>, from: String, to: String) -> Self {
+        Self {
+            repository,
+            from,
+            to,
+        }
+    }
+
+    /// Names of the boards explicitly using the source color scheme
+    pub fn referencing_boards(&self) -> Vec<String> {
+        self.repository.boards().into_iter()
+            .filter(|board_name| {
+                self.repository.get_board(board_name)
+                    .map(|board| board.color_scheme.as_deref() == Some(self.from.as_str()))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.from == self.to {
+            return Err(format!("Color scheme\n\"{}\"\nis already assigned", self.to));
+        }
+        if self.repository.get_color_scheme(&self.to).is_none() {
+            return Err(format!("Color scheme\n\"{}\"\ndoes not exist", self.to));
+        }
+        Ok(())
+    }
+
+    /// Assigns the target scheme to every board using the source scheme, returns the number of changed boards
+    pub fn reassign(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        self.validate()?;
+        let board_names = self.referencing_boards();
+        self.repository.edit_group(|| {
+            for board_name in &board_names {
+                BoardHandle::new(self.repository.clone(), board_name.clone())
+                    .set_color_scheme(Some(self.to.clone()))?;
+            }
+            Ok(())
+        })?;
+        Ok(board_names.len())
+    }
+}
+
+/// Folds color schemes with the same colors into one, boards and pads using a duplicate move over to the kept scheme
+pub struct MergeColorSchemesUseCase<R: SettingsRepository + SettingsRepositoryMut> {
+    repository: Rc<R>,
+}
+
+impl<R: SettingsRepository + SettingsRepositoryMut> MergeColorSchemesUseCase<R> {
+    pub fn new(repository: Rc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// (kept scheme, its duplicates) for every set of schemes sharing all colors
+    pub fn duplicates(&self) -> Vec<(String, Vec<String>)> {
+        let schemes: Vec<core::ColorScheme> = self.repository.color_schemes().iter()
+            .filter_map(|name| self.repository.get_color_scheme(name))
+            .collect();
+        duplicate_color_schemes(&schemes)
+    }
+
+    /// Reassigns the references to each duplicate and deletes it, returns the merged groups
+    pub fn merge(&self) -> Result<Vec<(String, Vec<String>)>, Box<dyn std::error::Error>> {
+        let groups = self.duplicates();
+        self.repository.edit_group(|| {
+            for (kept, duplicates) in &groups {
+                for duplicate in duplicates {
+                    ReassignColorSchemeUseCase::new(self.repository.clone(), duplicate.clone(), kept.clone()).reassign()?;
+                    self.reassign_pads(duplicate, kept)?;
+                    self.repository.delete_color_scheme(duplicate)?;
+                }
+            }
+            Ok(())
+        })?;
+        Ok(groups)
+    }
+
+    fn reassign_pads(&self, from: &str, to: &str) -> Result<(), Box<dyn std::error::Error>> {
+        for padset_name in self.repository.padsets() {
+            let mut padset = self.repository.get_padset(&padset_name)?;
+            let mut changed = false;
+            for pad in padset.items.iter_mut().filter(|pad| pad.color_scheme.as_deref() == Some(from)) {
+                pad.color_scheme = Some(to.to_string());
+                changed = true;
+            }
+            if changed {
+                self.repository.set_padset(padset)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 pub struct ConvertToBoardChainUseCase<R: SettingsRepository + SettingsRepositoryMut> {
     repository: Rc<R>,
     board_name: String,
@@ -529,7 +1098,7 @@ impl<R: SettingsRepository + SettingsRepositoryMut> ConvertToBoardChainUseCase<R
     }
 
     pub fn convert(&self) -> Result<(), Box<dyn std::error::Error>> {
-        convert_to_board_chain(self.repository.as_ref(), self.board_name.clone())
+        self.repository.edit_group(|| convert_to_board_chain(self.repository.as_ref(), self.board_name.clone()))
     }
 
     pub fn validate(&self) -> Result<(), String> {
@@ -626,4 +1195,228 @@ pub fn create_new_chain_with_board<R: SettingsRepository + SettingsRepositoryMut
     repository.insert_board(&board_name, board.clone())?;
 
     return repository.get_board(&board.name)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    fn padset(name: &str) -> core::PadSet {
+        core::PadSet { name: name.to_string(), ..Default::default() }
+    }
+
+    fn board(name: &str, base_pads: &str) -> core::Board {
+        core::Board { name: name.to_string(), base_pads: Some(base_pads.to_string()), ..Default::default() }
+    }
+
+    /// Settings whose `set_board` fails for one board, to break a use case part-way
+    struct FailingRepository {
+        settings: Rc<Settings>,
+        failing_board: String,
+    }
+
+    impl SettingsRepository for FailingRepository {
+        fn timeout(&self) -> u64 { self.settings.timeout() }
+        fn feedback(&self) -> u64 { self.settings.feedback() }
+        fn editor(&self) -> String { self.settings.editor() }
+        fn natural_key_order(&self) -> bool { self.settings.natural_key_order() }
+        fn pad_keys(&self) -> core::PadKeys { self.settings.pad_keys() }
+        fn letter_keys(&self) -> bool { self.settings.letter_keys() }
+        fn open_in_edit_mode(&self) -> bool { self.settings.open_in_edit_mode() }
+        fn structured_shortcuts(&self) -> bool { self.settings.structured_shortcuts() }
+        fn get_text_style(&self, name: &str) -> Option<TextStyle> { self.settings.get_text_style(name) }
+        fn get_color_scheme(&self, name: &str) -> Option<ColorScheme> { self.settings.get_color_scheme(name) }
+        fn get_board(&self, name: &str) -> Result<core::Board, Box<dyn std::error::Error>> { self.settings.get_board(name) }
+        fn get_padset(&self, name: &str) -> Result<core::PadSet, Box<dyn std::error::Error>> { self.settings.get_padset(name) }
+        fn resolve_color_scheme(&self, name: &Option<String>) -> ColorScheme { self.settings.resolve_color_scheme(name) }
+        fn resolve_text_style(&self, name: &Option<String>) -> TextStyle { self.settings.resolve_text_style(name) }
+        fn color_schemes(&self) -> Vec<String> { self.settings.color_schemes() }
+        fn text_styles(&self) -> Vec<String> { self.settings.text_styles() }
+        fn boards(&self) -> Vec<String> { self.settings.boards() }
+        fn padsets(&self) -> Vec<String> { self.settings.padsets() }
+        fn last_seen_version(&self) -> Option<String> { self.settings.last_seen_version() }
+        fn copy_board_key(&self) -> String { self.settings.copy_board_key() }
+        fn help_modifier(&self) -> String { self.settings.help_modifier() }
+        fn limits(&self) -> core::Limits { self.settings.limits() }
+        fn recent_boards(&self) -> Vec<String> { self.settings.recent_boards() }
+        fn dangling_references(&self) -> Vec<core::DanglingReference> { self.settings.dangling_references() }
+    }
+
+    impl SettingsRepositoryMut for FailingRepository {
+        fn add_board(&self, board: core::Board) -> Result<(), Box<dyn std::error::Error>> { self.settings.add_board(board) }
+        fn insert_board(&self, insert_before: &str, board: core::Board) -> Result<(), Box<dyn std::error::Error>> { self.settings.insert_board(insert_before, board) }
+        fn add_padset(&self, padset: core::PadSet) -> Result<(), Box<dyn std::error::Error>> { self.settings.add_padset(padset) }
+        fn set_board(&self, board: core::Board) -> Result<(), Box<dyn std::error::Error>> {
+            if board.name == self.failing_board {
+                return Err(format!("Board '{}' can't be saved", board.name).into());
+            }
+            self.settings.set_board(board)
+        }
+        fn set_padset(&self, padset: core::PadSet) -> Result<(), Box<dyn std::error::Error>> { self.settings.set_padset(padset) }
+        fn set_text_style(&self, text_style: TextStyle) -> Result<(), Box<dyn std::error::Error>> { self.settings.set_text_style(text_style) }
+        fn set_color_scheme(&self, color_scheme: ColorScheme) -> Result<(), Box<dyn std::error::Error>> { self.settings.set_color_scheme(color_scheme) }
+        fn add_color_scheme(&self, color_scheme: ColorScheme) -> Result<(), Box<dyn std::error::Error>> { self.settings.add_color_scheme(color_scheme) }
+        fn add_text_style(&self, text_style: TextStyle) -> Result<(), Box<dyn std::error::Error>> { self.settings.add_text_style(text_style) }
+        fn rename_color_scheme(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> { self.settings.rename_color_scheme(old_name, new_name) }
+        fn rename_text_style(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> { self.settings.rename_text_style(old_name, new_name) }
+        fn rename_board(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> { self.settings.rename_board(old_name, new_name) }
+        fn delete_color_scheme(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> { self.settings.delete_color_scheme(name) }
+        fn delete_text_style(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> { self.settings.delete_text_style(name) }
+        fn delete_board(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> { self.settings.delete_board(name) }
+        fn delete_padset(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> { self.settings.delete_padset(name) }
+        fn undo(&self) -> bool { self.settings.undo() }
+        fn redo(&self) -> bool { self.settings.redo() }
+        fn begin_edit_group(&self) { self.settings.begin_edit_group() }
+        fn end_edit_group(&self) { self.settings.end_edit_group() }
+        fn set_last_seen_version(&self, version: &str) { self.settings.set_last_seen_version(version) }
+        fn set_open_in_edit_mode(&self, enabled: bool) { self.settings.set_open_in_edit_mode(enabled) }
+        fn add_recent_board(&self, name: &str) -> bool { self.settings.add_recent_board(name) }
+        fn mark_dirty(&self) { self.settings.mark_dirty() }
+        fn is_dirty(&self) -> bool { self.settings.is_dirty() }
+        fn flush(&self) -> Result<(), Box<dyn std::error::Error>> { self.settings.flush() }
+        fn reload(&self) -> Result<(), Box<dyn std::error::Error>> { self.settings.reload() }
+    }
+
+    #[test]
+    fn test_import_overwrite_replaces_padsets() {
+        let dir = tempfile::tempdir().unwrap();
+        let resources = Resources::new(vec![dir.path().to_path_buf()]);
+        let settings = Settings::safe_mode(resources.clone(), String::new());
+
+        let mut dev = board("dev", "dev");
+        dev.modifier_pads.insert("Ctrl".to_string(), "dev/ctrl".to_string());
+        for name in ["dev", "dev/ctrl", "dev/old"] {
+            settings.add_padset(padset(name)).unwrap();
+        }
+        settings.add_board(dev).unwrap();
+        settings.add_board(board("old", "dev/old")).unwrap();
+
+        // The new "dev" keeps its base padset and drops the modifier one, the new "old" brings a padset of another name
+        let export = core::BoardExport {
+            boards: vec![board("dev", "dev"), board("old", "old")],
+            padsets: vec![padset("dev"), padset("old")],
+            ..Default::default()
+        };
+        let outcomes = ImportBoardUseCase::new(settings.clone(), resources, export, ImportPolicy::Overwrite).import().unwrap();
+        assert_eq!(outcomes, vec![ImportOutcome::Overwritten("dev".to_string()), ImportOutcome::Overwritten("old".to_string())]);
+
+        let mut padsets = settings.padsets();
+        padsets.sort();
+        assert_eq!(padsets, vec!["dev", "old"]);
+        assert!(dir.path().join("board_dev.json.bak").exists());
+    }
+
+    #[test]
+    fn test_create_board_undone_in_one_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::safe_mode(Resources::new(vec![dir.path().to_path_buf()]), String::new());
+        settings.add_padset(padset("dev")).unwrap();
+        settings.add_board(board("dev", "dev")).unwrap();
+
+        create_board(settings.as_ref(), "dev".to_string(), "Console".to_string()).unwrap();
+        assert!(settings.get_board("dev/console").is_ok());
+        assert!(settings.get_padset("dev/console").is_ok());
+
+        assert!(settings.undo());
+        assert!(settings.get_board("dev/console").is_err());
+        assert!(settings.get_padset("dev/console").is_err());
+        assert!(settings.get_board("dev").is_ok());
+        assert!(settings.get_padset("dev").is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::safe_mode(Resources::new(vec![dir.path().to_path_buf()]), String::new());
+        let mut pads = padset("dev");
+        pads.items.push(core::Pad { board: Some("dev".to_string()), ..Default::default() });
+        pads.items.push(core::Pad { board: Some(core::data::HOME_BOARD_NAME.to_string()), ..Default::default() });
+        settings.add_padset(pads).unwrap();
+        settings.add_padset(padset("dev/ctrl")).unwrap();
+        let mut dev = board("dev", "dev");
+        dev.modifier_pads.insert("Ctrl".to_string(), "dev/ctrl".to_string());
+        dev.detection = Detection::Win32("code".to_string());
+        dev.hotkey = Some("Ctrl Alt D".to_string());
+        settings.add_board(dev).unwrap();
+
+        let copy = duplicate_board(settings.as_ref(), "dev").unwrap();
+        assert_eq!(copy.name, "dev_2");
+        assert_eq!(copy.base_pads.as_deref(), Some("dev_2"));
+        assert_eq!(copy.modifier_pads.get("Ctrl").map(String::as_str), Some("dev/ctrl_2"));
+        assert_eq!(copy.detection, Detection::None);
+        assert_eq!(copy.hotkey, None);
+
+        // Pads opening the board itself open the copy, the others are left as they are
+        let targets: Vec<Option<String>> = settings.get_padset("dev_2").unwrap().items.into_iter().map(|pad| pad.board).collect();
+        assert_eq!(targets, vec![Some("dev_2".to_string()), Some(core::data::HOME_BOARD_NAME.to_string())]);
+        assert_eq!(settings.get_padset("dev").unwrap().items[0].board.as_deref(), Some("dev"));
+
+        assert!(settings.undo());
+        assert!(settings.get_board("dev_2").is_err());
+        assert!(settings.get_padset("dev_2").is_err());
+        assert!(settings.get_padset("dev/ctrl_2").is_err());
+        assert!(settings.get_board("dev").is_ok());
+    }
+
+    /// Board "dev" opened by a pad of "start" and listed by the collections "all" and "work"
+    fn rename_setup(settings: &Settings) {
+        let mut start = padset("start");
+        start.items.push(core::Pad { board: Some("dev".to_string()), ..Default::default() });
+        settings.add_padset(start).unwrap();
+        settings.add_padset(padset("dev")).unwrap();
+        settings.add_board(board("dev", "dev")).unwrap();
+        for (name, members) in [("all", "dev,git"), ("work", "git,dev")] {
+            let chain = ChainParams { boards: members.to_string(), initial_board: None, params: vec![] };
+            settings.add_board(core::Board { name: name.to_string(), board_type: BoardType::Chain(chain), ..Default::default() }).unwrap();
+        }
+    }
+
+    fn chain_members(repository: &impl SettingsRepository, name: &str) -> String {
+        match repository.get_board(name).unwrap().board_type {
+            BoardType::Chain(params) => params.boards,
+            _ => panic!("Board '{}' is not a collection", name),
+        }
+    }
+
+    #[test]
+    fn test_rename_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::safe_mode(Resources::new(vec![dir.path().to_path_buf()]), String::new());
+        rename_setup(&settings);
+
+        let references = RenameBoardUseCase::new(settings.clone(), "dev".to_string(), " code ".to_string()).rename().unwrap();
+        assert_eq!(references, 3);
+        assert!(settings.get_board("dev").is_err());
+        assert_eq!(settings.get_board("code").unwrap().base_pads.as_deref(), Some("dev"));
+        assert_eq!(settings.get_padset("start").unwrap().items[0].board.as_deref(), Some("code"));
+        assert_eq!(chain_members(settings.as_ref(), "all"), "code,git");
+        assert_eq!(chain_members(settings.as_ref(), "work"), "git,code");
+
+        assert!(settings.undo());
+        assert!(settings.get_board("dev").is_ok());
+        assert_eq!(settings.get_padset("start").unwrap().items[0].board.as_deref(), Some("dev"));
+        assert_eq!(chain_members(settings.as_ref(), "all"), "dev,git");
+        assert_eq!(chain_members(settings.as_ref(), "work"), "git,dev");
+
+        let taken = RenameBoardUseCase::new(settings.clone(), "dev".to_string(), "all".to_string());
+        assert!(taken.validate().is_err());
+    }
+
+    #[test]
+    fn test_rename_board_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::safe_mode(Resources::new(vec![dir.path().to_path_buf()]), String::new());
+        rename_setup(&settings);
+
+        // "all" is updated before saving "work" fails
+        let repository = Rc::new(FailingRepository { settings: settings.clone(), failing_board: "work".to_string() });
+        assert!(RenameBoardUseCase::new(repository, "dev".to_string(), "code".to_string()).rename().is_err());
+
+        assert!(settings.get_board("dev").is_ok());
+        assert!(settings.get_board("code").is_err());
+        assert_eq!(settings.get_padset("start").unwrap().items[0].board.as_deref(), Some("dev"));
+        assert_eq!(chain_members(settings.as_ref(), "all"), "dev,git");
+        assert_eq!(chain_members(settings.as_ref(), "work"), "git,dev");
+    }
+}
@@ -1,4 +1,4 @@
-use super::{ColorScheme, TextStyle, ModifierState, PadId, Pad, Tag};
+use super::{ColorScheme, TextStyle, ModifierState, PadId, Pad, PadSource, Tag, WindowSizing};
 
 pub trait Board {
     #[allow(dead_code)]
@@ -22,6 +22,31 @@ pub trait Board {
     fn tags(&self, _modifier: Option<ModifierState>) -> Vec<Tag> {
         vec![]
     }
+
+    /// Pad activated by Enter when the board doesn't handle the key itself
+    fn default_pad(&self) -> Option<PadId> {
+        None
+    }
+
+    /// Pads are labeled and selected with the letters a-i, besides the number keys
+    fn letter_keys(&self) -> bool {
+        false
+    }
+
+    /// Tags explaining the board's keys, shown in place of its tags while the help key is held
+    fn help_tags(&self) -> Vec<Tag> {
+        vec![]
+    }
+
+    /// How the window is sized when the board opens
+    fn sizing(&self) -> WindowSizing {
+        WindowSizing::Fixed
+    }
+
+    /// Pad of another board the given pad stands for, selecting it runs that pad instead
+    fn pad_source(&self, _pad_id: PadId) -> Option<PadSource> {
+        None
+    }
 }
 
 pub trait PadSet {
@@ -40,6 +65,10 @@ pub trait PadSet {
         }
         result
     }
+    /// Pads with nothing on them, top row first as the grid reads
+    fn empty_pad_ids(&self) -> Vec<PadId> {
+        PadId::reading_order().into_iter().filter(|id| self.pad(*id).data.is_empty()).collect()
+    }
 }
 
 
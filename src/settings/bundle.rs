@@ -0,0 +1,344 @@
+use std::{collections::HashMap, fs, io::{Read, Write}, path::{Path, PathBuf}};
+
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::core::{ImportOutcome, ImportPolicy, Resources};
+use super::persistence::{SettingsData, SettingsFileStroage};
+use super::validation::SettingsValidator;
+
+// Fixed layout of a bundle, independent of the resource names of either machine
+const BUNDLE_SETTINGS: &str = "settings.json";
+const BUNDLE_LOG: &str = "log.toml";
+const BUNDLE_ICONS: &str = "icons";
+
+/// Counts reported back to the command line after an export or import
+#[derive(Debug, Default)]
+pub struct BundleSummary {
+    pub boards: usize,
+    pub icons: usize,
+    pub renamed_icons: usize,
+    /// What an import did with the bundled icons clashing with existing ones
+    pub outcomes: Vec<ImportOutcome>,
+}
+
+/// Packages the whole configuration into a zip: a single settings file with the includes merged in,
+/// the icons it references and the log configuration.
+/// Icons from subfolders of the icons folder are stored under their file name, renamed when two different files share one.
+pub fn export_all(resources: &Resources, zip_path: &Path) -> Result<BundleSummary, Box<dyn std::error::Error>> {
+    let mut data = SettingsFileStroage::new(resources.clone()).load()?.flattened();
+
+    let mut sources: HashMap<PathBuf, String> = HashMap::new();
+    let mut summary = BundleSummary { boards: data.boards.len(), ..Default::default() };
+
+    rewrite_icons(&mut data, |icon| {
+        let source = resources.icon(icon).ok_or_else(|| format!("Icon '{}' not found", icon))?;
+        if let Some(name) = sources.get(&source) {
+            return Ok(name.clone());
+        }
+
+        let file_name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| icon.to_string());
+        let name = unique_name(&file_name, |name| sources.values().any(|taken| taken == name));
+        if name != icon {
+            log::info!("Icon '{}' stored in the bundle as '{}'", icon, name);
+        }
+        if name != file_name {
+            summary.renamed_icons += 1;
+        }
+        sources.insert(source, name.clone());
+        Ok(name)
+    })?;
+
+    let mut zip = ZipWriter::new(fs::File::create(zip_path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(BUNDLE_SETTINGS, options)?;
+    zip.write_all(serde_json::to_string_pretty(&data)?.as_bytes())?;
+
+    let mut icons: Vec<_> = sources.into_iter().collect();
+    icons.sort_by(|a, b| a.1.cmp(&b.1));
+    for (source, name) in &icons {
+        zip.start_file(format!("{}/{}", BUNDLE_ICONS, name), options)?;
+        zip.write_all(&fs::read(source)?)?;
+    }
+    summary.icons = icons.len();
+
+    if let Some(log_toml) = resources.log_toml() {
+        zip.start_file(BUNDLE_LOG, options)?;
+        zip.write_all(&fs::read(log_toml)?)?;
+    }
+
+    zip.finish()?;
+    log::info!("Exported {} boards and {} icons to {:?}", summary.boards, summary.icons, zip_path);
+    Ok(summary)
+}
+
+/// Extracts a bundle made by `export_all` into `config_dir`, created when missing.
+/// The whole bundle is validated before anything is written. An existing settings file is kept as `.bak`.
+/// Bundled icons clashing with different existing ones are handled by `policy`: renamed with the settings updated
+/// to match, written over the existing ones kept as `.bak`, or left out for the existing ones.
+/// The log configuration is only written when the folder has none.
+pub fn import_all(zip_path: &Path, config_dir: &Path, policy: ImportPolicy) -> Result<BundleSummary, Box<dyn std::error::Error>> {
+    let mut archive = ZipArchive::new(fs::File::open(zip_path)?)?;
+
+    let mut settings_text: Option<String> = None;
+    let mut log_toml: Option<Vec<u8>> = None;
+    let mut icons: HashMap<String, Vec<u8>> = HashMap::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let entry_name = entry.name().to_string();
+        let path = entry.enclosed_name()
+            .ok_or_else(|| format!("Invalid entry '{}' in bundle", entry_name))?;
+        let parts: Vec<String> = path.iter().map(|p| p.to_string_lossy().to_string()).collect();
+
+        match parts.iter().map(|p| p.as_str()).collect::<Vec<_>>().as_slice() {
+            [BUNDLE_SETTINGS] => {
+                let mut text = String::new();
+                entry.read_to_string(&mut text)?;
+                settings_text = Some(text);
+            },
+            [BUNDLE_LOG] => {
+                let mut bytes = vec![];
+                entry.read_to_end(&mut bytes)?;
+                log_toml = Some(bytes);
+            },
+            [BUNDLE_ICONS, name] => {
+                let mut bytes = vec![];
+                entry.read_to_end(&mut bytes)?;
+                icons.insert(name.to_string(), bytes);
+            },
+            _ => return Err(format!("Unexpected entry '{}' in bundle", entry_name).into()),
+        }
+    }
+
+    let settings_text = settings_text.ok_or_else(|| format!("Bundle has no {}", BUNDLE_SETTINGS))?;
+    let mut data = serde_json::from_str::<SettingsData>(&settings_text)
+        .map_err(|e| format!("Invalid {} in bundle: {}", BUNDLE_SETTINGS, e))?;
+
+    if !data.includes().is_empty() {
+        return Err(format!("Bundle settings reference include files: {}", data.includes().join(", ")).into());
+    }
+    data.validate_name_uniquenes()
+        .and_then(|_| data.validate_data_integrity())
+        .map_err(|e| format!("Bundle settings are not valid: {}", e))?;
+
+    // Settle the final icon names against what is already in the folder
+    let resources = Resources::new(vec![config_dir.to_path_buf()]);
+    let mut names: Vec<_> = icons.keys().cloned().collect();
+    names.sort();
+
+    let mut final_names: HashMap<String, String> = HashMap::new();
+    let mut summary = BundleSummary { boards: data.boards.len(), icons: icons.len(), ..Default::default() };
+    let differs = |candidate: &str, bytes: Option<&Vec<u8>>| {
+        let existing = resources.local_icon(candidate);
+        existing.exists() && fs::read(&existing).ok().as_ref() != bytes
+    };
+    for name in names {
+        let final_name = match policy {
+            ImportPolicy::Rename => unique_name(&name, |candidate| {
+                differs(candidate, icons.get(&name)) || final_names.values().any(|taken| taken == candidate)
+            }),
+            _ => name.clone(),
+        };
+        if final_name != name {
+            log::info!("Bundled icon '{}' imported as '{}', a different icon has that name", name, final_name);
+            summary.renamed_icons += 1;
+            summary.outcomes.push(ImportOutcome::Renamed { name: name.clone(), new_name: final_name.clone() });
+        } else if differs(&name, icons.get(&name)) {
+            let outcome = match policy {
+                ImportPolicy::Skip => ImportOutcome::Skipped(name.clone()),
+                _ => ImportOutcome::Overwritten(name.clone()),
+            };
+            log::info!("Bundled icon {}, a different icon has that name", outcome);
+            summary.outcomes.push(outcome);
+        }
+        final_names.insert(name, final_name);
+    }
+
+    rewrite_icons(&mut data, |icon| {
+        final_names.get(icon)
+            .cloned()
+            .ok_or_else(|| format!("Icon '{}' is referenced but missing from the bundle", icon).into())
+    })?;
+
+    // Everything checked, write it out
+    fs::create_dir_all(config_dir.join(resources.names().icons_dir()))?;
+    for (name, bytes) in &icons {
+        match summary.outcomes.iter().find(|outcome| matches!(outcome, ImportOutcome::Skipped(n) | ImportOutcome::Overwritten(n) if n == name)) {
+            Some(ImportOutcome::Skipped(_)) => continue,
+            Some(_) => {
+                let backup_path = resources.local_icon(&format!("{}.bak", name));
+                log::info!("Keeping the existing icon '{}' as {:?}", name, backup_path);
+                fs::rename(resources.local_icon(name), backup_path)?;
+            },
+            None => {},
+        }
+        fs::write(resources.local_icon(&final_names[name]), bytes)?;
+    }
+
+    let settings_path = config_dir.join(resources.names().settings_json());
+    if settings_path.exists() {
+        let backup_path = config_dir.join(format!("{}.bak", resources.names().settings_json()));
+        log::info!("Keeping the existing settings as {:?}", backup_path);
+        fs::rename(&settings_path, backup_path)?;
+    }
+    fs::write(&settings_path, serde_json::to_string_pretty(&data)?)?;
+
+    if let Some(bytes) = log_toml {
+        let log_path = config_dir.join(resources.names().log_toml());
+        if !log_path.exists() {
+            fs::write(log_path, bytes)?;
+        }
+    }
+
+    log::info!("Imported {} boards and {} icons into {:?}", summary.boards, summary.icons, config_dir);
+    Ok(summary)
+}
+
+/// Replaces every board and pad icon with the name returned for it
+fn rewrite_icons<F>(data: &mut SettingsData, mut rename: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnMut(&str) -> Result<String, Box<dyn std::error::Error>>,
+{
+    let board_icons = data.boards.iter_mut().map(|b| &mut b.icon);
+    let pad_icons = data.padsets.iter_mut().flat_map(|ps| ps.items.iter_mut()).map(|p| &mut p.icon);
+
+    for icon in board_icons.chain(pad_icons) {
+        if let Some(name) = icon.as_ref().filter(|name| !name.is_empty()) {
+            *icon = Some(rename(name)?);
+        }
+    }
+    Ok(())
+}
+
+/// `file_name`, or `stem_2.ext`, `stem_3.ext`, ... if it's taken
+fn unique_name(file_name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let path = Path::new(file_name);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+
+    let mut name = file_name.to_string();
+    let mut counter = 2;
+    while taken(&name) {
+        name = format!("{}_{}{}", stem, counter, extension);
+        counter += 1;
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Board, Pad, PadSet};
+
+    fn write_config(dir: &Path, boards: Vec<Board>, padsets: Vec<PadSet>) {
+        let mut data = SettingsData::default();
+        data.boards = boards;
+        data.padsets = padsets;
+        fs::write(dir.join(Resources::new(vec![]).names().settings_json()), serde_json::to_string_pretty(&data).unwrap()).unwrap();
+    }
+
+    fn board_with_icon(name: &str, icon: &str) -> Board {
+        Board { name: name.to_string(), icon: Some(icon.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn test_unique_name() {
+        assert_eq!(unique_name("a.png", |_| false), "a.png");
+        assert_eq!(unique_name("a.png", |n| n == "a.png" || n == "a_2.png"), "a_3.png");
+        assert_eq!(unique_name("noext", |n| n == "noext"), "noext_2");
+    }
+
+    #[test]
+    fn test_export_import_cycle() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+
+        // Two different icons sharing a file name, one of them in a subfolder
+        fs::create_dir_all(source.path().join("icons").join("brand")).unwrap();
+        fs::write(source.path().join("icons").join("app.png"), b"local").unwrap();
+        fs::write(source.path().join("icons").join("brand").join("app.png"), b"brand").unwrap();
+
+        let mut padset = PadSet::default();
+        padset.name = "pads".to_string();
+        padset.items = vec![Pad { icon: Some("app.png".to_string()), ..Default::default() }];
+        write_config(source.path(), vec![board_with_icon("local", "app.png"), board_with_icon("brand", "brand/app.png")], vec![padset]);
+
+        let zip_path = source.path().join("bundle.zip");
+        let exported = export_all(&Resources::new(vec![source.path().to_path_buf()]), &zip_path).unwrap();
+        assert_eq!(exported.icons, 2);
+        assert_eq!(exported.renamed_icons, 1);
+
+        // The target already has a different app.png and settings of its own
+        fs::create_dir_all(target.path().join("icons")).unwrap();
+        fs::write(target.path().join("icons").join("app.png"), b"other").unwrap();
+        write_config(target.path(), vec![], vec![]);
+
+        let imported = import_all(&zip_path, target.path(), ImportPolicy::Rename).unwrap();
+        assert_eq!(imported.boards, 2);
+        assert!(target.path().join("settings.json.bak").exists());
+
+        let text = fs::read_to_string(target.path().join("settings.json")).unwrap();
+        let data = serde_json::from_str::<SettingsData>(&text).unwrap();
+        let icon_of = |name: &str| data.boards.iter().find(|b| b.name == name).and_then(|b| b.icon.clone()).unwrap();
+
+        assert_eq!(fs::read(target.path().join("icons").join(icon_of("local"))).unwrap(), b"local");
+        assert_eq!(fs::read(target.path().join("icons").join(icon_of("brand"))).unwrap(), b"brand");
+        assert_eq!(data.padsets[0].items[0].icon, Some(icon_of("local")));
+        assert_eq!(fs::read(target.path().join("icons").join("app.png")).unwrap(), b"other");
+    }
+
+    #[test]
+    fn test_import_icon_policies() {
+        let source = tempfile::tempdir().unwrap();
+        fs::create_dir_all(source.path().join("icons")).unwrap();
+        fs::write(source.path().join("icons").join("app.png"), b"bundled").unwrap();
+        write_config(source.path(), vec![board_with_icon("app", "app.png")], vec![]);
+        let zip_path = source.path().join("bundle.zip");
+        export_all(&Resources::new(vec![source.path().to_path_buf()]), &zip_path).unwrap();
+
+        let import = |policy: ImportPolicy| {
+            let target = tempfile::tempdir().unwrap();
+            fs::create_dir_all(target.path().join("icons")).unwrap();
+            fs::write(target.path().join("icons").join("app.png"), b"existing").unwrap();
+            let summary = import_all(&zip_path, target.path(), policy).unwrap();
+            let data = serde_json::from_str::<SettingsData>(&fs::read_to_string(target.path().join("settings.json")).unwrap()).unwrap();
+            (target, summary, data.boards[0].icon.clone().unwrap())
+        };
+
+        let (target, summary, icon) = import(ImportPolicy::Overwrite);
+        assert_eq!(summary.outcomes, vec![ImportOutcome::Overwritten("app.png".to_string())]);
+        assert_eq!(icon, "app.png");
+        assert_eq!(fs::read(target.path().join("icons").join("app.png")).unwrap(), b"bundled");
+        assert_eq!(fs::read(target.path().join("icons").join("app.png.bak")).unwrap(), b"existing");
+
+        let (target, summary, icon) = import(ImportPolicy::Skip);
+        assert_eq!(summary.outcomes, vec![ImportOutcome::Skipped("app.png".to_string())]);
+        assert_eq!(icon, "app.png");
+        assert_eq!(fs::read(target.path().join("icons").join("app.png")).unwrap(), b"existing");
+
+        let (_, summary, icon) = import(ImportPolicy::Rename);
+        assert_eq!(summary.outcomes, vec![ImportOutcome::Renamed { name: "app.png".to_string(), new_name: "app_2.png".to_string() }]);
+        assert_eq!(icon, "app_2.png");
+    }
+
+    #[test]
+    fn test_import_rejects_unexpected_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("bundle.zip");
+
+        let mut zip = ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        zip.start_file("settings.json", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.start_file("scripts/run.bat", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"echo").unwrap();
+        zip.finish().unwrap();
+
+        let target = dir.path().join("config");
+        assert!(import_all(&zip_path, &target, ImportPolicy::default()).is_err());
+        assert!(!target.exists());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+
+use crate::core::data::{Board, ColorScheme, PadSet, TextStyle};
+use super::persistence::{BoardToggleSettings, ScheduledBoard, SettingsData};
+
+/// The part of the settings edits change, with the settings opening boards by name that follow a rename.
+/// Other settings such as the layout are not undone.
+pub struct Snapshot {
+    boards: Vec<Board>,
+    padsets: Vec<PadSet>,
+    color_schemes: Vec<ColorScheme>,
+    text_styles: Vec<TextStyle>,
+    board_toggle: Option<BoardToggleSettings>,
+    schedule: Vec<ScheduledBoard>,
+    home_key_board: Option<String>,
+    version: u64,
+}
+
+impl Snapshot {
+    pub fn of(data: &SettingsData) -> Self {
+        Self {
+            boards: data.boards.clone(),
+            padsets: data.padsets.clone(),
+            color_schemes: data.color_schemes.clone(),
+            text_styles: data.text_styles.clone(),
+            board_toggle: data.board_toggle.clone(),
+            schedule: data.schedule.clone(),
+            home_key_board: data.home_key_board.clone(),
+            version: 0,
+        }
+    }
+
+    /// The recent boards are a history of opened boards rather than an edit, only the ones gone are dropped.
+    /// Window positions aren't edits either, the boards keep the ones they have.
+    fn restore(mut self, data: &mut SettingsData) {
+        for board in &mut self.boards {
+            if let Some(current) = data.boards.iter().find(|current| current.name == board.name) {
+                board.rect = current.rect;
+            }
+        }
+        data.boards = self.boards;
+        data.padsets = self.padsets;
+        data.color_schemes = self.color_schemes;
+        data.text_styles = self.text_styles;
+        data.board_toggle = self.board_toggle;
+        data.schedule = self.schedule;
+        data.home_key_board = self.home_key_board;
+        let boards = &data.boards;
+        data.recent_boards.retain(|name| boards.iter().any(|board| &board.name == name));
+    }
+}
+
+/// Undo and redo of the settings edits, kept as snapshots taken before each edit.
+/// Every state gets a version, the settings are modified while it differs from the version last saved.
+pub struct EditHistory {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    version: u64,
+    saved_version: u64,
+    next_version: u64,
+    /// Settings before the outermost open group, its edits are recorded as one when it ends
+    group: Option<Snapshot>,
+    group_depth: usize,
+    group_edited: bool,
+}
+
+impl EditHistory {
+    pub fn new() -> Self {
+        Self { undo: VecDeque::new(), redo: vec![], version: 0, saved_version: 0, next_version: 1, group: None, group_depth: 0, group_edited: false }
+    }
+
+    /// Remembers the settings as they were before an edit, the oldest beyond `steps` are forgotten.
+    /// Edits undone before can't be redone anymore.
+    pub fn record(&mut self, mut before: Snapshot, steps: usize) {
+        if self.group_depth > 0 {
+            self.group_edited = true;
+            return;
+        }
+        before.version = self.version;
+        self.undo.push_back(before);
+        while self.undo.len() > steps {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+        self.version = self.next_version;
+        self.next_version += 1;
+    }
+
+    /// Starts edits undone as one, e.g. a board added with its padset. Groups can be nested, the outermost one counts.
+    pub fn begin_group(&mut self, data: &SettingsData) {
+        if self.group_depth == 0 {
+            self.group = Some(Snapshot::of(data));
+            self.group_edited = false;
+        }
+        self.group_depth += 1;
+    }
+
+    /// Ends a group, the outermost one is recorded as a single edit when anything was edited in it
+    pub fn end_group(&mut self, steps: usize) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0 {
+            if let Some(before) = self.group.take().filter(|_| self.group_edited) {
+                self.record(before, steps);
+            }
+        }
+    }
+
+    /// Brings back the settings before the last edit, returns whether there was one
+    pub fn undo(&mut self, data: &mut SettingsData) -> bool {
+        let Some(snapshot) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(Snapshot { version: self.version, ..Snapshot::of(data) });
+        self.version = snapshot.version;
+        snapshot.restore(data);
+        true
+    }
+
+    /// Makes the last undone edit again, returns whether there was one
+    pub fn redo(&mut self, data: &mut SettingsData) -> bool {
+        let Some(snapshot) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push_back(Snapshot { version: self.version, ..Snapshot::of(data) });
+        self.version = snapshot.version;
+        snapshot.restore(data);
+        true
+    }
+
+    /// Whether the edited settings differ from the ones saved, undoing back to them makes them unmodified again
+    pub fn is_modified(&self) -> bool {
+        self.version != self.saved_version
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_version = self.version;
+    }
+
+    /// Forgets all edits, for settings reloaded from the file
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.saved_version = self.version;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(history: &mut EditHistory, data: &mut SettingsData, board: &str, steps: usize) {
+        history.record(Snapshot::of(data), steps);
+        data.boards.push(Board { name: board.to_string(), ..Default::default() });
+    }
+
+    fn board_names(data: &SettingsData) -> Vec<&str> {
+        data.boards.iter().map(|board| board.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_edit_history() {
+        let mut data = SettingsData { boards: vec![], ..Default::default() };
+        let mut history = EditHistory::new();
+        assert!(!history.undo(&mut data));
+
+        edit(&mut history, &mut data, "a", 20);
+        edit(&mut history, &mut data, "b", 20);
+        assert!(history.is_modified());
+        history.mark_saved();
+
+        // Undone and redone back to the saved settings
+        assert!(history.undo(&mut data));
+        assert_eq!(board_names(&data), vec!["a"]);
+        assert!(history.is_modified());
+        assert!(history.redo(&mut data));
+        assert_eq!(board_names(&data), vec!["a", "b"]);
+        assert!(!history.is_modified());
+        assert!(!history.redo(&mut data));
+
+        // A new edit drops the undone ones
+        assert!(history.undo(&mut data));
+        edit(&mut history, &mut data, "c", 20);
+        assert!(!history.redo(&mut data));
+        assert_eq!(board_names(&data), vec!["a", "c"]);
+
+        // Other settings are left as they are
+        data.timeout = 1234;
+        assert!(history.undo(&mut data));
+        assert!(history.undo(&mut data));
+        assert!(data.boards.is_empty());
+        assert_eq!(data.timeout, 1234);
+        assert!(history.is_modified());
+    }
+
+    #[test]
+    fn test_edit_history_steps() {
+        let mut data = SettingsData { boards: vec![], ..Default::default() };
+        let mut history = EditHistory::new();
+        for board in ["a", "b", "c", "d"] {
+            edit(&mut history, &mut data, board, 2);
+        }
+        assert!(history.undo(&mut data));
+        assert!(history.undo(&mut data));
+        assert!(!history.undo(&mut data));
+        assert_eq!(board_names(&data), vec!["a", "b"]);
+
+        history.clear();
+        assert!(!history.is_modified());
+        assert!(!history.redo(&mut data));
+    }
+
+    #[test]
+    fn test_edit_history_group() {
+        let mut data = SettingsData { boards: vec![], ..Default::default() };
+        let mut history = EditHistory::new();
+        edit(&mut history, &mut data, "a", 20);
+
+        history.begin_group(&data);
+        edit(&mut history, &mut data, "b", 20);
+        history.begin_group(&data);
+        edit(&mut history, &mut data, "c", 20);
+        history.end_group(20);
+        edit(&mut history, &mut data, "d", 20);
+        history.end_group(20);
+
+        // A group without edits is no step
+        history.begin_group(&data);
+        history.end_group(20);
+
+        assert!(history.undo(&mut data));
+        assert_eq!(board_names(&data), vec!["a"]);
+        assert!(history.redo(&mut data));
+        assert_eq!(board_names(&data), vec!["a", "b", "c", "d"]);
+        assert!(history.undo(&mut data));
+        assert!(history.undo(&mut data));
+        assert!(!history.undo(&mut data));
+    }
+}
@@ -1,6 +1,9 @@
 mod persistence;
+mod history;
 mod validation;
 mod settings;
+mod bundle;
 
-pub use persistence::LayoutSettings;
+pub use persistence::{LayoutSettings, BoardToggleSettings, AutoSwitchSettings, ScheduledBoard, ClosedTarget, TrayAction, TraySettings};
 pub use settings::Settings;
+pub use bundle::{export_all, import_all, BundleSummary};
@@ -2,8 +2,8 @@ use std::{collections::HashMap, fs, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use crate::core::{Board, ColorScheme, PadSet, TextStyle, Resources};
-use crate::core::data::{DEFAULT_EDITOR, DEFAULT_FEEDBACK, DEFAULT_TIMEOUT};
+use crate::core::{parse_time_of_day, Board, BoardType, ColorScheme, Detection, Limits, PadKeys, PadSet, Renderer, TextStyle, Resources};
+use crate::core::data::{clamp_opacity, DEFAULT_AUTO_SWITCH_DEBOUNCE, DEFAULT_EDITOR, DEFAULT_FEEDBACK, DEFAULT_TIMEOUT, HOME_BOARD_NAME, SETTINGS_BOARD_NAME};
 use super::validation::SettingsValidator;
 
 
@@ -87,6 +87,71 @@ pub struct LayoutSettings {
     pub window_style: String, // "Window" | "Floating" | "Taskbar"
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BoardToggleSettings {
+    pub hotkey: String,   // e.g. "Ctrl Alt NumPad1"
+    pub boards: [String; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AutoSwitchSettings {
+    #[serde(default = "default_auto_switch_debounce")]
+    pub debounce: u64,  // milliseconds the foreground app has to keep focus before switching
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub open: bool,     // also open the detected board when no board is displayed
+}
+
+fn default_auto_switch_debounce() -> u64 {
+    DEFAULT_AUTO_SWITCH_DEBOUNCE
+}
+
+/// A board opened at a time of day, e.g. a standup board at "09:45"
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScheduledBoard {
+    pub time: String,   // "HH:MM", 24-hour local time
+    pub board: String,
+}
+
+impl ScheduledBoard {
+    /// Minutes since midnight, None for a malformed time
+    pub fn minute_of_day(&self) -> Option<u32> {
+        parse_time_of_day(&self.time)
+    }
+}
+
+/// What a click on the tray icon does
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrayAction {
+    None,
+    Menu,     // the tray menu, same as a right click
+    Home,     // the home board, same as "Open HotKeys"
+    Settings, // the settings board
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TraySettings {
+    pub click: TrayAction,
+    pub double_click: TrayAction,
+    pub middle_click: TrayAction,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self { click: TrayAction::Menu, double_click: TrayAction::Home, middle_click: TrayAction::None }
+    }
+}
+
+/// What pad actions do when the window the board was opened over has been closed
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClosedTarget {
+    #[default]
+    Foreground, // run them in whatever window is in the foreground now
+    Abort,      // skip them and log a warning
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 #[serde(default)]
 struct ComponentsData {
@@ -114,6 +179,94 @@ pub struct SettingsData {
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub natural_key_order: bool,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pad_keys: Option<PadKeys>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub letter_keys: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub open_in_edit_mode: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board_toggle: Option<BoardToggleSettings>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_switch: Option<AutoSwitchSettings>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<ScheduledBoard>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modifier_grace: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_target: Option<ClosedTarget>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tray: Option<TraySettings>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub flash_feedback: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub show_cooldown: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fast_render: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renderer: Option<Renderer>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub structured_shortcuts: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_and_close_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy_board_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help_modifier: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_key_board: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<Limits>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen_version: Option<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub restore_last_board: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_board: Option<String>,
+
+    /// Most recently used boards, the latest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_boards: Vec<String>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     includes: Vec<String>,
 
@@ -134,6 +287,35 @@ impl Default for SettingsData {
             padsets: vec![],
             layout: None,
             natural_key_order: false,
+            pad_keys: None,
+            letter_keys: false,
+            open_in_edit_mode: false,
+            board_toggle: None,
+            auto_switch: None,
+            idle_timeout: None,
+            schedule: vec![],
+            modifier_grace: None,
+            closed_target: None,
+            tray: None,
+            flash_feedback: false,
+            show_cooldown: false,
+            fast_render: false,
+            renderer: None,
+            structured_shortcuts: false,
+            save_and_close_key: None,
+            copy_board_key: None,
+            help_modifier: None,
+            help_key: None,
+            stack_key: None,
+            focus_key: None,
+            home_key: None,
+            home_key_board: None,
+            log_level: None,
+            limits: None,
+            last_seen_version: None,
+            restore_last_board: false,
+            last_board: None,
+            recent_boards: vec![],
             includes: vec![],
             source_mappings: vec![],
         }
@@ -191,6 +373,57 @@ impl ComponentsData {
 
 
 impl SettingsData {
+    /// Minimal built-in configuration used when the settings file cannot be loaded
+    pub fn safe_mode() -> Self {
+        let home_board = |name: &str| Board {
+            board_type: BoardType::Home,
+            name: name.to_string(),
+            detection: Detection::None,
+            ..Default::default()
+        };
+
+        Self {
+            boards: vec![home_board(HOME_BOARD_NAME), home_board(SETTINGS_BOARD_NAME)],
+            ..Default::default()
+        }
+    }
+
+    /// Copy with the components of all included files in the main file
+    pub(super) fn flattened(&self) -> Self {
+        let mut data = self.clone();
+        data.includes = vec![];
+        data.source_mappings = vec![];
+        data
+    }
+
+    /// Moves the board to the front of the recently used boards, the oldest beyond the limit are dropped.
+    /// Returns whether the list changed.
+    pub(super) fn add_recent_board(&mut self, board_name: &str) -> bool {
+        if self.recent_boards.first().map(String::as_str) == Some(board_name) {
+            return false;
+        }
+        self.recent_boards.retain(|name| name != board_name);
+        self.recent_boards.insert(0, board_name.to_string());
+        self.recent_boards.truncate(self.limits.clone().unwrap_or_default().recent_boards);
+        true
+    }
+
+    /// Drops the recently used boards that no longer exist or are listed twice, and the ones beyond the limit
+    fn prune_recent_boards(&mut self) {
+        let mut recent_boards: Vec<String> = vec![];
+        for name in std::mem::take(&mut self.recent_boards) {
+            if !recent_boards.contains(&name) && self.boards.iter().any(|board| board.name == name) {
+                recent_boards.push(name);
+            }
+        }
+        recent_boards.truncate(self.limits.clone().unwrap_or_default().recent_boards);
+        self.recent_boards = recent_boards;
+    }
+
+    pub(super) fn includes(&self) -> &[String] {
+        &self.includes
+    }
+
     fn as_components(&self) -> ComponentsData {
         ComponentsData {
             color_schemes: self.color_schemes.clone(),
@@ -227,6 +460,31 @@ impl SettingsFileStroage {
 
     /// Load settings from the main settings file and all included files
     pub fn load(&self) -> Result<SettingsData, Box<dyn std::error::Error>> {
+        let mut settings = self.load_unchecked()?;
+
+        // Validate the entire settings configuration (data integrity only)
+        settings.validate_data_integrity()
+            .map_err(|e| format!("Settings data integrity validation failed: {}", e))?;
+
+        // Validate resource-dependent aspects
+        self.validate_icons_availability(&settings)
+            .map_err(|e| format!("Icon availability validation failed: {}", e))?;
+
+        settings.limits = settings.limits.map(|limits| limits.clamped());
+        settings.prune_recent_boards();
+        for board in &mut settings.boards {
+            board.opacity = board.opacity.map(clamp_opacity);
+            if let BoardType::Chain(params) = &mut board.board_type {
+                params.correct_initial_board(&board.name);
+            }
+        }
+
+        Ok(settings)
+    }
+
+    /// Loads the settings and their includes checking only that names are unique,
+    /// for reporting all the problems of a configuration instead of stopping at the first one
+    pub fn load_unchecked(&self) -> Result<SettingsData, Box<dyn std::error::Error>> {
         let settings_path: PathBuf = self.resources.settings_json().unwrap();
 
         if !settings_path.exists() {
@@ -257,16 +515,7 @@ impl SettingsFileStroage {
                 .map_err(|e| format!("Validation error in included file '{:?}': {}", include_path, e))?;
         }
 
-        // Validate the entire settings configuration (data integrity only)
-        settings.validate_data_integrity()
-            .map_err(|e| format!("Settings data integrity validation failed: {}", e))?;
-
-        // Validate resource-dependent aspects
-        self.validate_icons_availability(&settings)
-            .map_err(|e| format!("Icon availability validation failed: {}", e))?;
-
         settings.source_mappings = source_mappings;
-
         Ok(settings)
     }
 
@@ -341,7 +590,7 @@ impl SettingsFileStroage {
 mod tests {
 
     use super::*;
-    use crate::core::data::{TextStyle, ColorScheme, Board, PadSet};
+    use crate::core::data::{TextStyle, ColorScheme, Board, Pad, PadSet};
 
     fn new_mapping(type_name: &str, name: &str, source: Option<&str>) -> SourceMapping {
         SourceMapping {
@@ -492,6 +741,95 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_safe_mode_settings_are_valid() {
+        let settings = SettingsData::safe_mode();
+
+        assert!(settings.boards.iter().any(|b| b.name == HOME_BOARD_NAME));
+        assert!(settings.validate_name_uniquenes().is_ok());
+        assert!(settings.validate_data_integrity().is_ok());
+    }
+
+    #[test]
+    fn test_board_hotkey_validation() {
+        let mut settings = SettingsData::safe_mode();
+        settings.boards[0].hotkey = Some("Ctrl Alt H".to_string());
+        assert!(settings.validate_data_integrity().is_ok());
+
+        // A board hotkey is a single combination, not a chord
+        settings.boards[0].hotkey = Some("Ctrl K + Ctrl H".to_string());
+        assert!(settings.validate_data_integrity().is_err());
+    }
+
+    #[test]
+    fn test_pad_background_validation() {
+        let mut settings = SettingsData::safe_mode();
+        let mut padset = new_padset("pads");
+        padset.items.push(Pad { background: Some("#204060".to_string()), ..Default::default() });
+        settings.padsets.push(padset);
+        assert!(settings.validate_data_integrity().is_ok());
+
+        settings.padsets[0].items[0].background = Some("navy".to_string());
+        assert!(settings.validate_data_integrity().is_err());
+    }
+
+    #[test]
+    fn test_reference_validation_matches_dangling_references() {
+        let mut settings = SettingsData::safe_mode();
+        let mut chain = new_board("chain");
+        chain.board_type = crate::core::BoardType::Chain(crate::core::integration::ChainParams {
+            boards: "missing".to_string(),
+            initial_board: None,
+            params: vec![],
+        });
+        settings.boards.push(chain);
+
+        let problem = crate::core::data::dangling_references(&settings.boards, &settings.padsets, &settings.color_schemes, &settings.text_styles)
+            .remove(0);
+        let error = settings.validate_data_integrity().unwrap_err();
+        assert!(error.ends_with(&problem.to_string()), "{}", error);
+    }
+
+    #[test]
+    fn test_recent_boards() {
+        let mut settings = SettingsData {
+            boards: vec![new_board("home"), new_board("code"), new_board("chrome"), new_board("git")],
+            limits: Some(Limits { recent_boards: 3, ..Default::default() }),
+            ..Default::default()
+        };
+
+        assert!(settings.add_recent_board("code"));
+        assert!(settings.add_recent_board("chrome"));
+        assert!(!settings.add_recent_board("chrome"));
+        assert!(settings.add_recent_board("code"));
+        assert_eq!(settings.recent_boards, vec!["code", "chrome"]);
+
+        assert!(settings.add_recent_board("git"));
+        assert!(settings.add_recent_board("home"));
+        assert_eq!(settings.recent_boards, vec!["home", "git", "code"]);
+
+        // Deleted boards and duplicates edited into the file are dropped on load
+        settings.recent_boards = ["deleted", "git", "code", "git", "chrome", "home"].map(String::from).to_vec();
+        settings.prune_recent_boards();
+        assert_eq!(settings.recent_boards, vec!["git", "code", "chrome"]);
+    }
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("00:00"), Some(0));
+        assert_eq!(parse_time_of_day("09:45"), Some(9 * 60 + 45));
+        assert_eq!(parse_time_of_day("9:05"), Some(9 * 60 + 5));
+        assert_eq!(parse_time_of_day(" 23:59 "), Some(23 * 60 + 59));
+
+        assert_eq!(parse_time_of_day("24:00"), None);
+        assert_eq!(parse_time_of_day("12:60"), None);
+        assert_eq!(parse_time_of_day("12:5"), None);
+        assert_eq!(parse_time_of_day("1200"), None);
+        assert_eq!(parse_time_of_day("+1:30"), None);
+        assert_eq!(parse_time_of_day("9:45 AM"), None);
+        assert_eq!(parse_time_of_day(""), None);
+    }
+
     #[test]
     fn test_save_load_cycle() {
         let config_dir = std::env::current_dir().unwrap().join("test_resources");
@@ -534,6 +872,35 @@ mod tests {
             padsets: vec![],
             layout: None,
             natural_key_order: true,
+            pad_keys: Some(PadKeys::Numpad),
+            letter_keys: true,
+            open_in_edit_mode: true,
+            board_toggle: None,
+            auto_switch: Some(AutoSwitchSettings { debounce: 250, open: true }),
+            idle_timeout: Some(300),
+            schedule: vec![ScheduledBoard { time: "09:45".to_string(), board: "code".to_string() }],
+            modifier_grace: Some(30),
+            closed_target: Some(ClosedTarget::Abort),
+            flash_feedback: true,
+            show_cooldown: true,
+            fast_render: true,
+            renderer: Some(Renderer::Direct2D),
+            structured_shortcuts: true,
+            tray: Some(TraySettings { click: TrayAction::Home, double_click: TrayAction::None, middle_click: TrayAction::Settings }),
+            save_and_close_key: Some("Ctrl+S".to_string()),
+            copy_board_key: Some("Ctrl+Shift+C".to_string()),
+            help_modifier: Some("Ctrl+Alt".to_string()),
+            help_key: Some("F12".to_string()),
+            stack_key: Some("Ctrl+Shift+F12".to_string()),
+            focus_key: Some("Ctrl+F6".to_string()),
+            home_key: Some("Ctrl+H".to_string()),
+            home_key_board: Some("code".to_string()),
+            log_level: Some("debug".to_string()),
+            limits: Some(Limits { palette_results: 18, ..Default::default() }),
+            last_seen_version: Some("0.1.0".to_string()),
+            restore_last_board: true,
+            last_board: Some("code".to_string()),
+            recent_boards: vec!["code".to_string(), "home".to_string()],
             includes: vec![],
             source_mappings: vec![],
         };
@@ -553,6 +920,28 @@ mod tests {
         assert_eq!(settings.timeout, reloaded_settings.timeout);
         assert_eq!(settings.feedback, reloaded_settings.feedback);
         assert_eq!(settings.editor, reloaded_settings.editor);
+        assert_eq!(settings.closed_target, reloaded_settings.closed_target);
+        assert_eq!(settings.pad_keys, reloaded_settings.pad_keys);
+        assert_eq!(settings.letter_keys, reloaded_settings.letter_keys);
+        assert_eq!(settings.open_in_edit_mode, reloaded_settings.open_in_edit_mode);
+        assert_eq!(settings.limits, reloaded_settings.limits);
+        assert_eq!(settings.flash_feedback, reloaded_settings.flash_feedback);
+        assert_eq!(settings.show_cooldown, reloaded_settings.show_cooldown);
+        assert_eq!(settings.fast_render, reloaded_settings.fast_render);
+        assert_eq!(settings.renderer, reloaded_settings.renderer);
+        assert_eq!(settings.modifier_grace, reloaded_settings.modifier_grace);
+        assert_eq!(settings.schedule, reloaded_settings.schedule);
+        assert_eq!(settings.help_key, reloaded_settings.help_key);
+        assert_eq!(settings.stack_key, reloaded_settings.stack_key);
+        assert_eq!(settings.focus_key, reloaded_settings.focus_key);
+        assert_eq!(settings.home_key, reloaded_settings.home_key);
+        assert_eq!(settings.home_key_board, reloaded_settings.home_key_board);
+        assert_eq!(settings.log_level, reloaded_settings.log_level);
+        assert_eq!(settings.structured_shortcuts, reloaded_settings.structured_shortcuts);
+        assert_eq!(settings.tray.as_ref().map(|t| t.click), reloaded_settings.tray.as_ref().map(|t| t.click));
+        assert_eq!(settings.restore_last_board, reloaded_settings.restore_last_board);
+        assert_eq!(settings.last_board, reloaded_settings.last_board);
+        assert_eq!(settings.recent_boards, reloaded_settings.recent_boards);
 
         assert_eq!(settings.color_schemes.len(), reloaded_settings.color_schemes.len());
         for (original, reloaded) in settings.color_schemes.iter().zip(reloaded_settings.color_schemes.iter()) {
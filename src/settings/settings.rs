@@ -1,33 +1,57 @@
 use std::cell::{RefCell, Cell};
 use std::rc::Rc;
 
-use crate::core::data::{Board, ColorScheme, Detection, PadSet, TextStyle};
+use log::LevelFilter;
+
+use crate::core::data::{dangling_references, detect_board, Board, ColorScheme, DanglingReference, Detection, Limits, Pad, PadKeys, PadSet, Renderer, TextStyle, WindowRect};
+use crate::core::{ActionType, KeyChord};
 use crate::core::repository::{SettingsRepository, SettingsRepositoryMut};
 use crate::core::{Resources};
 
-use super::persistence::{SettingsData, SettingsFileStroage, LayoutSettings};
-use crate::core::data::{HOME_BOARD_NAME};
+use super::history::{EditHistory, Snapshot};
+use super::persistence::{SettingsData, SettingsFileStroage, LayoutSettings, BoardToggleSettings, AutoSwitchSettings, ScheduledBoard, ClosedTarget, TraySettings};
+use crate::core::data::{DEFAULT_COPY_BOARD_KEY, DEFAULT_FOCUS_KEY, DEFAULT_HELP_KEY, DEFAULT_HELP_MODIFIER, DEFAULT_HOME_KEY, DEFAULT_SAVE_AND_CLOSE_KEY, HOME_BOARD_NAME, MAX_MODIFIER_GRACE};
+use crate::input::{ModifierState, script::{parse_hotkey, parse_shortcut_keys}};
 
 
 /// Main Settings implementation - orchestrates domain and infrastructure
 pub struct Settings {
     data: RefCell<SettingsData>,
+    /// Changes outside the edit history, such as the layout
     dirty: Cell<bool>,
+    history: RefCell<EditHistory>,
     resources: Resources,
+    load_error: RefCell<Option<String>>,
 }
 
 impl Settings {
     /// Create Settings from loaded data
-    fn from_data(data: SettingsData, resources: Resources) -> Rc<Self> {
+    fn from_data(mut data: SettingsData, resources: Resources) -> Rc<Self> {
+        migrate_shortcuts(&mut data);
         let settings = Rc::new(Self {
             data: RefCell::new(data),
             dirty: Cell::new(false),
+            history: RefCell::new(EditHistory::new()),
             resources,
+            load_error: RefCell::new(None),
         });
 
         settings
     }
 
+    /// Settings with the minimal built-in configuration, remembering why the settings file failed to load.
+    /// Nothing is written to the settings file until it loads successfully again.
+    pub fn safe_mode(resources: Resources, load_error: String) -> Rc<Self> {
+        let settings = Self::from_data(SettingsData::safe_mode(), resources);
+        *settings.load_error.borrow_mut() = Some(load_error);
+        settings
+    }
+
+    /// Error of the last failed load while running in safe mode
+    pub fn load_error(&self) -> Option<String> {
+        self.load_error.borrow().clone()
+    }
+
 
     /// Load Settings from persistence
     pub fn load(resources: Resources) -> Result<Rc<Self>, Box<dyn std::error::Error>> {
@@ -36,14 +60,14 @@ impl Settings {
         Ok(Self::from_data(data, resources))
     }
 
+    /// Load Settings without validating them, see [`SettingsFileStroage::load_unchecked`]
+    pub fn load_unchecked(resources: Resources) -> Result<Rc<Self>, Box<dyn std::error::Error>> {
+        let data = SettingsFileStroage::new(resources.clone()).load_unchecked()?;
+        Ok(Self::from_data(data, resources))
+    }
+
     pub fn detect(&self, detection: &str) -> Option<String> {
-        let data = self.data.borrow();
-        for board in &data.boards {
-            if board.detection.is_match(detection) {
-                return Some(board.name.clone());
-            }
-        }
-        None
+        detect_board(&self.data.borrow().boards, detection).map(|board| board.name.clone())
     }
 
     pub fn detections(&self) -> Vec<Detection> {
@@ -57,6 +81,27 @@ impl Settings {
         HOME_BOARD_NAME.to_string()
     }
 
+    /// Board to open on startup: the last active one when restoring is enabled and it still exists, the home board otherwise
+    pub fn startup_board_name(&self) -> String {
+        let data = self.data.borrow();
+        data.last_board.clone()
+            .filter(|name| data.restore_last_board && data.boards.iter().any(|b| b.name == *name))
+            .unwrap_or_else(|| self.home_board_name())
+    }
+
+    pub fn restore_last_board(&self) -> bool {
+        self.data.borrow().restore_last_board
+    }
+
+    pub fn last_board(&self) -> Option<String> {
+        self.data.borrow().last_board.clone()
+    }
+
+    pub fn set_last_board(&self, board_name: &str) {
+        self.data.borrow_mut().last_board = Some(board_name.to_string());
+        self.mark_dirty();
+    }
+
     pub fn get_layout_settings(&self) -> Option<LayoutSettings> {
         self.data.borrow().layout.clone()
     }
@@ -66,24 +111,154 @@ impl Settings {
         self.mark_dirty();
     }
 
+    /// Remembers where the board's window was moved, like the shared layout this is not an edit that can be undone
+    /// and undoing other edits leaves it as it is.
+    /// Returns false for boards that are not in the settings.
+    pub fn set_board_rect(&self, board_name: &str, rect: WindowRect) -> bool {
+        let mut data = self.data.borrow_mut();
+        let Some(board) = data.boards.iter_mut().find(|b| b.name == board_name) else {
+            return false;
+        };
+        if board.rect != Some(rect) {
+            board.rect = Some(rect);
+            self.mark_dirty();
+        }
+        true
+    }
+
+    pub fn board_toggle(&self) -> Option<BoardToggleSettings> {
+        self.data.borrow().board_toggle.clone()
+    }
+
+    /// Detection-driven board switching, disabled unless configured
+    pub fn auto_switch(&self) -> Option<AutoSwitchSettings> {
+        self.data.borrow().auto_switch.clone()
+    }
+
+    /// Seconds without input after which an open board is closed, disabled unless configured
+    pub fn idle_timeout(&self) -> Option<u64> {
+        self.data.borrow().idle_timeout.filter(|seconds| *seconds > 0)
+    }
+
+    /// Boards opened at a time of day, empty unless configured
+    pub fn schedule(&self) -> Vec<ScheduledBoard> {
+        self.data.borrow().schedule.clone()
+    }
+
+    /// Milliseconds a pad key waits for a modifier pressed together with it, 0 resolves pads right away
+    pub fn modifier_grace(&self) -> u64 {
+        self.data.borrow().modifier_grace.unwrap_or(0).min(MAX_MODIFIER_GRACE)
+    }
+
+    /// Highlights the selected pad for a single frame when `feedback` is 0, without delaying its actions
+    pub fn flash_feedback(&self) -> bool {
+        self.data.borrow().flash_feedback
+    }
+
+    /// Draws a wipe over pads in their cooldown, shrinking until they can run again
+    pub fn show_cooldown(&self) -> bool {
+        self.data.borrow().show_cooldown
+    }
+
+    /// Opaque text boxes instead of per-pixel text blending on translucent color schemes
+    pub fn fast_render(&self) -> bool {
+        self.data.borrow().fast_render
+    }
+
+    /// Backend drawing board text, GDI unless configured
+    pub fn renderer(&self) -> Renderer {
+        self.data.borrow().renderer.unwrap_or_default()
+    }
+
+    /// Actions of the tray icon clicks, the defaults unless configured
+    pub fn tray(&self) -> TraySettings {
+        self.data.borrow().tray.clone().unwrap_or_default()
+    }
+
+    /// Handling of pad actions whose target window was closed while the board was open
+    pub fn closed_target(&self) -> ClosedTarget {
+        self.data.borrow().closed_target.unwrap_or_default()
+    }
+
+    /// Key combination saving pending changes and closing the board, an empty string disables it
+    pub fn save_and_close_key(&self) -> Option<(ModifierState, u16)> {
+        let data = self.data.borrow();
+        let key = data.save_and_close_key.as_deref().unwrap_or(DEFAULT_SAVE_AND_CLOSE_KEY);
+        parse_hotkey(key)
+    }
+
+    /// Key showing the help of the displayed board while held, an empty string disables it
+    pub fn help_key(&self) -> Option<u16> {
+        let data = self.data.borrow();
+        let key = data.help_key.as_deref().unwrap_or(DEFAULT_HELP_KEY);
+        parse_hotkey(key)
+            .filter(|(modifiers, _)| modifiers.is_none())
+            .map(|(_, vk_code)| vk_code)
+    }
+
+    /// Key combination toggling the state stack view of the displayed board, not set by default
+    pub fn stack_key(&self) -> Option<(ModifierState, u16)> {
+        self.data.borrow().stack_key.as_deref().and_then(parse_hotkey)
+    }
+
+    /// Key combination moving the focus between a dialog and the board it was opened over, an empty string disables it
+    pub fn focus_key(&self) -> Option<(ModifierState, u16)> {
+        let data = self.data.borrow();
+        let key = data.focus_key.as_deref().unwrap_or(DEFAULT_FOCUS_KEY);
+        parse_hotkey(key)
+    }
+
+    /// Key combination going back to the home key board from anywhere in the navigation, an empty string disables it
+    pub fn home_key(&self) -> Option<(ModifierState, u16)> {
+        let data = self.data.borrow();
+        let key = data.home_key.as_deref().unwrap_or(DEFAULT_HOME_KEY);
+        parse_hotkey(key)
+    }
+
+    /// Board the home key goes back to, the home board by default
+    pub fn home_key_board(&self) -> String {
+        self.data.borrow().home_key_board.clone().unwrap_or_else(|| HOME_BOARD_NAME.to_owned())
+    }
+
+    /// Level replacing the levels of the log configuration file, None keeps them
+    pub fn log_level(&self) -> Option<LevelFilter> {
+        self.data.borrow().log_level.as_deref().and_then(|level| level.parse().ok())
+    }
+
+    pub fn set_log_level(&self, level: Option<LevelFilter>) {
+        self.data.borrow_mut().log_level = level.map(|level| level.as_str().to_lowercase());
+        self.mark_dirty();
+    }
+
     pub fn get_resources(&self) -> &Resources {
         &self.resources
     }
 
 
+    /// Runs an edit of the boards, padsets, color schemes or text styles, which can be undone when it succeeds
+    fn edit<T>(&self, edit: impl FnOnce(&mut SettingsData) -> Result<T, Box<dyn std::error::Error>>) -> Result<T, Box<dyn std::error::Error>> {
+        let steps = self.limits().undo_steps;
+        let before = Snapshot::of(&self.data.borrow());
+        let result = edit(&mut self.data.borrow_mut());
+        if result.is_ok() {
+            self.history.borrow_mut().record(before, steps);
+        }
+        result
+    }
+
     #[allow(dead_code)]
     pub fn modify_board<F>(&self, board_name: &str, modifier: F) -> Result<(), Box<dyn std::error::Error>>
     where
         F: FnOnce(&mut Board),
     {
-        let mut data = self.data.borrow_mut();
-        if let Some(board) = data.boards.iter_mut().find(|b| b.name == board_name) {
-            modifier(board);
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("Board '{}' not found", board_name).into())
-        }
+        self.edit(|data| {
+            if let Some(board) = data.boards.iter_mut().find(|b| b.name == board_name) {
+                modifier(board);
+                Ok(())
+            } else {
+                Err(format!("Board '{}' not found", board_name).into())
+            }
+        })
     }
 
 }
@@ -105,6 +280,22 @@ impl SettingsRepository for Settings {
         self.data.borrow().natural_key_order
     }
 
+    fn pad_keys(&self) -> PadKeys {
+        self.data.borrow().pad_keys.unwrap_or_default()
+    }
+
+    fn letter_keys(&self) -> bool {
+        self.data.borrow().letter_keys
+    }
+
+    fn open_in_edit_mode(&self) -> bool {
+        self.data.borrow().open_in_edit_mode
+    }
+
+    fn structured_shortcuts(&self) -> bool {
+        self.data.borrow().structured_shortcuts
+    }
+
     fn get_text_style(&self, name: &str) -> Option<TextStyle> {
         self.data.borrow().text_styles.iter()
             .find(|ts| ts.name == name)
@@ -165,297 +356,382 @@ impl SettingsRepository for Settings {
         self.data.borrow().padsets.iter().map(|ps| ps.name.clone()).collect()
     }
 
+    fn last_seen_version(&self) -> Option<String> {
+        self.data.borrow().last_seen_version.clone()
+    }
+
+    fn copy_board_key(&self) -> String {
+        self.data.borrow().copy_board_key.clone().unwrap_or_else(|| DEFAULT_COPY_BOARD_KEY.to_string())
+    }
+
+    fn limits(&self) -> Limits {
+        self.data.borrow().limits.clone().unwrap_or_default()
+    }
+
+    fn recent_boards(&self) -> Vec<String> {
+        let data = self.data.borrow();
+        data.recent_boards.iter()
+            .filter(|name| data.boards.iter().any(|b| b.name == **name))
+            .cloned()
+            .collect()
+    }
+
+    fn help_modifier(&self) -> String {
+        self.data.borrow().help_modifier.clone().unwrap_or_else(|| DEFAULT_HELP_MODIFIER.to_string())
+    }
+
+    fn dangling_references(&self) -> Vec<DanglingReference> {
+        let data = self.data.borrow();
+        dangling_references(&data.boards, &data.padsets, &data.color_schemes, &data.text_styles)
+    }
+
 }
 
 
 impl SettingsRepositoryMut for Settings {
 
     fn add_board(&self, board: Board) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.boards.iter().any(|b| b.name == board.name) {
-            return Err(format!("Board '{}' already exists", board.name).into());
-        }
-        data.boards.push(board);
-        self.mark_dirty();
-        Ok(())
+        self.edit(|data| {
+            if data.boards.iter().any(|b| b.name == board.name) {
+                return Err(format!("Board '{}' already exists", board.name).into());
+            }
+            data.boards.push(board);
+            Ok(())
+        })
     }
 
     fn insert_board(&self, insert_before: &str, board: Board) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.boards.iter().any(|b| b.name == board.name) {
-            return Err(format!("Board '{}' already exists", board.name).into());
-        }
-        if let Some(pos) = data.boards.iter().position(|b| b.name == insert_before) {
-            data.boards.insert(pos, board);
-        } else {
-            data.boards.push(board);
-        }
-        self.mark_dirty();
-        Ok(())
+        self.edit(|data| {
+            if data.boards.iter().any(|b| b.name == board.name) {
+                return Err(format!("Board '{}' already exists", board.name).into());
+            }
+            if let Some(pos) = data.boards.iter().position(|b| b.name == insert_before) {
+                data.boards.insert(pos, board);
+            } else {
+                data.boards.push(board);
+            }
+            Ok(())
+        })
     }
 
-    fn add_padset(&self, padset: PadSet) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.padsets.iter().any(|ps| ps.name == padset.name) {
-            return Err(format!("PadSet '{}' already exists", padset.name).into());
-        }
-        data.padsets.push(padset);
-        self.mark_dirty();
-        Ok(())
+    fn add_padset(&self, mut padset: PadSet) -> Result<(), Box<dyn std::error::Error>> {
+        self.edit(|data| {
+            if data.padsets.iter().any(|ps| ps.name == padset.name) {
+                return Err(format!("PadSet '{}' already exists", padset.name).into());
+            }
+            if data.structured_shortcuts {
+                structure_shortcuts(&mut padset.items);
+            }
+            data.padsets.push(padset);
+            Ok(())
+        })
     }
 
     fn add_color_scheme(&self, color_scheme: ColorScheme) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.color_schemes.iter().any(|cs| cs.name == color_scheme.name) {
-            return Err(format!("ColorScheme '{}' already exists", color_scheme.name).into());
-        }
-        data.color_schemes.push(color_scheme);
-        self.mark_dirty();
-        Ok(())
+        self.edit(|data| {
+            if data.color_schemes.iter().any(|cs| cs.name == color_scheme.name) {
+                return Err(format!("ColorScheme '{}' already exists", color_scheme.name).into());
+            }
+            data.color_schemes.push(color_scheme);
+            Ok(())
+        })
     }
 
     fn add_text_style(&self, text_style: TextStyle) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.text_styles.iter().any(|ts| ts.name == text_style.name) {
-            return Err(format!("TextStyle '{}' already exists", text_style.name).into());
-        }
-        data.text_styles.push(text_style);
-        self.mark_dirty();
-        Ok(())
+        self.edit(|data| {
+            if data.text_styles.iter().any(|ts| ts.name == text_style.name) {
+                return Err(format!("TextStyle '{}' already exists", text_style.name).into());
+            }
+            data.text_styles.push(text_style);
+            Ok(())
+        })
     }
 
     fn delete_color_scheme(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if let Some(pos) = data.color_schemes.iter().position(|cs| cs.name == name) {
-            data.color_schemes.remove(pos);
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("ColorScheme '{}' not found", name).into())
-        }
+        self.edit(|data| {
+            if let Some(pos) = data.color_schemes.iter().position(|cs| cs.name == name) {
+                data.color_schemes.remove(pos);
+                Ok(())
+            } else {
+                Err(format!("ColorScheme '{}' not found", name).into())
+            }
+        })
     }
 
     fn delete_text_style(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if let Some(pos) = data.text_styles.iter().position(|ts| ts.name == name) {
-            data.text_styles.remove(pos);
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("TextStyle '{}' not found", name).into())
-        }
+        self.edit(|data| {
+            if let Some(pos) = data.text_styles.iter().position(|ts| ts.name == name) {
+                data.text_styles.remove(pos);
+                Ok(())
+            } else {
+                Err(format!("TextStyle '{}' not found", name).into())
+            }
+        })
     }
 
     fn delete_board(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-
-        // // Check for references in PadSets (pad.board - board navigation)
-        // let first_dependent_padset = data.padsets.iter()
-        //     .find(|ps| ps.items.iter()
-        //         .any(|p| p.board.as_ref().map_or(false, |b| b == name))
-        //     )
-        //     .map(|ps| ps.name.clone());
-
-        // match first_dependent_padset {
-        //     Some(ref padset_name) => {
-        //         if !cascade {
-        //             return Err(format!("Board '{}' is referenced by PadSet '{}'", name, padset_name).into());
-        //         } else {
-        //             // Remove references to the board in all padsets
-        //             data.padsets.iter_mut().for_each(|ps| {
-        //                 ps.items.iter_mut().for_each(|p| {
-        //                     if p.board.as_ref().map_or(false, |b| b == name) {
-        //                         p.board = None;
-        //                     }
-        //                 });
-        //             });
-        //         }
-        //     },
-        //     _ => {}
-        // }
-
-        // // Check for references in chain boards
-        // let first_dependent_chain_board = data.boards.iter()
-        //     .find(|b| {
-        //         if let BoardType::Chain(params) = &b.board_type {
-        //             params.boards().contains(&name.to_string())
-        //         } else {
-        //             false
-        //         }
-        //     })
-        //     .map(|b| b.name.clone());
-
-        // match first_dependent_chain_board {
-        //     Some(ref board_name) => {
-        //         if !cascade {
-        //             return Err(format!("Board '{}' is listed in Collection '{}'", name, board_name).into());
-        //         } else {
-        //             // Remove references to the board in all chain boards
-        //             data.boards.iter_mut().for_each(|b| {
-        //                 if let BoardType::Chain(params) = &mut b.board_type {
-        //                     params.remove_board(name);
-        //                 }
-        //             });
-        //         }
-        //     },
-        //     _ => {}
-        // }
-
-        if let Some(pos) = data.boards.iter().position(|b| b.name == name) {
-            data.boards.remove(pos);
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("Board '{}' not found", name).into())
-        }
+        self.edit(|data| {
+
+            // // Check for references in PadSets (pad.board - board navigation)
+            // let first_dependent_padset = data.padsets.iter()
+            //     .find(|ps| ps.items.iter()
+            //         .any(|p| p.board.as_ref().map_or(false, |b| b == name))
+            //     )
+            //     .map(|ps| ps.name.clone());
+
+            // match first_dependent_padset {
+            //     Some(ref padset_name) => {
+            //         if !cascade {
+            //             return Err(format!("Board '{}' is referenced by PadSet '{}'", name, padset_name).into());
+            //         } else {
+            //             // Remove references to the board in all padsets
+            //             data.padsets.iter_mut().for_each(|ps| {
+            //                 ps.items.iter_mut().for_each(|p| {
+            //                     if p.board.as_ref().map_or(false, |b| b == name) {
+            //                         p.board = None;
+            //                     }
+            //                 });
+            //             });
+            //         }
+            //     },
+            //     _ => {}
+            // }
+
+            // // Check for references in chain boards
+            // let first_dependent_chain_board = data.boards.iter()
+            //     .find(|b| {
+            //         if let BoardType::Chain(params) = &b.board_type {
+            //             params.boards().contains(&name.to_string())
+            //         } else {
+            //             false
+            //         }
+            //     })
+            //     .map(|b| b.name.clone());
+
+            // match first_dependent_chain_board {
+            //     Some(ref board_name) => {
+            //         if !cascade {
+            //             return Err(format!("Board '{}' is listed in Collection '{}'", name, board_name).into());
+            //         } else {
+            //             // Remove references to the board in all chain boards
+            //             data.boards.iter_mut().for_each(|b| {
+            //                 if let BoardType::Chain(params) = &mut b.board_type {
+            //                     params.remove_board(name);
+            //                 }
+            //             });
+            //         }
+            //     },
+            //     _ => {}
+            // }
+
+            if let Some(pos) = data.boards.iter().position(|b| b.name == name) {
+                data.boards.remove(pos);
+
+                // Scheduled times opening the board go with it
+                data.schedule.retain(|entry| entry.board != name);
+                Ok(())
+            } else {
+                Err(format!("Board '{}' not found", name).into())
+            }
+        })
     }
 
     fn delete_padset(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if let Some(pos) = data.padsets.iter().position(|ps| ps.name == name) {
-            data.padsets.remove(pos);
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("PadSet '{}' not found", name).into())
-        }
+        self.edit(|data| {
+            if let Some(pos) = data.padsets.iter().position(|ps| ps.name == name) {
+                data.padsets.remove(pos);
+                Ok(())
+            } else {
+                Err(format!("PadSet '{}' not found", name).into())
+            }
+        })
     }
 
     fn set_board(&self, board: Board) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if let Some(existing) = data.boards.iter_mut().find(|b| b.name == board.name) {
-            *existing = board;
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("Board '{}' not found", board.name).into())
-        }
+        self.edit(|data| {
+            if let Some(existing) = data.boards.iter_mut().find(|b| b.name == board.name) {
+                *existing = board;
+                Ok(())
+            } else {
+                Err(format!("Board '{}' not found", board.name).into())
+            }
+        })
     }
 
     fn set_padset(&self, padset: PadSet) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if let Some(existing) = data.padsets.iter_mut().find(|ps| ps.name == padset.name) {
-            *existing = padset;
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("PadSet '{}' not found", padset.name).into())
-        }
+        self.edit(|data| {
+            let structured = data.structured_shortcuts;
+            if let Some(existing) = data.padsets.iter_mut().find(|ps| ps.name == padset.name) {
+                *existing = padset;
+                if structured {
+                    structure_shortcuts(&mut existing.items);
+                }
+                Ok(())
+            } else {
+                Err(format!("PadSet '{}' not found", padset.name).into())
+            }
+        })
     }
 
     fn set_text_style(&self, text_style: TextStyle) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if let Some(existing) = data.text_styles.iter_mut().find(|ts| ts.name == text_style.name) {
-            *existing = text_style;
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("TextStyle '{}' not found", text_style.name).into())
-        }
+        self.edit(|data| {
+            if let Some(existing) = data.text_styles.iter_mut().find(|ts| ts.name == text_style.name) {
+                *existing = text_style;
+                Ok(())
+            } else {
+                Err(format!("TextStyle '{}' not found", text_style.name).into())
+            }
+        })
     }
 
     fn set_color_scheme(&self, color_scheme: ColorScheme) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if let Some(existing) = data.color_schemes.iter_mut().find(|cs| cs.name == color_scheme.name) {
-            *existing = color_scheme;
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("ColorScheme '{}' not found", color_scheme.name).into())
-        }
+        self.edit(|data| {
+            if let Some(existing) = data.color_schemes.iter_mut().find(|cs| cs.name == color_scheme.name) {
+                *existing = color_scheme;
+                Ok(())
+            } else {
+                Err(format!("ColorScheme '{}' not found", color_scheme.name).into())
+            }
+        })
     }
 
     fn rename_color_scheme(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.color_schemes.iter().any(|cs| cs.name == new_name) {
-            return Err(format!("ColorScheme '{}' already exists", new_name).into());
-        }
+        self.edit(|data| {
+            if data.color_schemes.iter().any(|cs| cs.name == new_name) {
+                return Err(format!("ColorScheme '{}' already exists", new_name).into());
+            }
 
-        let rename_refefence = |cs_name: &mut Option<String>| {
-            if let Some(ref mut name) = cs_name {
-                if name == old_name {
-                    *name = new_name.to_string();
+            let rename_refefence = |cs_name: &mut Option<String>| {
+                if let Some(ref mut name) = cs_name {
+                    if name == old_name {
+                        *name = new_name.to_string();
+                    }
                 }
-            }
-        };
+            };
 
-        if let Some(existing) = data.color_schemes.iter_mut().find(|cs| cs.name == old_name) {
-            existing.name = new_name.to_string();
+            if let Some(existing) = data.color_schemes.iter_mut().find(|cs| cs.name == old_name) {
+                existing.name = new_name.to_string();
 
-            for board in &mut data.boards {
-                rename_refefence(&mut board.color_scheme);
-            }
-            for padset in &mut data.padsets {
-                for pad in &mut padset.items {
-                    rename_refefence(&mut pad.color_scheme);
+                for board in &mut data.boards {
+                    rename_refefence(&mut board.color_scheme);
+                }
+                for padset in &mut data.padsets {
+                    for pad in &mut padset.items {
+                        rename_refefence(&mut pad.color_scheme);
+                    }
                 }
-            }
 
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("ColorScheme '{}' not found", old_name).into())
-        }
+                Ok(())
+            } else {
+                Err(format!("ColorScheme '{}' not found", old_name).into())
+            }
+        })
     }
 
     fn rename_text_style(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.text_styles.iter().any(|ts| ts.name == new_name) {
-            return Err(format!("TextStyle '{}' already exists", new_name).into());
-        }
-        if let Some(existing) = data.text_styles.iter_mut().find(|ts| ts.name == old_name) {
-            existing.name = new_name.to_string();
-
-            for board in &mut data.boards {
-                if let Some(ref mut ts_name) = board.text_style {
-                    if ts_name == old_name {
-                        *ts_name = new_name.to_string();
-                    }
-                }
+        self.edit(|data| {
+            if data.text_styles.iter().any(|ts| ts.name == new_name) {
+                return Err(format!("TextStyle '{}' already exists", new_name).into());
             }
-            for padset in &mut data.padsets {
-                for pad in &mut padset.items {
-                    if let Some(ref mut ts_name) = pad.text_style {
+            if let Some(existing) = data.text_styles.iter_mut().find(|ts| ts.name == old_name) {
+                existing.name = new_name.to_string();
+
+                for board in &mut data.boards {
+                    if let Some(ref mut ts_name) = board.text_style {
                         if ts_name == old_name {
                             *ts_name = new_name.to_string();
                         }
                     }
                 }
-            }
+                for padset in &mut data.padsets {
+                    for pad in &mut padset.items {
+                        if let Some(ref mut ts_name) = pad.text_style {
+                            if ts_name == old_name {
+                                *ts_name = new_name.to_string();
+                            }
+                        }
+                    }
+                }
 
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("TextStyle '{}' not found", old_name).into())
-        }
+                Ok(())
+            } else {
+                Err(format!("TextStyle '{}' not found", old_name).into())
+            }
+        })
     }
 
     fn rename_board(&self, old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut data = self.data.borrow_mut();
-        if data.boards.iter().any(|b| b.name == new_name) {
-            return Err(format!("Board '{}' already exists", new_name).into());
-        }
-        if let Some(existing) = data.boards.iter_mut().find(|b| b.name == old_name) {
-
-            existing.name = new_name.to_string();
-            existing.base_pads = existing.base_pads.clone().or(Some(old_name.to_string()));
-
-            // Update references to this board in all pads globally
-            for padset in &mut data.padsets {
-                for pad in &mut padset.items {
-                    if let Some(ref mut board_name) = pad.board {
-                        if board_name == old_name {
-                            *board_name = new_name.to_string();
+        self.edit(|data| {
+            if data.boards.iter().any(|b| b.name == new_name) {
+                return Err(format!("Board '{}' already exists", new_name).into());
+            }
+            if let Some(existing) = data.boards.iter_mut().find(|b| b.name == old_name) {
+
+                existing.name = new_name.to_string();
+                existing.base_pads = existing.base_pads.clone().or(Some(old_name.to_string()));
+
+                // Update references to this board in all pads globally
+                for padset in &mut data.padsets {
+                    for pad in &mut padset.items {
+                        if let Some(ref mut board_name) = pad.board {
+                            if board_name == old_name {
+                                *board_name = new_name.to_string();
+                            }
                         }
                     }
                 }
+
+                // And in the settings opening boards by name
+                let rename_reference = |board_name: &mut String| {
+                    if board_name == old_name {
+                        *board_name = new_name.to_string();
+                    }
+                };
+                if let Some(toggle) = &mut data.board_toggle {
+                    toggle.boards.iter_mut().for_each(rename_reference);
+                }
+                data.schedule.iter_mut().for_each(|entry| rename_reference(&mut entry.board));
+                if let Some(board_name) = &mut data.home_key_board {
+                    rename_reference(board_name);
+                }
+                data.recent_boards.iter_mut().for_each(rename_reference);
+
+                Ok(())
+            } else {
+                Err(format!("Board '{}' not found", old_name).into())
             }
+        })
+    }
 
-            self.mark_dirty();
-            Ok(())
-        } else {
-            Err(format!("Board '{}' not found", old_name).into())
-        }
+    fn set_last_seen_version(&self, version: &str) {
+        self.data.borrow_mut().last_seen_version = Some(version.to_string());
+        self.mark_dirty();
+    }
+
+    fn set_open_in_edit_mode(&self, enabled: bool) {
+        self.data.borrow_mut().open_in_edit_mode = enabled;
+        self.mark_dirty();
+    }
+
+    fn add_recent_board(&self, name: &str) -> bool {
+        self.data.borrow_mut().add_recent_board(name)
+    }
+
+    fn undo(&self) -> bool {
+        self.history.borrow_mut().undo(&mut self.data.borrow_mut())
+    }
+
+    fn redo(&self) -> bool {
+        self.history.borrow_mut().redo(&mut self.data.borrow_mut())
+    }
+
+    fn begin_edit_group(&self) {
+        self.history.borrow_mut().begin_group(&self.data.borrow());
+    }
+
+    fn end_edit_group(&self) {
+        let steps = self.limits().undo_steps;
+        self.history.borrow_mut().end_group(steps);
     }
 
     fn mark_dirty(&self) {
@@ -463,23 +739,96 @@ impl SettingsRepositoryMut for Settings {
     }
 
     fn is_dirty(&self) -> bool {
-        self.dirty.get()
+        self.dirty.get() || self.history.borrow().is_modified()
     }
 
     fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.load_error.borrow().is_some() {
+            // Saving the built-in defaults would overwrite the user's (broken) settings file
+            return Err("Running in safe mode, fix and reload the settings file before saving".into());
+        }
         if self.is_dirty() {
             let file_storage = SettingsFileStroage::new(self.resources.clone());
             file_storage.save(&self.data.borrow())?;
             self.dirty.set(false);
+            self.history.borrow_mut().mark_saved();
         }
         Ok(())
     }
 
     fn reload(&self) -> Result<(), Box<dyn std::error::Error>> {
         let file_storage = SettingsFileStroage::new(self.resources.clone());
-        let data = file_storage.load()?;
+        let mut data = file_storage.load()?;
+        migrate_shortcuts(&mut data);
         *self.data.borrow_mut() = data;
+        *self.load_error.borrow_mut() = None;
         self.dirty.set(false);
+        self.history.borrow_mut().clear();
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Text shortcuts loaded while `structured_shortcuts` is on are migrated to key codes
+fn migrate_shortcuts(data: &mut SettingsData) {
+    if data.structured_shortcuts {
+        data.padsets.iter_mut().for_each(|padset| structure_shortcuts(&mut padset.items));
+    }
+}
+
+/// Stores the text shortcuts of the pads as key codes, keeping the text for display.
+/// Shortcuts without exactly one key per combination (e.g. a lone "Shift") stay text.
+fn structure_shortcuts(pads: &mut [Pad]) {
+    for action in pads.iter_mut().flat_map(|pad| pad.actions.iter_mut()) {
+        let ActionType::Shortcut(text) = action else { continue };
+        let Some(keys) = parse_shortcut_keys(text) else { continue };
+
+        let text = text.clone();
+        let keys = keys.into_iter().map(|(modifiers, key)| KeyChord::new(modifiers, key)).collect();
+        *action = ActionType::ShortcutKeys { text, keys };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_rename_restores_references() {
+        let mut data = SettingsData::safe_mode();
+        data.boards.push(Board { name: "dev".to_string(), ..Default::default() });
+        data.board_toggle = Some(BoardToggleSettings { hotkey: "Ctrl Alt NumPad1".to_string(), boards: ["dev".to_string(), HOME_BOARD_NAME.to_string()] });
+        data.schedule = vec![ScheduledBoard { time: "09:00".to_string(), board: "dev".to_string() }];
+        data.home_key_board = Some("dev".to_string());
+        data.recent_boards = vec!["dev".to_string()];
+        let settings = Settings::from_data(data, Resources::new(vec![]));
+
+        settings.rename_board("dev", "code").unwrap();
+        assert_eq!(settings.data.borrow().home_key_board.as_deref(), Some("code"));
+        assert!(settings.undo());
+
+        let data = settings.data.borrow();
+        assert!(data.boards.iter().any(|b| b.name == "dev"));
+        assert_eq!(data.board_toggle.as_ref().unwrap().boards[0], "dev");
+        assert_eq!(data.schedule[0].board, "dev");
+        assert_eq!(data.home_key_board.as_deref(), Some("dev"));
+        // The renamed board is gone from the recent ones rather than left under a name that doesn't exist
+        assert!(data.recent_boards.is_empty());
+        assert!(!settings.is_dirty());
+    }
+
+    #[test]
+    fn test_undo_keeps_window_rects() {
+        let mut data = SettingsData::safe_mode();
+        data.boards.push(Board { name: "dev".to_string(), ..Default::default() });
+        let settings = Settings::from_data(data, Resources::new(vec![]));
+
+        settings.modify_board("dev", |board| board.title = Some("Dev".to_string())).unwrap();
+        let rect = WindowRect { x: 10, y: 20, width: 300, height: 200 };
+        assert!(settings.set_board_rect("dev", rect));
+
+        assert!(settings.undo());
+        let board = settings.get_board("dev").unwrap();
+        assert_eq!(board.title, None);
+        assert_eq!(board.rect, Some(rect));
+    }
+}
@@ -1,6 +1,10 @@
 use std::collections::HashSet;
 
-use crate::core::{ColorScheme, PadSet, TextStyle};
+use log::LevelFilter;
+
+use crate::core::{all_actions, expand_chain_members, validate_base_url, validate_conditions, ActionType, BoardType};
+use crate::core::data::{dangling_references, ReferenceKind};
+use crate::input::script::{parse_hotkey, parse_modifiers};
 
 use super::persistence::SettingsData;
 pub trait SettingsValidator {
@@ -19,24 +23,15 @@ impl SettingsValidator for SettingsData {
 
 impl SettingsData {
 
-    fn find_scheme(&self, name: &String) -> Option<ColorScheme> {
-        self.color_schemes
-            .iter()
-            .find(|cs| cs.name == *name)
-            .map(|cs| cs.clone())
-    }
-
-    fn find_text_style(&self, name: &String) -> Option<TextStyle> {
-        self.text_styles
-            .iter()
-            .find(|ts| ts.name == *name)
-            .map(|ts| ts.clone())
-    }
-
-    fn find_padset(&self, name: &str) -> Option<&PadSet> {
-        self.padsets
-            .iter()
-            .find(|ps| ps.name == name)
+    /// The first name of the kind referring to a component the settings don't define, as `--validate` reports it
+    fn first_dangling_reference(&self, kind: ReferenceKind) -> Result<(), String> {
+        match dangling_references(&self.boards, &self.padsets, &self.color_schemes, &self.text_styles)
+            .into_iter()
+            .find(|problem| problem.kind == kind)
+        {
+            Some(problem) => Err(problem.to_string()),
+            None => Ok(()),
+        }
     }
 
     /// Validate if no two components of the same type have equal name
@@ -74,75 +69,185 @@ impl SettingsData {
 
     /// Validate color scheme references (no resource dependency)
     fn validate_color_scheme_references(&self) -> Result<(), String> {
-        for board in &self.boards {
-            if let Some(scheme_name) = &board.color_scheme {
-                if self.find_scheme(scheme_name).is_none() {
-                    return Err(format!("Color scheme '{}' for board '{}' not found in settings", scheme_name, board.name));
-                }
-            }
-        }
-        Ok(())
+        self.first_dangling_reference(ReferenceKind::ColorScheme)
     }
 
     /// Validate text style references (no resource dependency)
     fn validate_text_style_references(&self) -> Result<(), String> {
-        for board in &self.boards {
-            if let Some(text_style) = &board.text_style {
-                if self.find_text_style(text_style).is_none() {
-                    return Err(format!("Text style '{}' for board '{}' not found in settings", text_style, board.name));
-                }
-            }
-        }
-        Ok(())
+        self.first_dangling_reference(ReferenceKind::TextStyle)
     }
 
     /// Validate pad references (no resource dependency)
     fn validate_pad_references(&self) -> Result<(), String> {
-        for board in &self.boards {
-            if let Some(ref padset_name) = board.base_pads {
-                if self.find_padset(padset_name).is_none() {
-                    return Err(format!("Base pad set '{}' not found for board '{}'", padset_name, board.name));
-                }
-            }
+        self.first_dangling_reference(ReferenceKind::PadSet)?;
 
-            for (modifier, padset_name) in &board.modifier_pads {
-                if self.find_padset(padset_name).is_none() {
-                    return Err(format!("Modifier pad set '{}' not found for board '{}' with modifier '{}'", padset_name, board.name, modifier));
+        for board in &self.boards {
+            if let Some(pad) = board.default_pad {
+                if !(1..=9).contains(&pad) {
+                    return Err(format!("Default pad {} of board '{}' must be between 1 and 9", pad, board.name));
                 }
             }
         }
         Ok(())
     }
 
-    /// Validate cross-board references (no resource dependency)
+    /// Validate cross-board references, pads and collections opening boards (no resource dependency)
     fn validate_cross_board_references(&self) -> Result<(), String> {
+        self.first_dangling_reference(ReferenceKind::Board)?;
+
         for padset in &self.padsets {
             for pad in &padset.items {
-                if let Some(ref board_ref) = pad.board {
-                    let found = self.boards.iter().any(|b| b.name == *board_ref);
-                    if !found {
-                        return Err(format!("Invalid board reference '{}' in pad '{:?}' of padset '{}'", board_ref, pad.header, padset.name));
-                    }
+                if let Some(background) = pad.background.as_ref().filter(|background| !is_hex_color(background)) {
+                    return Err(format!("Invalid background '{}' for pad '{:?}' in padset '{}', expected #rrggbb", background, pad.header, padset.name));
                 }
 
-                // Validate pad-level color scheme references
-                if let Some(ref scheme_name) = pad.color_scheme {
-                    if self.find_scheme(scheme_name).is_none() {
-                        return Err(format!("Color scheme '{}' not found for pad '{:?}' in padset '{}'", scheme_name, pad.header, padset.name));
+                validate_conditions(&pad.actions)
+                    .map_err(|e| format!("{} in pad '{:?}' of padset '{}'", e, pad.header, padset.name))?;
+                for action in all_actions(&pad.actions) {
+                    if let ActionType::OpenQuery { base, .. } = action {
+                        validate_base_url(base)
+                            .map_err(|e| format!("{} in pad '{:?}' of padset '{}'", e, pad.header, padset.name))?;
                     }
                 }
+            }
+        }
+        Ok(())
+    }
 
-                // Validate pad-level text style references
-                if let Some(ref style_name) = pad.text_style {
-                    if self.find_text_style(style_name).is_none() {
-                        return Err(format!("Text style '{}' not found for pad '{:?}' in padset '{}'", style_name, pad.header, padset.name));
-                    }
+    /// Validate that collections don't contain themselves, directly or through nested collections
+    fn validate_chain_cycles(&self) -> Result<(), String> {
+        let sub_members = |name: &str| self.boards.iter()
+            .find(|b| b.name == name)
+            .and_then(|b| match &b.board_type {
+                BoardType::Chain(params) => Some(params.boards()),
+                _ => None,
+            });
+
+        for board in &self.boards {
+            if let BoardType::Chain(params) = &board.board_type {
+                expand_chain_members(&board.name, &params.boards(), &sub_members)
+                    .map_err(|e| format!("{} in collection '{}'", e, board.name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate the A/B board toggle hotkey and board references
+    fn validate_board_toggle(&self) -> Result<(), String> {
+        if let Some(toggle) = &self.board_toggle {
+            if parse_hotkey(&toggle.hotkey).is_none() {
+                return Err(format!("Invalid hotkey '{}'", toggle.hotkey));
+            }
+            for board_name in &toggle.boards {
+                if !self.boards.iter().any(|b| b.name == *board_name) {
+                    return Err(format!("Board '{}' not found", board_name));
                 }
             }
         }
         Ok(())
     }
 
+    /// Validate the global hotkeys opening boards, conflicts between them are resolved when they are registered
+    fn validate_board_hotkeys(&self) -> Result<(), String> {
+        for board in &self.boards {
+            if let Some(hotkey) = board.hotkey.as_ref().filter(|hotkey| parse_hotkey(hotkey).is_none()) {
+                return Err(format!("Invalid hotkey '{}' of board '{}'", hotkey, board.name));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_schedule(&self) -> Result<(), String> {
+        for entry in &self.schedule {
+            if entry.minute_of_day().is_none() {
+                return Err(format!("Invalid time '{}' for board '{}', expected HH:MM", entry.time, entry.board));
+            }
+            if !self.boards.iter().any(|b| b.name == entry.board) {
+                return Err(format!("Board '{}' not found", entry.board));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_save_and_close_key(&self) -> Result<(), String> {
+        match self.save_and_close_key.as_deref() {
+            Some(key) if !key.trim().is_empty() && parse_hotkey(key).is_none() => {
+                Err(format!("Invalid key combination '{}'", key))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_copy_board_key(&self) -> Result<(), String> {
+        match self.copy_board_key.as_deref() {
+            Some(key) if !key.trim().is_empty() && parse_hotkey(key).is_none() => {
+                Err(format!("Invalid key combination '{}'", key))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_help_modifier(&self) -> Result<(), String> {
+        match self.help_modifier.as_deref() {
+            Some(modifier) if parse_modifiers(modifier).is_none() => {
+                Err(format!("Invalid modifier '{}'", modifier))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// The help key is held on its own, so it can't include modifiers
+    fn validate_help_key(&self) -> Result<(), String> {
+        match self.help_key.as_deref() {
+            Some(key) if !key.trim().is_empty() && !parse_hotkey(key).is_some_and(|(modifiers, _)| modifiers.is_none()) => {
+                Err(format!("Invalid key '{}', a single key without modifiers is expected", key))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_stack_key(&self) -> Result<(), String> {
+        match self.stack_key.as_deref() {
+            Some(key) if !key.trim().is_empty() && parse_hotkey(key).is_none() => {
+                Err(format!("Invalid key combination '{}'", key))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_focus_key(&self) -> Result<(), String> {
+        match self.focus_key.as_deref() {
+            Some(key) if !key.trim().is_empty() && parse_hotkey(key).is_none() => {
+                Err(format!("Invalid key combination '{}'", key))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_home_key(&self) -> Result<(), String> {
+        match self.home_key.as_deref() {
+            Some(key) if !key.trim().is_empty() && parse_hotkey(key).is_none() => {
+                return Err(format!("Invalid key combination '{}'", key));
+            },
+            _ => {},
+        }
+        match self.home_key_board.as_deref() {
+            Some(board_name) if !self.boards.iter().any(|b| b.name == board_name) => {
+                Err(format!("Board '{}' not found", board_name))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_log_level(&self) -> Result<(), String> {
+        match self.log_level.as_deref() {
+            Some(level) if level.parse::<LevelFilter>().is_err() => {
+                Err(format!("Invalid level '{}', expected off, error, warn, info, debug or trace", level))
+            },
+            _ => Ok(()),
+        }
+    }
+
     /// Validate settings data integrity (no resource dependencies)
     fn validate_data_integrity(&self) -> Result<(), String> {
         if self.boards.is_empty() {
@@ -161,7 +266,49 @@ impl SettingsData {
         self.validate_cross_board_references()
             .map_err(|e| format!("Cross board validation failed: {}", e))?;
 
+        self.validate_chain_cycles()
+            .map_err(|e| format!("Collection validation failed: {}", e))?;
+
+        self.validate_board_toggle()
+            .map_err(|e| format!("Board toggle validation failed: {}", e))?;
+
+        self.validate_board_hotkeys()
+            .map_err(|e| format!("Board hotkey validation failed: {}", e))?;
+
+        self.validate_schedule()
+            .map_err(|e| format!("Schedule validation failed: {}", e))?;
+
+        self.validate_save_and_close_key()
+            .map_err(|e| format!("Save and close key validation failed: {}", e))?;
+
+        self.validate_copy_board_key()
+            .map_err(|e| format!("Copy board key validation failed: {}", e))?;
+
+        self.validate_help_modifier()
+            .map_err(|e| format!("Help modifier validation failed: {}", e))?;
+
+        self.validate_help_key()
+            .map_err(|e| format!("Help key validation failed: {}", e))?;
+
+        self.validate_stack_key()
+            .map_err(|e| format!("Stack key validation failed: {}", e))?;
+
+        self.validate_focus_key()
+            .map_err(|e| format!("Focus key validation failed: {}", e))?;
+
+        self.validate_home_key()
+            .map_err(|e| format!("Home key validation failed: {}", e))?;
+
+        self.validate_log_level()
+            .map_err(|e| format!("Log level validation failed: {}", e))?;
+
         Ok(())
     }
 
 }
+
+
+/// A color written as `#rrggbb`
+fn is_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').is_some_and(|hex| hex.len() == 6 && hex.chars().all(|ch| ch.is_ascii_hexdigit()))
+}
@@ -13,10 +13,11 @@ pub struct Assets<'a> {
     colors: HashMap<&'a str, COLORREF>,
     color_scheme: ColorScheme,
     text_style: TextStyle,
+    zoom: f64,
 }
 
 impl<'a> Assets<'a> {
-    pub fn new(colors: &ColorScheme, text_style: &TextStyle) -> Self {
+    pub fn new(colors: &ColorScheme, text_style: &TextStyle, zoom: f64) -> Self {
         let mut assets = Self {
             fonts: HashMap::new(),
             brushes: HashMap::new(),
@@ -24,6 +25,7 @@ impl<'a> Assets<'a> {
             colors: HashMap::new(),
             color_scheme: colors.clone(),
             text_style: text_style.clone(),
+            zoom,
         };
         unsafe { assets.initialize(); }
         assets
@@ -53,6 +55,11 @@ impl<'a> Assets<'a> {
         self.brushes.get("selected_tile_brush").unwrap().clone()
     }
 
+    /// Color of the selected tile brush
+    pub fn selected_tile_color(&self) -> COLORREF {
+        self.colors.get("line_color").unwrap().clone()
+    }
+
     pub fn line_pen(&self) -> HPEN {
         self.pens.get("line_pen").unwrap().clone()
     }
@@ -65,6 +72,10 @@ impl<'a> Assets<'a> {
         self.colors.get("tag_color").unwrap().clone()
     }
 
+    pub fn accent_color(&self) -> COLORREF {
+        self.colors.get("accent_color").unwrap().clone()
+    }
+
     pub fn font_disabled_color(&self) -> COLORREF {
         self.colors.get("font_disabled_color").unwrap().clone()
     }
@@ -85,6 +96,10 @@ impl<'a> Assets<'a> {
         self.colors.get(&format!("palette_color_{}", index) as &str).cloned().unwrap_or_else(|| fallback(self))
     }
 
+    pub fn palette_brush(&self, index: usize) -> Option<HBRUSH> {
+        self.brushes.get(&format!("palette_brush_{}", index) as &str).cloned()
+    }
+
     pub fn palette_font(&self, index: usize) -> Option<HFONT> {
         self.fonts.get(&format!("palette_font_{}", index) as &str).cloned()
     }
@@ -105,6 +120,10 @@ impl<'a> Assets<'a> {
         &self.text_style
     }
 
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
     unsafe fn initialize(&mut self) {
         let colors = &self.color_scheme;
         let text_style = &self.text_style;
@@ -122,6 +141,19 @@ impl<'a> Assets<'a> {
             "palette_color_9",
         ];
 
+        let palette_brush_names = vec![
+            "palette_brush_0",
+            "palette_brush_1",
+            "palette_brush_2",
+            "palette_brush_3",
+            "palette_brush_4",
+            "palette_brush_5",
+            "palette_brush_6",
+            "palette_brush_7",
+            "palette_brush_8",
+            "palette_brush_9",
+        ];
+
         let palette_font_names = vec![
             "palette_font_0",
             "palette_font_1",
@@ -139,6 +171,7 @@ impl<'a> Assets<'a> {
         self.colors.insert("line_color", colors.foreground1().to_colorref());
         self.colors.insert("font_color", colors.foreground2().to_colorref());
         self.colors.insert("tag_color", colors.tag_foreground().to_colorref());
+        self.colors.insert("accent_color", colors.accent().to_colorref());
         self.colors.insert("font_disabled_color", colors.foreground2().equidistant(&colors.background()).to_colorref());
         for (i, _) in colors.palette().iter().enumerate() {
             self.colors.insert(palette_color_names[i], colors.palette_color(i).expect("Cannot fail").to_colorref());
@@ -146,15 +179,19 @@ impl<'a> Assets<'a> {
 
         self.brushes.insert("background_brush", CreateSolidBrush(self.colors.get("background_color").unwrap().clone()));
         self.brushes.insert("selected_tile_brush", CreateSolidBrush(self.colors.get("line_color").unwrap().clone()));
+        for (i, _) in colors.palette().iter().enumerate() {
+            self.brushes.insert(palette_brush_names[i], CreateSolidBrush(self.colors.get(palette_color_names[i]).unwrap().clone()));
+        }
         self.pens.insert("line_pen", CreatePen(PS_SOLID, 2, self.colors.get("line_color").unwrap().clone()));
 
-        self.fonts.insert("tile_id_font", text_style.pad_id_font());
-        self.fonts.insert("tile_header_font", text_style.pad_header_font());
-        self.fonts.insert("tile_text_font", text_style.pad_text_font());
-        self.fonts.insert("header_font", text_style.header_font());
-        self.fonts.insert("tag_font", text_style.tag_font());
+        let zoom = self.zoom;
+        self.fonts.insert("tile_id_font", text_style.create_scaled_font(&text_style.pad_id_font, zoom));
+        self.fonts.insert("tile_header_font", text_style.create_scaled_font(&text_style.pad_header_font, zoom));
+        self.fonts.insert("tile_text_font", text_style.create_scaled_font(&text_style.pad_text_font, zoom));
+        self.fonts.insert("header_font", text_style.create_scaled_font(&text_style.header_font, zoom));
+        self.fonts.insert("tag_font", text_style.create_scaled_font(&text_style.tag_font, zoom));
         for (i, font_str) in text_style.palette().iter().enumerate() {
-            self.fonts.insert(palette_font_names[i], text_style.create_font(font_str));
+            self.fonts.insert(palette_font_names[i], text_style.create_scaled_font(font_str, zoom));
         }
     }
 
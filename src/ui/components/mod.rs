@@ -1,4 +1,29 @@
 pub mod assets;
 pub mod painter;
 pub mod svg;
-pub mod png;
\ No newline at end of file
+pub mod png;
+pub mod text;
+pub mod warmup;
+
+use crate::core::Resources;
+use svg::ICON_CACHE;
+use png::PNG_CACHE;
+
+/// Points the icon caches at `resources` and drops the icons loaded or warmed up so far, so edited icon files are read again.
+/// Returns false when a cache is borrowed by a paint cycle, the caches are then left as they are.
+pub fn initialize_icon_caches(resources: &Resources) -> bool {
+    warmup::clear_warm_icons();
+    let svg = ICON_CACHE.with(|cache| cache.try_borrow_mut()
+        .map(|mut cache| {
+            cache.clear();
+            cache.initialize(resources.clone());
+        })
+        .is_ok());
+    let png = PNG_CACHE.with(|cache| cache.try_borrow_mut()
+        .map(|mut cache| {
+            cache.clear();
+            cache.initialize(resources.clone());
+        })
+        .is_ok());
+    svg && png
+}
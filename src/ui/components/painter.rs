@@ -1,13 +1,26 @@
 use windows::Win32::{
     Foundation::{COLORREF, HWND, POINT, RECT},
     Graphics::Gdi::{
-        DrawTextW, FillRect, Polyline, SelectObject, SetBkMode, SetTextColor, TextOutW, DT_BOTTOM, DT_CALCRECT, DT_CENTER, DT_NOCLIP, DT_NOPREFIX, DT_RIGHT, DT_SINGLELINE, DT_TOP, DT_VCENTER, DT_WORDBREAK, DT_WORD_ELLIPSIS, HDC, TRANSPARENT
+        CreateCompatibleDC, DeleteDC, DeleteObject, DrawTextW, FillRect, Polyline, SelectObject, SetBkMode, SetTextColor, DT_BOTTOM, DT_CALCRECT, DT_CENTER, DT_LEFT, DT_NOCLIP, DT_NOPREFIX, DT_RIGHT, DT_SINGLELINE, DT_TOP, DT_VCENTER, DT_WORDBREAK, DT_WORD_ELLIPSIS, HDC, HFONT, TRANSPARENT
     },
     UI::WindowsAndMessaging::GetClientRect,
 };
 
-use crate::model::{AnchorPin, Board, Color, ModifierState, Pad, PadId, Tag};
-use super::{assets::Assets, png::PNG_CACHE, svg::ICON_CACHE};
+use crate::core::{Renderer, VerticalAlign};
+use crate::model::{shortcut_key_caps, AnchorPin, Board, Color, ColorScheme, ModifierState, Pad, PadId, Tag, TextStyle};
+use super::{assets::Assets, png::PNG_CACHE, svg::ICON_CACHE, text::{text_renderer, to_wstr, Canvas, TextRenderer}, warmup::IconRequest};
+
+/// Shrinking the text of a `shrink_text` pad: steps tried at most, font size kept per step and the smallest font size
+const MAX_SHRINK_STEPS: usize = 6;
+const SHRINK_FACTOR: f64 = 0.85;
+const MIN_SHRUNK_FONT_SIZE: f64 = 8.0;
+
+/// Space around a tile's icon and text: left and right, top and bottom
+const TILE_MARGIN: (i32, i32) = (20, 25);
+/// Space the header keeps on either side of its centered title, for the timeout dots and the tags
+const HEADER_SIDE_SPACE: i32 = 100;
+/// How strongly the cooldown wipe covers the part of a pad still in its cooldown
+const COOLDOWN_OPACITY: f64 = 0.35;
 
 #[repr(C)]
 pub struct RGBA {
@@ -22,10 +35,19 @@ pub struct BoardPainter<'a> {
     pub board: &'a dyn Board,
     pub timeout: u8,
     pub selected_pad: Option<PadId>,
+    /// How opaque the fill of the selected pad is, from 0 to 1
+    pub selection_progress: f64,
+    /// Pads in their cooldown with the part of it still to go, from 1 down to 0
+    pub cooldowns: Vec<(PadId, f64)>,
+    pub zoom: f64,
+    pub fast_render: bool,
+    /// Draws the texts only, the rest of the board is painted the same whatever the renderer
+    pub renderer: Renderer,
 }
 
 struct TilePainter<'a> {
     pad_id: PadId,
+    letter_label: bool,
     pad: &'a Pad,
     assets: &'a Assets<'a>,
 }
@@ -40,11 +62,14 @@ struct IconPainter {
 }
 
 
-fn alpha_blend_rect(pixels: &mut [RGBA], width: usize, rect: &RECT, bg_color: COLORREF, fg_color: COLORREF, bg_opacity: f32) {
-    let (bg_r, bg_g, bg_b) = Color::from_colorref(bg_color).to_rgb();
+/// Sets the alpha of the text pixels in `rect` by how close they are to the foreground rather than the background,
+/// which is taken per row as a gradient background changes from top to bottom
+fn alpha_blend_rect(pixels: &mut [RGBA], width: usize, rect: &RECT, color_scheme: &ColorScheme, fg_color: COLORREF, bg_opacity: f32) {
     let (fg_r, fg_g, fg_b) = Color::from_colorref(fg_color).to_rgb();
+    let height = (pixels.len() / width.max(1)) as i32;
 
     for y in rect.top..rect.bottom {
+        let (bg_r, bg_g, bg_b) = color_scheme.background_at(y, height).to_rgb();
         for x in rect.left..rect.right {
             if x >= 0 && y >= 0 && x < width as i32 {
                 let idx = y as usize * width + x as usize;
@@ -95,6 +120,54 @@ fn set_opaque_rect(pixels: &mut [RGBA], width: usize, rect: &RECT) {
     }
 }
 
+/// Fills `rect` opaque with the gradient background of `color_scheme`, rows colored as the board's are,
+/// so the text blended onto it later is matched against the same color
+fn fill_gradient_rect(pixels: &mut [RGBA], width: usize, rect: &RECT, color_scheme: &ColorScheme) {
+    let height = (pixels.len() / width.max(1)) as i32;
+    for y in rect.top.max(0)..rect.bottom.min(height) {
+        let (r, g, b) = color_scheme.background_at(y, height).to_rgb();
+        for x in rect.left.max(0)..rect.right.min(width as i32) {
+            pixels[y as usize * width + x as usize] = RGBA { r, g, b, a: 255 };
+        }
+    }
+}
+
+/// Blends the opaque `color` over the premultiplied pixels of `rect` with the given opacity
+fn fade_rect(pixels: &mut [RGBA], width: usize, rect: &RECT, color: COLORREF, opacity: f64) {
+    let (r, g, b) = Color::from_colorref(color).to_rgb();
+    let opacity = opacity.clamp(0.0, 1.0) as f32;
+    let blend = |src: u8, dst: u8| (src as f32 * opacity + dst as f32 * (1.0 - opacity)).round() as u8;
+
+    for y in rect.top..rect.bottom {
+        for x in rect.left..rect.right {
+            if x >= 0 && y >= 0 && x < width as i32 {
+                let idx = y as usize * width + x as usize;
+                if idx < pixels.len() {
+                    let pixel = &mut pixels[idx];
+                    pixel.r = blend(r, pixel.r);
+                    pixel.g = blend(g, pixel.g);
+                    pixel.b = blend(b, pixel.b);
+                    pixel.a = blend(255, pixel.a);
+                }
+            }
+        }
+    }
+}
+
+/// Makes text drawn into `rect` show on the layered window. Opaque schemes need nothing, the bitmap
+/// is already opaque. Fast rendering replaces the per-pixel blend with an opaque box behind the text.
+pub(super) fn blend_text_rect(pixels: &mut [RGBA], width: usize, rect: &RECT, assets: &Assets, fg_color: COLORREF, fast_render: bool) {
+    let opacity = assets.color_scheme().opacity();
+    if opacity >= 1.0 {
+        return;
+    }
+    if fast_render {
+        set_opaque_rect(pixels, width, rect);
+    } else {
+        alpha_blend_rect(pixels, width, rect, assets.color_scheme(), fg_color, opacity as f32);
+    }
+}
+
 fn set_opaque_hline(pixels: &mut [RGBA], width: usize, y: i32, x1: i32, x2: i32, line_width: u8) {
     for dy in 0..line_width {
         let yy = y + dy as i32;
@@ -139,22 +212,36 @@ fn draw_vline(hdc: HDC, pixels: &mut [RGBA], width: usize, x: i32, y1: i32, y2:
     set_opaque_vline(pixels, width, x - 1, y1, y2, line_width);
 }
 
-// fn draw_rect(hdc: HDC, pixels: &mut [RGBA], width: usize, rect: &RECT, line_width: u8) {
-//     unsafe {
-//         let points = [
-//             POINT{ x:rect.left, y:rect.top },
-//             POINT{ x:rect.right, y:rect.top },
-//             POINT{ x:rect.right, y:rect.bottom },
-//             POINT{ x:rect.left, y:rect.bottom },
-//             POINT{ x:rect.left, y:rect.top },
-//         ];
-//         let _ = Polyline(hdc, &points);
-//     }
-//     set_opaque_hline(pixels, width, rect.top - 1, rect.left, rect.right, line_width);
-//     set_opaque_hline(pixels, width, rect.bottom - 1, rect.left, rect.right, line_width);
-//     set_opaque_vline(pixels, width, rect.left - 1, rect.top, rect.bottom, line_width);
-//     set_opaque_vline(pixels, width, rect.right - 1, rect.top, rect.bottom, line_width);
-// }
+fn draw_rect(hdc: HDC, pixels: &mut [RGBA], width: usize, rect: &RECT, line_width: u8) {
+    unsafe {
+        let points = [
+            POINT{ x:rect.left, y:rect.top },
+            POINT{ x:rect.right, y:rect.top },
+            POINT{ x:rect.right, y:rect.bottom },
+            POINT{ x:rect.left, y:rect.bottom },
+            POINT{ x:rect.left, y:rect.top },
+        ];
+        let _ = Polyline(hdc, &points);
+    }
+    set_opaque_hline(pixels, width, rect.top - 1, rect.left, rect.right, line_width);
+    set_opaque_hline(pixels, width, rect.bottom - 1, rect.left, rect.right, line_width);
+    set_opaque_vline(pixels, width, rect.left - 1, rect.top, rect.bottom, line_width);
+    set_opaque_vline(pixels, width, rect.right - 1, rect.top, rect.bottom, line_width);
+}
+
+/// Wipe over the left part of the tile shrinking with the cooldown, its edge drawn as a line
+fn draw_cooldown(hdc: HDC, pixels: &mut [RGBA], width: usize, rect: &RECT, remaining: f64, assets: &Assets) {
+    let wipe = RECT { right: rect.left + ((rect.right - rect.left) as f64 * remaining.clamp(0.0, 1.0)) as i32, ..*rect };
+    if wipe.right <= wipe.left {
+        return;
+    }
+    fade_rect(pixels, width, &wipe, assets.selected_tile_color(), COOLDOWN_OPACITY);
+    unsafe {
+        let hpen_original = SelectObject(hdc, assets.line_pen().into());
+        draw_vline(hdc, pixels, width, wipe.right, wipe.top, wipe.bottom, 2);
+        SelectObject(hdc, hpen_original);
+    }
+}
 
 fn resize_rect(rect: &RECT, dx: i32, dy: i32) -> RECT {
     RECT {
@@ -176,7 +263,7 @@ impl<'a> BoardPainter<'a> {
         // Create board assets locally
         let color_scheme = self.board.color_scheme();
         let text_style = self.board.text_style();
-        let board_assets = Assets::new(&color_scheme, &text_style);
+        let board_assets = Assets::new(&color_scheme, &text_style, self.zoom);
         // Don't fill background - it's already initialized in bitmap
 
         // Draw grid lines
@@ -196,8 +283,10 @@ impl<'a> BoardPainter<'a> {
         draw_hline(hdc, pixels, width, frame.bottom, frame.left, frame.right, 2);
         SelectObject(hdc, hpen_original);
 
+        let mut text = text_renderer(self.renderer, self.fast_render);
         SetBkMode(hdc, TRANSPARENT);
         SetTextColor(hdc, board_assets.font_color()); // 0x00ffffff 0x003c3a3d
+        let letter_label = self.board.letter_keys();
         for pad_id in PadId::all() {
             let row = pad_id.row();
             let col = pad_id.col();
@@ -207,37 +296,52 @@ impl<'a> BoardPainter<'a> {
                 left: wtile*col,  right: wtile*(col+1), top: h-htile*(row+1), bottom: h-htile*row
             };
 
-            if self.selected_pad == Some(pad_id) {
-                FillRect(hdc, &rect, board_assets.selected_tile_brush());
-                set_opaque_rect(pixels, width, &rect);
+            let pad = &self.board.padset(Some(modifier_state.clone())).pad(pad_id);
+
+            if self.selected_pad == Some(pad_id) && !pad.is_label() {
+                if self.selection_progress >= 1.0 {
+                    FillRect(hdc, &rect, board_assets.selected_tile_brush());
+                    set_opaque_rect(pixels, width, &rect);
+                } else {
+                    fade_rect(pixels, width, &rect, board_assets.selected_tile_color(), self.selection_progress);
+                }
             }
 
-            let pad = &self.board.padset(Some(modifier_state.clone())).pad(pad_id);
+            // A pad's own background is painted with an unnamed copy of its scheme
+            let background_scheme = pad.data.background.as_deref()
+                .map(|background| pad.color_scheme.as_ref().unwrap_or(board_assets.color_scheme()).with_background(background));
+            let pad_color_scheme = background_scheme.as_ref().or(pad.color_scheme.as_ref());
 
             // Check if we need pad-specific assets
             let pad_assets;
-            let (assets_to_use, repaint_background) = if pad.color_scheme.is_some() || pad.text_style.is_some() {
+            let (assets_to_use, repaint_background) = if pad_color_scheme.is_some() || pad.text_style.is_some() {
                 // Create new assets with pad-specific overrides
-                let color_scheme = pad.color_scheme.as_ref().unwrap_or(board_assets.color_scheme());
+                let color_scheme = pad_color_scheme.unwrap_or(board_assets.color_scheme());
                 let text_style = pad.text_style.as_ref().unwrap_or(board_assets.text_style());
-                pad_assets = Assets::new(color_scheme, text_style);
-                (&pad_assets, pad_assets.color_scheme().background != board_assets.color_scheme().background)
+                pad_assets = Assets::new(color_scheme, text_style, self.zoom);
+                let repaint = pad_assets.color_scheme().background != board_assets.color_scheme().background
+                    || pad_assets.color_scheme().background2 != board_assets.color_scheme().background2;
+                (&pad_assets, repaint)
             } else {
                 // Use board assets
                 (&board_assets, false)
             };
 
-            TilePainter { pad_id, pad, assets: assets_to_use }
-                .paint(hdc, &rect, repaint_background, pixels, width);
+            TilePainter { pad_id, letter_label, pad, assets: assets_to_use }
+                .paint(hdc, &rect, repaint_background, text.as_mut(), pixels, width);
+
+            if let Some((_, remaining)) = self.cooldowns.iter().find(|(id, _)| *id == pad_id) {
+                draw_cooldown(hdc, pixels, width, &rect, *remaining, &board_assets);
+            }
         }
 
         let header_rect = RECT { left: 0, right: w, top: 0, bottom: (h as f32/10.) as i32 };
         SetTextColor(hdc, board_assets.font_color());
         HeaderPainter { title: &self.board.title(), timeout: self.timeout, assets: &board_assets }
-            .paint(hdc, &header_rect, self.board.icon(), pixels, width);
+            .paint(hdc, &header_rect, self.board.icon(), text.as_mut(), pixels, width);
 
         self.board.tags(Some(modifier_state)).iter().for_each(|tag| {
-            TagPainter::draw_tag(hdc, tag, &header_rect, &board_assets, pixels, width);
+            TagPainter::draw_tag(hdc, tag, &header_rect, &board_assets, text.as_mut(), pixels, width);
         });
 
         // // Debugging: draw main screen anchor points
@@ -258,105 +362,173 @@ impl<'a> BoardPainter<'a> {
 
 impl<'a> TilePainter<'a> {
 
-    pub fn paint(&self, hdc: HDC, rect: &RECT, repaint_background: bool, pixels: &mut [RGBA], width: usize) {
+    pub fn paint(&self, hdc: HDC, rect: &RECT, repaint_background: bool, text: &mut dyn TextRenderer, pixels: &mut [RGBA], width: usize) {
         unsafe {
             if repaint_background {
-                FillRect(hdc, rect, self.assets.background_brush());
-                set_opaque_rect(pixels, width, rect);
+                if self.assets.color_scheme().background2().is_some() {
+                    fill_gradient_rect(pixels, width, rect, self.assets.color_scheme());
+                } else {
+                    FillRect(hdc, rect, self.assets.background_brush());
+                    set_opaque_rect(pixels, width, rect);
+                }
             }
             SetTextColor(hdc, self.assets.font_color());
 
+            // Separator pads can't be selected, so they have no number and their text is set apart in the tag color
             let previous_font = SelectObject(hdc, self.assets.tile_id_font().into());
-            let _ = TextOutW(hdc, rect.right-15, rect.bottom-25, to_wstr(&self.pad_id.to_string()).as_slice());
-            let id_rect = RECT {
-                left: rect.right-20,
-                right: rect.right-3,
-                top: rect.bottom-25,
-                bottom: rect.bottom-3
-            };
-            alpha_blend_rect(pixels, width, &id_rect, self.assets.color_scheme().background().to_colorref(), self.assets.font_color(), self.assets.color_scheme().opacity() as f32);
+            if !self.pad.is_label() {
+                let label = if self.letter_label { self.pad_id.as_letter().to_string() } else { self.pad_id.to_string() };
+                let label_rect = RECT {
+                    left: rect.right-15,
+                    right: rect.right-3,
+                    top: rect.bottom-25,
+                    bottom: rect.bottom-3
+                };
+                let id_rect = RECT {
+                    left: rect.right-20,
+                    right: rect.right-3,
+                    top: rect.bottom-25,
+                    bottom: rect.bottom-3
+                };
+                text.draw_text(&mut Canvas::new(hdc, pixels, width), &label, &label_rect, DT_LEFT | DT_TOP | DT_SINGLELINE | DT_NOCLIP | DT_NOPREFIX,
+                    Some(&id_rect), self.assets);
+            }
 
             SelectObject(hdc, self.assets.tile_header_font().into());
 
-            if self.pad.disabled() {
-                SetTextColor(hdc, self.assets.font_disabled_color());
-            }
+            let fg_color = match (self.pad.disabled(), self.pad.is_label()) {
+                (true, _) => self.assets.font_disabled_color(),
+                (false, true) => self.assets.tag_color(),
+                (false, false) => self.assets.font_color(),
+            };
+            SetTextColor(hdc, fg_color);
 
             // Header at top of tile
             let header_height = 60; // Enough space for 3 lines
-            let mut header_rect = RECT{
+            let header_rect = RECT{
                 left: rect.left,
                 right: rect.right,
                 top: rect.top,
                 bottom: rect.top + header_height
             };
 
-            DrawTextW(hdc, to_wstr(&self.pad.header()).as_mut_slice(),
-                &mut header_rect, DT_CENTER | DT_TOP | DT_WORDBREAK | DT_WORD_ELLIPSIS | DT_NOPREFIX);
+            text.draw_text(&mut Canvas::new(hdc, pixels, width), &self.pad.header(), &header_rect, DT_CENTER | DT_TOP | DT_WORDBREAK | DT_WORD_ELLIPSIS | DT_NOPREFIX,
+                Some(&resize_rect(&header_rect, -2, -1)), self.assets);
 
-            // Apply alpha blending to header text
-            let bg_color = self.assets.color_scheme().background().to_colorref();
-            let fg_color = if self.pad.disabled() { self.assets.font_disabled_color() } else { self.assets.font_color() };
-            let bg_opacity = self.assets.color_scheme().opacity();
-            alpha_blend_rect(pixels, width, &resize_rect(&header_rect, -2, -1), bg_color, fg_color, bg_opacity as f32);
+            // Main content area: icon and text - aligned in tile as the pad says, centered by default (independent of header)
+            let content_rect = RECT {
+                left: rect.left + TILE_MARGIN.0,
+                right: rect.right - TILE_MARGIN.0,
+                top: rect.top + TILE_MARGIN.1,       // margin from top (header area)
+                bottom: rect.bottom - TILE_MARGIN.1  // margin from bottom (pad ID area)
+            };
 
-            // Main content area: icon or text - vertically centered in tile (independent of header)
-            SelectObject(hdc, self.assets.tile_text_font().into());
+            let shrunk_font = if self.pad.shrink_text() { self.shrink_text_font(hdc, &content_rect) } else { None };
+            SelectObject(hdc, shrunk_font.unwrap_or(self.assets.tile_text_font()).into());
             let mut text_size = RECT::default();
             DrawTextW(hdc, to_wstr(&self.pad.text()).as_mut_slice(), &mut text_size, DT_CALCRECT | DT_NOPREFIX);
 
-            let content_rect = RECT {
-                left: rect.left + 20,
-                right: rect.right - 20,
-                top: rect.top + 25,        // 25px margin from top (header area)
-                bottom: rect.bottom - 25   // 25px margin from bottom (pad ID area)
+            // Icon above the text, both sized by the text height; shrunk text is laid out wrapped, as it was fitted
+            let icon_size = if self.pad.icon().is_empty() { 0 } else { text_size.bottom };
+            let text_height = match self.pad.shrink_text() {
+                true => wrapped_text_size(hdc, &self.pad.text(), content_rect.right - content_rect.left).bottom,
+                false => text_size.bottom,
             };
 
-            let mut icon_size = 0;
+            let vertical_align = if self.pad.is_label() { VerticalAlign::Center } else { self.pad.vertical_align() };
+            let (text_rect, icon_y, valign) = match vertical_align {
+                VerticalAlign::Top => {
+                    let top = content_rect.top + icon_size;
+                    (RECT { left: content_rect.left, right: content_rect.right, top, bottom: top + text_height },
+                        content_rect.top, DT_TOP)
+                },
+                VerticalAlign::Bottom => {
+                    let top = content_rect.bottom - text_height;
+                    (RECT { left: content_rect.left, right: content_rect.right, top, bottom: content_rect.bottom },
+                        top - icon_size, DT_BOTTOM)
+                },
+                VerticalAlign::Center => {
+                    let gap = POINT { x: (content_rect.right - content_rect.left - text_size.right)/2,
+                                    y: (content_rect.bottom - content_rect.top - text_height)/2 };
+                    (RECT {
+                        left: content_rect.left, //  + gap.x,
+                        right: content_rect.right, // - gap.x,
+                        bottom: content_rect.bottom + icon_size - gap.y,
+                        top: content_rect.top + icon_size + gap.y },
+                        (content_rect.top + content_rect.bottom) / 2 - icon_size / 2, DT_BOTTOM)
+                },
+            };
 
-            if ! self.pad.icon().is_empty() {
-                // Draw icon if available
-                icon_size = text_size.bottom; // Use text height as icon size
+            if icon_size > 0 {
                 let center_x = (content_rect.left + content_rect.right) / 2;
-                let center_y = (content_rect.top + content_rect.bottom) / 2;
                 IconPainter::paint(
                     hdc,
                     &self.pad.icon(),
                     self.assets.font_color(),
                     center_x - icon_size / 2,
-                    center_y - icon_size / 2,
+                    icon_y,
                     icon_size
                 );
             }
 
             // Draw text
-            let gap = POINT { x: (content_rect.right - content_rect.left - text_size.right)/2,
-                            y: (content_rect.bottom - content_rect.top - text_size.bottom)/2 };
-            let mut text_rect = RECT {
-                left: content_rect.left, //  + gap.x,
-                right: content_rect.right, // - gap.x,
-                bottom: content_rect.bottom + icon_size - gap.y,
-                top: content_rect.top + icon_size + gap.y };
-
-            DrawTextW(hdc, to_wstr(&self.pad.text()).as_mut_slice(),
-                &mut text_rect, DT_WORDBREAK | DT_CENTER | DT_BOTTOM | DT_WORD_ELLIPSIS | DT_NOCLIP | DT_NOPREFIX);
-
-            // Apply alpha blending to main text
-            alpha_blend_rect(pixels, width, &text_rect, bg_color, fg_color, bg_opacity as f32);
+            text.draw_text(&mut Canvas::new(hdc, pixels, width), &self.pad.text(), &text_rect, DT_WORDBREAK | DT_CENTER | valign | DT_WORD_ELLIPSIS | DT_NOCLIP | DT_NOPREFIX,
+                Some(&text_rect), self.assets);
 
             // Draw tags
             self.pad.tags().iter().for_each(|tag| {
-                TagPainter::draw_tag(hdc, tag, rect, self.assets, pixels, width);
+                TagPainter::draw_tag(hdc, tag, rect, self.assets, text, pixels, width);
             });
 
             SelectObject(hdc, previous_font);
             SetTextColor(hdc, self.assets.font_color());
+            if let Some(font) = shrunk_font {
+                let _ = DeleteObject(font.into());
+            }
         }
     }
+
+    /// A smaller tile text font the pad's icon and wrapped text fit `content_rect` with, None when the tile text font fits.
+    /// The font is stepped down at most MAX_SHRINK_STEPS times and not below MIN_SHRUNK_FONT_SIZE, the last one tried is
+    /// used even if the text still doesn't fit. The caller deletes the font.
+    unsafe fn shrink_text_font(&self, hdc: HDC, content_rect: &RECT) -> Option<HFONT> {
+        let text = self.pad.text();
+        let text_style = self.assets.text_style();
+        let (width, height) = (content_rect.right - content_rect.left, content_rect.bottom - content_rect.top);
+        let font_size = TextStyle::parse_font(&text_style.pad_text_font).3 as f64 * self.assets.zoom();
+
+        let mut font: Option<HFONT> = None;
+        let mut scale = 1.0;
+        for step in 0..=MAX_SHRINK_STEPS {
+            SelectObject(hdc, font.unwrap_or(self.assets.tile_text_font()).into());
+            let mut text_size = RECT::default();
+            DrawTextW(hdc, to_wstr(&text).as_mut_slice(), &mut text_size, DT_CALCRECT | DT_NOPREFIX);
+            let icon_size = if self.pad.icon().is_empty() { 0 } else { text_size.bottom };
+            let wrapped = wrapped_text_size(hdc, &text, width);
+            if (wrapped.right <= width && icon_size + wrapped.bottom <= height) || step == MAX_SHRINK_STEPS
+                || font_size * scale * SHRINK_FACTOR < MIN_SHRUNK_FONT_SIZE {
+                break;
+            }
+
+            scale *= SHRINK_FACTOR;
+            if let Some(font) = font.take() {
+                let _ = DeleteObject(font.into());
+            }
+            font = Some(text_style.create_scaled_font(&text_style.pad_text_font, self.assets.zoom() * scale));
+        }
+        font
+    }
+}
+
+/// Size of `text` in the font selected into the DC when wrapped at `width`, wider if a single word doesn't fit
+unsafe fn wrapped_text_size(hdc: HDC, text: &str, width: i32) -> RECT {
+    let mut text_size = RECT { left: 0, top: 0, right: width, bottom: 0 };
+    DrawTextW(hdc, to_wstr(text).as_mut_slice(), &mut text_size, DT_CALCRECT | DT_WORDBREAK | DT_NOPREFIX);
+    text_size
 }
 
 impl<'a> HeaderPainter<'a> {
-    pub fn paint(&self, hdc: HDC, rect: &RECT, icon: Option<String>,  pixels: &mut [RGBA], width: usize) {
+    pub fn paint(&self, hdc: HDC, rect: &RECT, icon: Option<String>, text: &mut dyn TextRenderer, pixels: &mut [RGBA], width: usize) {
         unsafe {
             let previous_font = SelectObject(hdc, self.assets.header_font().into());
 
@@ -386,47 +558,48 @@ impl<'a> HeaderPainter<'a> {
                     );
 
                     // Draw title next to icon
-                    let mut title_rect = RECT {
+                    let title_rect = RECT {
                         left: text_x,
                         right: text_x + text_width,
                         top: rect.top + 5,
                         bottom: rect.bottom - 5,
                     };
-                    DrawTextW(hdc, to_wstr(&self.title).as_mut_slice(), &mut title_rect, DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX);
-                    alpha_blend_rect(pixels, width, &resize_rect(&title_rect, -1, -1), self.assets.color_scheme().background().to_colorref(), self.assets.font_color(), self.assets.color_scheme().opacity() as f32);
+                    text.draw_text(&mut Canvas::new(hdc, pixels, width), self.title, &title_rect, DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX,
+                        Some(&resize_rect(&title_rect, -1, -1)), self.assets);
                 } else {
                     // Just draw title centered (no icon)
-                    let mut title_rect = RECT {
+                    let title_rect = RECT {
                         left: rect.left + 10,
                         right: rect.right - 10,
                         top: rect.top + 5,
                         bottom: rect.bottom - 5,
                     };
-                    DrawTextW(hdc, to_wstr(&self.title).as_mut_slice(), &mut title_rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX);
-                    alpha_blend_rect(pixels, width, &resize_rect(&title_rect, -1, -1), self.assets.color_scheme().background().to_colorref(), self.assets.font_color(), self.assets.color_scheme().opacity() as f32);
+                    text.draw_text(&mut Canvas::new(hdc, pixels, width), self.title, &title_rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX,
+                        Some(&resize_rect(&title_rect, -1, -1)), self.assets);
                 }
             } else {
                 // Just draw title centered (no icon)
-                let mut title_rect = RECT {
+                let title_rect = RECT {
                     left: rect.left + 10,
                     right: rect.right - 10,
                     top: rect.top + 5,
                     bottom: rect.bottom - 5,
                 };
-                DrawTextW(hdc, to_wstr(&self.title).as_mut_slice(), &mut title_rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX);
-                alpha_blend_rect(pixels, width, &resize_rect(&title_rect, -1, -1), self.assets.color_scheme().background().to_colorref(), self.assets.font_color(), self.assets.color_scheme().opacity() as f32);
+                text.draw_text(&mut Canvas::new(hdc, pixels, width), self.title, &title_rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX,
+                    Some(&resize_rect(&title_rect, -1, -1)), self.assets);
             }
 
             // Draw the timeout dots, VCENTER, RIGHT
             if self.timeout > 0 {
                 let timeout_text = ".".repeat(self.timeout as usize);
-                let mut timeout_rect = RECT {
+                let timeout_rect = RECT {
                     left: rect.right - 100,
                     right: rect.right - 10,
                     top: rect.top + 5,
                     bottom: rect.bottom - 5,
                 };
-                DrawTextW(hdc, to_wstr(&timeout_text).as_mut_slice(), &mut timeout_rect, DT_RIGHT | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX);
+                text.draw_text(&mut Canvas::new(hdc, pixels, width), &timeout_text, &timeout_rect, DT_RIGHT | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX,
+                    None, self.assets);
             }
 
             SelectObject(hdc, previous_font);
@@ -434,6 +607,111 @@ impl<'a> HeaderPainter<'a> {
     }
 }
 
+/// Icons the first paint of `board` draws, sized and colored the way the tile and header painters do it
+pub fn icon_requests(board: &dyn Board, zoom: f64) -> Vec<IconRequest> {
+    let color_scheme = board.color_scheme();
+    let text_style = board.text_style();
+    let board_assets = Assets::new(&color_scheme, &text_style, zoom);
+    let mut requests = vec![];
+
+    unsafe {
+        let hdc = CreateCompatibleDC(None);
+        let text_height = |font: HFONT, text: &str, format| {
+            SelectObject(hdc, font.into());
+            let mut text_size = RECT::default();
+            DrawTextW(hdc, to_wstr(text).as_mut_slice(), &mut text_size, format);
+            text_size.bottom
+        };
+
+        let padset = board.padset(Some(ModifierState::default()));
+        for pad_id in PadId::all() {
+            let pad = padset.pad(pad_id);
+            // Shrunk text sizes its icon by the tile, which isn't known before the window is
+            if pad.icon().is_empty() || pad.shrink_text() {
+                continue;
+            }
+            let pad_assets;
+            let assets = if pad.color_scheme.is_some() || pad.text_style.is_some() {
+                let color_scheme = pad.color_scheme.as_ref().unwrap_or(board_assets.color_scheme());
+                let text_style = pad.text_style.as_ref().unwrap_or(board_assets.text_style());
+                pad_assets = Assets::new(color_scheme, text_style, zoom);
+                &pad_assets
+            } else {
+                &board_assets
+            };
+            requests.push(IconRequest {
+                icon: pad.icon(),
+                size: text_height(assets.tile_text_font(), &pad.text(), DT_CALCRECT | DT_NOPREFIX),
+                color: assets.font_color(),
+            });
+        }
+
+        if let Some(icon) = board.icon().filter(|icon| !icon.is_empty()) {
+            requests.push(IconRequest {
+                icon,
+                size: text_height(board_assets.header_font(), &board.title(), DT_CALCRECT | DT_SINGLELINE | DT_NOPREFIX),
+                color: board_assets.font_color(),
+            });
+        }
+
+        let _ = DeleteDC(hdc);
+    }
+    requests
+}
+
+/// Client size fitting the content of `board`: its widest and tallest pad, icon included, and its title.
+/// Measured in the fonts the tile and header painters use, with the margins they keep around the text.
+pub fn content_size(board: &dyn Board, zoom: f64) -> (i32, i32) {
+    let color_scheme = board.color_scheme();
+    let text_style = board.text_style();
+    let board_assets = Assets::new(&color_scheme, &text_style, zoom);
+    let (mut tile_width, mut tile_height) = (0, 0);
+    let (header_width, header_height);
+
+    unsafe {
+        let hdc = CreateCompatibleDC(None);
+        // The DC's own font is selected back after each measure, the fonts belong to assets dropped before the DC
+        let text_size = |font: HFONT, text: &str, format| {
+            let previous = SelectObject(hdc, font.into());
+            let mut text_size = RECT::default();
+            DrawTextW(hdc, to_wstr(text).as_mut_slice(), &mut text_size, format);
+            SelectObject(hdc, previous);
+            (text_size.right, text_size.bottom)
+        };
+
+        let padset = board.padset(Some(ModifierState::default()));
+        for pad_id in PadId::all() {
+            let pad = padset.pad(pad_id);
+            let pad_assets;
+            let assets = if pad.color_scheme.is_some() || pad.text_style.is_some() {
+                let color_scheme = pad.color_scheme.as_ref().unwrap_or(board_assets.color_scheme());
+                let text_style = pad.text_style.as_ref().unwrap_or(board_assets.text_style());
+                pad_assets = Assets::new(color_scheme, text_style, zoom);
+                &pad_assets
+            } else {
+                &board_assets
+            };
+            let (text_width, text_height) = text_size(assets.tile_text_font(), &pad.text(), DT_CALCRECT | DT_NOPREFIX);
+            let icon_size = if pad.icon().is_empty() { 0 } else { text_height };
+            let (pad_header_width, _) = text_size(assets.tile_header_font(), &pad.header(), DT_CALCRECT | DT_SINGLELINE | DT_NOPREFIX);
+            tile_width = tile_width.max(text_width + 2 * TILE_MARGIN.0).max(pad_header_width + 4);
+            tile_height = tile_height.max(icon_size + text_height + 2 * TILE_MARGIN.1);
+        }
+
+        let (title_width, title_height) = text_size(board_assets.header_font(), &board.title(), DT_CALCRECT | DT_SINGLELINE | DT_NOPREFIX);
+        let icon_width = if board.icon().is_some_and(|icon| !icon.is_empty()) { title_height + 10 } else { 0 };
+        header_width = icon_width + title_width + 2 * HEADER_SIDE_SPACE;
+        header_height = title_height + 10;
+
+        let _ = DeleteDC(hdc);
+    }
+
+    // The header takes a tenth of the height, each of the three rows of tiles three tenths
+    let width = (3 * tile_width).max(header_width);
+    let height = ((tile_height * 10 + 2) / 3).max(10 * header_height);
+    (width, height)
+}
+
 impl IconPainter {
     pub fn paint(hdc: HDC, icon_path: &str, color: COLORREF, x: i32, y: i32, size: i32) {
         if !icon_path.is_empty() {
@@ -456,10 +734,21 @@ impl IconPainter {
     }
 }
 
+/// Horizontal room between a tag's text and the edges of its chip
+const TAG_CHIP_PADDING: i32 = 3;
+/// Space between a key cap's frame and its label on either side, half of it above and below
+const KEY_CAP_PADDING: i32 = 4;
+/// Space between the caps of a combination, combinations played one after another are set further apart
+const KEY_CAP_GAP: i32 = 3;
+const KEY_CAP_SEQUENCE_GAP: i32 = 10;
+
 struct TagPainter;
 
 impl TagPainter {
-    pub fn draw_tag(hdc: HDC, tag: &Tag, rect: &RECT, assets: &Assets, pixels: &mut [RGBA], width: usize) {
+    pub fn draw_tag(hdc: HDC, tag: &Tag, rect: &RECT, assets: &Assets, text: &mut dyn TextRenderer, pixels: &mut [RGBA], width: usize) {
+        if tag.key_caps {
+            return Self::draw_key_caps(hdc, tag, rect, assets, text, pixels, width);
+        }
         unsafe {
             let font = tag.get_font(assets);
             let color = tag.get_color(assets);
@@ -491,17 +780,72 @@ impl TagPainter {
             // FillRect(hdc, &target_rect, assets.selected_tile_brush());
             // set_opaque_rect(pixels, width, rect);
 
+            // Badge-style tags sit on an opaque chip, the text on it needs no blending
+            let chip = tag.get_background(assets);
+            if let Some(brush) = chip {
+                let chip_rect = RECT { left: target_rect.left - TAG_CHIP_PADDING, right: target_rect.right + TAG_CHIP_PADDING, ..target_rect };
+                FillRect(hdc, &chip_rect, brush);
+                set_opaque_rect(pixels, width, &chip_rect);
+            }
+
             // Use Windows text alignment within the calculated rect
             let dt_flags = handle.to_dt_flags() | DT_VCENTER | DT_NOPREFIX; //  | DT_SINGLELINE;
-            let mut draw_rect = target_rect;
-            DrawTextW(hdc, to_wstr(&tag.text).as_mut_slice(), &mut draw_rect, dt_flags);
+            let blend = if chip.is_some() { None } else { Some(&target_rect) };
+            text.draw_text(&mut Canvas::new(hdc, pixels, width), &tag.text, &target_rect, dt_flags, blend, assets);
+
+            SelectObject(hdc, previous_font);
+            SetTextColor(hdc, previous_color);
+        }
+    }
 
-            // Apply alpha blending for transparency
-            let bg_color = assets.color_scheme().background().to_colorref();
-            let fg_color = color;
-            let bg_opacity = assets.color_scheme().opacity() as f32;
-            alpha_blend_rect(pixels, width, &target_rect, bg_color, fg_color, bg_opacity);
+    /// Draws the tag's shortcut as a row of framed keys, the row placed like the text of a plain tag
+    fn draw_key_caps(hdc: HDC, tag: &Tag, rect: &RECT, assets: &Assets, text: &mut dyn TextRenderer, pixels: &mut [RGBA], width: usize) {
+        unsafe {
+            let previous_font = SelectObject(hdc, tag.get_font(assets).into());
+            let previous_color = SetTextColor(hdc, tag.get_color(assets));
+            let previous_pen = SelectObject(hdc, assets.line_pen().into());
+
+            // Label widths, one list per combination, and the height of the tallest label
+            let mut label_height = 0;
+            let combinations: Vec<Vec<(String, i32)>> = shortcut_key_caps(&tag.text).into_iter()
+                .map(|keys| keys.into_iter()
+                    .map(|key| {
+                        let mut size = RECT::default();
+                        DrawTextW(hdc, to_wstr(&key).as_mut_slice(), &mut size, DT_CALCRECT | DT_SINGLELINE | DT_NOPREFIX);
+                        label_height = label_height.max(size.bottom);
+                        (key, size.right + 2 * KEY_CAP_PADDING)
+                    })
+                    .collect())
+                .collect();
+
+            let row_width = combinations.iter()
+                .map(|keys| keys.iter().map(|(_, cap_width)| cap_width + KEY_CAP_GAP).sum::<i32>() - KEY_CAP_GAP)
+                .sum::<i32>()
+                + KEY_CAP_SEQUENCE_GAP * (combinations.len() as i32 - 1).max(0);
 
+            let (anchor_x, anchor_y) = tag.anchor.to_coords(rect);
+            let row = Self::calculate_target_rect(anchor_x as i32, anchor_y as i32, row_width, label_height + KEY_CAP_PADDING, tag.get_effective_handle());
+
+            let chip = tag.get_background(assets);
+            let mut left = row.left;
+            for keys in &combinations {
+                for (key, cap_width) in keys {
+                    let cap = RECT { left, right: left + cap_width, ..row };
+                    if let Some(brush) = chip {
+                        FillRect(hdc, &cap, brush);
+                        set_opaque_rect(pixels, width, &cap);
+                    }
+                    draw_rect(hdc, pixels, width, &cap, 2);
+
+                    let label = resize_rect(&cap, -KEY_CAP_PADDING, 0);
+                    let blend = if chip.is_some() { None } else { Some(&label) };
+                    text.draw_text(&mut Canvas::new(hdc, pixels, width), key, &label, DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX, blend, assets);
+                    left = cap.right + KEY_CAP_GAP;
+                }
+                left += KEY_CAP_SEQUENCE_GAP - KEY_CAP_GAP;
+            }
+
+            SelectObject(hdc, previous_pen);
             SelectObject(hdc, previous_font);
             SetTextColor(hdc, previous_color);
         }
@@ -546,9 +890,3 @@ impl TagPainter {
         }
     }
 }
-
-fn to_wstr(str: &str) -> Vec<u16> {
-    str.encode_utf16()
-        .chain(Some(0))
-        .collect::<Vec<_>>()
-}
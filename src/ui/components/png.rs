@@ -9,31 +9,72 @@ use windows::Win32::Graphics::Gdi::{
 };
 use once_cell::unsync::Lazy;
 
+use super::warmup::{is_warming, take_warm_icon};
+
 pub struct PngIcon {
     hbitmap: HBITMAP,
     size: i32,
 }
 
-impl PngIcon {
-    fn from_png_file(path: &str, size: i32, hdc: HDC) -> Option<Self> {
-        // Read the PNG file
-        let png_data = std::fs::read(path).ok()?;
-
-        // Decode PNG using resvg's image handling (via tiny_skia which supports PNG)
-        let img = image::load_from_memory(&png_data).ok()?;
-        let rgba_img = img.to_rgba8();
-        let (width, height) = rgba_img.dimensions();
-
-        // Scale the image to fit the requested size while maintaining aspect ratio
-        let scale_x = size as f32 / width as f32;
-        let scale_y = size as f32 / height as f32;
-        let scale = scale_x.min(scale_y);
+/// Key of an icon scaled to `size`, shared with the warmup
+pub fn png_cache_key(icon_name: &str, size: i32) -> String {
+    format!("{}:{}", icon_name, size)
+}
 
-        let new_width = (width as f32 * scale) as u32;
-        let new_height = (height as f32 * scale) as u32;
+/// Decodes and scales the image into `size` x `size` premultiplied BGRA pixels, centered on a transparent background.
+/// Needs no device context, so it can run on any thread.
+pub fn rasterize_png(path: &str, size: i32) -> Option<Vec<u8>> {
+    // Read the PNG file
+    let png_data = std::fs::read(path).ok()?;
+
+    // Decode PNG using resvg's image handling (via tiny_skia which supports PNG)
+    let img = image::load_from_memory(&png_data).ok()?;
+    let rgba_img = img.to_rgba8();
+    let (width, height) = rgba_img.dimensions();
+
+    // Scale the image to fit the requested size while maintaining aspect ratio
+    let scale_x = size as f32 / width as f32;
+    let scale_y = size as f32 / height as f32;
+    let scale = scale_x.min(scale_y);
+
+    let new_width = (width as f32 * scale) as u32;
+    let new_height = (height as f32 * scale) as u32;
+
+    let resized = image::imageops::resize(&rgba_img, new_width, new_height, image::imageops::FilterType::Triangle);
+
+    // Transparent background
+    let mut dst = vec![0u8; (size * size * 4) as usize];
+
+    // Calculate centering offset
+    let offset_x = (size as u32 - new_width) / 2;
+    let offset_y = (size as u32 - new_height) / 2;
+
+    // Copy resized PNG data, converting RGBA to BGRA with premultiplied alpha and centering
+    let raw_data = resized.as_raw();
+    for y in 0..new_height {
+        for x in 0..new_width {
+            let src_idx = ((y * new_width + x) * 4) as usize;
+            let dst_x = x + offset_x;
+            let dst_y = y + offset_y;
+            let dst_idx = ((dst_y * size as u32 + dst_x) * 4) as usize;
+
+            if dst_idx + 3 < dst.len() && src_idx + 3 < raw_data.len() {
+                let alpha = raw_data[src_idx + 3] as u16;
+
+                // Premultiply RGB with alpha for proper transparency (using integer math)
+                dst[dst_idx] = ((raw_data[src_idx + 2] as u16 * alpha) / 255) as u8;     // B
+                dst[dst_idx + 1] = ((raw_data[src_idx + 1] as u16 * alpha) / 255) as u8; // G
+                dst[dst_idx + 2] = ((raw_data[src_idx] as u16 * alpha) / 255) as u8;     // R
+                dst[dst_idx + 3] = raw_data[src_idx + 3]; // A
+            }
+        }
+    }
 
-        let resized = image::imageops::resize(&rgba_img, new_width, new_height, image::imageops::FilterType::Triangle);
+    Some(dst)
+}
 
+impl PngIcon {
+    fn from_pixels(pixels: &[u8], size: i32, hdc: HDC) -> Option<Self> {
         unsafe {
             let bmi = BITMAPINFO {
                 bmiHeader: BITMAPINFOHEADER {
@@ -58,39 +99,8 @@ impl PngIcon {
                 return None;
             }
 
-            // Fill bitmap with transparent background
             let dst = std::slice::from_raw_parts_mut(bits as *mut u8, (size * size * 4) as usize);
-            for pixel in dst.chunks_exact_mut(4) {
-                pixel[0] = 0; // B
-                pixel[1] = 0; // G
-                pixel[2] = 0; // R
-                pixel[3] = 0; // A
-            }
-
-            // Calculate centering offset
-            let offset_x = (size as u32 - new_width) / 2;
-            let offset_y = (size as u32 - new_height) / 2;
-
-            // Copy resized PNG data to bitmap, converting RGBA to BGRA with premultiplied alpha and centering
-            let raw_data = resized.as_raw();
-            for y in 0..new_height {
-                for x in 0..new_width {
-                    let src_idx = ((y * new_width + x) * 4) as usize;
-                    let dst_x = x + offset_x;
-                    let dst_y = y + offset_y;
-                    let dst_idx = ((dst_y * size as u32 + dst_x) * 4) as usize;
-
-                    if dst_idx + 3 < dst.len() && src_idx + 3 < raw_data.len() {
-                        let alpha = raw_data[src_idx + 3] as u16;
-
-                        // Premultiply RGB with alpha for proper transparency (using integer math)
-                        dst[dst_idx] = ((raw_data[src_idx + 2] as u16 * alpha) / 255) as u8;     // B
-                        dst[dst_idx + 1] = ((raw_data[src_idx + 1] as u16 * alpha) / 255) as u8; // G
-                        dst[dst_idx + 2] = ((raw_data[src_idx] as u16 * alpha) / 255) as u8;     // R
-                        dst[dst_idx + 3] = raw_data[src_idx + 3]; // A
-                    }
-                }
-            }
+            dst.copy_from_slice(pixels);
 
             Some(Self { hbitmap, size })
         }
@@ -144,9 +154,19 @@ impl PngCache {
         map.clear();
     }
 
-    /// Lazy paint: load only if needed
+    pub fn contains(&self, icon_name: &str, size: i32) -> bool {
+        self.icons.lock().unwrap().contains_key(&png_cache_key(icon_name, size))
+    }
+
+    fn load(&self, icon_name: &str, size: i32) -> Option<Vec<u8>> {
+        let icon_path = self.resources.as_ref()?.icon(icon_name)?;
+        log::debug!("Icon '{}' loaded on paint", icon_name);
+        rasterize_png(icon_path.to_str()?, size)
+    }
+
+    /// Lazy paint: load only if needed, taking over the pixels when the warmup scaled them already
     pub fn paint(&self, hdc: HDC, icon_name: &str, size: i32, x: i32, y: i32) {
-        let cache_key = format!("{}:{}", icon_name, size);
+        let cache_key = png_cache_key(icon_name, size);
         let mut map = self.icons.lock().unwrap();
 
         match map.get(&cache_key) {
@@ -154,14 +174,16 @@ impl PngCache {
                 icon.paint(hdc, x, y);
             }
             _ => {
-                if let Some(ref resources) = self.resources {
-                    if let Some(icon_path) = resources.icon(icon_name) {
-                        if let Some(icon) = PngIcon::from_png_file(icon_path.to_str().unwrap(), size, hdc) {
-                            let arc = Arc::new(icon);
-                            arc.paint(hdc, x, y);
-                            map.insert(cache_key, arc);
-                        }
-                    }
+                let pixels = match take_warm_icon(&cache_key) {
+                    Some(pixels) => Some(pixels),
+                    // Left out until the repaint once the warmup is done
+                    None if is_warming(&cache_key) => return,
+                    None => self.load(icon_name, size),
+                };
+                if let Some(icon) = pixels.and_then(|pixels| PngIcon::from_pixels(&pixels, size, hdc)) {
+                    let arc = Arc::new(icon);
+                    arc.paint(hdc, x, y);
+                    map.insert(cache_key, arc);
                 }
             }
         }
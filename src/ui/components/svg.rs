@@ -9,32 +9,43 @@ use windows::Win32::Graphics::Gdi::{
 use resvg::{usvg, tiny_skia};
 use once_cell::unsync::Lazy;
 
+use super::warmup::{is_warming, take_warm_icon};
+
 pub struct SvgIcon {
     hbitmap: HBITMAP,
     size: i32,
 }
 
-impl SvgIcon {
-    fn from_svg(svg_data: &[u8], size: i32, color: (u8,u8,u8), hdc: HDC) -> Option<Self> {
-        // Parse + rasterize
-        let mut options = usvg::Options::default();
-        let color_str = format!("rgb({}, {}, {})", color.0, color.1, color.2);
-        options.style_sheet = Some(format!(
-            ".board-s {{ stroke: {}; }} \
-             .board-f {{ fill: {}; }} \
-             .board-sf {{ stroke: {}; fill: {}; }} ",
-            color_str, color_str, color_str, color_str
-        ));
-        let tree = usvg::Tree::from_data(svg_data, &options).ok()?;
-
-        let pixmap_size = tree.size().to_int_size();
-        let scale_x = size as f64 / pixmap_size.width() as f64;
-        let scale_y = size as f64 / pixmap_size.height() as f64;
-        let scale = scale_x.min(scale_y);
-
-        let mut pixmap = tiny_skia::Pixmap::new(size as u32, size as u32)?;
-        resvg::render(&tree, tiny_skia::Transform::from_scale(scale as f32, scale as f32), &mut pixmap.as_mut());
+/// Key of an icon rasterized at `size` in `color`, shared with the warmup
+pub fn svg_cache_key(icon_name: &str, size: i32, color: (u8,u8,u8)) -> String {
+    format!("{}:{}:{},{},{}", icon_name, size, color.0, color.1, color.2)
+}
+
+/// Parses and renders the icon into `size` x `size` pixels, the expensive part of loading it.
+/// Needs no device context, so it can run on any thread.
+pub fn rasterize_svg(svg_data: &[u8], size: i32, color: (u8,u8,u8)) -> Option<Vec<u8>> {
+    let mut options = usvg::Options::default();
+    let color_str = format!("rgb({}, {}, {})", color.0, color.1, color.2);
+    options.style_sheet = Some(format!(
+        ".board-s {{ stroke: {}; }} \
+         .board-f {{ fill: {}; }} \
+         .board-sf {{ stroke: {}; fill: {}; }} ",
+        color_str, color_str, color_str, color_str
+    ));
+    let tree = usvg::Tree::from_data(svg_data, &options).ok()?;
+
+    let pixmap_size = tree.size().to_int_size();
+    let scale_x = size as f64 / pixmap_size.width() as f64;
+    let scale_y = size as f64 / pixmap_size.height() as f64;
+    let scale = scale_x.min(scale_y);
+
+    let mut pixmap = tiny_skia::Pixmap::new(size as u32, size as u32)?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale as f32, scale as f32), &mut pixmap.as_mut());
+    Some(pixmap.take())
+}
 
+impl SvgIcon {
+    fn from_pixels(pixels: &[u8], size: i32, hdc: HDC) -> Option<Self> {
         unsafe {
             let bmi = BITMAPINFO {
                 bmiHeader: BITMAPINFOHEADER {
@@ -60,7 +71,7 @@ impl SvgIcon {
             }
 
             let dst = std::slice::from_raw_parts_mut(bits as *mut u8, (size * size * 4) as usize);
-            dst.copy_from_slice(pixmap.data());
+            dst.copy_from_slice(pixels);
 
             Some(Self { hbitmap, size })
         }
@@ -114,9 +125,19 @@ impl IconCache {
         map.clear();
     }
 
-    /// Lazy paint: load only if needed
+    pub fn contains(&self, icon_name: &str, size: i32, color: (u8,u8,u8)) -> bool {
+        self.icons.lock().unwrap().contains_key(&svg_cache_key(icon_name, size, color))
+    }
+
+    fn load(&self, icon_name: &str, size: i32, color: (u8,u8,u8)) -> Option<Vec<u8>> {
+        let icon_path = self.resources.as_ref()?.icon(icon_name)?;
+        log::debug!("Icon '{}' loaded on paint", icon_name);
+        rasterize_svg(&std::fs::read(&icon_path).ok()?, size, color)
+    }
+
+    /// Lazy paint: load only if needed, taking over the pixels when the warmup rasterized them already
     pub fn paint(&self, hdc: HDC, icon_name: &str, size: i32, color: (u8,u8,u8), x: i32, y: i32) {
-        let cache_key = format!("{}:{}:{},{},{}", icon_name, size, color.0, color.1, color.2);
+        let cache_key = svg_cache_key(icon_name, size, color);
         let mut map = self.icons.lock().unwrap();
 
         match map.get(&cache_key) {
@@ -124,16 +145,16 @@ impl IconCache {
                 icon.paint(hdc, x, y);
             }
             _ => {
-                if let Some(ref resources) = self.resources {
-                    if let Some(icon_path) = resources.icon(icon_name) {
-                        if let Ok(svg_data) = std::fs::read(&icon_path) {
-                            if let Some(icon) = SvgIcon::from_svg(&svg_data, size, color, hdc) {
-                                let arc = Arc::new(icon);
-                                arc.paint(hdc, x, y);
-                                map.insert(cache_key, arc);
-                            }
-                        }
-                    }
+                let pixels = match take_warm_icon(&cache_key) {
+                    Some(pixels) => Some(pixels),
+                    // Left out until the repaint once the warmup is done
+                    None if is_warming(&cache_key) => return,
+                    None => self.load(icon_name, size, color),
+                };
+                if let Some(icon) = pixels.and_then(|pixels| SvgIcon::from_pixels(&pixels, size, hdc)) {
+                    let arc = Arc::new(icon);
+                    arc.paint(hdc, x, y);
+                    map.insert(cache_key, arc);
                 }
             }
         }
@@ -0,0 +1,255 @@
+use std::cell::RefCell;
+
+use windows::core::{w, PCWSTR, Result};
+use windows::Win32::{
+    Foundation::{COLORREF, RECT},
+    Graphics::{
+        Direct2D::{
+            Common::{D2D1_ALPHA_MODE_PREMULTIPLIED, D2D1_COLOR_F, D2D1_PIXEL_FORMAT, D2D_RECT_F},
+            D2D1CreateFactory, ID2D1DCRenderTarget, ID2D1Factory, D2D1_DRAW_TEXT_OPTIONS_CLIP, D2D1_DRAW_TEXT_OPTIONS_NONE,
+            D2D1_FACTORY_TYPE_SINGLE_THREADED, D2D1_FEATURE_LEVEL_DEFAULT, D2D1_RENDER_TARGET_PROPERTIES,
+            D2D1_RENDER_TARGET_TYPE_DEFAULT, D2D1_RENDER_TARGET_USAGE_NONE, D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE,
+        },
+        DirectWrite::{
+            DWriteCreateFactory, IDWriteFactory, IDWriteTextFormat, DWRITE_FACTORY_TYPE_SHARED, DWRITE_PARAGRAPH_ALIGNMENT,
+            DWRITE_TEXT_ALIGNMENT, DWRITE_WORD_WRAPPING, DWRITE_FONT_STRETCH_NORMAL,
+            DWRITE_FONT_STYLE_ITALIC, DWRITE_FONT_STYLE_NORMAL, DWRITE_FONT_WEIGHT, DWRITE_FONT_WEIGHT_NORMAL,
+            DWRITE_MEASURING_MODE_NATURAL, DWRITE_PARAGRAPH_ALIGNMENT_CENTER, DWRITE_PARAGRAPH_ALIGNMENT_FAR,
+            DWRITE_PARAGRAPH_ALIGNMENT_NEAR, DWRITE_TEXT_ALIGNMENT_CENTER, DWRITE_TEXT_ALIGNMENT_LEADING,
+            DWRITE_TEXT_ALIGNMENT_TRAILING, DWRITE_TRIMMING, DWRITE_TRIMMING_GRANULARITY_WORD, DWRITE_WORD_WRAPPING_NO_WRAP,
+            DWRITE_WORD_WRAPPING_WRAP,
+        },
+        Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM,
+        Gdi::{
+            DrawTextW, GetCurrentObject, GetObjectW, GetTextColor, DRAW_TEXT_FORMAT, DT_BOTTOM, DT_CENTER, DT_NOCLIP,
+            DT_RIGHT, DT_SINGLELINE, DT_VCENTER, DT_WORDBREAK, DT_WORD_ELLIPSIS, HDC, LOGFONTW, OBJ_FONT,
+        },
+    },
+};
+
+use crate::core::Renderer;
+use crate::model::Color;
+use super::{assets::Assets, painter::{blend_text_rect, RGBA}};
+
+/// The memory DC a board is painted on and the pixels of its bitmap, `width` pixels to a row
+pub struct Canvas<'a> {
+    pub hdc: HDC,
+    pub pixels: &'a mut [RGBA],
+    pub width: usize,
+}
+
+impl<'a> Canvas<'a> {
+    pub fn new(hdc: HDC, pixels: &'a mut [RGBA], width: usize) -> Self {
+        Self { hdc, pixels, width }
+    }
+}
+
+/// Draws the texts of a board, the painter lays them out and the renderer puts them on the bitmap
+pub trait TextRenderer {
+    /// Draws `text` into `rect` with the font and text color selected into the DC, like DrawTextW does.
+    /// `blend` is the part of the bitmap to make show the text on translucent color schemes.
+    fn draw_text(&mut self, canvas: &mut Canvas, text: &str, rect: &RECT, format: DRAW_TEXT_FORMAT, blend: Option<&RECT>, assets: &Assets);
+}
+
+/// The renderer configured in settings
+pub fn text_renderer(renderer: Renderer, fast_render: bool) -> Box<dyn TextRenderer> {
+    match renderer {
+        Renderer::Gdi => Box::new(GdiText { fast_render }),
+        Renderer::Direct2D => Box::new(Direct2DText { gdi: GdiText { fast_render }, failed: false }),
+    }
+}
+
+/// GDI text, antialiased against the background and blended into the bitmap's alpha afterwards
+pub struct GdiText {
+    fast_render: bool,
+}
+
+impl TextRenderer for GdiText {
+    fn draw_text(&mut self, canvas: &mut Canvas, text: &str, rect: &RECT, format: DRAW_TEXT_FORMAT, blend: Option<&RECT>, assets: &Assets) {
+        unsafe {
+            let mut draw_rect = *rect;
+            DrawTextW(canvas.hdc, to_wstr(text).as_mut_slice(), &mut draw_rect, format);
+            if let Some(blend) = blend {
+                blend_text_rect(canvas.pixels, canvas.width, blend, assets, GetTextColor(canvas.hdc), self.fast_render);
+            }
+        }
+    }
+}
+
+/// DirectWrite text drawn through Direct2D, which writes the alpha of the text itself.
+/// Falls back to GDI for the rest of the paint when Direct2D fails.
+pub struct Direct2DText {
+    gdi: GdiText,
+    failed: bool,
+}
+
+impl TextRenderer for Direct2DText {
+    fn draw_text(&mut self, canvas: &mut Canvas, text: &str, rect: &RECT, format: DRAW_TEXT_FORMAT, blend: Option<&RECT>, assets: &Assets) {
+        if !self.failed {
+            let drawn = DIRECT2D.with(|cell| {
+                let mut direct2d = cell.borrow_mut();
+                if direct2d.is_none() {
+                    *direct2d = Some(Direct2D::new()?);
+                }
+                let result = direct2d.as_ref().unwrap().draw_text(canvas.hdc, text, rect, format);
+                if result.is_err() {
+                    // Created again on the next paint, devices can be lost
+                    *direct2d = None;
+                }
+                result
+            });
+            match drawn {
+                Ok(()) => return,
+                Err(e) => {
+                    log::warn!("Direct2D text rendering failed, falling back to GDI: {}", e);
+                    self.failed = true;
+                }
+            }
+        }
+        self.gdi.draw_text(canvas, text, rect, format, blend, assets);
+    }
+}
+
+struct Direct2D {
+    _factory: ID2D1Factory,
+    write_factory: IDWriteFactory,
+    target: ID2D1DCRenderTarget,
+}
+
+thread_local! {
+    static DIRECT2D: RefCell<Option<Direct2D>> = RefCell::new(None);
+}
+
+impl Direct2D {
+    fn new() -> Result<Self> {
+        unsafe {
+            let factory: ID2D1Factory = D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, None)?;
+            let write_factory: IDWriteFactory = DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)?;
+
+            // Board bitmaps are premultiplied BGRA, 96 DPI keeps DIPs equal to pixels
+            let properties = D2D1_RENDER_TARGET_PROPERTIES {
+                r#type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+                pixelFormat: D2D1_PIXEL_FORMAT {
+                    format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                    alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+                },
+                dpiX: 96.0,
+                dpiY: 96.0,
+                usage: D2D1_RENDER_TARGET_USAGE_NONE,
+                minLevel: D2D1_FEATURE_LEVEL_DEFAULT,
+            };
+            let target = factory.CreateDCRenderTarget(&properties)?;
+            // ClearType needs an opaque target, the board bitmap has an alpha channel
+            target.SetTextAntialiasMode(D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE);
+
+            Ok(Self { _factory: factory, write_factory, target })
+        }
+    }
+
+    /// Binds the target to just the text's rect, so text is drawn in turn with what GDI paints around it
+    fn draw_text(&self, hdc: HDC, text: &str, rect: &RECT, format: DRAW_TEXT_FORMAT) -> Result<()> {
+        unsafe {
+            let text_format = self.text_format(hdc, format)?;
+            let brush = self.target.CreateSolidColorBrush(&color_f(GetTextColor(hdc)), None)?;
+            let layout = D2D_RECT_F {
+                left: 0.0,
+                top: 0.0,
+                right: (rect.right - rect.left) as f32,
+                bottom: (rect.bottom - rect.top) as f32,
+            };
+            let wide: Vec<u16> = text.encode_utf16().collect();
+            let options = if format.contains(DT_NOCLIP) { D2D1_DRAW_TEXT_OPTIONS_NONE } else { D2D1_DRAW_TEXT_OPTIONS_CLIP };
+
+            self.target.BindDC(hdc, rect)?;
+            self.target.BeginDraw();
+            self.target.DrawText(&wide, &text_format, &layout, &brush, options, DWRITE_MEASURING_MODE_NATURAL);
+            self.target.EndDraw(None, None)
+        }
+    }
+
+    /// Text format of the font selected into the DC, aligned and trimmed as the DrawTextW flags say
+    fn text_format(&self, hdc: HDC, format: DRAW_TEXT_FORMAT) -> Result<IDWriteTextFormat> {
+        unsafe {
+            let mut font = LOGFONTW::default();
+            GetObjectW(GetCurrentObject(hdc, OBJ_FONT), std::mem::size_of::<LOGFONTW>() as i32, Some(&mut font as *mut _ as _));
+
+            let weight = if font.lfWeight > 0 { DWRITE_FONT_WEIGHT(font.lfWeight) } else { DWRITE_FONT_WEIGHT_NORMAL };
+            let style = if font.lfItalic != 0 { DWRITE_FONT_STYLE_ITALIC } else { DWRITE_FONT_STYLE_NORMAL };
+            let text_format = self.write_factory.CreateTextFormat(
+                PCWSTR(font.lfFaceName.as_ptr()),
+                None,
+                weight,
+                style,
+                DWRITE_FONT_STRETCH_NORMAL,
+                font.lfHeight.unsigned_abs() as f32,
+                w!(""),
+            )?;
+
+            let (horizontal, vertical, wrapping) = text_alignment(format);
+            text_format.SetTextAlignment(horizontal)?;
+            text_format.SetParagraphAlignment(vertical)?;
+            text_format.SetWordWrapping(wrapping)?;
+
+            // DT_WORD_ELLIPSIS: text running out of the rect ends with an ellipsis after the last whole word
+            if format.contains(DT_WORD_ELLIPSIS) {
+                let trimming = DWRITE_TRIMMING { granularity: DWRITE_TRIMMING_GRANULARITY_WORD, delimiter: 0, delimiterCount: 0 };
+                let ellipsis = self.write_factory.CreateEllipsisTrimmingSign(&text_format)?;
+                text_format.SetTrimming(&trimming, &ellipsis)?;
+            }
+            Ok(text_format)
+        }
+    }
+}
+
+/// DirectWrite alignment of DrawTextW flags, vertical alignment only applies to single lines as with GDI
+fn text_alignment(format: DRAW_TEXT_FORMAT) -> (DWRITE_TEXT_ALIGNMENT, DWRITE_PARAGRAPH_ALIGNMENT, DWRITE_WORD_WRAPPING) {
+    let horizontal = if format.contains(DT_CENTER) {
+        DWRITE_TEXT_ALIGNMENT_CENTER
+    } else if format.contains(DT_RIGHT) {
+        DWRITE_TEXT_ALIGNMENT_TRAILING
+    } else {
+        DWRITE_TEXT_ALIGNMENT_LEADING
+    };
+    let vertical = if !format.contains(DT_SINGLELINE) {
+        DWRITE_PARAGRAPH_ALIGNMENT_NEAR
+    } else if format.contains(DT_VCENTER) {
+        DWRITE_PARAGRAPH_ALIGNMENT_CENTER
+    } else if format.contains(DT_BOTTOM) {
+        DWRITE_PARAGRAPH_ALIGNMENT_FAR
+    } else {
+        DWRITE_PARAGRAPH_ALIGNMENT_NEAR
+    };
+    let wrapping = if format.contains(DT_WORDBREAK) { DWRITE_WORD_WRAPPING_WRAP } else { DWRITE_WORD_WRAPPING_NO_WRAP };
+    (horizontal, vertical, wrapping)
+}
+
+fn color_f(color: COLORREF) -> D2D1_COLOR_F {
+    let (r, g, b) = Color::from_colorref(color).to_rgb();
+    D2D1_COLOR_F { r: r as f32 / 255.0, g: g as f32 / 255.0, b: b as f32 / 255.0, a: 1.0 }
+}
+
+/// Null terminated UTF-16 for the GDI text functions
+pub(super) fn to_wstr(str: &str) -> Vec<u16> {
+    str.encode_utf16()
+        .chain(Some(0))
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::Graphics::Gdi::{DT_LEFT, DT_NOPREFIX, DT_TOP};
+
+    #[test]
+    fn test_text_alignment() {
+        assert_eq!(text_alignment(DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX),
+            (DWRITE_TEXT_ALIGNMENT_CENTER, DWRITE_PARAGRAPH_ALIGNMENT_CENTER, DWRITE_WORD_WRAPPING_NO_WRAP));
+        assert_eq!(text_alignment(DT_RIGHT | DT_BOTTOM | DT_SINGLELINE),
+            (DWRITE_TEXT_ALIGNMENT_TRAILING, DWRITE_PARAGRAPH_ALIGNMENT_FAR, DWRITE_WORD_WRAPPING_NO_WRAP));
+        // DrawTextW ignores vertical alignment of wrapped text
+        assert_eq!(text_alignment(DT_WORDBREAK | DT_CENTER | DT_BOTTOM),
+            (DWRITE_TEXT_ALIGNMENT_CENTER, DWRITE_PARAGRAPH_ALIGNMENT_NEAR, DWRITE_WORD_WRAPPING_WRAP));
+        assert_eq!(text_alignment(DT_LEFT | DT_TOP),
+            (DWRITE_TEXT_ALIGNMENT_LEADING, DWRITE_PARAGRAPH_ALIGNMENT_NEAR, DWRITE_WORD_WRAPPING_NO_WRAP));
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    thread,
+    time::Instant,
+};
+use once_cell::sync::Lazy;
+use windows::Win32::Foundation::COLORREF;
+
+use crate::core::Resources;
+use crate::model::Color;
+use super::{png::{png_cache_key, rasterize_png, PNG_CACHE}, svg::{rasterize_svg, svg_cache_key, ICON_CACHE}};
+
+/// Pixels rasterized ahead of the first paint, keyed as in the icon caches, which take them over on paint
+static WARM_ICONS: Lazy<Mutex<HashMap<String, Vec<u8>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Keys of the icons being rasterized in the background right now
+static WARMING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+pub(super) fn take_warm_icon(cache_key: &str) -> Option<Vec<u8>> {
+    WARM_ICONS.lock().unwrap().remove(cache_key)
+}
+
+/// Whether the icon is still being rasterized, the paint leaves it out and the repaint after the warmup draws it
+pub(super) fn is_warming(cache_key: &str) -> bool {
+    WARMING.lock().unwrap().contains(cache_key)
+}
+
+/// Drops the icons warmed up and not painted yet, the icon files may have changed since
+pub(super) fn clear_warm_icons() {
+    WARM_ICONS.lock().unwrap().clear();
+}
+
+/// An icon as a board paints it
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconRequest {
+    pub icon: String,
+    pub size: i32,
+    pub color: COLORREF,
+}
+
+impl IconRequest {
+    fn is_png(&self) -> bool {
+        self.icon.to_lowercase().ends_with(".png")
+    }
+
+    fn rgb(&self) -> (u8, u8, u8) {
+        Color::from_colorref(self.color).to_rgb()
+    }
+
+    fn cache_key(&self) -> String {
+        match self.is_png() {
+            true => png_cache_key(&self.icon, self.size),
+            false => svg_cache_key(&self.icon, self.size, self.rgb()),
+        }
+    }
+
+    /// Whether the icon caches of this thread hold the icon already, a cache borrowed by a paint counts as not
+    fn is_cached(&self) -> bool {
+        match self.is_png() {
+            true => PNG_CACHE.with(|cache| cache.try_borrow().map(|cache| cache.contains(&self.icon, self.size)).unwrap_or(false)),
+            false => ICON_CACHE.with(|cache| cache.try_borrow().map(|cache| cache.contains(&self.icon, self.size, self.rgb())).unwrap_or(false)),
+        }
+    }
+
+    fn rasterize(&self, resources: &Resources) -> Option<Vec<u8>> {
+        let path = resources.icon(&self.icon)?;
+        match self.is_png() {
+            true => rasterize_png(path.to_str()?, self.size),
+            false => rasterize_svg(&std::fs::read(&path).ok()?, self.size, self.rgb()),
+        }
+    }
+}
+
+/// Rasterizes the requested icons that are not cached yet on a background thread, spread over the available cores,
+/// and calls `done` there when some were. Icons warmed up for an earlier board and never painted are dropped.
+/// Must be called on the thread painting the boards, whose icon caches are checked.
+pub fn warm_up_icons(requests: Vec<IconRequest>, resources: &Resources, done: impl FnOnce() + Send + 'static) {
+    let mut pending: HashMap<String, IconRequest> = HashMap::new();
+    {
+        let mut warm = WARM_ICONS.lock().unwrap();
+        let requested: HashSet<String> = requests.iter().map(IconRequest::cache_key).collect();
+        warm.retain(|cache_key, _| requested.contains(cache_key));
+
+        for request in requests {
+            let cache_key = request.cache_key();
+            if !warm.contains_key(&cache_key) && !request.is_cached() {
+                pending.insert(cache_key, request);
+            }
+        }
+    }
+    if pending.is_empty() {
+        return;
+    }
+    WARMING.lock().unwrap().extend(pending.keys().cloned());
+    let pending: Vec<(String, IconRequest)> = pending.into_iter().collect();
+
+    let resources = resources.clone();
+    thread::spawn(move || {
+        let start = Instant::now();
+        let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(pending.len());
+        thread::scope(|scope| {
+            for chunk in pending.chunks(pending.len().div_ceil(workers)) {
+                let resources = &resources;
+                scope.spawn(move || {
+                    for (cache_key, request) in chunk {
+                        match request.rasterize(resources) {
+                            Some(pixels) => { WARM_ICONS.lock().unwrap().insert(cache_key.clone(), pixels); },
+                            None => log::warn!("Icon '{}' could not be warmed up", request.icon),
+                        }
+                        WARMING.lock().unwrap().remove(cache_key);
+                    }
+                });
+            }
+        });
+        log::debug!("Warmed up {} icons in {:?} on {} threads", pending.len(), start.elapsed(), workers);
+        done();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{path::PathBuf, sync::mpsc, time::Duration};
+
+    #[test]
+    fn test_warm_up_icons() {
+        let resources = Resources::new(vec![PathBuf::from("resources")]);
+        let request = |icon: &str| IconRequest { icon: icon.to_string(), size: 24, color: COLORREF(0x00ffffff) };
+        let requests = vec![request("gear.svg"), request("gear.svg"), request("icon.png"), request("missing.svg")];
+
+        let (sender, done) = mpsc::channel();
+        warm_up_icons(requests.clone(), &resources, move || sender.send(()).unwrap());
+        done.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        assert!(requests.iter().all(|request| !is_warming(&request.cache_key())));
+        assert_eq!(take_warm_icon(&requests[0].cache_key()).map(|pixels| pixels.len()), Some(24 * 24 * 4));
+        assert!(take_warm_icon(&requests[0].cache_key()).is_none());
+        assert!(take_warm_icon(&requests[3].cache_key()).is_none());
+
+        // The png warmed up but not painted is dropped by the warmup of the next board
+        warm_up_icons(vec![request("missing.svg")], &resources, || {});
+        assert!(take_warm_icon(&requests[2].cache_key()).is_none());
+    }
+}
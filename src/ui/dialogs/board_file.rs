@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use windows::core::*;
+use windows::Win32::Foundation::*;
+use windows::Win32::System::Com::*;
+use windows::Win32::UI::Shell::*;
+use windows::Win32::UI::Shell::Common::COMDLG_FILTERSPEC;
+
+/// Asks where to export a board, proposing `save_as` as the file name, or without it which board file to import.
+/// Returns None when the dialog is cancelled.
+pub fn pick_board_file(parent: Option<HWND>, save_as: Option<&str>) -> Option<PathBuf> {
+    // Also succeeds (S_FALSE) when COM was already initialized on the thread, each success needs its uninitialize
+    let initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+
+    let path = match show_file_dialog(parent, save_as) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            // Cancelling the dialog ends up here as well
+            log::debug!("No board file picked: {}", e);
+            None
+        }
+    };
+
+    if initialized {
+        unsafe { CoUninitialize() };
+    }
+    path
+}
+
+fn show_file_dialog(parent: Option<HWND>, save_as: Option<&str>) -> Result<PathBuf> {
+    unsafe {
+        let dialog: IFileDialog = match save_as {
+            Some(file_name) => {
+                let dialog: IFileSaveDialog = CoCreateInstance(&FileSaveDialog, None, CLSCTX_INPROC_SERVER)?;
+                dialog.SetTitle(w!("Export board"))?;
+                dialog.SetFileName(&HSTRING::from(file_name))?;
+                dialog.SetOptions(dialog.GetOptions()? | FOS_OVERWRITEPROMPT)?;
+                dialog.cast()?
+            },
+            None => {
+                let dialog: IFileOpenDialog = CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER)?;
+                dialog.SetTitle(w!("Import board"))?;
+                dialog.SetOptions(dialog.GetOptions()? | FOS_FILEMUSTEXIST)?;
+                dialog.cast()?
+            },
+        };
+        dialog.SetFileTypes(&[
+            COMDLG_FILTERSPEC { pszName: w!("Board files (*.json)"), pszSpec: w!("*.json") },
+            COMDLG_FILTERSPEC { pszName: w!("All files (*.*)"), pszSpec: w!("*.*") },
+        ])?;
+        dialog.SetDefaultExtension(w!("json"))?;
+        dialog.Show(parent)?;
+
+        let display_name = dialog.GetResult()?.GetDisplayName(SIGDN_FILESYSPATH)?;
+        let path = display_name.to_string();
+        CoTaskMemFree(Some(display_name.0 as *const _));
+        Ok(PathBuf::from(path?))
+    }
+}
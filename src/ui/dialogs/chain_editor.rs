@@ -7,11 +7,14 @@ use windows::Win32::UI::WindowsAndMessaging::*;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::Graphics::Gdi::{HBRUSH, COLOR_BTNFACE};
 
+use crate::ui::shared::utils::DpiUnawareScope;
+
 // Control IDs
 const ID_BOARDS_LIST: u16 = 1000;
 const ID_BOARDS_COMBO: u16 = 1001;
 const ID_ADD_BOARD: u16 = 1002;
 const ID_DELETE_BOARD: u16 = 1003;
+const ID_INCLUDE_COLLECTIONS: u16 = 1004;
 const IDOK: u16 = 1;
 const IDCANCEL: u16 = 2;
 
@@ -24,6 +27,8 @@ struct ChainEditor {
     chain_boards: Vec<String>,
     initial_board: String,
     all_boards: Vec<String>,
+    collections: Vec<String>,
+    include_collections: bool,
     result: DialogResult,
 }
 
@@ -35,16 +40,29 @@ enum DialogResult {
 }
 
 impl ChainEditor {
-    fn new(chain_boards: Vec<String>, initial_board: Option<String>, all_boards: Vec<String>) -> Self {
+    fn new(chain_boards: Vec<String>, initial_board: Option<String>, all_boards: Vec<String>, collections: Vec<String>) -> Self {
+        // Collections are offered from the start when the chain already nests one
+        let include_collections = chain_boards.iter().any(|b| collections.contains(b));
         Self {
             hwnd: HWND::default(),
             chain_boards,
             initial_board: initial_board.unwrap_or_default(),
             all_boards,
+            collections,
+            include_collections,
             result: DialogResult::None,
         }
     }
 
+    /// Boards offered in the combo, other collections only when enabled with the checkbox
+    fn candidates(&self) -> Vec<String> {
+        let mut candidates = self.all_boards.clone();
+        if self.include_collections {
+            candidates.extend(self.collections.iter().cloned());
+        }
+        candidates
+    }
+
     /// Register window class once using `Once` to ensure one-time initialization
     fn register_window_class(instance: HMODULE) {
         REGISTER_CHAIN_DIALOG_CLASS.call_once(|| {
@@ -118,6 +136,10 @@ impl ChainEditor {
             // Message loop with dialog message processing
             let mut msg = MSG::default();
             while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                if super::focus_cycle::cycle_focus(&msg) {
+                    continue;
+                }
+
                 // Use IsDialogMessage to handle tab navigation automatically
                 if !IsDialogMessageW(self.hwnd, &msg).as_bool() {
                     let _ = TranslateMessage(&msg);
@@ -151,7 +173,7 @@ impl ChainEditor {
         );
 
         // Boards combo
-        let combo = CreateWindowExW(
+        let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("COMBOBOX"),
             w!(""),
@@ -161,13 +183,8 @@ impl ChainEditor {
             Some(HMENU(ID_BOARDS_COMBO as _)),
             Some(instance.into()),
             None,
-        ).unwrap();
-
-        // Add action types to combo
-        for board in self.all_boards.iter() {
-            let wide = to_wide_string(board.as_str());
-            SendMessageW(combo, CB_ADDSTRING, Some(WPARAM(0)), Some(LPARAM(wide.as_ptr() as _)));
-        }
+        );
+        self.refresh_candidates();
 
         // Add/Delete buttons
         let _ = CreateWindowExW(
@@ -197,6 +214,19 @@ impl ChainEditor {
 
 
 
+        let include_check = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            w!("Include &collections"),
+            WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+            10, 172, 290, 20,
+            Some(self.hwnd),
+            Some(HMENU(ID_INCLUDE_COLLECTIONS as _)),
+            Some(instance.into()),
+            None,
+        ).unwrap();
+        SendMessageW(include_check, BM_SETCHECK, Some(WPARAM(if self.include_collections { 1 } else { 0 })), Some(LPARAM(0)));
+
         // OK/Cancel buttons
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
@@ -227,6 +257,23 @@ impl ChainEditor {
         self.refresh_chain_boards_list();
     }
 
+    unsafe fn refresh_candidates(&mut self) {
+        let combo = GetDlgItem(Some(self.hwnd), ID_BOARDS_COMBO as _).unwrap();
+        SendMessageW(combo, CB_RESETCONTENT, Some(WPARAM(0)), Some(LPARAM(0)));
+
+        for board in self.candidates() {
+            let text = if self.collections.contains(&board) { format!("{} (collection)", board) } else { board };
+            let wide = to_wide_string(text.as_str());
+            SendMessageW(combo, CB_ADDSTRING, Some(WPARAM(0)), Some(LPARAM(wide.as_ptr() as _)));
+        }
+    }
+
+    unsafe fn toggle_collections(&mut self) {
+        let check = GetDlgItem(Some(self.hwnd), ID_INCLUDE_COLLECTIONS as _).unwrap();
+        self.include_collections = SendMessageW(check, BM_GETCHECK, Some(WPARAM(0)), Some(LPARAM(0))).0 == 1;
+        self.refresh_candidates();
+    }
+
     unsafe fn refresh_chain_boards_list(&mut self) {
         let list = GetDlgItem(Some(self.hwnd), ID_BOARDS_LIST as _).unwrap();
         SendMessageW(list, LB_RESETCONTENT, Some(WPARAM(0)), Some(LPARAM(0)));
@@ -234,7 +281,9 @@ impl ChainEditor {
         let mut initial_board_found = false;
         for board in &self.chain_boards {
             let mut text = board.clone();
-            if !self.all_boards.contains(board) {
+            if self.collections.contains(board) {
+                text = format!("{} (collection)", text);
+            } else if !self.all_boards.contains(board) {
                 text = format!("{} (missing)", text);
             }
             if board == &self.initial_board && !initial_board_found {
@@ -253,8 +302,9 @@ impl ChainEditor {
             return;
         }
 
-        if sel < self.all_boards.len() {
-            let new_board = self.all_boards[sel].clone();
+        let candidates = self.candidates();
+        if sel < candidates.len() {
+            let new_board = candidates[sel].clone();
             if self.chain_boards.contains(&new_board) {
                 // Prevent adding duplicate boards
                 MessageBoxW(Some(self.hwnd), w!("This board is already in the list."), w!("Cannot Add Board"), MB_OK | MB_ICONWARNING);
@@ -339,6 +389,11 @@ impl ChainEditor {
                     }
                     ID_ADD_BOARD => (*dialog).add_board(),
                     ID_DELETE_BOARD => (*dialog).delete_board(),
+                    ID_INCLUDE_COLLECTIONS => {
+                        if notification == BN_CLICKED as u16 {
+                            (*dialog).toggle_collections();
+                        }
+                    }
                     _ => {}
                 }
                 LRESULT(0)
@@ -376,8 +431,10 @@ impl ChainEditor {
 }
 
 
-pub fn open_chain_editor(chain_boards: Vec<String>,  initial_board: Option<String>, all_boards: Vec<String>, parent: Option<HWND>) -> Option<(Vec<String>, String)> {
-    let mut editor = ChainEditor::new(chain_boards, initial_board, all_boards);
+/// `collections` are other collections that can be nested, offered once "Include collections" is checked
+pub fn open_chain_editor(chain_boards: Vec<String>,  initial_board: Option<String>, all_boards: Vec<String>, collections: Vec<String>, parent: Option<HWND>) -> Option<(Vec<String>, String)> {
+    let _dpi_unaware = DpiUnawareScope::enter();
+    let mut editor = ChainEditor::new(chain_boards, initial_board, all_boards, collections);
     let result = editor.show_modal(parent);
     if result == DialogResult::Ok {
         Some((editor.chain_boards, editor.initial_board.clone()))
@@ -0,0 +1,81 @@
+use std::cell::RefCell;
+use windows::Win32::{
+    Foundation::HWND,
+    UI::{
+        Input::KeyboardAndMouse::{GetFocus, GetKeyState, SetFocus, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT},
+        WindowsAndMessaging::{GetLastActivePopup, IsWindow, SetForegroundWindow, MSG, WM_KEYDOWN, WM_SYSKEYDOWN},
+    },
+};
+
+use crate::input::ModifierState;
+
+/// The board a dialog is open over, with the key moving the focus between the two
+struct FocusTarget {
+    board: HWND,
+    key: (ModifierState, u16),
+    dialog_focus: Option<HWND>, // control focused in the dialog when the board was brought to front
+}
+
+thread_local! {
+    static FOCUS_TARGET: RefCell<Option<FocusTarget>> = const { RefCell::new(None) };
+}
+
+/// Lets the dialogs opened over `board` until it is dropped switch the focus to the board and back with `key`
+pub struct FocusCycle;
+
+impl FocusCycle {
+    pub fn begin(board: HWND, key: Option<(ModifierState, u16)>) -> Self {
+        FOCUS_TARGET.with(|target| {
+            *target.borrow_mut() = key.map(|key| FocusTarget { board, key, dialog_focus: None });
+        });
+        FocusCycle
+    }
+}
+
+impl Drop for FocusCycle {
+    fn drop(&mut self) {
+        FOCUS_TARGET.with(|target| target.borrow_mut().take());
+    }
+}
+
+/// Called by the dialogs' message loops before a message is dispatched, whichever window it is for.
+/// The focus key brings the board to the front, or the dialog when the board has the focus; the message
+/// is consumed and the loop keeps running, so the dialog stays open and modal to its caller.
+pub(super) unsafe fn cycle_focus(msg: &MSG) -> bool {
+    if msg.message != WM_KEYDOWN && msg.message != WM_SYSKEYDOWN {
+        return false;
+    }
+
+    FOCUS_TARGET.with(|target| {
+        let mut target = target.borrow_mut();
+        let Some(target) = target.as_mut() else { return false };
+        if target.key != (current_modifiers(), msg.wParam.0 as u16) {
+            return false;
+        }
+
+        if msg.hwnd == target.board {
+            let dialog = GetLastActivePopup(target.board);
+            if dialog != target.board {
+                let _ = SetForegroundWindow(dialog);
+                if let Some(control) = target.dialog_focus.take().filter(|control| IsWindow(Some(*control)).as_bool()) {
+                    let _ = SetFocus(Some(control));
+                }
+            }
+        } else {
+            target.dialog_focus = Some(GetFocus()).filter(|control| !control.is_invalid());
+            let _ = SetForegroundWindow(target.board);
+        }
+        true
+    })
+}
+
+/// Modifiers held when the message being processed was posted
+unsafe fn current_modifiers() -> ModifierState {
+    let held = |vk_code: u16| GetKeyState(vk_code as i32) < 0;
+    ModifierState {
+        ctrl: held(VK_CONTROL.0),
+        shift: held(VK_SHIFT.0),
+        alt: held(VK_MENU.0),
+        super_key: held(VK_LWIN.0) || held(VK_RWIN.0),
+    }
+}
@@ -7,6 +7,9 @@ use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::WindowsAndMessaging::*;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
+use crate::core::TextStyle;
+use crate::ui::shared::utils::DpiUnawareScope;
+
 // Control IDs
 const ID_FONT_COMBO: u16 = 1001;
 const ID_SIZE_COMBO: u16 = 1002;
@@ -40,7 +43,7 @@ struct FontSelectionDialog {
 
 impl FontSelectionDialog {
     fn new(initial_font: &str) -> Self {
-        let (face, bold, italic, size) = parse_font(initial_font);
+        let (face, bold, italic, size) = TextStyle::parse_font(initial_font);
         Self {
             hwnd: HWND::default(),
             selected_font: face,
@@ -89,13 +92,13 @@ impl FontSelectionDialog {
                 let parent_width = rect.right - rect.left;
                 let parent_height = rect.bottom - rect.top;
                 let x = rect.left + (parent_width - 470) / 2;
-                let y = rect.top + (parent_height - 320) / 2;
+                let y = rect.top + (parent_height - 340) / 2;
                 (x, y)
             } else {
                 let screen_width = GetSystemMetrics(SM_CXSCREEN);
                 let screen_height = GetSystemMetrics(SM_CYSCREEN);
                 let x = (screen_width - 470) / 2;
-                let y = (screen_height - 320) / 2;
+                let y = (screen_height - 340) / 2;
                 (x, y)
             };
 
@@ -109,7 +112,7 @@ impl FontSelectionDialog {
                 x,
                 y,
                 470,
-                320,
+                340,
                 parent,
                 None,
                 Some(instance.into()),
@@ -124,7 +127,11 @@ impl FontSelectionDialog {
             // Message loop
             let mut msg = MSG::default();
             while GetMessageW(&mut msg, None, 0, 0).as_bool() {
-                // Handle tab navigation and enter key
+                if super::focus_cycle::cycle_focus(&msg) {
+                    continue;
+                }
+
+                // Handle tab navigation, mnemonics and Enter/Escape
                 if !IsDialogMessageW(self.hwnd, &msg).as_bool() {
                     let _ = TranslateMessage(&msg);
                     let _ = DispatchMessageW(&msg);
@@ -148,13 +155,29 @@ impl FontSelectionDialog {
         let instance = GetModuleHandleW(None).unwrap();
         let default_font = GetStockObject(DEFAULT_GUI_FONT);
 
+        // Labels carry the mnemonics for the combos that follow them in tab order
+        for (text, x) in [(w!("&Font:"), 20), (w!("Si&ze:"), 280)] {
+            let label = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                w!("STATIC"),
+                text,
+                WS_CHILD | WS_VISIBLE,
+                x, 12, 80, 16,
+                Some(self.hwnd),
+                None,
+                Some(instance.into()),
+                None,
+            ).unwrap();
+            SendMessageW(label, WM_SETFONT, Some(WPARAM(default_font.0 as usize)), Some(LPARAM(1)));
+        }
+
         // Font combo
         let font_combo = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("COMBOBOX"),
             w!(""),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE(CBS_DROPDOWNLIST as u32 | CBS_HASSTRINGS as u32 | CBS_DISABLENOSCROLL as u32),
-            20, 20, 250, 200,
+            20, 32, 250, 200,
             Some(self.hwnd),
             Some(HMENU(ID_FONT_COMBO as _)),
             Some(instance.into()),
@@ -168,7 +191,7 @@ impl FontSelectionDialog {
             w!("COMBOBOX"),
             w!(""),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWN as u32 | CBS_HASSTRINGS as u32),
-            280, 20, 80, 200,
+            280, 32, 80, 200,
             Some(self.hwnd),
             Some(HMENU(ID_SIZE_COMBO as _)),
             Some(instance.into()),
@@ -180,9 +203,9 @@ impl FontSelectionDialog {
         let bold_check = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("BUTTON"),
-            w!("B"),
+            w!("&B"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32 | BS_PUSHLIKE as u32),
-            370, 19, 30, 24,
+            370, 31, 30, 24,
             Some(self.hwnd),
             Some(HMENU(ID_BOLD_CHECK as _)),
             Some(instance.into()),
@@ -199,9 +222,9 @@ impl FontSelectionDialog {
         let italic_check = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("BUTTON"),
-            w!("I"),
+            w!("&I"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32 | BS_PUSHLIKE as u32),
-            410, 19, 30, 24,
+            410, 31, 30, 24,
             Some(self.hwnd),
             Some(HMENU(ID_ITALIC_CHECK as _)),
             Some(instance.into()),
@@ -220,7 +243,7 @@ impl FontSelectionDialog {
             w!("STATIC"),
             w!(""),
             WS_CHILD | WS_VISIBLE | WINDOW_STYLE(0x0000000D), // SS_OWNERDRAW
-            20, 60, 420, 160,
+            20, 72, 420, 160,
             Some(self.hwnd),
             Some(HMENU(ID_PREVIEW_PANEL as _)),
             Some(instance.into()),
@@ -233,7 +256,7 @@ impl FontSelectionDialog {
             w!("BUTTON"),
             w!("OK"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as _),
-            240, 235, 90, 30,
+            240, 247, 90, 30,
             Some(self.hwnd),
             Some(HMENU(IDOK as _)),
             Some(instance.into()),
@@ -247,7 +270,7 @@ impl FontSelectionDialog {
             w!("BUTTON"),
             w!("Cancel"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
-            350, 235, 90, 30,
+            350, 247, 90, 30,
             Some(self.hwnd),
             Some(HMENU(IDCANCEL as _)),
             Some(instance.into()),
@@ -419,15 +442,7 @@ impl FontSelectionDialog {
     }
 
     fn get_font_string(&self) -> String {
-        let mut parts = vec![self.selected_font.clone()];
-        if self.is_bold {
-            parts.push("Bold".to_string());
-        }
-        if self.is_italic {
-            parts.push("Italic".to_string());
-        }
-        parts.push(self.selected_size.to_string());
-        parts.join(" ")
+        TextStyle::format_font(&self.selected_font, self.is_bold, self.is_italic, self.selected_size)
     }
 
     unsafe fn save_data(&mut self) {
@@ -444,6 +459,10 @@ impl FontSelectionDialog {
             WM_CREATE => {
                 LRESULT(0)
             }
+            DM_GETDEFID => {
+                // Enter maps to OK regardless of which control has focus
+                LRESULT(((DC_HASDEFID << 16) | IDOK as u32) as isize)
+            }
             WM_COMMAND => {
                 let dialog = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FontSelectionDialog;
                 if dialog.is_null() {
@@ -550,38 +569,6 @@ fn to_wide_string(s: &str) -> Vec<u16> {
     s.encode_utf16().chain(std::iter::once(0)).collect()
 }
 
-fn parse_font(font_str: &str) -> (String, bool, bool, i32) {
-    let parts: Vec<&str> = font_str.split_whitespace().collect();
-    if parts.is_empty() {
-        return ("Arial".to_string(), false, false, 12);
-    }
-
-    let size = parts
-        .last()
-        .and_then(|s| s.parse::<i32>().ok())
-        .unwrap_or(12);
-
-    let mut face_parts = Vec::new();
-    let mut bold = false;
-    let mut italic = false;
-
-    for part in &parts[..parts.len().saturating_sub(1)] {
-        match part.to_lowercase().as_str() {
-            "bold" => bold = true,
-            "italic" => italic = true,
-            _ => face_parts.push(*part),
-        }
-    }
-
-    let face = if face_parts.is_empty() {
-        "Arial".to_string()
-    } else {
-        face_parts.join(" ")
-    };
-
-    (face, bold, italic, size)
-}
-
 fn get_system_fonts() -> Vec<String> {
     unsafe {
         let mut fonts = Vec::new();
@@ -643,6 +630,7 @@ fn get_font_sizes() -> Vec<i32> {
 /// Convenience function to show font picker and return the result
 /// Returns Some(font_string) if user selected a font, None if cancelled
 pub fn open_font_editor(initial_font: &str, parent: Option<HWND>) -> Option<String> {
+    let _dpi_unaware = DpiUnawareScope::enter();
     let mut dialog = FontSelectionDialog::new(initial_font);
     match dialog.show_modal(parent) {
         DialogResult::Ok => Some(dialog.get_selected_font()),
@@ -3,8 +3,14 @@ mod pad_editor;
 mod color_picker;
 mod font_selector;
 mod chain_editor;
+mod focus_cycle;
+mod shortcut_import;
+mod board_file;
 
 pub use color_picker::open_color_picker;
 pub use font_selector::open_font_editor;
 pub use pad_editor::open_pad_editor;
-pub use chain_editor::open_chain_editor;
\ No newline at end of file
+pub use chain_editor::open_chain_editor;
+pub use focus_cycle::FocusCycle;
+pub use shortcut_import::{pick_shortcuts, Shortcut};
+pub use board_file::pick_board_file;
\ No newline at end of file
@@ -8,8 +8,11 @@ use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::Graphics::Gdi::{HBRUSH, COLOR_BTNFACE};
 
 use crate::input::capture::{self, DisplayFormatable};
-use crate::core::integration::ActionType;
-use crate::model::Pad;
+use crate::core::integration::{join_command_line, split_command_line, split_snippet, validate_base_url, ActionType, AudioCommand, MediaKey, Param, TextTransform, WindowCommand, SNIPPET_CURSOR};
+use crate::model::{format_key_chords, Color, Pad};
+use crate::ui::shared::utils::DpiUnawareScope;
+
+use super::open_color_picker;
 
 // Control IDs
 const ID_HEADER_EDIT: u16 = 1001;
@@ -22,6 +25,13 @@ const ID_DELETE_ACTION: u16 = 1007;
 const ID_UPDATE_ACTION: u16 = 1008;
 const ID_CAPTURE_SHORTCUT: u16 = 1009;
 const ID_BOARD_COMBO: u16 = 1010;
+const ID_CONFIRM_EDIT: u16 = 1011;
+const ID_LABEL_CHECK: u16 = 1012;
+const ID_REPEAT_EDIT: u16 = 1013;
+const ID_BACKGROUND_EDIT: u16 = 1014;
+const ID_PICK_BACKGROUND: u16 = 1015;
+const ID_CLEAR_BACKGROUND: u16 = 1016;
+const ID_ACTION_CHOICE_COMBO: u16 = 1017;
 const IDOK: u16 = 1;
 const IDCANCEL: u16 = 2;
 
@@ -36,12 +46,17 @@ struct PadEditor {
     hwnd: HWND,
     pad: Pad,
     actions: Vec<ActionType>, // Store actions separately for editing
+    background: Option<String>, // Set by the color picker, None for the scheme's background
+    board_background: Color, // Where the picker starts when the pad has no scheme of its own
     focus_board_combo: bool,
     result: DialogResult,
     // Store final data after dialog closes
     final_header: String,
     final_text: String,
     final_board: String,
+    final_confirm: String,
+    final_label: bool,
+    final_repeat: String,
     boards: Vec<String>,
 }
 
@@ -53,18 +68,24 @@ enum DialogResult {
 }
 
 impl PadEditor {
-    fn new(pad: Pad, boards: Vec<String>, focus_board_combo: bool) -> Self {
+    fn new(pad: Pad, boards: Vec<String>, board_background: Color, focus_board_combo: bool) -> Self {
         let actions = pad.actions().clone();
+        let background = pad.data.background.clone();
         Self {
             hwnd: HWND::default(),
             pad,
             actions,
+            background,
+            board_background,
             boards,
             focus_board_combo,
             result: DialogResult::None,
             final_header: String::new(),
             final_text: String::new(),
             final_board: String::new(),
+            final_confirm: String::new(),
+            final_label: false,
+            final_repeat: String::new(),
         }
     }
 
@@ -104,7 +125,7 @@ impl PadEditor {
                     let parent_width = parent_rect.right - parent_rect.left;
                     let parent_height = parent_rect.bottom - parent_rect.top;
                     let dialog_width = 600;
-                    let dialog_height = 445;
+                    let dialog_height = 555;
 
                     // Center dialog on parent
                     let x = parent_rect.left + (parent_width - dialog_width) / 2;
@@ -127,7 +148,7 @@ impl PadEditor {
                 dialog_x,
                 dialog_y,
                 600,
-                445,
+                555,
                 parent,
                 None,
                 Some(instance.into()),
@@ -157,7 +178,16 @@ impl PadEditor {
             // Message loop with dialog message processing
             let mut msg = MSG::default();
             while GetMessageW(&mut msg, None, 0, 0).as_bool() {
-                // Use IsDialogMessage to handle tab navigation automatically
+                if super::focus_cycle::cycle_focus(&msg) {
+                    continue;
+                }
+
+                // Actions list keys have to be seen before IsDialogMessage consumes them
+                if self.handle_actions_list_key(&msg) {
+                    continue;
+                }
+
+                // Use IsDialogMessage to handle tab navigation, mnemonics and Enter/Escape
                 if !IsDialogMessageW(self.hwnd, &msg).as_bool() {
                     let _ = TranslateMessage(&msg);
                     let _ = DispatchMessageW(&msg);
@@ -187,6 +217,17 @@ impl PadEditor {
             board_params: self.pad.board_params().clone(),
             color_scheme: self.pad.color_scheme.as_ref().map(|cs| cs.name.clone()),
             text_style: self.pad.text_style.as_ref().map(|ts| ts.name.clone()),
+            background: self.background.clone(),
+            confirm: if self.final_confirm.trim().is_empty() { None } else { Some(self.final_confirm.clone()) },
+            behavior: self.pad.data.behavior,
+            on_release: self.pad.data.on_release,
+            feedback: self.pad.data.feedback,
+            // Running once is the default, kept out of the settings
+            repeat: self.final_repeat.trim().parse().ok().filter(|repeat| *repeat != 1),
+            cooldown: self.pad.data.cooldown,
+            vertical_align: self.pad.data.vertical_align,
+            shrink_text: self.pad.data.shrink_text,
+            label: self.final_label,
         };
 
         // Create new model pad
@@ -200,7 +241,7 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("STATIC"),
-            w!("Header:"),
+            w!("&Header:"),
             WS_CHILD | WS_VISIBLE,
             10, 10, 80, 20,
             Some(self.hwnd),
@@ -214,18 +255,43 @@ impl PadEditor {
             w!("EDIT"),
             PCWSTR::from_raw(to_wide_string(&self.pad.header()).as_ptr()),
             WS_CHILD | WS_VISIBLE | WS_BORDER | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as _),
-            100, 10, 475, 25,
+            100, 10, 355, 25,
             Some(self.hwnd),
             Some(HMENU(ID_HEADER_EDIT as _)),
             Some(instance.into()),
             None,
         );
 
+        // Repeat label and edit, the times the actions run in a row
+        let _ = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            w!("&Repeat:"),
+            WS_CHILD | WS_VISIBLE,
+            465, 13, 50, 20,
+            Some(self.hwnd),
+            None,
+            Some(instance.into()),
+            None,
+        );
+
+        let _ = CreateWindowExW(
+            WS_EX_CLIENTEDGE,
+            w!("EDIT"),
+            PCWSTR::from_raw(to_wide_string(&self.pad.repeat().to_string()).as_ptr()),
+            WS_CHILD | WS_VISIBLE | WS_BORDER | WS_TABSTOP | WINDOW_STYLE((ES_AUTOHSCROLL | ES_NUMBER) as _),
+            520, 10, 55, 25,
+            Some(self.hwnd),
+            Some(HMENU(ID_REPEAT_EDIT as _)),
+            Some(instance.into()),
+            None,
+        );
+
         // Text label and edit (multiline)
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("STATIC"),
-            w!("Text:"),
+            w!("&Text:"),
             WS_CHILD | WS_VISIBLE,
             10, 45, 80, 20,
             Some(self.hwnd),
@@ -237,9 +303,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WS_EX_CLIENTEDGE,
             w!("EDIT"),
-            PCWSTR::from_raw(to_wide_string(&newline_to_backslash_n(&self.pad.text())).as_ptr()),
-            WS_CHILD | WS_VISIBLE | WS_BORDER | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as _),
-            100, 45, 475, 25,
+            PCWSTR::from_raw(to_wide_string(&newline_to_crlf(&self.pad.text())).as_ptr()),
+            WS_CHILD | WS_VISIBLE | WS_BORDER | WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE((ES_MULTILINE | ES_AUTOVSCROLL) as _),
+            100, 45, 475, 55,
             Some(self.hwnd),
             Some(HMENU(ID_TEXT_EDIT as _)),
             Some(instance.into()),
@@ -250,9 +316,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("STATIC"),
-            w!("Actions:"),
+            w!("Acti&ons:"),
             WS_CHILD | WS_VISIBLE,
-            10, 85, 80, 20,
+            10, 115, 80, 20,
             Some(self.hwnd),
             None,
             Some(instance.into()),
@@ -265,7 +331,7 @@ impl PadEditor {
             w!("LISTBOX"),
             w!(""),
             WS_CHILD | WS_VISIBLE | WS_BORDER | WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE(LBS_NOTIFY as _),
-            100, 85, 475, 120,
+            100, 115, 475, 120,
             Some(self.hwnd),
             Some(HMENU(ID_ACTIONS_LIST as _)),
             Some(instance.into()),
@@ -276,9 +342,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("STATIC"),
-            w!("Action Type:"),
+            w!("Action t&ype:"),
             WS_CHILD | WS_VISIBLE,
-            10, 220, 80, 20,
+            10, 250, 80, 20,
             Some(self.hwnd),
             None,
             Some(instance.into()),
@@ -290,7 +356,7 @@ impl PadEditor {
             w!("COMBOBOX"),
             w!(""),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as _),
-            100, 215, 100, 200,
+            100, 245, 100, 200,
             Some(self.hwnd),
             Some(HMENU(ID_ACTION_TYPE_COMBO as _)),
             Some(instance.into()),
@@ -298,7 +364,7 @@ impl PadEditor {
         ).unwrap();
 
         // Add action types to combo
-        for action_type in ["Shortcut", "Text", "Line", "Paste", "PasteEnter", "Pause", "OpenUrl"] {
+        for action_type in ["Shortcut", "Text", "Line", "Paste", "PasteEnter", "Pause", "OpenUrl", "OpenQuery", "TypeClipboard", "WaitForClipboard", "SwitchTextStyle", "FocusPrevious", "Audio", "Snippet", "TypeTransformed", "WrapSelection", "LaunchApp", "MediaKey", "PasteKeep", "WindowCommand", "PasteEnterKeep"] {
             let wide = to_wide_string(action_type);
             SendMessageW(combo, CB_ADDSTRING, Some(WPARAM(0)), Some(LPARAM(wide.as_ptr() as _)));
         }
@@ -308,9 +374,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("STATIC"),
-            w!("Value:"),
+            w!("&Value:"),
             WS_CHILD | WS_VISIBLE,
-            210, 220, 50, 20,
+            210, 250, 50, 20,
             Some(self.hwnd),
             None,
             Some(instance.into()),
@@ -322,20 +388,33 @@ impl PadEditor {
             w!("EDIT"),
             w!(""),
             WS_CHILD | WS_VISIBLE | WS_BORDER | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as _),
-            270, 215, 305, 25,
+            270, 245, 305, 25,
             Some(self.hwnd),
             Some(HMENU(ID_ACTION_VALUE_EDIT as _)),
             Some(instance.into()),
             None,
         );
 
+        // Takes the place of the value edit for action types with a fixed set of values
+        let _ = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("COMBOBOX"),
+            w!(""),
+            WS_CHILD | WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE(CBS_DROPDOWNLIST as _),
+            270, 245, 305, 200,
+            Some(self.hwnd),
+            Some(HMENU(ID_ACTION_CHOICE_COMBO as _)),
+            Some(instance.into()),
+            None,
+        );
+
         // Action buttons
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("BUTTON"),
-            w!("Add"),
+            w!("&Add"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
-            100, 255, 80, 30,
+            100, 285, 80, 30,
             Some(self.hwnd),
             Some(HMENU(ID_ADD_ACTION as _)),
             Some(instance.into()),
@@ -345,9 +424,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("BUTTON"),
-            w!("Update"),
+            w!("&Update"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
-            190, 255, 80, 30,
+            190, 285, 80, 30,
             Some(self.hwnd),
             Some(HMENU(ID_UPDATE_ACTION as _)),
             Some(instance.into()),
@@ -357,9 +436,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("BUTTON"),
-            w!("Delete"),
+            w!("&Delete"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
-            280, 255, 80, 30,
+            280, 285, 80, 30,
             Some(self.hwnd),
             Some(HMENU(ID_DELETE_ACTION as _)),
             Some(instance.into()),
@@ -369,9 +448,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("BUTTON"),
-            w!("Capture Shortcut"),
+            w!("&Capture Shortcut"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
-            370, 255, 120, 30,
+            370, 285, 120, 30,
             Some(self.hwnd),
             Some(HMENU(ID_CAPTURE_SHORTCUT as _)),
             Some(instance.into()),
@@ -382,9 +461,9 @@ impl PadEditor {
         let _ = CreateWindowExW(
             WINDOW_EX_STYLE::default(),
             w!("STATIC"),
-            w!("Open Board:"),
+            w!("Open &Board:"),
             WS_CHILD | WS_VISIBLE,
-            10, 320, 100, 20,
+            10, 350, 100, 20,
             Some(self.hwnd),
             None,
             Some(instance.into()),
@@ -396,7 +475,7 @@ impl PadEditor {
             w!("COMBOBOX"),
             w!(""),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(CBS_DROPDOWNLIST as _),
-            100, 315, 300, 200,
+            100, 345, 300, 200,
             Some(self.hwnd),
             Some(HMENU(ID_BOARD_COMBO as _)),
             Some(instance.into()),
@@ -416,6 +495,94 @@ impl PadEditor {
         };
         SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(selected_index)), Some(LPARAM(0)));
 
+        // Separator pads only show their text
+        let label_check = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            w!("&Label only"),
+            WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+            415, 348, 160, 20,
+            Some(self.hwnd),
+            Some(HMENU(ID_LABEL_CHECK as _)),
+            Some(instance.into()),
+            None,
+        ).unwrap();
+        SendMessageW(label_check, BM_SETCHECK, Some(WPARAM(if self.pad.is_label() { 1 } else { 0 })), Some(LPARAM(0)));
+
+        // Confirmation prompt label and edit
+        let _ = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            w!("Con&firm:"),
+            WS_CHILD | WS_VISIBLE,
+            10, 385, 80, 20,
+            Some(self.hwnd),
+            None,
+            Some(instance.into()),
+            None,
+        );
+
+        let _ = CreateWindowExW(
+            WS_EX_CLIENTEDGE,
+            w!("EDIT"),
+            PCWSTR::from_raw(to_wide_string(&self.pad.data.confirm.clone().unwrap_or_default()).as_ptr()),
+            WS_CHILD | WS_VISIBLE | WS_BORDER | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as _),
+            100, 380, 475, 25,
+            Some(self.hwnd),
+            Some(HMENU(ID_CONFIRM_EDIT as _)),
+            Some(instance.into()),
+            None,
+        );
+
+        // Background color of the pad only, picked rather than typed
+        let _ = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            w!("Bac&kground:"),
+            WS_CHILD | WS_VISIBLE,
+            10, 420, 85, 20,
+            Some(self.hwnd),
+            None,
+            Some(instance.into()),
+            None,
+        );
+
+        let _ = CreateWindowExW(
+            WS_EX_CLIENTEDGE,
+            w!("EDIT"),
+            PCWSTR::from_raw(to_wide_string(&self.background_text()).as_ptr()),
+            WS_CHILD | WS_VISIBLE | WS_BORDER | WINDOW_STYLE(ES_READONLY as _),
+            100, 415, 200, 25,
+            Some(self.hwnd),
+            Some(HMENU(ID_BACKGROUND_EDIT as _)),
+            Some(instance.into()),
+            None,
+        );
+
+        let _ = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            w!("&Pick..."),
+            WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
+            310, 413, 80, 30,
+            Some(self.hwnd),
+            Some(HMENU(ID_PICK_BACKGROUND as _)),
+            Some(instance.into()),
+            None,
+        );
+
+        let _ = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            w!("Cl&ear"),
+            WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
+            400, 413, 80, 30,
+            Some(self.hwnd),
+            Some(HMENU(ID_CLEAR_BACKGROUND as _)),
+            Some(instance.into()),
+            None,
+        );
+
 
         // OK/Cancel buttons
         let _ = CreateWindowExW(
@@ -423,7 +590,7 @@ impl PadEditor {
             w!("BUTTON"),
             w!("OK"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as _),
-            210, 365, 80, 30,
+            210, 470, 80, 30,
             Some(self.hwnd),
             Some(HMENU(IDOK as _)),
             Some(instance.into()),
@@ -435,7 +602,7 @@ impl PadEditor {
             w!("BUTTON"),
             w!("Cancel"),
             WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as _),
-            310, 365, 80, 30,
+            310, 470, 80, 30,
             Some(self.hwnd),
             Some(HMENU(IDCANCEL as _)),
             Some(instance.into()),
@@ -457,14 +624,13 @@ impl PadEditor {
         }
     }
 
-    unsafe fn add_action(&mut self) {
+    /// The action set up in the action type and value controls for adding or updating, a typed value that is invalid is reported
+    unsafe fn read_action(&self) -> Option<ActionType> {
         let combo = GetDlgItem(Some(self.hwnd), ID_ACTION_TYPE_COMBO as _).unwrap();
-        let edit = GetDlgItem(Some(self.hwnd), ID_ACTION_VALUE_EDIT as _).unwrap();
-
-        let sel = SendMessageW(combo, CB_GETCURSEL, Some(WPARAM(0)), Some(LPARAM(0))).0;
-        let value = get_window_text(edit);
+        let type_sel = SendMessageW(combo, CB_GETCURSEL, Some(WPARAM(0)), Some(LPARAM(0))).0;
+        let value = self.action_value();
 
-        let action = match sel {
+        let action = match type_sel {
             0 => ActionType::Shortcut(value),
             1 => ActionType::Text(backslash_n_to_newline(&value)),
             2 => ActionType::Line(backslash_n_to_newline(&value)),
@@ -472,11 +638,63 @@ impl PadEditor {
             4 => ActionType::PasteEnter(backslash_n_to_newline(&value)),
             5 => ActionType::Pause(value.parse().unwrap_or(1000)),
             6 => ActionType::OpenUrl(value), // Using OpenUrl instead of Board for now
-            _ => return,
+            7 => match parse_query_template(&value) {
+                Ok(action) => action,
+                Err(e) => {
+                    MessageBoxW(Some(self.hwnd), &HSTRING::from(e), w!("Invalid URL"), MB_OK | MB_ICONWARNING);
+                    return None;
+                }
+            },
+            8 => ActionType::TypeClipboard(value.parse().unwrap_or(0)),
+            9 => ActionType::WaitForClipboard { timeout_ms: value.parse().unwrap_or(5000) },
+            10 => ActionType::SwitchTextStyle(value),
+            11 => ActionType::FocusPrevious,
+            12 => match AudioCommand::parse(&value) {
+                Some(command) => ActionType::Audio { command },
+                None => {
+                    MessageBoxW(Some(self.hwnd), w!("Use VolumeUp, VolumeDown, Mute or a volume from 0 to 100"), w!("Invalid audio command"), MB_OK | MB_ICONWARNING);
+                    return None;
+                }
+            },
+            13 => ActionType::Snippet(backslash_n_to_newline(&value)),
+            14 => match parse_type_transformed(&value) {
+                Some(action) => action,
+                None => {
+                    MessageBoxW(Some(self.hwnd), w!("Use Upper, Lower or Title, optionally followed by a colon and the text, e.g. \"Title: hello world\". Without a text the clipboard is typed."), w!("Invalid transform"), MB_OK | MB_ICONWARNING);
+                    return None;
+                }
+            },
+            15 => parse_wrap_selection(&value),
+            16 => match parse_launch_app(&value) {
+                Some(action) => action,
+                None => {
+                    MessageBoxW(Some(self.hwnd), w!("Enter the executable, optionally followed by its arguments and by | with the working directory, e.g. \"code.exe --new-window | C:\\Projects\". Quote values with spaces."), w!("Invalid application"), MB_OK | MB_ICONWARNING);
+                    return None;
+                }
+            },
+            17 => match MediaKey::parse(&value) {
+                Some(key) => ActionType::MediaKey(key),
+                None => return None,
+            },
+            18 => ActionType::PasteKeep(backslash_n_to_newline(&value)),
+            19 => match WindowCommand::parse(&value) {
+                Some(command) => ActionType::WindowCommand(command),
+                None => return None,
+            },
+            20 => ActionType::PasteEnterKeep(backslash_n_to_newline(&value)),
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    unsafe fn add_action(&mut self) {
+        let Some(action) = self.read_action() else {
+            return;
         };
 
         self.actions.push(action);
         self.refresh_actions_list();
+        let edit = GetDlgItem(Some(self.hwnd), ID_ACTION_VALUE_EDIT as _).unwrap();
         let _ = SetWindowTextW(edit, w!(""));
     }
 
@@ -487,31 +705,58 @@ impl PadEditor {
         if sel != LB_ERR as usize && sel < self.actions.len() {
             self.actions.remove(sel);
             self.refresh_actions_list();
+
+            // Keep a selection so that consecutive deletes work from the keyboard
+            if !self.actions.is_empty() {
+                let next = sel.min(self.actions.len() - 1);
+                SendMessageW(list, LB_SETCURSEL, Some(WPARAM(next)), Some(LPARAM(0)));
+            }
+        }
+    }
+
+    /// Keyboard equivalents of the action buttons while the actions list has focus:
+    /// Insert adds, Delete removes, F2 or Space loads the selected action for editing.
+    unsafe fn handle_actions_list_key(&mut self, msg: &MSG) -> bool {
+        if msg.message != WM_KEYDOWN {
+            return false;
+        }
+        let Ok(list) = GetDlgItem(Some(self.hwnd), ID_ACTIONS_LIST as _) else {
+            return false;
+        };
+        if msg.hwnd != list {
+            return false;
+        }
+
+        match VIRTUAL_KEY(msg.wParam.0 as u16) {
+            VK_INSERT => self.add_action(),
+            VK_DELETE => self.delete_action(),
+            VK_F2 | VK_SPACE => {
+                self.load_selected_action();
+                match GetDlgItem(Some(self.hwnd), ID_ACTION_CHOICE_COMBO as _) {
+                    Ok(choice) if IsWindowVisible(choice).as_bool() => {
+                        let _ = SetFocus(Some(choice));
+                    }
+                    _ => if let Ok(edit) = GetDlgItem(Some(self.hwnd), ID_ACTION_VALUE_EDIT as _) {
+                        let _ = SetFocus(Some(edit));
+                        SendMessageW(edit, EM_SETSEL, Some(WPARAM(0)), Some(LPARAM(-1)));
+                    }
+                }
+            }
+            _ => return false,
         }
+        true
     }
 
     unsafe fn update_action(&mut self) {
         let list = GetDlgItem(Some(self.hwnd), ID_ACTIONS_LIST as _).unwrap();
-        let combo = GetDlgItem(Some(self.hwnd), ID_ACTION_TYPE_COMBO as _).unwrap();
-        let edit = GetDlgItem(Some(self.hwnd), ID_ACTION_VALUE_EDIT as _).unwrap();
 
         let sel = SendMessageW(list, LB_GETCURSEL, Some(WPARAM(0)), Some(LPARAM(0))).0 as usize;
         if sel == LB_ERR as usize || sel >= self.actions.len() {
             return;
         }
 
-        let type_sel = SendMessageW(combo, CB_GETCURSEL, Some(WPARAM(0)), Some(LPARAM(0))).0;
-        let value = get_window_text(edit);
-
-        let action = match type_sel {
-            0 => ActionType::Shortcut(value),
-            1 => ActionType::Text(backslash_n_to_newline(&value)),
-            2 => ActionType::Line(backslash_n_to_newline(&value)),
-            3 => ActionType::Paste(backslash_n_to_newline(&value)),
-            4 => ActionType::PasteEnter(backslash_n_to_newline(&value)),
-            5 => ActionType::Pause(value.parse().unwrap_or(1000)),
-            6 => ActionType::OpenUrl(value), // Using OpenUrl instead of Board for now
-            _ => return,
+        let Some(action) = self.read_action() else {
+            return;
         };
 
         self.actions[sel] = action;
@@ -536,6 +781,11 @@ impl PadEditor {
                 SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(0)), Some(LPARAM(0)));
                 let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(keys).as_ptr()));
             }
+            ActionType::ShortcutKeys { keys, .. } => {
+                // Edited as text, structured again when the padset is saved
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(0)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&format_key_chords(keys)).as_ptr()));
+            }
             ActionType::Text(content) => {
                 SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(1)), Some(LPARAM(0)));
                 let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&newline_to_backslash_n(content)).as_ptr()));
@@ -560,16 +810,144 @@ impl PadEditor {
                 SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(6)), Some(LPARAM(0)));
                 let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(url).as_ptr()));
             }
-            ActionType::Custom(_) => {
-                // Handle custom action type if needed
+            ActionType::OpenQuery { base, params } => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(7)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&format_query_template(base, params)).as_ptr()));
+            }
+            ActionType::TypeClipboard(delay) => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(8)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&delay.to_string()).as_ptr()));
+            }
+            ActionType::WaitForClipboard { timeout_ms } => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(9)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&timeout_ms.to_string()).as_ptr()));
+            }
+            ActionType::SwitchTextStyle(style) => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(10)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(style).as_ptr()));
+            }
+            ActionType::FocusPrevious => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(11)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, w!(""));
+            }
+            ActionType::Audio { command } => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(12)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&command.to_string()).as_ptr()));
+            }
+            ActionType::Snippet(content) => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(13)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&newline_to_backslash_n(content)).as_ptr()));
+            }
+            ActionType::TypeTransformed { text, transform } => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(14)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&format_type_transformed(text, *transform)).as_ptr()));
+            }
+            ActionType::WrapSelection { prefix, suffix } => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(15)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&format_wrap_selection(prefix, suffix)).as_ptr()));
+            }
+            ActionType::LaunchApp { path, args, working_dir } => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(16)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&format_launch_app(path, args, working_dir)).as_ptr()));
+            }
+            ActionType::MediaKey(key) => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(17)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&key.to_string()).as_ptr()));
+            }
+            ActionType::PasteKeep(content) => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(18)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&newline_to_backslash_n(content)).as_ptr()));
+            }
+            ActionType::WindowCommand(command) => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(19)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&command.to_string()).as_ptr()));
+            }
+            ActionType::PasteEnterKeep(content) => {
+                SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(20)), Some(LPARAM(0)));
+                let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&newline_to_backslash_n(content)).as_ptr()));
+            }
+            ActionType::AppendFile { .. } | ActionType::IfTime { .. } | ActionType::Custom(_) => {
+                // Not editable with a single value field
+            }
+        }
+        self.show_action_choices();
+    }
+
+    /// Shows the values of the selected action type in a dropdown when it has a fixed set of them,
+    /// the one in the value edit is selected
+    unsafe fn show_action_choices(&self) {
+        let combo = GetDlgItem(Some(self.hwnd), ID_ACTION_TYPE_COMBO as _).unwrap();
+        let edit = GetDlgItem(Some(self.hwnd), ID_ACTION_VALUE_EDIT as _).unwrap();
+        let choice = GetDlgItem(Some(self.hwnd), ID_ACTION_CHOICE_COMBO as _).unwrap();
+
+        let type_sel = SendMessageW(combo, CB_GETCURSEL, Some(WPARAM(0)), Some(LPARAM(0))).0;
+        match action_choices(type_sel) {
+            Some(choices) => {
+                let value = get_window_text(edit);
+                SendMessageW(choice, CB_RESETCONTENT, Some(WPARAM(0)), Some(LPARAM(0)));
+                for item in &choices {
+                    let wide = to_wide_string(item);
+                    SendMessageW(choice, CB_ADDSTRING, Some(WPARAM(0)), Some(LPARAM(wide.as_ptr() as _)));
+                }
+                let selected = choices.iter().position(|item| item.eq_ignore_ascii_case(value.trim())).unwrap_or(0);
+                SendMessageW(choice, CB_SETCURSEL, Some(WPARAM(selected)), Some(LPARAM(0)));
+                let _ = ShowWindow(edit, SW_HIDE);
+                let _ = ShowWindow(choice, SW_SHOW);
+            }
+            None => {
+                let _ = ShowWindow(choice, SW_HIDE);
+                let _ = ShowWindow(edit, SW_SHOW);
             }
         }
     }
 
+    /// Value of the action being added or updated, from the dropdown when it is shown
+    unsafe fn action_value(&self) -> String {
+        let choice = GetDlgItem(Some(self.hwnd), ID_ACTION_CHOICE_COMBO as _).unwrap();
+        if IsWindowVisible(choice).as_bool() {
+            get_window_text(choice)
+        } else {
+            get_window_text(GetDlgItem(Some(self.hwnd), ID_ACTION_VALUE_EDIT as _).unwrap())
+        }
+    }
+
+    fn background_text(&self) -> String {
+        self.background.clone().unwrap_or_else(|| "(color scheme)".to_string())
+    }
+
+    /// Starts from the pad's background, its own color scheme's, or else the board's
+    unsafe fn pick_background(&mut self) {
+        let initial = self.background.as_deref()
+            .and_then(Color::from_hex)
+            .or_else(|| self.pad.color_scheme.as_ref().map(|scheme| scheme.background()))
+            .unwrap_or_else(|| self.board_background.clone());
+        if let Some(color) = open_color_picker(initial, Some(self.hwnd)) {
+            self.background = Some(color.to_hex());
+            self.refresh_background();
+        }
+    }
+
+    /// The pad goes back to the background of its color scheme
+    unsafe fn clear_background(&mut self) {
+        self.background = None;
+        self.refresh_background();
+    }
+
+    unsafe fn refresh_background(&self) {
+        let edit = GetDlgItem(Some(self.hwnd), ID_BACKGROUND_EDIT as _).unwrap();
+        let _ = SetWindowTextW(edit, PCWSTR::from_raw(to_wide_string(&self.background_text()).as_ptr()));
+    }
+
     unsafe fn save_data(&mut self) {
         // Save data while window is still valid
         self.final_header = get_window_text(GetDlgItem(Some(self.hwnd), ID_HEADER_EDIT as _).unwrap());
-        self.final_text = backslash_n_to_newline(&get_window_text(GetDlgItem(Some(self.hwnd), ID_TEXT_EDIT as _).unwrap()));
+        self.final_text = crlf_to_newline(&get_window_text(GetDlgItem(Some(self.hwnd), ID_TEXT_EDIT as _).unwrap()));
+
+        self.final_confirm = get_window_text(GetDlgItem(Some(self.hwnd), ID_CONFIRM_EDIT as _).unwrap());
+        self.final_repeat = get_window_text(GetDlgItem(Some(self.hwnd), ID_REPEAT_EDIT as _).unwrap());
+
+        let label_check = GetDlgItem(Some(self.hwnd), ID_LABEL_CHECK as _).unwrap();
+        self.final_label = SendMessageW(label_check, BM_GETCHECK, Some(WPARAM(0)), Some(LPARAM(0))).0 == 1;
 
         let combo = GetDlgItem(Some(self.hwnd), ID_BOARD_COMBO as _).unwrap();
         let sel = SendMessageW(combo, CB_GETCURSEL, Some(WPARAM(0)), Some(LPARAM(0))).0 as usize;
@@ -589,6 +967,7 @@ impl PadEditor {
         // Set action type to Shortcut
         let combo = GetDlgItem(Some(self.hwnd), ID_ACTION_TYPE_COMBO as _).unwrap();
         SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(0)), Some(LPARAM(0)));
+        self.show_action_choices();
     }
 
     unsafe extern "system" fn window_proc(
@@ -601,6 +980,10 @@ impl PadEditor {
             WM_CREATE => {
                 LRESULT(0)
             }
+            DM_GETDEFID => {
+                // Enter maps to OK regardless of which control has focus
+                LRESULT(((DC_HASDEFID << 16) | IDOK as u32) as isize)
+            }
             WM_COMMAND => {
                 let dialog = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut PadEditor;
                 if dialog.is_null() {
@@ -623,6 +1006,7 @@ impl PadEditor {
                     ID_ADD_ACTION => (*dialog).add_action(),
                     ID_DELETE_ACTION => (*dialog).delete_action(),
                     ID_UPDATE_ACTION => (*dialog).update_action(),
+                    ID_ACTION_TYPE_COMBO if notification == CBN_SELCHANGE as u16 => (*dialog).show_action_choices(),
                     ID_ACTIONS_LIST => {
                         if notification == LBN_DBLCLK as u16 {
                             (*dialog).load_selected_action();
@@ -632,6 +1016,8 @@ impl PadEditor {
                         // Open shortcut capture dialog
                         let _ = PostMessageW(Some(hwnd), WM_CAPTURE_SHORTCUT, WPARAM(0), LPARAM(0));
                     }
+                    ID_PICK_BACKGROUND => (*dialog).pick_background(),
+                    ID_CLEAR_BACKGROUND => (*dialog).clear_background(),
                     _ => {}
                 }
                 LRESULT(0)
@@ -685,8 +1071,9 @@ impl PadEditor {
 }
 
 
-pub fn open_pad_editor(pad: Pad, parent: Option<HWND>, boards: Vec<String>, focus_board_combo: bool) -> Option<Pad> {
-    let mut editor = PadEditor::new(pad, boards, focus_board_combo);
+pub fn open_pad_editor(pad: Pad, parent: Option<HWND>, boards: Vec<String>, board_background: Color, focus_board_combo: bool) -> Option<Pad> {
+    let _dpi_unaware = DpiUnawareScope::enter();
+    let mut editor = PadEditor::new(pad, boards, board_background, focus_board_combo);
     let result = editor.show_modal(parent);
     if result == DialogResult::Ok {
         Some(editor.get_updated_pad())
@@ -708,22 +1095,161 @@ fn backslash_n_to_newline(text: &str) -> String {
     text.replace("\\n", "\n")
 }
 
+/// Multiline edit controls break lines on \r\n, a lone \n shows up as a box
+fn newline_to_crlf(text: &str) -> String {
+    crlf_to_newline(text).replace('\n', "\r\n")
+}
+
+fn crlf_to_newline(text: &str) -> String {
+    text.replace("\r\n", "\n")
+}
+
+/// Only the copied characters are converted, unpaired surrogates become U+FFFD instead of failing
 unsafe fn get_window_text(hwnd: HWND) -> String {
     let len = GetWindowTextLengthW(hwnd) + 1;
     let mut buffer = vec![0u16; len as usize];
-    GetWindowTextW(hwnd, &mut buffer);
-    String::from_utf16_lossy(&buffer).trim_end_matches('\0').to_string()
+    let copied = GetWindowTextW(hwnd, &mut buffer).max(0) as usize;
+    String::from_utf16_lossy(&buffer[..copied.min(buffer.len())])
+}
+
+/// OpenQuery is edited as a single "base?name=value&name=value" line, values are kept unencoded
+fn format_query_template(base: &str, params: &[Param]) -> String {
+    if params.is_empty() {
+        return base.to_string();
+    }
+    let query = params.iter()
+        .map(|p| format!("{}={}", p.name, p.value))
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}?{}", base, query)
+}
+
+/// Values of the action types picked from a dropdown rather than typed, by their index in the action type combo
+fn action_choices(type_sel: isize) -> Option<Vec<String>> {
+    match type_sel {
+        17 => Some(MediaKey::all().iter().map(|key| key.to_string()).collect()),
+        19 => Some(WindowCommand::all().iter().map(|command| command.to_string()).collect()),
+        _ => None,
+    }
+}
+
+/// TypeTransformed is edited as "Transform: text", or only the transform when typing the clipboard
+fn format_type_transformed(text: &str, transform: TextTransform) -> String {
+    if text.is_empty() {
+        transform.to_string()
+    } else {
+        format!("{}: {}", transform, newline_to_backslash_n(text))
+    }
+}
+
+fn parse_type_transformed(value: &str) -> Option<ActionType> {
+    let (transform, text) = value.split_once(':').unwrap_or((value, ""));
+    let text = text.strip_prefix(' ').unwrap_or(text);
+    TextTransform::parse(transform).map(|transform| ActionType::TypeTransformed { text: backslash_n_to_newline(text), transform })
+}
+
+/// WrapSelection is edited as the prefix and suffix joined by the snippet cursor marker, where the selection goes
+fn format_wrap_selection(prefix: &str, suffix: &str) -> String {
+    let escape = |text: &str| newline_to_backslash_n(&text.replace(SNIPPET_CURSOR, &format!("\\{}", SNIPPET_CURSOR)));
+    format!("{}{}{}", escape(prefix), SNIPPET_CURSOR, escape(suffix))
+}
+
+fn parse_wrap_selection(value: &str) -> ActionType {
+    let (prefix, suffix) = split_snippet(&backslash_n_to_newline(value));
+    ActionType::WrapSelection { prefix, suffix }
+}
+
+/// LaunchApp is edited as a command line, followed by "|" and the working directory when it has one
+fn format_launch_app(path: &str, args: &[String], working_dir: &Option<String>) -> String {
+    let parts: Vec<&str> = std::iter::once(path).chain(args.iter().map(String::as_str)).collect();
+    match working_dir {
+        Some(dir) => format!("{} | {}", join_command_line(&parts), dir),
+        None => join_command_line(&parts),
+    }
+}
+
+fn parse_launch_app(value: &str) -> Option<ActionType> {
+    // Arguments with a "|" are quoted, the first one outside quotes starts the working directory
+    let mut quoted = false;
+    let separator = value.char_indices().find(|(_, ch)| {
+        quoted ^= *ch == '"';
+        *ch == '|' && !quoted
+    });
+    let (command_line, working_dir) = match separator {
+        Some((index, _)) => (&value[..index], Some(value[index + 1..].trim()).filter(|dir| !dir.is_empty())),
+        None => (value, None),
+    };
+    let mut parts = split_command_line(command_line).into_iter();
+    let path = parts.next().filter(|path| !path.is_empty())?;
+    Some(ActionType::LaunchApp { path, args: parts.collect(), working_dir: working_dir.map(str::to_string) })
+}
+
+fn parse_query_template(text: &str) -> std::result::Result<ActionType, String> {
+    let text = text.trim();
+    let (base, query) = text.split_once('?').unwrap_or((text, ""));
+    validate_base_url(base)?;
+
+    let params = query.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Param::new(name.to_string(), value.to_string())
+        })
+        .collect();
+
+    Ok(ActionType::OpenQuery { base: base.to_string(), params })
 }
 
 fn format_action_type(action: &ActionType) -> String {
     match action {
         ActionType::Shortcut(keys) => format!("Shortcut: {}", keys),
+        ActionType::ShortcutKeys { keys, .. } => format!("Shortcut: {}", format_key_chords(keys)),
         ActionType::Text(content) => format!("Text: {}", content),
         ActionType::Line(content) => format!("Line: {}", content),
+        ActionType::Snippet(content) => format!("Snippet: {}", content),
         ActionType::Pause(duration) => format!("Pause: {}ms", duration),
         ActionType::OpenUrl(url) => format!("OpenUrl: {}", url),
+        ActionType::OpenQuery { base, params } => format!("OpenQuery: {}", format_query_template(base, params)),
         ActionType::Paste(text) => format!("Paste: {}", text),
         ActionType::PasteEnter(text) => format!("PasteEnter: {}", text),
+        ActionType::PasteKeep(text) => format!("PasteKeep: {}", text),
+        ActionType::PasteEnterKeep(text) => format!("PasteEnterKeep: {}", text),
+        ActionType::TypeClipboard(delay) => format!("TypeClipboard: {}ms", delay),
+        ActionType::WaitForClipboard { timeout_ms } => format!("WaitForClipboard: {}ms", timeout_ms),
+        ActionType::LaunchApp { path, args, working_dir } => format!("LaunchApp: {}", format_launch_app(path, args, working_dir)),
+        ActionType::AppendFile { path, text, .. } => format!("AppendFile: {} << {}", path, text),
+        ActionType::SwitchTextStyle(style) => format!("SwitchTextStyle: {}", style),
+        ActionType::FocusPrevious => "FocusPrevious".to_string(),
+        ActionType::Audio { command } => format!("Audio: {}", command),
+        ActionType::MediaKey(key) => format!("MediaKey: {}", key),
+        ActionType::WindowCommand(command) => format!("WindowCommand: {}", command),
+        ActionType::TypeTransformed { text, transform } => format!("TypeTransformed: {}", format_type_transformed(text, *transform)),
+        ActionType::WrapSelection { prefix, suffix } => format!("WrapSelection: {}", format_wrap_selection(prefix, suffix)),
+        ActionType::IfTime { from, to, then, else_ } => format!("IfTime: {}-{} ({} / {} actions)", from, to, then.len(), else_.len()),
         ActionType::Custom(params) => format!("Custom: {}", params.action_type),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_app_round_trip() {
+        let actions = [
+            ("calc.exe", vec![], None),
+            (r"C:\Program Files\App\app.exe", vec!["-v", "two words", ""], Some(r"C:\Work")),
+            ("cmd.exe", vec!["/c", "dir | sort", r#"say "hi""#], Some("%USERPROFILE%")),
+        ];
+        for (path, args, working_dir) in actions {
+            let args: Vec<String> = args.into_iter().map(str::to_string).collect();
+            let working_dir = working_dir.map(str::to_string);
+            let parsed = parse_launch_app(&format_launch_app(path, &args, &working_dir));
+            assert!(matches!(&parsed, Some(ActionType::LaunchApp { path: p, args: a, working_dir: w })
+                if p == path && *a == args && *w == working_dir), "{:?}", parsed);
+        }
+
+        assert!(matches!(parse_launch_app(r#"cmd.exe /c "a|b" | C:\Temp"#), Some(ActionType::LaunchApp { args, working_dir: Some(dir), .. })
+            if args == ["/c", "a|b"] && dir == r"C:\Temp"));
+    }
+}
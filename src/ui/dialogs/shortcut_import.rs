@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use windows::core::*;
+use windows::Win32::Foundation::*;
+use windows::Win32::Storage::FileSystem::WIN32_FIND_DATAW;
+use windows::Win32::System::Com::*;
+use windows::Win32::UI::Shell::*;
+
+use crate::core::{self, split_command_line, ActionType};
+
+/// A Windows shortcut (.lnk) resolved to what it starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortcut {
+    /// The shortcut file itself, its icon is the one shown in the Start menu
+    pub path: PathBuf,
+    pub name: String,
+    pub target: String,
+    pub args: String,
+    pub working_dir: Option<String>,
+}
+
+impl Shortcut {
+    /// Opens the target the way the shell does, started with LaunchApp when the shortcut passes arguments or a working directory
+    pub fn action(&self) -> ActionType {
+        match (self.args.trim().is_empty(), &self.working_dir) {
+            (true, None) => ActionType::OpenUrl(self.target.clone()),
+            _ => ActionType::LaunchApp {
+                path: self.target.clone(),
+                args: split_command_line(&self.args),
+                working_dir: self.working_dir.clone(),
+            },
+        }
+    }
+
+    /// The pad opening the shortcut, titled with its name
+    pub fn to_pad(&self, icon: Option<String>) -> core::Pad {
+        core::Pad {
+            text: Some(self.name.clone()),
+            icon,
+            actions: vec![self.action()],
+            ..Default::default()
+        }
+    }
+}
+
+/// Asks for shortcut files, or with `folder` for a folder whose shortcuts are all taken, and resolves them.
+/// Shortcuts that don't resolve to an existing target are skipped and logged.
+pub fn pick_shortcuts(parent: Option<HWND>, folder: bool) -> Vec<Shortcut> {
+    // Also succeeds (S_FALSE) when COM was already initialized on the thread, each success needs its uninitialize
+    let initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+
+    let shortcuts = match show_open_dialog(parent, folder) {
+        Ok(paths) => paths.iter()
+            .flat_map(|path| match folder {
+                true => shortcut_files(path),
+                false => vec![path.clone()],
+            })
+            .filter_map(|path| match resolve_shortcut(&path) {
+                Ok(shortcut) => Some(shortcut),
+                Err(e) => {
+                    log::warn!("Skipped shortcut {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect(),
+        Err(e) => {
+            // Cancelling the dialog ends up here as well
+            log::debug!("No shortcuts picked: {}", e);
+            vec![]
+        }
+    };
+
+    if initialized {
+        unsafe { CoUninitialize() };
+    }
+    shortcuts
+}
+
+fn show_open_dialog(parent: Option<HWND>, folder: bool) -> Result<Vec<PathBuf>> {
+    unsafe {
+        let dialog: IFileOpenDialog = CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER)?;
+        let (title, options) = match folder {
+            true => (w!("Import shortcuts from folder"), FOS_PICKFOLDERS),
+            false => (w!("Import shortcuts"), FOS_ALLOWMULTISELECT | FOS_FILEMUSTEXIST | FOS_NODEREFERENCELINKS),
+        };
+        dialog.SetOptions(options)?;
+        dialog.SetTitle(title)?;
+        dialog.Show(parent)?;
+
+        let items = dialog.GetResults()?;
+        let mut paths = vec![];
+        for index in 0..items.GetCount()? {
+            let display_name = items.GetItemAt(index)?.GetDisplayName(SIGDN_FILESYSPATH)?;
+            let path = display_name.to_string();
+            CoTaskMemFree(Some(display_name.0 as *const _));
+            paths.push(PathBuf::from(path?));
+        }
+        Ok(paths)
+    }
+}
+
+/// The shortcuts directly in the folder, by name
+fn shortcut_files(folder: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(folder) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| is_shortcut_file(path))
+            .collect(),
+        Err(e) => {
+            log::warn!("Failed to list shortcuts in {}: {}", folder.display(), e);
+            vec![]
+        }
+    };
+    files.sort();
+    files
+}
+
+fn is_shortcut_file(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"))
+}
+
+fn resolve_shortcut(path: &Path) -> std::result::Result<Shortcut, Box<dyn std::error::Error>> {
+    if !is_shortcut_file(path) {
+        return Err("not a shortcut file".into());
+    }
+
+    let (target, args, working_dir) = unsafe {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+        link.cast::<IPersistFile>()?.Load(&HSTRING::from(path.as_os_str()), STGM_READ)?;
+        // Tracks down a moved target where it can, without asking
+        let _ = link.Resolve(HWND::default(), (SLR_NO_UI.0 | SLR_NOSEARCH.0) as u32);
+
+        let mut target = [0u16; MAX_PATH as usize];
+        link.GetPath(&mut target, std::ptr::null_mut::<WIN32_FIND_DATAW>(), 0)?;
+        let mut args = [0u16; 1024];
+        link.GetArguments(&mut args)?;
+        let mut working_dir = [0u16; MAX_PATH as usize];
+        link.GetWorkingDirectory(&mut working_dir)?;
+        (from_wide(&target), from_wide(&args), from_wide(&working_dir))
+    };
+
+    // Shortcuts to Store apps and other shell items have no file target
+    if target.is_empty() {
+        return Err("no file target".into());
+    }
+    if !Path::new(&target).exists() {
+        return Err(format!("target {} not found", target).into());
+    }
+
+    Ok(Shortcut {
+        name: path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| target.clone()),
+        path: path.to_path_buf(),
+        target,
+        args,
+        working_dir: Some(working_dir).filter(|dir| !dir.is_empty()),
+    })
+}
+
+fn from_wide(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(args: &str, working_dir: Option<&str>) -> Shortcut {
+        Shortcut {
+            path: PathBuf::from(r"C:\Users\me\Desktop\Notepad++.lnk"),
+            name: "Notepad++".to_string(),
+            target: r"C:\Program Files\Notepad++\notepad++.exe".to_string(),
+            args: args.to_string(),
+            working_dir: working_dir.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_shortcut_pad() {
+        let pad = shortcut("", None).to_pad(Some("shortcut_notepad__.png".to_string()));
+        assert_eq!(pad.text.as_deref(), Some("Notepad++"));
+        assert_eq!(pad.icon.as_deref(), Some("shortcut_notepad__.png"));
+        assert!(matches!(&pad.actions[..], [ActionType::OpenUrl(target)] if target.ends_with("notepad++.exe")));
+
+        let action = shortcut(r#"-multiInst "C:\my notes.txt""#, None).action();
+        assert!(matches!(&action, ActionType::LaunchApp { args, working_dir: None, .. }
+            if *args == vec!["-multiInst".to_string(), r"C:\my notes.txt".to_string()]));
+
+        let action = shortcut("  ", Some(r"C:\Users\me")).action();
+        assert!(matches!(&action, ActionType::LaunchApp { args, working_dir: Some(dir), .. } if args.is_empty() && dir == r"C:\Users\me"));
+    }
+}
@@ -4,17 +4,35 @@ use serde::{Deserialize, Serialize};
 use windows::{
     core::Result,
     Win32::{
-        Foundation::{ HWND, RECT },
+        Foundation::{ HWND, LPARAM, POINT, RECT, TRUE },
+        Graphics::Gdi::{ EnumDisplayMonitors, GetMonitorInfoW, MonitorFromPoint, MonitorFromRect, MonitorFromWindow, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTOPRIMARY },
+        UI::HiDpi::{ AdjustWindowRectExForDpi, GetDpiForMonitor, MDT_EFFECTIVE_DPI },
         UI::WindowsAndMessaging::{
-                GetWindowLongW, SetWindowLongW, AdjustWindowRectEx,
-                WS_EX_LAYERED, WS_OVERLAPPEDWINDOW, WINDOW_EX_STYLE, WINDOW_STYLE, WS_POPUP, WS_BORDER, WS_SIZEBOX, WS_EX_TOOLWINDOW, WS_EX_APPWINDOW, GWL_STYLE, GWL_EXSTYLE,
+                GetCursorPos, GetWindowLongW, SetWindowLongW, MONITORINFOF_PRIMARY,
+                WS_EX_LAYERED, WS_OVERLAPPEDWINDOW, WINDOW_EX_STYLE, WINDOW_STYLE, WS_POPUP, WS_BORDER, WS_SIZEBOX, WS_EX_TOOLWINDOW, WS_EX_APPWINDOW, WS_EX_TRANSPARENT, GWL_STYLE, GWL_EXSTYLE,
             },
     }
 };
 
 
+use crate::core::{MonitorSelector, WindowSizing};
 use super::utils::reset_window_pos;
 
+/// Smallest and largest client size of a window fitted to its board
+const FIT_MIN_SIZE: (i32, i32) = (360, 240);
+const FIT_MAX_SIZE: (i32, i32) = (1600, 1000);
+
+/// DPI of a monitor at 100% scaling, layouts are stored at this DPI
+pub const BASE_DPI: u32 = 96;
+
+/// Scale factor of a DPI against [`BASE_DPI`], e.g. 1.5 for a monitor at 150%
+pub fn dpi_scale(dpi: u32) -> f64 {
+    match dpi {
+        0 => 1.0,
+        dpi => dpi as f64 / BASE_DPI as f64,
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Rect {
     pub left: i32,
@@ -38,33 +56,53 @@ impl Rect {
     pub fn height(&self) -> i32 {
         self.bottom - self.top
     }
+
+    fn contains(&self, (x, y): (i32, i32)) -> bool {
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
+
+    /// Left or right half, the right one gets the extra pixel of an odd width
+    pub fn half(&self, left: bool) -> Rect {
+        let middle = self.left + self.width() / 2;
+        match left {
+            true => Rect { right: middle, ..*self },
+            false => Rect { left: middle, ..*self },
+        }
+    }
+
+    fn center(&self) -> (i32, i32) {
+        ((self.left + self.right) / 2, (self.top + self.bottom) / 2)
+    }
+
+    /// Area the two rects have in common, 0 when they don't meet
+    fn overlap(&self, other: &Rect) -> i64 {
+        let width = (self.right.min(other.right) - self.left.max(other.left)).max(0);
+        let height = (self.bottom.min(other.bottom) - self.top.max(other.top)).max(0);
+        width as i64 * height as i64
+    }
 }
 
 impl Default for WindowLayout {
 
+    /// Centered on the monitor the cursor is on, where the user is working
     fn default() -> Self {
-        fn get_screen_size() -> Result<(i32, i32)> {
-            use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
-            let screen_w = unsafe { GetSystemMetrics(SM_CXSCREEN) };
-            let screen_h = unsafe { GetSystemMetrics(SM_CYSCREEN) };
-            Ok((screen_w, screen_h))
-        }
-
         let style = WindowStyle::Taskbar;
         let (width, height) = (862, 568);
-        let (scr_width, scr_height) = get_screen_size().unwrap_or((width, height));
-
-        // center the window on the screen
-        let left = (scr_width - width) / 2;
-        let top = (scr_height - height) / 2;
+        let rect = Rect { left: 0, top: 0, right: width, bottom: height };
 
         WindowLayout {
             style,
-            rect: Rect { left, top, right: left + width, bottom: top + height },
+            rect: cursor_work_area().map(|area| center_in(rect, area)).unwrap_or(rect),
         }
     }
 }
 
+impl From<RECT> for Rect {
+    fn from(rect: RECT) -> Self {
+        Rect { left: rect.left, top: rect.top, right: rect.right, bottom: rect.bottom }
+    }
+}
+
 impl Into<RECT> for Rect {
     fn into(self) -> RECT {
         RECT {
@@ -84,7 +122,8 @@ impl WindowLayout {
             right: self.rect.right,
             bottom: self.rect.bottom,
         };
-        unsafe { AdjustWindowRectEx(&mut rect, self.style.style(), false, self.style.ex_style())?; }
+        // The frame is as thick as the monitor's DPI makes it
+        unsafe { AdjustWindowRectExForDpi(&mut rect, self.style.style(), false, self.style.ex_style(), self.dpi())?; }
         let rect = Rect {
             left: rect.left,
             top: rect.top,
@@ -94,6 +133,206 @@ impl WindowLayout {
         Ok(rect)
     }
 
+    /// DPI of the monitor showing most of the rect, the nearest one when it is off-screen
+    pub fn dpi(&self) -> u32 {
+        let rect: RECT = self.rect.into();
+        let (mut dpi_x, mut dpi_y) = (BASE_DPI, BASE_DPI);
+        unsafe {
+            let monitor = MonitorFromRect(&rect, MONITOR_DEFAULTTONEAREST);
+            if let Err(e) = GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) {
+                log::debug!("Monitor DPI not available, using {}: {}", BASE_DPI, e);
+                return BASE_DPI;
+            }
+        }
+        dpi_x
+    }
+
+    /// Same layout with the size multiplied by the zoom factor, the top left corner stays in place
+    pub fn zoomed(&self, zoom: f64) -> WindowLayout {
+        let width = (self.rect.width() as f64 * zoom).round() as i32;
+        let height = (self.rect.height() as f64 * zoom).round() as i32;
+        WindowLayout {
+            style: self.style.clone(),
+            rect: Rect { right: self.rect.left + width, bottom: self.rect.top + height, ..self.rect },
+        }
+    }
+
+    /// Moves the window back on screen, e.g. after a monitor was disconnected or the resolution changed.
+    /// A window partly off its monitor is pushed back into the work area, one on no monitor at all is
+    /// centered on the primary monitor. Returns true when the rect was changed.
+    pub fn fit_to_screen(&mut self) -> bool {
+        let rect = on_screen(self.rect, &monitors());
+        let changed = rect != self.rect;
+        self.rect = rect;
+        changed
+    }
+
+    /// Same layout kept on screen the way [`WindowLayout::fit_to_screen`] does, for the window showing it at `zoom`
+    pub fn on_screen(&self, zoom: f64) -> WindowLayout {
+        let mut zoomed = self.zoomed(zoom);
+        match zoomed.fit_to_screen() {
+            true => zoomed.zoomed(1.0 / zoom),
+            false => self.clone(),
+        }
+    }
+
+    /// Same layout moved onto the selected monitor, at the same offset from its work area as from the one
+    /// the window is on now. Falls back to the primary monitor when the selected one isn't connected.
+    pub fn on_monitor(&self, selector: &MonitorSelector) -> WindowLayout {
+        let monitors = monitors();
+        let center = self.rect.center();
+        let primary = monitors.iter().find(|monitor| monitor.primary);
+
+        let target = monitors.iter().enumerate()
+            .find(|(index, monitor)| selector.matches(*index, &monitor.device))
+            .map(|(_, monitor)| monitor)
+            .or_else(|| {
+                log::warn!("Monitor {:?} not found, using the primary monitor", selector);
+                primary
+            });
+        let source = monitors.iter()
+            .find(|monitor| monitor.work_area.contains(center))
+            .or(primary);
+
+        match (source, target) {
+            (Some(source), Some(target)) => WindowLayout {
+                style: self.style.clone(),
+                rect: move_into(self.rect, source.work_area, target.work_area),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Same layout sized the way the board asks. Fit sizes the window to `content`, the board's client size at `zoom`,
+    /// measured only then. The fitted window keeps the center of the rect and stays within the work area of its monitor.
+    /// Like the layout it comes from, the result is unzoomed.
+    pub fn sized(&self, sizing: WindowSizing, zoom: f64, content: impl FnOnce() -> (i32, i32)) -> WindowLayout {
+        match sizing {
+            WindowSizing::Fixed => self.clone(),
+            WindowSizing::Fit => {
+                let zoomed = self.zoomed(zoom);
+                let center = zoomed.rect.center();
+                let monitors = monitors();
+                let work_area = monitors.iter()
+                    .find(|monitor| monitor.work_area.contains(center))
+                    .or_else(|| monitors.iter().find(|monitor| monitor.primary))
+                    .map(|monitor| monitor.work_area);
+                WindowLayout { style: self.style.clone(), rect: fit_rect(zoomed.rect, content(), work_area) }.zoomed(1.0 / zoom)
+            },
+        }
+    }
+
+    /// Same size, moved to be centered on `rect`
+    pub fn centered_on(&self, rect: &Rect) -> WindowLayout {
+        let (x, y) = rect.center();
+        let (width, height) = (self.rect.width(), self.rect.height());
+        let (left, top) = (x - width / 2, y - height / 2);
+        WindowLayout {
+            style: self.style.clone(),
+            rect: Rect { left, top, right: left + width, bottom: top + height },
+        }
+    }
+
+}
+
+struct Monitor {
+    work_area: Rect,
+    device: String,
+    primary: bool,
+}
+
+/// Work area of the monitor the cursor is on, the primary monitor's when the cursor position is not available
+pub fn cursor_work_area() -> Option<Rect> {
+    unsafe {
+        let mut point = POINT::default();
+        if let Err(e) = GetCursorPos(&mut point) {
+            log::debug!("Cursor position not available: {}", e);
+        }
+        let monitor = MonitorFromPoint(point, MONITOR_DEFAULTTOPRIMARY);
+        let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+        GetMonitorInfoW(monitor, &mut info).as_bool().then(|| info.rcWork.into())
+    }
+}
+
+/// Work area of the monitor showing most of the window
+pub fn window_work_area(hwnd: HWND) -> Option<Rect> {
+    unsafe {
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+        GetMonitorInfoW(monitor, &mut info).as_bool().then(|| info.rcWork.into())
+    }
+}
+
+/// Connected monitors, in the system's enumeration order
+fn monitors() -> Vec<Monitor> {
+    unsafe extern "system" fn enum_monitor_proc(monitor: HMONITOR, _hdc: HDC, _rect: *mut RECT, lparam: LPARAM) -> windows::core::BOOL {
+        let monitors = &mut *(lparam.0 as *mut Vec<Monitor>);
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+
+        if GetMonitorInfoW(monitor, &mut info.monitorInfo).as_bool() {
+            let len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+            monitors.push(Monitor {
+                work_area: info.monitorInfo.rcWork.into(),
+                device: String::from_utf16_lossy(&info.szDevice[..len]),
+                primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+            });
+        }
+        TRUE // Continue enumeration
+    }
+
+    let mut monitors: Vec<Monitor> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(None, None, Some(enum_monitor_proc), LPARAM(&mut monitors as *mut _ as isize));
+    }
+    monitors
+}
+
+/// `rect` moved from the `from` area into the `to` area at the same offset, shrunk and pushed back to fit inside it
+fn move_into(rect: Rect, from: Rect, to: Rect) -> Rect {
+    let width = rect.width().min(to.width());
+    let height = rect.height().min(to.height());
+    let left = (to.left + rect.left - from.left).clamp(to.left, to.right - width);
+    let top = (to.top + rect.top - from.top).clamp(to.top, to.bottom - height);
+    Rect { left, top, right: left + width, bottom: top + height }
+}
+
+/// `rect` within the work area of the monitor showing most of it, centered on the primary monitor when it shows on none
+fn on_screen(rect: Rect, monitors: &[Monitor]) -> Rect {
+    let nearest = monitors.iter()
+        .max_by_key(|monitor| rect.overlap(&monitor.work_area))
+        .filter(|monitor| rect.overlap(&monitor.work_area) > 0);
+    match nearest {
+        Some(monitor) => move_into(rect, monitor.work_area, monitor.work_area),
+        None => match monitors.iter().find(|monitor| monitor.primary) {
+            Some(primary) => center_in(rect, primary.work_area),
+            None => rect,
+        },
+    }
+}
+
+/// `rect` centered on the `area`, shrunk to fit it
+fn center_in(rect: Rect, area: Rect) -> Rect {
+    let width = rect.width().min(area.width());
+    let height = rect.height().min(area.height());
+    let left = area.left + (area.width() - width) / 2;
+    let top = area.top + (area.height() - height) / 2;
+    Rect { left, top, right: left + width, bottom: top + height }
+}
+
+/// `rect` resized to `(width, height)` within the fitted size limits around its center, shrunk and pushed back to fit the work area
+fn fit_rect(rect: Rect, (width, height): (i32, i32), work_area: Option<Rect>) -> Rect {
+    let mut width = width.clamp(FIT_MIN_SIZE.0, FIT_MAX_SIZE.0);
+    let mut height = height.clamp(FIT_MIN_SIZE.1, FIT_MAX_SIZE.1);
+    let (x, y) = rect.center();
+    let (mut left, mut top) = (x - width / 2, y - height / 2);
+    if let Some(area) = work_area {
+        width = width.min(area.width());
+        height = height.min(area.height());
+        left = left.clamp(area.left, area.right - width);
+        top = top.clamp(area.top, area.bottom - height);
+    }
+    Rect { left, top, right: left + width, bottom: top + height }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -104,22 +343,34 @@ pub enum WindowStyle {
     Floating,
     // No title bar, no borders, HAS taskbar icon
     Taskbar,
+    // No title bar, no borders, NO taskbar icon, mouse clicks pass through to the window below.
+    // The board can only be driven by keyboard / global hotkeys in this style.
+    Overlay,
 }
 
 impl WindowStyle {
+    const ALL: [WindowStyle; 4] = [WindowStyle::Window, WindowStyle::Floating, WindowStyle::Taskbar, WindowStyle::Overlay];
+
+    // Overlay is intentionally left out of the rotation, rotating away from it restores a clickable window
     pub fn next(&self) -> WindowStyle {
         match *self {
             WindowStyle::Window => WindowStyle::Floating,
             WindowStyle::Floating => WindowStyle::Taskbar,
             WindowStyle::Taskbar => WindowStyle::Window,
+            WindowStyle::Overlay => WindowStyle::Window,
         }
     }
 
+    pub fn is_click_through(&self) -> bool {
+        matches!(self, WindowStyle::Overlay)
+    }
+
     pub fn style(&self) -> WINDOW_STYLE {
         match *self {
             WindowStyle::Window => WS_OVERLAPPEDWINDOW | WS_SIZEBOX,
             WindowStyle::Floating => WS_POPUP | WS_BORDER,
             WindowStyle::Taskbar => WS_POPUP | WS_BORDER,
+            WindowStyle::Overlay => WS_POPUP,
         }
     }
 
@@ -128,6 +379,7 @@ impl WindowStyle {
             WindowStyle::Window => WS_EX_LAYERED,
             WindowStyle::Floating => WS_EX_LAYERED | WS_EX_TOOLWINDOW,
             WindowStyle::Taskbar => WS_EX_LAYERED | WS_EX_APPWINDOW,
+            WindowStyle::Overlay => WS_EX_LAYERED | WS_EX_TOOLWINDOW | WS_EX_TRANSPARENT,
         }
     }
 
@@ -135,16 +387,9 @@ impl WindowStyle {
         let my_style = self.style();
         let my_ex_style = self.ex_style();
 
-        let other_style = match self {
-            WindowStyle::Window => WindowStyle::Floating.style() | WindowStyle::Taskbar.style(),
-            WindowStyle::Floating => WindowStyle::Window.style() | WindowStyle::Taskbar.style(),
-            WindowStyle::Taskbar => WindowStyle::Window.style() | WindowStyle::Floating.style(),
-        };
-        let other_ex_style = match self {
-            WindowStyle::Window => WindowStyle::Floating.ex_style() | WindowStyle::Taskbar.ex_style(),
-            WindowStyle::Floating => WindowStyle::Window.ex_style() | WindowStyle::Taskbar.ex_style(),
-            WindowStyle::Taskbar => WindowStyle::Window.ex_style() | WindowStyle::Floating.ex_style(),
-        };
+        let others = WindowStyle::ALL.iter().filter(|other| *other != self);
+        let other_style = others.clone().fold(WINDOW_STYLE(0), |acc, other| acc | other.style());
+        let other_ex_style = others.fold(WINDOW_EX_STYLE(0), |acc, other| acc | other.ex_style());
 
         let current_style = unsafe { WINDOW_STYLE(GetWindowLongW(hwnd, GWL_STYLE) as u32) };
         let current_ex_style = unsafe { WINDOW_EX_STYLE(GetWindowLongW(hwnd, GWL_EXSTYLE) as u32) };
@@ -164,6 +409,7 @@ impl WindowStyle {
             "Floating" => WindowStyle::Floating,
             "Taskbar" => WindowStyle::Taskbar,
             "Window" => WindowStyle::Window,
+            "Overlay" => WindowStyle::Overlay,
             _ => WindowStyle::Window, // Fallback variant
         }
     }
@@ -175,3 +421,88 @@ impl Display for WindowStyle {
         write!(f, "{:?}", self)
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_into() {
+        let primary = Rect { left: 0, top: 0, right: 1920, bottom: 1040 };
+        let secondary = Rect { left: 1920, top: 0, right: 3200, bottom: 984 };
+
+        // Same offset within the other work area
+        let rect = Rect { left: 100, top: 50, right: 962, bottom: 618 };
+        assert_eq!(move_into(rect, primary, secondary), Rect { left: 2020, top: 50, right: 2882, bottom: 618 });
+
+        // Pushed back inside when the offset doesn't fit
+        let rect = Rect { left: 1000, top: 600, right: 1862, bottom: 1168 };
+        assert_eq!(move_into(rect, primary, secondary), Rect { left: 2338, top: 416, right: 3200, bottom: 984 });
+
+        // Shrunk to the smaller work area
+        let rect = Rect { left: 0, top: 0, right: 1500, bottom: 1000 };
+        assert_eq!(move_into(rect, primary, secondary), Rect { left: 1920, top: 0, right: 3200, bottom: 984 });
+    }
+
+    #[test]
+    fn test_on_screen() {
+        let monitors = [
+            Monitor { work_area: Rect { left: 0, top: 0, right: 1920, bottom: 1040 }, device: r"\\.\DISPLAY1".to_string(), primary: true },
+            Monitor { work_area: Rect { left: 1920, top: 0, right: 3200, bottom: 984 }, device: r"\\.\DISPLAY2".to_string(), primary: false },
+        ];
+
+        // Left as it is on screen
+        let rect = Rect { left: 2020, top: 50, right: 2882, bottom: 618 };
+        assert_eq!(on_screen(rect, &monitors), rect);
+
+        // Pushed back onto the monitor showing most of it
+        let rect = Rect { left: 2800, top: 600, right: 3662, bottom: 1168 };
+        assert_eq!(on_screen(rect, &monitors), Rect { left: 2338, top: 416, right: 3200, bottom: 984 });
+        let rect = Rect { left: 1400, top: -100, right: 2262, bottom: 468 };
+        assert_eq!(on_screen(rect, &monitors), Rect { left: 1058, top: 0, right: 1920, bottom: 568 });
+
+        // Centered on the primary monitor when on a disconnected one
+        let rect = Rect { left: -1500, top: 100, right: -638, bottom: 668 };
+        assert_eq!(on_screen(rect, &monitors), Rect { left: 529, top: 236, right: 1391, bottom: 804 });
+        assert_eq!(on_screen(rect, &[]), rect);
+    }
+
+    #[test]
+    fn test_half() {
+        let work_area = Rect { left: 1920, top: 0, right: 3201, bottom: 984 };
+        assert_eq!(work_area.half(true), Rect { left: 1920, top: 0, right: 2560, bottom: 984 });
+        assert_eq!(work_area.half(false), Rect { left: 2560, top: 0, right: 3201, bottom: 984 });
+    }
+
+    #[test]
+    fn test_dpi_scale() {
+        assert_eq!(dpi_scale(BASE_DPI), 1.0);
+        assert_eq!(dpi_scale(144), 1.5);
+        assert_eq!(dpi_scale(192), 2.0);
+        assert_eq!(dpi_scale(0), 1.0);
+
+        // Scaling works like a zoom, from the stored size with the top left corner in place
+        let layout = WindowLayout { style: WindowStyle::Taskbar, rect: Rect { left: 100, top: 50, right: 962, bottom: 618 } };
+        assert_eq!(layout.zoomed(dpi_scale(144)).rect, Rect { left: 100, top: 50, right: 1393, bottom: 902 });
+    }
+
+    #[test]
+    fn test_fit_rect() {
+        let work_area = Some(Rect { left: 0, top: 0, right: 1920, bottom: 1040 });
+        let rect = Rect { left: 529, top: 236, right: 1391, bottom: 804 };
+
+        // Around the center of the fixed rect
+        assert_eq!(fit_rect(rect, (600, 400), work_area), Rect { left: 660, top: 320, right: 1260, bottom: 720 });
+
+        // Within the size limits
+        assert_eq!(fit_rect(rect, (100, 50), work_area), Rect { left: 780, top: 400, right: 1140, bottom: 640 });
+        assert_eq!(fit_rect(rect, (3000, 400), None), Rect { left: 160, top: 320, right: 1760, bottom: 720 });
+
+        // Pushed back into the work area
+        let corner = Rect { left: 1700, top: 900, right: 1900, bottom: 1000 };
+        assert_eq!(fit_rect(corner, (600, 400), work_area), Rect { left: 1320, top: 640, right: 1920, bottom: 1040 });
+        let small_area = Some(Rect { left: 0, top: 0, right: 1280, bottom: 720 });
+        assert_eq!(fit_rect(rect, (1500, 900), small_area), Rect { left: 0, top: 0, right: 1280, bottom: 720 });
+    }
+}
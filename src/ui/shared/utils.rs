@@ -2,6 +2,7 @@ use windows::
     Win32::{
         Foundation::{HWND, LPARAM, WPARAM},
         Graphics::Gdi::UpdateWindow,
+        UI::HiDpi::{SetThreadDpiAwarenessContext, DPI_AWARENESS_CONTEXT, DPI_AWARENESS_CONTEXT_UNAWARE},
         UI::WindowsAndMessaging::{SetWindowPos, PostMessageW, HWND_TOP, HWND_TOPMOST, SWP_DRAWFRAME, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER},
     }
 ;
@@ -22,18 +23,44 @@ pub unsafe fn set_window_rect(hwnd: HWND, rect: &Rect) {
     let _ = UpdateWindow(hwnd);
 }
 
-/// Copy string into fixed-size array with null termination
+/// While alive, windows created on this thread are DPI unaware and Windows scales them as a whole.
+/// For the dialogs, whose controls are placed in fixed pixels, unlike the boards that scale themselves.
+pub struct DpiUnawareScope(DPI_AWARENESS_CONTEXT);
+
+impl DpiUnawareScope {
+    pub fn enter() -> Self {
+        Self(unsafe { SetThreadDpiAwarenessContext(DPI_AWARENESS_CONTEXT_UNAWARE) })
+    }
+}
+
+impl Drop for DpiUnawareScope {
+    fn drop(&mut self) {
+        // Null when the context couldn't be changed
+        if !self.0.0.is_null() {
+            unsafe { SetThreadDpiAwarenessContext(self.0) };
+        }
+    }
+}
+
+/// Copy string into fixed-size array with null termination.
+/// Long strings are cut at a character boundary, so the stored bytes stay valid UTF-8.
 pub fn copy_string_to_array(array: &mut [u8], s: &str) {
-    let bytes = s.as_bytes();
-    let len = bytes.len().min(array.len() - 1); // Leave room for null terminator
-    array[..len].copy_from_slice(&bytes[..len]);
+    let mut len = s.len().min(array.len() - 1); // Leave room for null terminator
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    array[..len].copy_from_slice(&s.as_bytes()[..len]);
     array[len] = 0; // Null terminator
 }
 
-/// Extract string from fixed-size array (stops at null terminator)
+/// Extract string from fixed-size array (stops at null terminator).
+/// Invalid UTF-8 keeps the valid prefix instead of dropping the whole string.
 pub fn get_string_from_array(array: &[u8]) -> &str {
     let end = array.iter().position(|&x| x == 0).unwrap_or(array.len());
-    std::str::from_utf8(&array[..end]).unwrap_or("")
+    match std::str::from_utf8(&array[..end]) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&array[..e.valid_up_to()]).unwrap_or_default(),
+    }
 }
 
 /// Generic function to send serializable data through Windows message queue
@@ -50,3 +77,33 @@ pub unsafe fn receive_window_message<T>(wparam: WPARAM) -> T {
     let ptr = wparam.0 as *mut T;
     *Box::from_raw(ptr)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_array_round_trip() {
+        let mut array = [0u8; 32];
+        copy_string_to_array(&mut array, "Ünïcödé 🚀 Title");
+        assert_eq!(get_string_from_array(&array), "Ünïcödé 🚀 Title");
+    }
+
+    #[test]
+    fn test_string_array_truncates_at_char_boundary() {
+        // The rocket takes 4 bytes, only 2 of them would fit
+        let mut array = [0u8; 6];
+        copy_string_to_array(&mut array, "abc🚀");
+        assert_eq!(get_string_from_array(&array), "abc");
+
+        let mut array = [0u8; 4];
+        copy_string_to_array(&mut array, "日本");
+        assert_eq!(get_string_from_array(&array), "日");
+    }
+
+    #[test]
+    fn test_string_array_keeps_valid_prefix() {
+        let array = [b'a', b'b', 0xF0, 0x9F, 0];
+        assert_eq!(get_string_from_array(&array), "ab");
+    }
+}